@@ -0,0 +1,79 @@
+//! The benches use only the public API, so that the results are comparable with the
+//! sorted `Vec` of the timers which the earlier releases used as the `TimerQueue`.
+
+#![feature(test)]
+extern crate asyncio;
+extern crate test;
+
+use asyncio::*;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use test::Bencher;
+
+fn on_wait(_: Arc<SteadyTimer>, _: io::Result<()>) {}
+
+/// Returns a scattered timeout from 60 to 120 seconds.
+fn timeout(i: usize) -> Duration {
+    Duration::from_millis(60_000 + (i * 7919 % 60_000) as u64)
+}
+
+/// Resets the idle timeout of each connection, which erases and inserts the timer in the `TimerQueue`.
+fn reset_idle_timeouts(b: &mut Bencher, len: usize) {
+    let ctx = &IoContext::new().unwrap();
+    let timers: Arc<Vec<_>> = Arc::new((0..len).map(|_| Arc::new(SteadyTimer::new(ctx))).collect());
+    let mut count = 0;
+    b.iter(|| {
+        ctx.restart();
+        let timers = timers.clone();
+        let base = count;
+        count += len;
+        // resets on the thread of the `IoContext`, since the waits from the outside are posted and not queued.
+        ctx.post(move |ctx| {
+            for (i, timer) in timers.iter().enumerate() {
+                timer.expires_from_now(timeout(base + i));
+                timer.async_wait(wrap(timer, on_wait));
+            }
+            ctx.stop();
+        });
+        ctx.run();
+    });
+    ctx.restart();
+    ctx.post(move |_| for timer in timers.iter() {
+        timer.cancel();
+    });
+    ctx.run();
+}
+
+#[bench]
+fn bench_reset_idle_timeouts_100(b: &mut Bencher) {
+    reset_idle_timeouts(b, 100)
+}
+
+#[bench]
+fn bench_reset_idle_timeouts_1000(b: &mut Bencher) {
+    reset_idle_timeouts(b, 1000)
+}
+
+#[bench]
+fn bench_reset_idle_timeouts_10000(b: &mut Bencher) {
+    reset_idle_timeouts(b, 10000)
+}
+
+#[bench]
+fn bench_expire_10000(b: &mut Bencher) {
+    let ctx = &IoContext::new().unwrap();
+    b.iter(|| {
+        ctx.restart();
+        let timers: Vec<_> = (0..10000)
+            .map(|i| {
+                let timer = Arc::new(SteadyTimer::new(ctx));
+                timer.expires_from_now(Duration::new(0, (i % 100) as u32));
+                timer.async_wait(wrap(&timer, on_wait));
+                timer
+            })
+            .collect();
+        ctx.run();
+        timers
+    })
+}
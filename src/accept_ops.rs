@@ -62,6 +62,10 @@ where
             }
         }
     }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
 }
 
 impl<P, S, F> Exec for AsyncAccept<P, S, F>
@@ -1,10 +1,11 @@
-use ffi::{SystemError};
+use ffi::SystemError;
 use core::{AsIoContext, Exec, IoContext, Perform, ThreadIoContext, Cancel};
 use handler::{Complete, Handler};
 use timer::{Expiry, TimerImpl};
 
 use std::io;
 use std::marker::PhantomData;
+use std::sync::Mutex;
 use std::ops::Add;
 use std::time::{Duration, Instant, SystemTime};
 
@@ -45,6 +46,10 @@ where
             self.failure(this, err.into())
         }
     }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
 }
 
 impl<W, F> Exec for AsyncWait<W, F>
@@ -68,7 +73,7 @@ where
     W: AsyncWaitOp,
     F: Handler<(), io::Error>,
 {
-    handler.wrap(wait.as_ctx(), |ctx, handler| {
+    handler.wrap_cancel(wait, |ctx, handler| {
         ctx.do_dispatch(AsyncWait {
             wait: wait,
             handler: handler,
@@ -77,11 +82,18 @@ where
 }
 
 pub trait Clock: Send + 'static {
-    type Duration;
+    type Duration: Clone + Into<Duration>;
 
-    type TimePoint: Add<Self::Duration, Output = Self::TimePoint> + Into<Expiry>;
+    type TimePoint: Add<Self::Duration, Output = Self::TimePoint>
+        + Into<Expiry>
+        + From<Expiry>;
 
     fn now() -> Self::TimePoint;
+
+    /// Returns the time of the system clock, if the `TimePoint` follows the changes of it.
+    fn wall_time(_: &Self::TimePoint) -> Option<SystemTime> {
+        None
+    }
 }
 
 /// Provides a monotonic clock.
@@ -98,6 +110,9 @@ impl Clock for SteadyClock {
 }
 
 /// Provides a real-time clock.
+///
+/// The expiry of the timer is re-evaluated when the system clock is changed discontinuously,
+/// e.g. by `settimeofday` or a NTP step. (Linux only)
 pub struct SystemClock;
 
 impl Clock for SystemClock {
@@ -108,6 +123,10 @@ impl Clock for SystemClock {
     fn now() -> Self::TimePoint {
         SystemTime::now()
     }
+
+    fn wall_time(tp: &Self::TimePoint) -> Option<SystemTime> {
+        Some(*tp)
+    }
 }
 
 /// Provides waitable timer functionality.
@@ -127,6 +146,9 @@ where
         }
     }
 
+    /// Asynchronously waits until the expiry.
+    ///
+    /// Multiple waits may be outstanding at the same time, and all of them are completed at the expiry.
     pub fn async_wait<F>(&self, handler: F) -> F::Output
     where
         F: Handler<(), io::Error>,
@@ -134,16 +156,60 @@ where
         async_wait(self, handler)
     }
 
-    pub fn expires_at(&self, expiry: C::TimePoint) {
-        self.pimpl.reset_expiry(expiry.into());
+    /// Blocks until the expiry.
+    ///
+    /// Returns a `OperationCanceled` error if the expiry is reset or the timer is canceled by the other thread.
+    pub fn wait(&self) -> io::Result<()> {
+        Ok(self.pimpl.wait()?)
     }
 
-    pub fn expires_from_now(&self, expiry: C::Duration) {
-        self.expires_at(C::now() + expiry);
+    /// Returns the expiry.
+    pub fn expiry(&self) -> C::TimePoint {
+        self.pimpl.expiry().into()
     }
 
-    pub fn wait(&self) -> io::Result<()> {
-        Ok(())
+    /// Returns a duration until the expiry, or zero if it was already expired.
+    pub fn expires_in(&self) -> Duration {
+        self.pimpl.expiry().duration_since(self.pimpl.now())
+    }
+
+    /// Sets the expiry, and cancels any pending waits.
+    ///
+    /// Returns a number of the canceled waits.
+    pub fn expires_at(&self, expiry: C::TimePoint) -> usize {
+        let wall = C::wall_time(&expiry);
+        self.pimpl.reset_expiry(expiry.into(), wall)
+    }
+
+    /// Sets the expiry relative to now, and cancels any pending waits.
+    ///
+    /// Returns a number of the canceled waits.
+    pub fn expires_from_now(&self, expiry: C::Duration) -> usize {
+        let tp = C::now() + expiry.clone();
+        let wall = C::wall_time(&tp);
+        match self.pimpl.manual_now() {
+            Some(now) => self.pimpl.reset_expiry(now + expiry.into(), wall),
+            None => self.pimpl.reset_expiry(tp.into(), wall),
+        }
+    }
+
+    /// Cancels any pending waits, and returns a number of them.
+    pub fn cancel(&self) -> usize {
+        self.pimpl.cancel()
+    }
+
+    /// Cancels the oldest pending asynchronous wait, and returns a number of it.
+    pub fn cancel_one(&self) -> usize {
+        self.pimpl.cancel_one()
+    }
+}
+
+impl WaitableTimer<SystemClock> {
+    /// Sets whether the pending asynchronous waits fail when the system clock is changed discontinuously.
+    ///
+    /// If it is set, the waits fail with a error of `ETIME` instead of being re-evaluated. (Linux only)
+    pub fn set_cancel_on_clock_change(&self, on: bool) {
+        self.pimpl.set_cancel_on_clock_change(on)
     }
 }
 
@@ -155,7 +221,11 @@ unsafe impl<C> AsIoContext for WaitableTimer<C> {
 
 impl<C: 'static> Cancel for WaitableTimer<C> {
     fn cancel(&self) {
-        self.pimpl.cancel()
+        self.pimpl.cancel();
+    }
+
+    fn cancel_expired(&self) {
+        self.pimpl.cancel_expired()
     }
 }
 
@@ -171,3 +241,528 @@ where
 unsafe impl<C> Send for WaitableTimer<C> {}
 
 unsafe impl<C> Sync for WaitableTimer<C> {}
+
+/// A policy of the ticks which are missed, e.g. while the `IoContext` is busy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MissedTickPolicy {
+    /// Completes the missed ticks one by one as soon as possible, and keeps the original schedule.
+    Burst,
+
+    /// Completes the missed ticks at once, and keeps the original schedule.
+    Skip,
+
+    /// Completes the missed ticks at once, and schedules the next tick at one interval after now.
+    Delay,
+}
+
+struct Periodic {
+    deadline: Expiry,
+    interval: Duration,
+    policy: MissedTickPolicy,
+}
+
+impl Periodic {
+    /// Advances the deadline at `now`, and returns a number of the elapsed intervals.
+    fn advance(&mut self, now: Expiry) -> u64 {
+        let missed = (now.duration_since(self.deadline).as_nanos() / self.interval.as_nanos()) as u64;
+        match self.policy {
+            MissedTickPolicy::Burst => {
+                self.deadline = self.deadline + self.interval;
+                1
+            }
+            MissedTickPolicy::Skip => {
+                let nanos = self.interval.as_nanos() * (missed + 1) as u128;
+                self.deadline = self.deadline + Duration::from_nanos(nanos as u64);
+                missed + 1
+            }
+            MissedTickPolicy::Delay => {
+                self.deadline = now + self.interval;
+                missed + 1
+            }
+        }
+    }
+}
+
+struct AsyncTick<C, F> {
+    timer: *const PeriodicTimer<C>,
+    handler: F,
+}
+
+unsafe impl<C, F> Send for AsyncTick<C, F> {}
+
+impl<C, F> Complete<u64, io::Error> for AsyncTick<C, F>
+where
+    C: Clock,
+    F: Complete<u64, io::Error>,
+{
+    fn success(self, this: &mut ThreadIoContext, res: u64) {
+        self.handler.success(this, res)
+    }
+
+    fn failure(self, this: &mut ThreadIoContext, err: io::Error) {
+        self.handler.failure(this, err)
+    }
+}
+
+impl<C, F> Perform for AsyncTick<C, F>
+where
+    C: Clock,
+    F: Complete<u64, io::Error>,
+{
+    fn perform(self: Box<Self>, this: &mut ThreadIoContext, err: SystemError) {
+        if err == SystemError::default() {
+            let count = unsafe { &*self.timer }.advance();
+            self.success(this, count)
+        } else {
+            self.failure(this, err.into())
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
+}
+
+impl<C, F> Exec for AsyncTick<C, F>
+where
+    C: Clock,
+    F: Complete<u64, io::Error>,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        let timer = unsafe { &*self.timer };
+        timer.pimpl.set_wait_op(this, Box::new(self))
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        let timer = unsafe { &*self.timer };
+        timer.pimpl.set_wait_op(this, self)
+    }
+}
+
+/// Provides a periodic timer functionality.
+///
+/// The ticks are scheduled at a fixed interval from the start, so the delay of the handlers does not accumulate.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use asyncio::{IoContext, wrap};
+/// use asyncio::clock::{PeriodicTimer, SteadyClock};
+///
+/// fn on_tick(timer: Arc<PeriodicTimer<SteadyClock>>, res: io::Result<u64>) {
+///     assert_eq!(res.unwrap(), 1);
+///     timer.async_tick(wrap(&timer, |_, res: io::Result<u64>| { res.unwrap(); }));
+/// }
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let timer = Arc::new(PeriodicTimer::<SteadyClock>::new(ctx, Duration::new(0, 1000000)));
+/// timer.async_tick(wrap(&timer, on_tick));
+/// ctx.run();
+/// ```
+pub struct PeriodicTimer<C> {
+    pimpl: Box<TimerImpl>,
+    state: Mutex<Periodic>,
+    _marker: PhantomData<C>,
+}
+
+impl<C> PeriodicTimer<C>
+where
+    C: Clock,
+{
+    /// Returns a timer which ticks every `interval`, and the first tick is at one interval after now.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `interval` is zero.
+    pub fn new(ctx: &IoContext, interval: Duration) -> Self {
+        assert!(interval != Duration::new(0, 0), "interval must be non-zero");
+        let pimpl = TimerImpl::new(ctx);
+        let deadline = pimpl.now() + interval;
+        pimpl.reset_expiry(deadline, None);
+        PeriodicTimer {
+            pimpl: pimpl,
+            state: Mutex::new(Periodic {
+                deadline: deadline,
+                interval: interval,
+                policy: MissedTickPolicy::Burst,
+            }),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Asynchronously waits until the next tick, and returns a number of the elapsed intervals.
+    ///
+    /// Only one tick should be waited at the same time, since a tick cancels the other pending waits.
+    pub fn async_tick<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<u64, io::Error>,
+    {
+        handler.wrap_cancel(self, |ctx, handler| {
+            ctx.do_dispatch(AsyncTick {
+                timer: self,
+                handler: handler,
+            })
+        })
+    }
+
+    /// Blocks until the next tick, and returns a number of the elapsed intervals.
+    pub fn tick(&self) -> io::Result<u64> {
+        self.pimpl.wait()?;
+        Ok(self.advance())
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.state.lock().unwrap().interval
+    }
+
+    pub fn missed_tick_policy(&self) -> MissedTickPolicy {
+        self.state.lock().unwrap().policy
+    }
+
+    pub fn set_missed_tick_policy(&self, policy: MissedTickPolicy) {
+        self.state.lock().unwrap().policy = policy
+    }
+
+    /// Restarts the ticks at `start`, and cancels any pending waits.
+    ///
+    /// Returns a number of the canceled waits.
+    pub fn reset_at(&self, start: C::TimePoint) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.deadline = start.into();
+        self.pimpl.reset_expiry(state.deadline, None)
+    }
+
+    /// Restarts the ticks at one interval after now, and cancels any pending waits.
+    ///
+    /// Returns a number of the canceled waits.
+    pub fn reset(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.deadline = self.pimpl.now() + state.interval;
+        self.pimpl.reset_expiry(state.deadline, None)
+    }
+
+    /// Cancels any pending waits, and returns a number of them.
+    pub fn cancel(&self) -> usize {
+        self.pimpl.cancel()
+    }
+
+    fn advance(&self) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let count = state.advance(self.pimpl.now());
+        self.pimpl.reset_expiry(state.deadline, None);
+        count
+    }
+}
+
+unsafe impl<C> AsIoContext for PeriodicTimer<C> {
+    fn as_ctx(&self) -> &IoContext {
+        &self.pimpl.as_ctx()
+    }
+}
+
+impl<C: 'static> Cancel for PeriodicTimer<C> {
+    fn cancel(&self) {
+        self.pimpl.cancel();
+    }
+
+    fn cancel_expired(&self) {
+        self.pimpl.cancel_expired()
+    }
+}
+
+unsafe impl<C> Send for PeriodicTimer<C> {}
+
+unsafe impl<C> Sync for PeriodicTimer<C> {}
+
+#[test]
+fn test_expiry() {
+    let ctx = &IoContext::new().unwrap();
+    let timer = WaitableTimer::<SteadyClock>::new(ctx);
+    let now = Instant::now();
+    timer.expires_at(now + Duration::new(10, 0));
+    assert!(timer.expiry() == now + Duration::new(10, 0));
+    assert!(timer.expires_in() > Duration::new(9, 0));
+    assert!(timer.expires_in() <= Duration::new(10, 0));
+    timer.expires_at(now);
+    assert_eq!(timer.expires_in(), Duration::new(0, 0));
+}
+
+#[test]
+fn test_multiple_async_wait() {
+    use handler::wrap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let ctx = &IoContext::new().unwrap();
+    let timer = Arc::new(WaitableTimer::<SteadyClock>::new(ctx));
+    timer.expires_from_now(Duration::new(0, 1000000));
+    for _ in 0..3 {
+        timer.async_wait(wrap(&timer, |_, res: io::Result<()>| {
+            res.unwrap();
+            COUNT.fetch_add(1, Ordering::SeqCst);
+        }));
+    }
+    ctx.run();
+    assert_eq!(COUNT.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_cancel_count() {
+    use handler::wrap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CANCELED: AtomicUsize = AtomicUsize::new(0);
+
+    let ctx = &IoContext::new().unwrap();
+    let timer = Arc::new(WaitableTimer::<SteadyClock>::new(ctx));
+    timer.expires_from_now(Duration::new(60, 0));
+    for _ in 0..3 {
+        timer.async_wait(wrap(&timer, |_, res: io::Result<()>| {
+            assert_eq!(res.unwrap_err().raw_os_error(), Some(::libc::ECANCELED));
+            CANCELED.fetch_add(1, Ordering::SeqCst);
+        }));
+    }
+    let t = timer.clone();
+    ctx.post(move |_| {
+        assert_eq!(t.cancel_one(), 1);
+        assert_eq!(t.cancel(), 2);
+        assert_eq!(t.cancel(), 0);
+    });
+    ctx.run();
+    assert_eq!(CANCELED.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_drop_with_pending_wait() {
+    use handler::wrap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CANCELED: AtomicUsize = AtomicUsize::new(0);
+
+    let ctx = &IoContext::new().unwrap();
+    ctx.post(|ctx| {
+        let owner = Arc::new(WaitableTimer::<SteadyClock>::new(ctx));
+        let timer = WaitableTimer::<SteadyClock>::new(ctx);
+        timer.expires_from_now(Duration::new(60, 0));
+        timer.async_wait(wrap(&owner, |_, res: io::Result<()>| {
+            assert_eq!(res.unwrap_err().raw_os_error(), Some(::libc::ECANCELED));
+            CANCELED.fetch_add(1, Ordering::SeqCst);
+        }));
+    });
+    ctx.run();
+    assert_eq!(CANCELED.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_blocking_wait() {
+    use std::sync::Arc;
+    use std::thread;
+
+    let ctx = &IoContext::new().unwrap();
+    let timer = Arc::new(WaitableTimer::<SteadyClock>::new(ctx));
+    timer.expires_from_now(Duration::new(0, 1000000));
+    let now = Instant::now();
+    timer.wait().unwrap();
+    assert!(now.elapsed() >= Duration::new(0, 1000000) - Duration::new(0, 1000));
+
+    timer.expires_from_now(Duration::new(60, 0));
+    let t = timer.clone();
+    let thrd = thread::spawn(move || t.wait());
+    while timer.cancel() == 0 {
+        thread::sleep(Duration::new(0, 1000000));
+    }
+    assert_eq!(
+        thrd.join().unwrap().unwrap_err().raw_os_error(),
+        Some(::libc::ECANCELED)
+    );
+}
+
+#[test]
+fn test_system_timer() {
+    use handler::wrap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let ctx = &IoContext::new().unwrap();
+    let timer = Arc::new(WaitableTimer::<SystemClock>::new(ctx));
+    timer.set_cancel_on_clock_change(true);
+    let now = SystemTime::now();
+    timer.expires_at(now + Duration::new(0, 1000000));
+    timer.async_wait(wrap(&timer, |_, res: io::Result<()>| {
+        res.unwrap();
+        COUNT.fetch_add(1, Ordering::SeqCst);
+    }));
+    ctx.run();
+    assert_eq!(COUNT.load(Ordering::SeqCst), 1);
+    assert!(now.elapsed().unwrap() >= Duration::new(0, 1000000));
+
+    timer.expires_at(SystemTime::now() + Duration::new(0, 1000000));
+    timer.wait().unwrap();
+}
+
+#[test]
+#[ignore] // requires CAP_SYS_TIME
+fn test_system_timer_clock_changed() {
+    use handler::wrap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let ctx = &IoContext::new().unwrap();
+    let timer = Arc::new(WaitableTimer::<SystemClock>::new(ctx));
+    timer.set_cancel_on_clock_change(true);
+    // the every change of the system clock must be notified to the wait.
+    for i in 0..2 {
+        timer.expires_at(SystemTime::now() + Duration::new(10, 0));
+        timer.async_wait(wrap(&timer, |_, res: io::Result<()>| {
+            assert_eq!(res.unwrap_err().raw_os_error(), Some(::libc::ETIME));
+            COUNT.fetch_add(1, Ordering::SeqCst);
+        }));
+        let thrd = thread::spawn(|| {
+            thread::sleep(Duration::new(0, 50000000));
+            unsafe {
+                let mut ts = ::std::mem::zeroed();
+                ::libc::clock_gettime(::libc::CLOCK_REALTIME, &mut ts);
+                assert_eq!(::libc::clock_settime(::libc::CLOCK_REALTIME, &ts), 0);
+            }
+        });
+        ctx.restart();
+        ctx.run();
+        thrd.join().unwrap();
+        assert_eq!(COUNT.load(Ordering::SeqCst), i + 1);
+    }
+}
+
+#[test]
+fn test_periodic_advance() {
+    let ms = Duration::new(0, 1000000);
+    let start = Expiry::zero() + ms * 10;
+    let mut p = Periodic {
+        deadline: start,
+        interval: ms * 10,
+        policy: MissedTickPolicy::Burst,
+    };
+    assert_eq!(p.advance(start + ms * 35), 1);
+    assert!(p.deadline == start + ms * 10);
+
+    p.deadline = start;
+    p.policy = MissedTickPolicy::Skip;
+    assert_eq!(p.advance(start + ms * 35), 4);
+    assert!(p.deadline == start + ms * 40);
+
+    p.deadline = start;
+    p.policy = MissedTickPolicy::Delay;
+    assert_eq!(p.advance(start + ms * 35), 4);
+    assert!(p.deadline == start + ms * 45);
+}
+
+#[test]
+fn test_periodic_timer() {
+    use handler::wrap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static TICKS: AtomicUsize = AtomicUsize::new(0);
+
+    fn on_tick(timer: Arc<PeriodicTimer<SteadyClock>>, res: io::Result<u64>) {
+        let count = res.unwrap() as usize;
+        if TICKS.fetch_add(count, Ordering::SeqCst) + count < 5 {
+            timer.async_tick(wrap(&timer, on_tick));
+        }
+    }
+
+    let ctx = &IoContext::new().unwrap();
+    let now = Instant::now();
+    let timer = Arc::new(PeriodicTimer::<SteadyClock>::new(
+        ctx,
+        Duration::new(0, 1000000),
+    ));
+    timer.async_tick(wrap(&timer, on_tick));
+    ctx.run();
+    assert_eq!(TICKS.load(Ordering::SeqCst), 5);
+    assert!(now.elapsed() >= Duration::new(0, 5000000));
+
+    timer.reset();
+    assert_eq!(timer.tick().unwrap(), 1);
+}
+
+#[test]
+fn test_manual_clock() {
+    use handler::wrap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let ctx = &IoContext::with_manual_clock().unwrap();
+    let now = ctx.now();
+    let timer = Arc::new(WaitableTimer::<SteadyClock>::new(ctx));
+    timer.expires_from_now(Duration::new(3600, 0));
+    assert!(timer.expiry() == now + Duration::new(3600, 0));
+    assert_eq!(timer.expires_in(), Duration::new(3600, 0));
+    timer.async_wait(wrap(&timer, |timer: Arc<WaitableTimer<SteadyClock>>, res: io::Result<()>| {
+        res.unwrap();
+        assert_eq!(COUNT.fetch_add(1, Ordering::SeqCst), 1);
+        assert_eq!(timer.expires_in(), Duration::new(0, 0));
+    }));
+    ctx.post(|ctx| {
+        ctx.advance(Duration::new(3599, 0));
+        ctx.post(|ctx| {
+            assert_eq!(COUNT.fetch_add(1, Ordering::SeqCst), 0);
+            ctx.advance(Duration::new(1, 0));
+        });
+    });
+    let real = Instant::now();
+    ctx.run();
+    assert_eq!(COUNT.load(Ordering::SeqCst), 2);
+    assert!(real.elapsed() < Duration::new(60, 0));
+    assert!(ctx.now() == now + Duration::new(3600, 0));
+}
+
+#[test]
+fn test_manual_clock_periodic() {
+    let ctx = &IoContext::with_manual_clock().unwrap();
+    let timer = PeriodicTimer::<SteadyClock>::new(ctx, Duration::new(10, 0));
+    timer.set_missed_tick_policy(MissedTickPolicy::Skip);
+    ctx.advance(Duration::new(35, 0));
+    assert_eq!(timer.tick().unwrap(), 3);
+    ctx.advance(Duration::new(5, 0));
+    assert_eq!(timer.tick().unwrap(), 1);
+}
+
+#[test]
+fn test_manual_clock_blocking_wait() {
+    use std::sync::Arc;
+    use std::thread;
+
+    let ctx = &IoContext::with_manual_clock().unwrap();
+    let timer = Arc::new(WaitableTimer::<SystemClock>::new(ctx));
+    timer.expires_from_now(Duration::new(3600, 0));
+    let t = timer.clone();
+    let thrd = thread::spawn(move || t.wait());
+    ctx.advance(Duration::new(3600, 0));
+    thrd.join().unwrap().unwrap();
+
+    timer.expires_from_now(Duration::new(3600, 0));
+    let t = timer.clone();
+    let thrd = thread::spawn(move || t.wait());
+    ctx.advance(Duration::new(1, 0));
+    while timer.cancel() == 0 {
+        thread::yield_now();
+    }
+    assert_eq!(
+        thrd.join().unwrap().unwrap_err().raw_os_error(),
+        Some(::libc::ECANCELED)
+    );
+}
@@ -60,6 +60,10 @@ where
             self.failure(this, err.into())
         }
     }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
 }
 
 impl<P, S, F> Exec for AsyncConnect<P, S, F>
@@ -30,6 +30,8 @@ impl<K: Eq, V> ThreadCallStack<K, V> {
         TOP.set(self as *mut _ as *mut _);
     }
 
+    // the returned reference points to the frame of the current thread, not to the `key`.
+    #[allow(clippy::mut_from_ref)]
     pub fn callstack<'a>(key: &'a K) -> Option<&'a mut Self> {
         let mut ptr = TOP.get() as *mut Self;
         unsafe {
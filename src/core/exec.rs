@@ -7,9 +7,15 @@ use std::sync::{Arc, Condvar, Mutex};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::collections::VecDeque;
 use std::ops::Deref;
+use std::time::{Duration, Instant};
 
 pub trait Perform: Send + 'static {
     fn perform(self: Box<Self>, this: &mut ThreadIoContext, err: SystemError);
+
+    /// Returns true if the deadline of the operation is expired.
+    fn is_expired(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Default)]
@@ -139,6 +145,47 @@ impl IoContext {
         Ok(IoContext(ctx))
     }
 
+    /// Returns a `IoContext` whose timers run on a manual clock.
+    ///
+    /// The manual clock starts at now, and advances only by the `advance`.
+    /// So the timers complete deterministically, regardless of the real time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use asyncio::{IoContext, SteadyTimer, wrap};
+    ///
+    /// let ctx = &IoContext::with_manual_clock().unwrap();
+    /// let timer = Arc::new(SteadyTimer::new(ctx));
+    /// timer.expires_from_now(Duration::new(3600, 0));
+    /// timer.async_wait(wrap(&timer, |_, res: io::Result<()>| res.unwrap()));
+    /// ctx.advance(Duration::new(3600, 0));
+    /// ctx.run();
+    /// ```
+    pub fn with_manual_clock() -> io::Result<Self> {
+        let ctx = IoContext::new()?;
+        ctx.as_reactor().tq.set_manual_clock();
+        Ok(ctx)
+    }
+
+    /// Advances the manual clock by `dur`, and completes the expired timers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `IoContext` does not use the manual clock.
+    pub fn advance(&self, dur: Duration) {
+        self.as_reactor().tq.advance(dur);
+        self.as_reactor().interrupt();
+    }
+
+    /// Returns the current time on the clock of the timers.
+    pub fn now(&self) -> Instant {
+        self.as_reactor().tq.now().into()
+    }
+
     #[doc(hidden)]
     pub fn as_reactor(&self) -> &Reactor {
         &self.0.reactor
@@ -83,4 +83,12 @@ pub trait SetSocketOption<P>: SocketOption<P> {
 
 pub trait Cancel: AsIoContext + 'static {
     fn cancel(&self);
+
+    /// Cancels the pending operations whose deadline is expired.
+    ///
+    /// The objects which can not cancel the operations individually cancel all of them.
+    #[doc(hidden)]
+    fn cancel_expired(&self) {
+        self.cancel()
+    }
 }
@@ -0,0 +1,396 @@
+use ffi::{SystemError, Timeout, TIMED_OUT};
+use core::{AsIoContext, IoContext, ThreadIoContext, Cancel};
+use handler::{Handler, Complete, wrap};
+use SteadyTimer;
+
+use std::io;
+use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+const PENDING: usize = 0;
+const EXPIRED: usize = 1;
+const DONE: usize = 2;
+
+struct Deadline {
+    timer: SteadyTimer,
+    soc: Weak<Cancel + Send + Sync>,
+    state: AtomicUsize,
+}
+
+unsafe impl AsIoContext for Deadline {
+    fn as_ctx(&self) -> &IoContext {
+        self.timer.as_ctx()
+    }
+}
+
+fn on_deadline(deadline: Arc<Deadline>, res: io::Result<()>) {
+    if res.is_ok() &&
+        deadline
+            .state
+            .compare_exchange(PENDING, EXPIRED, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    {
+        // the deadline does nothing if the `soc` has been dropped.
+        if let Some(soc) = deadline.soc.upgrade() {
+            soc.cancel_expired();
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct DeadlineComplete<F> {
+    deadline: Arc<Deadline>,
+    handler: F,
+}
+
+impl<F> DeadlineComplete<F> {
+    /// Returns `true` if the operation has been canceled by the deadline.
+    fn done(&self) -> bool {
+        let expired = self.deadline.state.swap(DONE, Ordering::SeqCst) == EXPIRED;
+        // the pending wait is canceled, and the wait which is not started yet completes immediately.
+        self.deadline.timer.expires_from_now(Duration::new(0, 0));
+        expired
+    }
+}
+
+impl<F, R, E> Complete<R, E> for DeadlineComplete<F>
+where
+    F: Complete<R, E>,
+    E: From<SystemError>,
+{
+    fn success(self, this: &mut ThreadIoContext, res: R) {
+        self.done();
+        self.handler.success(this, res)
+    }
+
+    fn failure(self, this: &mut ThreadIoContext, err: E) {
+        if self.done() {
+            self.handler.failure(this, TIMED_OUT.into())
+        } else {
+            self.handler.failure(this, err)
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.deadline.state.load(Ordering::SeqCst) == EXPIRED
+    }
+}
+
+/// Provides a handler which cancels the asynchronous operation at the deadline.
+///
+/// It is made by the `with_deadline` or the `with_timeout`.
+pub struct DeadlineHandler<S, H> {
+    soc: Weak<S>,
+    expiry: Instant,
+    handler: H,
+}
+
+impl<S, H> DeadlineHandler<S, H>
+where
+    S: Cancel + Send + Sync,
+{
+    fn wrapper<W, F>(soc: Weak<S>, expiry: Instant, wrapper: W) -> impl FnOnce(&IoContext, F)
+    where
+        W: FnOnce(&IoContext, DeadlineComplete<F>),
+    {
+        move |ctx: &IoContext, handler: F| {
+            let timer = SteadyTimer::new(ctx);
+            timer.expires_at(expiry);
+            let deadline = Arc::new(Deadline {
+                timer: timer,
+                soc: soc,
+                state: AtomicUsize::new(PENDING),
+            });
+            wrapper(
+                ctx,
+                DeadlineComplete {
+                    deadline: deadline.clone(),
+                    handler: handler,
+                },
+            );
+            deadline.timer.async_wait(wrap(&deadline, on_deadline));
+        }
+    }
+}
+
+impl<S, H, R, E> Handler<R, E> for DeadlineHandler<S, H>
+where
+    S: Cancel + Send + Sync,
+    H: Handler<R, E>,
+    E: From<SystemError>,
+{
+    type Output = H::Output;
+
+    #[doc(hidden)]
+    type WrappedHandler = DeadlineComplete<H::WrappedHandler>;
+
+    #[doc(hidden)]
+    fn wrap<W>(self, ctx: &IoContext, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        let DeadlineHandler {
+            soc,
+            expiry,
+            handler,
+        } = self;
+        handler.wrap(ctx, Self::wrapper(soc, expiry, wrapper))
+    }
+
+    #[doc(hidden)]
+    fn wrap_timeout<W>(self, ctx: &Cancel, timeout: &Timeout, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        let DeadlineHandler {
+            soc,
+            expiry,
+            handler,
+        } = self;
+        handler.wrap_timeout(ctx, timeout, Self::wrapper(soc, expiry, wrapper))
+    }
+
+    #[doc(hidden)]
+    fn wrap_cancel<W>(self, ctx: &Cancel, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        let DeadlineHandler {
+            soc,
+            expiry,
+            handler,
+        } = self;
+        handler.wrap_cancel(ctx, Self::wrapper(soc, expiry, wrapper))
+    }
+}
+
+/// Returns a handler which cancels the asynchronous operation of `soc` at the `expiry`.
+///
+/// If the deadline passes before the operation completes, the operation is canceled,
+/// and the `handler` completes with a `TimedOut` error.
+/// The other pending operations of `soc` are not canceled, except for the objects which can not
+/// cancel an operation individually, e.g. the `SignalSet`, the `Child` and the `RandomAccessFile`.
+///
+/// The `soc` is not kept alive by the deadline.
+/// It works for any handler, e.g. the `ArcHandler`, the `StrandHandler` and the `CoroutineHandler`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::sync::Arc;
+/// use std::time::{Duration, Instant};
+/// use asyncio::{IoContext, wrap, with_deadline};
+/// use asyncio::ip::{IpAddrV4, IpProtocol, Tcp, TcpListener, TcpSocket, TcpEndpoint};
+///
+/// fn on_accept(_: Arc<TcpListener>, res: io::Result<(TcpSocket, TcpEndpoint)>) {
+///     assert_eq!(res.unwrap_err().kind(), io::ErrorKind::TimedOut);
+/// }
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let soc = Arc::new(TcpListener::new(ctx, Tcp::v4()).unwrap());
+/// soc.bind(&TcpEndpoint::new(IpAddrV4::loopback(), 0)).unwrap();
+/// soc.listen().unwrap();
+/// soc.async_accept(with_deadline(&soc, Instant::now() + Duration::new(0, 1000000), wrap(&soc, on_accept)));
+/// ctx.run();
+/// ```
+pub fn with_deadline<S, H>(soc: &Arc<S>, expiry: Instant, handler: H) -> DeadlineHandler<S, H>
+where
+    S: Cancel + Send + Sync,
+{
+    DeadlineHandler {
+        soc: Arc::downgrade(soc),
+        expiry: expiry,
+        handler: handler,
+    }
+}
+
+/// Returns a handler which cancels the asynchronous operation of `soc` after the `timeout`.
+///
+/// It is same as the `with_deadline(soc, ctx.now() + timeout, handler)`.
+pub fn with_timeout<S, H>(soc: &Arc<S>, timeout: Duration, handler: H) -> DeadlineHandler<S, H>
+where
+    S: Cancel + Send + Sync,
+{
+    with_deadline(soc, soc.as_ctx().now() + timeout, handler)
+}
+
+#[cfg(test)]
+fn listener(ctx: &IoContext) -> Arc<::ip::TcpListener> {
+    use ip::{IpAddrV4, IpProtocol, Tcp, TcpEndpoint, TcpListener};
+
+    let soc = Arc::new(TcpListener::new(ctx, Tcp::v4()).unwrap());
+    soc.bind(&TcpEndpoint::new(IpAddrV4::loopback(), 0)).unwrap();
+    soc.listen().unwrap();
+    soc
+}
+
+#[test]
+fn test_with_deadline() {
+    use ip::{TcpEndpoint, TcpListener, TcpSocket};
+
+    let ctx = &IoContext::new().unwrap();
+    let soc = listener(ctx);
+    let now = Instant::now();
+    soc.async_accept(with_deadline(
+        &soc,
+        now + Duration::new(0, 1000000),
+        wrap(&soc, |_: Arc<TcpListener>, res: io::Result<(TcpSocket, TcpEndpoint)>| {
+            assert_eq!(res.unwrap_err().kind(), io::ErrorKind::TimedOut);
+        }),
+    ));
+    ctx.run();
+    assert!(now.elapsed() >= Duration::new(0, 1000000));
+}
+
+#[test]
+fn test_with_deadline_completed() {
+    use posix::{pipe, ReadPipe};
+    use stream::Stream;
+    use std::io::Write;
+
+    static mut BUF: [u8; 16] = [0; 16];
+
+    let ctx = &IoContext::new().unwrap();
+    let (rx, mut tx) = pipe(ctx).unwrap();
+    let rx = Arc::new(rx);
+    tx.write_all(b"hello").unwrap();
+    let now = Instant::now();
+    rx.async_read_some(
+        unsafe { &mut BUF },
+        with_timeout(
+            &rx,
+            Duration::new(60, 0),
+            wrap(&rx, |_: Arc<ReadPipe>, res: io::Result<usize>| {
+                assert_eq!(res.unwrap(), 5);
+            }),
+        ),
+    );
+    ctx.run();
+    assert!(now.elapsed() < Duration::new(10, 0));
+}
+
+#[test]
+fn test_with_deadline_strand() {
+    use ip::{TcpEndpoint, TcpSocket};
+    use strand::Strand;
+
+    let ctx = &IoContext::new().unwrap();
+    let st = Strand::new(ctx, (listener(ctx), false));
+    st.dispatch(|st| {
+        let soc = st.0.clone();
+        soc.async_accept(with_timeout(
+            &soc,
+            Duration::new(0, 1000000),
+            st.wrap(|mut st, res: io::Result<(TcpSocket, TcpEndpoint)>| {
+                assert_eq!(res.unwrap_err().kind(), io::ErrorKind::TimedOut);
+                st.1 = true;
+            }),
+        ))
+    });
+    ctx.run();
+    assert!(st.1);
+}
+
+#[cfg(feature = "context")]
+#[test]
+fn test_with_deadline_coroutine() {
+    use strand::spawn;
+
+    let ctx = &IoContext::new().unwrap();
+    let soc = listener(ctx);
+    spawn(ctx, move |coro| {
+        let res = soc.async_accept(with_timeout(&soc, Duration::new(0, 1000000), coro.wrap()));
+        assert_eq!(res.unwrap_err().kind(), io::ErrorKind::TimedOut);
+    }).unwrap();
+    ctx.run();
+}
+
+#[test]
+fn test_with_deadline_manual_clock() {
+    use ip::{TcpEndpoint, TcpListener, TcpSocket};
+
+    let ctx = &IoContext::with_manual_clock().unwrap();
+    let soc = listener(ctx);
+    soc.async_accept(with_timeout(
+        &soc,
+        Duration::new(3600, 0),
+        wrap(&soc, |_: Arc<TcpListener>, res: io::Result<(TcpSocket, TcpEndpoint)>| {
+            assert_eq!(res.unwrap_err().kind(), io::ErrorKind::TimedOut);
+        }),
+    ));
+    ctx.post(|ctx| ctx.advance(Duration::new(3600, 0)));
+    ctx.run();
+}
+
+#[test]
+fn test_with_deadline_other_ops() {
+    use ip::{TcpEndpoint, TcpListener, TcpSocket};
+    use std::net::TcpStream;
+    use std::sync::atomic::AtomicUsize;
+
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let ctx = &IoContext::new().unwrap();
+    let soc = listener(ctx);
+    soc.async_accept(wrap(&soc, |_: Arc<TcpListener>, res: io::Result<(TcpSocket, TcpEndpoint)>| {
+        res.unwrap();
+        assert_eq!(COUNT.fetch_add(1, Ordering::SeqCst), 1);
+    }));
+    soc.async_accept(with_timeout(
+        &soc,
+        Duration::new(0, 1000000),
+        wrap(&soc, |soc: Arc<TcpListener>, res: io::Result<(TcpSocket, TcpEndpoint)>| {
+            assert_eq!(res.unwrap_err().kind(), io::ErrorKind::TimedOut);
+            assert_eq!(COUNT.fetch_add(1, Ordering::SeqCst), 0);
+            // the other accept is still pending.
+            let port = soc.local_endpoint().unwrap().port();
+            TcpStream::connect(("127.0.0.1", port)).unwrap();
+        }),
+    ));
+    ctx.run();
+    assert_eq!(COUNT.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_with_deadline_timer() {
+    use std::sync::atomic::AtomicUsize;
+
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let ctx = &IoContext::new().unwrap();
+    let timer = Arc::new(SteadyTimer::new(ctx));
+    timer.expires_from_now(Duration::new(60, 0));
+    timer.async_wait(wrap(&timer, |_, res: io::Result<()>| {
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(::libc::ECANCELED));
+        assert_eq!(COUNT.fetch_add(1, Ordering::SeqCst), 1);
+    }));
+    timer.async_wait(with_timeout(
+        &timer,
+        Duration::new(0, 1000000),
+        wrap(&timer, |timer: Arc<SteadyTimer>, res: io::Result<()>| {
+            assert_eq!(res.unwrap_err().kind(), io::ErrorKind::TimedOut);
+            assert_eq!(COUNT.fetch_add(1, Ordering::SeqCst), 0);
+            // the other wait is still pending.
+            assert_eq!(timer.cancel(), 1);
+        }),
+    ));
+    ctx.run();
+    assert_eq!(COUNT.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_with_deadline_dropped() {
+    let ctx = &IoContext::new().unwrap();
+    let soc = listener(ctx);
+    let deadline = Arc::new(Deadline {
+        timer: SteadyTimer::new(ctx),
+        soc: Arc::downgrade(&soc) as Weak<Cancel + Send + Sync>,
+        state: AtomicUsize::new(PENDING),
+    });
+    drop(soc);
+    // the deadline does not touch the dropped object.
+    on_deadline(deadline.clone(), Ok(()));
+    assert_eq!(deadline.state.load(Ordering::SeqCst), EXPIRED);
+}
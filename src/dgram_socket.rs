@@ -192,6 +192,10 @@ impl<P: 'static> Cancel for DgramSocket<P> {
     fn cancel(&self) {
         self.pimpl.cancel()
     }
+
+    fn cancel_expired(&self) {
+        self.pimpl.cancel_expired()
+    }
 }
 
 impl<P> AsyncReadOp for DgramSocket<P>
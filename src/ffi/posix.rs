@@ -21,7 +21,11 @@ pub use libc::{addrinfo, c_void, in_addr, ip_mreq, linger, sockaddr, sockaddr_in
                SO_ERROR, SO_KEEPALIVE, SO_LINGER, SO_RCVBUF, SO_RCVLOWAT, SO_REUSEADDR, SO_SNDBUF,
                SO_SNDLOWAT, TCP_NODELAY, FIONREAD};
 #[cfg(target_os = "linux")]
-pub use libc::{SOCK_CLOEXEC, SOCK_NONBLOCK};
+pub use libc::{SOCK_CLOEXEC, SOCK_NONBLOCK, AF_NETLINK, sockaddr_nl, AF_PACKET, sockaddr_ll,
+               SOL_PACKET, PACKET_ADD_MEMBERSHIP, PACKET_DROP_MEMBERSHIP, PACKET_MR_MULTICAST,
+               PACKET_MR_PROMISC, PACKET_MR_ALLMULTI, PACKET_RX_RING, PACKET_VERSION,
+               TP_STATUS_KERNEL, TP_STATUS_USER, packet_mreq, tpacket_req, tpacket2_hdr,
+               IP_MTU_DISCOVER, IPV6_MTU_DISCOVER, IP_PMTUDISC_DO, IP_PMTUDISC_DONT};
 
 pub const IPV6_UNICAST_HOPS: libc::c_int = 16;
 pub const IPV6_MULTICAST_IF: libc::c_int = 17;
@@ -40,6 +44,9 @@ pub const AI_NUMERICSERV: libc::c_int = 0x0400;
 pub const IPV6_JOIN_GROUP: libc::c_int = 20;
 #[cfg(target_os = "linux")]
 pub const IPV6_LEAVE_GROUP: libc::c_int = 21;
+#[cfg(target_os = "linux")]
+pub const TPACKET_V2: libc::c_int = 1;
+
 #[cfg(target_os = "macos")]
 pub use libc::{IPV6_JOIN_GROUP, IPV6_LEAVE_GROUP};
 
@@ -149,6 +156,71 @@ pub fn raise(sig: Signal) -> Result<(), SystemError> {
     }
 }
 
+pub fn kill(pid: libc::pid_t, sig: Signal) -> Result<(), SystemError> {
+    match unsafe { libc::kill(pid, sig as i32) } {
+        -1 => Err(SystemError::last_error()),
+        _ => Ok(()),
+    }
+}
+
+/// Returns a raw status of the terminated child process, or `None` if `nohang` and still running.
+pub fn waitpid(pid: libc::pid_t, nohang: bool) -> Result<Option<i32>, SystemError> {
+    let mut status = 0;
+    let options = if nohang { libc::WNOHANG } else { 0 };
+    match unsafe { libc::waitpid(pid, &mut status, options) } {
+        -1 => Err(SystemError::last_error()),
+        0 => Ok(None),
+        _ => Ok(Some(status)),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn pidfd_open(pid: libc::pid_t) -> Result<RawFd, SystemError> {
+    match unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) } {
+        -1 => Err(SystemError::last_error()),
+        fd => Ok(fd as RawFd),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn inotify_init() -> Result<RawFd, SystemError> {
+    match unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) } {
+        -1 => Err(SystemError::last_error()),
+        fd => Ok(fd),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn inotify_add_watch<S>(soc: &S, path: &CStr, mask: u32) -> Result<i32, SystemError>
+where
+    S: AsRawFd,
+{
+    match unsafe { libc::inotify_add_watch(soc.as_raw_fd(), path.as_ptr(), mask) } {
+        -1 => Err(SystemError::last_error()),
+        wd => Ok(wd),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn inotify_rm_watch<S>(soc: &S, wd: i32) -> Result<(), SystemError>
+where
+    S: AsRawFd,
+{
+    match unsafe { libc::inotify_rm_watch(soc.as_raw_fd(), wd) } {
+        -1 => Err(SystemError::last_error()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn eventfd(initval: u32, semaphore: bool) -> Result<RawFd, SystemError> {
+    let flags = libc::EFD_CLOEXEC | libc::EFD_NONBLOCK | if semaphore { libc::EFD_SEMAPHORE } else { 0 };
+    match unsafe { libc::eventfd(initval, flags) } {
+        -1 => Err(SystemError::last_error()),
+        fd => Ok(fd),
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub struct SystemError(Errno);
 
@@ -202,11 +274,14 @@ pub const ADDRESS_FAMILY_NOT_SUPPORTED: SystemError = SystemError(Errno(libc::EA
 // /// Transport endpoint is already connected.
 // pub const ALREADY_CONNECTED: SystemError = SystemError(Errno(libc::EISCONN));
 
-// /// Operation already in progress.
-// pub const ALREADY_STARTED: SystemError = SystemError(Errno(libc::EALREADY));
+/// Operation already in progress.
+pub const ALREADY_STARTED: SystemError = SystemError(Errno(libc::EALREADY));
 
-// /// Broken pipe.
-// pub const BROKEN_PIPE: SystemError = SystemError(Errno(libc::EPIPE));
+/// Broken pipe.
+pub const BROKEN_PIPE: SystemError = SystemError(Errno(libc::EPIPE));
+
+/// The system clock has been changed discontinuously.
+pub const CLOCK_CHANGED: SystemError = SystemError(Errno(libc::ETIME));
 
 /// A connection has been aborted.
 pub const CONNECTION_ABORTED: SystemError = SystemError(Errno(libc::ECONNABORTED));
@@ -217,14 +292,14 @@ pub const CONNECTION_ABORTED: SystemError = SystemError(Errno(libc::ECONNABORTED
 // /// Connection reset by peer.
 // pub const CONNECTION_RESET: SystemError = SystemError(Errno(libc::ECONNRESET));
 
-// /// Bad file descriptor.
-// pub const BAD_DESCRIPTOR: SystemError = SystemError(Errno(libc::EBADF));
+/// Bad file descriptor.
+pub const BAD_DESCRIPTOR: SystemError = SystemError(Errno(libc::EBADF));
 
 // /// Bad address.
 // pub const FAULT: SystemError = SystemError(Errno(libc::EFAULT));
 
-// /// No route to host.
-// pub const HOST_UNREACHABLE: SystemError = SystemError(Errno(libc::EHOSTUNREACH));
+/// No route to host.
+pub const HOST_UNREACHABLE: SystemError = SystemError(Errno(libc::EHOSTUNREACH));
 
 /// peration now in progress.
 pub const IN_PROGRESS: SystemError = SystemError(Errno(libc::EINPROGRESS));
@@ -235,8 +310,8 @@ pub const INTERRUPTED: SystemError = SystemError(Errno(libc::EINTR));
 /// Invalid argument.
 pub const INVALID_ARGUMENT: SystemError = SystemError(Errno(libc::EINVAL));
 
-// /// Message to long.
-// pub const MESSAGE_SIZE: SystemError = SystemError(Errno(libc::EMSGSIZE));
+/// Message to long.
+pub const MESSAGE_SIZE: SystemError = SystemError(Errno(libc::EMSGSIZE));
 
 /// The name was too long.
 pub const NAME_TOO_LONG: SystemError = SystemError(Errno(libc::ENAMETOOLONG));
@@ -604,6 +679,14 @@ pub fn if_nametoindex(name: &CStr) -> Result<u32, SystemError> {
     }
 }
 
+pub fn if_indextoname(index: u32) -> Result<String, SystemError> {
+    let mut buf = [0; libc::IF_NAMESIZE];
+    match unsafe { libc::if_indextoname(index, buf.as_mut_ptr()) } {
+        ptr if ptr.is_null() => Err(SystemError::last_error()),
+        _ => unsafe { Ok(CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned()) },
+    }
+}
+
 pub fn ioctl<S, D>(soc: &S, data: &mut D) -> Result<(), SystemError>
 where
     S: AsRawFd,
@@ -640,7 +723,6 @@ pub fn pipe() -> Result<(RawFd, RawFd), SystemError> {
 }
 
 #[cfg(target_os = "linux")]
-#[allow(dead_code)]
 pub fn pipe() -> Result<(RawFd, RawFd), SystemError> {
     let mut fds: [RawFd; 2] = unsafe { mem::uninitialized() };
     match unsafe { libc::pipe2(fds.as_mut_ptr(), O_CLOEXEC | O_NONBLOCK) } {
@@ -649,6 +731,29 @@ pub fn pipe() -> Result<(RawFd, RawFd), SystemError> {
     }
 }
 
+#[cfg(target_os = "linux")]
+pub fn pipe_size(fd: RawFd) -> Result<usize, SystemError> {
+    match unsafe { libc::fcntl(fd, libc::F_GETPIPE_SZ) } {
+        -1 => Err(SystemError::last_error()),
+        size => Ok(size as usize),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_pipe_size(fd: RawFd, size: usize) -> Result<usize, SystemError> {
+    match unsafe { libc::fcntl(fd, libc::F_SETPIPE_SZ, size as libc::c_int) } {
+        -1 => Err(SystemError::last_error()),
+        size => Ok(size as usize),
+    }
+}
+
+pub fn dup(fd: RawFd) -> Result<RawFd, SystemError> {
+    match unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) } {
+        -1 => Err(SystemError::last_error()),
+        fd => Ok(fd),
+    }
+}
+
 pub fn read<S>(soc: &S, buf: &mut [u8]) -> Result<usize, SystemError>
 where
     S: AsRawFd,
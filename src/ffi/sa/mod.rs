@@ -9,6 +9,11 @@ impl PodTrait for libc::sockaddr_in6 {}
 impl PodTrait for libc::sockaddr_storage {}
 #[cfg(unix)]
 impl PodTrait for libc::sockaddr_un {}
+#[cfg(target_os = "linux")]
+impl PodTrait for libc::sockaddr_nl {}
+
+#[cfg(target_os = "linux")]
+impl PodTrait for libc::sockaddr_ll {}
 
 #[cfg(target_os = "macos")]
 mod bsd;
@@ -188,6 +188,10 @@ pub fn if_nametoindex(_: &CStr) -> Result<u32, ()> {
     Err(())
 }
 
+pub fn if_indextoname(_: u32) -> Result<String, ()> {
+    Err(())
+}
+
 pub fn ioctl<T, C>(t: &T, cmd: &mut C) -> io::Result<()>
 where
     T: AsRawFd,
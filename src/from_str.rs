@@ -1,5 +1,6 @@
-use ffi::{if_nametoindex, ADDRESS_FAMILY_NOT_SUPPORTED};
-use ip::{IpAddr, IpAddrV4, IpAddrV6, LlAddr};
+use ffi::{if_nametoindex, ADDRESS_FAMILY_NOT_SUPPORTED, INVALID_ARGUMENT};
+use ip::{IpAddr, IpAddrRange, IpAddrV4, IpAddrV6, IpEndpoint, IpNetwork, IpNetworkV4, IpNetworkV6,
+         IpProtocol, LlAddr};
 
 use std::io;
 use std::str::{Chars, FromStr};
@@ -107,6 +108,46 @@ impl Parser for Dec8 {
     }
 }
 
+fn parse_dec<'a>(mut it: Chars<'a>, max: u32) -> Result<(u32, Chars<'a>)> {
+    let mut n = match it.next().and_then(|ch| ch.to_digit(10)) {
+        Some(i) => i as u64,
+        _ => return Err(ParseError),
+    };
+    loop {
+        let p = it.clone();
+        match it.next().and_then(|ch| ch.to_digit(10)) {
+            Some(i) => n = n * 10 + i as u64,
+            _ => return Ok((n as u32, p)),
+        }
+        if n > max as u64 {
+            return Err(ParseError);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Dec16;
+
+impl Parser for Dec16 {
+    type Output = u16;
+
+    fn parse<'a>(&self, it: Chars<'a>) -> Result<(Self::Output, Chars<'a>)> {
+        let (n, it) = try!(parse_dec(it, 0xFFFF));
+        Ok((n as u16, it))
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Dec32;
+
+impl Parser for Dec32 {
+    type Output = u32;
+
+    fn parse<'a>(&self, it: Chars<'a>) -> Result<(Self::Output, Chars<'a>)> {
+        parse_dec(it, 0xFFFF_FFFF)
+    }
+}
+
 #[derive(Clone, Copy)]
 struct Hex08;
 
@@ -402,8 +443,8 @@ impl Parser for ScopeId {
                     return Ok((id, it));
                 }
             }
-            if let Ok((dec, it)) = Dec8.parse(it.clone()) {
-                return Ok((dec as u32, it));
+            if let Ok((dec, it)) = Dec32.parse(it.clone()) {
+                return Ok((dec, it));
             }
         }
         Ok((0, it))
@@ -423,6 +464,12 @@ impl FromStr for LlAddr {
                 addr[4],
                 addr[5],
             ))
+        } else if let Ok(((a, (_, (b, (_, c)))), _)) = Eos(Cat(
+            Cat(Hex08, Hex08),
+            Cat(Lit('.'), Cat(Cat(Hex08, Hex08), Cat(Lit('.'), Cat(Hex08, Hex08)))),
+        )).parse(s.chars())
+        {
+            Ok(LlAddr::new(a.0, a.1, b.0, b.1, c.0, c.1))
         } else {
             Err(ADDRESS_FAMILY_NOT_SUPPORTED.into())
         }
@@ -479,6 +526,128 @@ impl FromStr for IpAddr {
     }
 }
 
+impl FromStr for IpNetworkV4 {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<IpNetworkV4> {
+        if let Ok((addr, it)) = Sep4By(Dec8, Lit('.')).parse(s.chars()) {
+            let addr = IpAddrV4::new(addr[0], addr[1], addr[2], addr[3]);
+            let net = if it.as_str().is_empty() {
+                IpNetworkV4::from(addr, 32)
+            } else if let Ok(((_, len), _)) = Eos(Cat(Lit('/'), Dec8)).parse(it.clone()) {
+                IpNetworkV4::from(addr, len as u16)
+            } else if let Ok(((_, mask), _)) = Eos(Cat(Lit('/'), Sep4By(Dec8, Lit('.'))))
+                .parse(it)
+            {
+                IpNetworkV4::new(addr, IpAddrV4::new(mask[0], mask[1], mask[2], mask[3]))
+            } else {
+                None
+            };
+            if let Some(net) = net {
+                return Ok(net);
+            }
+        }
+        Err(ADDRESS_FAMILY_NOT_SUPPORTED.into())
+    }
+}
+
+impl FromStr for IpNetworkV6 {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<IpNetworkV6> {
+        if let Ok((addr, it)) = IpV6.parse(s.chars()) {
+            let addr = IpAddrV6::new(
+                addr[0],
+                addr[1],
+                addr[2],
+                addr[3],
+                addr[4],
+                addr[5],
+                addr[6],
+                addr[7],
+            );
+            let net = if it.as_str().is_empty() {
+                IpNetworkV6::from(addr, 128)
+            } else if let Ok(((_, len), _)) = Eos(Cat(Lit('/'), Dec8)).parse(it) {
+                IpNetworkV6::from(addr, len as u16)
+            } else {
+                None
+            };
+            if let Some(net) = net {
+                return Ok(net);
+            }
+        }
+        Err(ADDRESS_FAMILY_NOT_SUPPORTED.into())
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<IpNetwork> {
+        match IpNetworkV4::from_str(s) {
+            Ok(v4) => Ok(IpNetwork::V4(v4)),
+            Err(_) => {
+                match IpNetworkV6::from_str(s) {
+                    Ok(v6) => Ok(IpNetwork::V6(v6)),
+                    Err(err) => Err(err),
+                }
+            }
+        }
+    }
+}
+
+impl<P: IpProtocol> FromStr for IpEndpoint<P> {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<IpEndpoint<P>> {
+        if let Ok(((addr, (_, port)), _)) = Eos(Cat(Sep4By(Dec8, Lit('.')), Cat(Lit(':'), Dec16)))
+            .parse(s.chars())
+        {
+            let addr = IpAddrV4::new(addr[0], addr[1], addr[2], addr[3]);
+            return Ok(IpEndpoint::new(addr, port));
+        }
+        if let Ok((((_, (addr, id)), (_, (_, port))), _)) =
+            Eos(Cat(
+                Cat(Lit('['), Cat(IpV6, ScopeId)),
+                Cat(Lit(']'), Cat(Lit(':'), Dec16)),
+            )).parse(s.chars())
+        {
+            let addr = IpAddrV6::with_scope_id(
+                addr[0],
+                addr[1],
+                addr[2],
+                addr[3],
+                addr[4],
+                addr[5],
+                addr[6],
+                addr[7],
+                id,
+            );
+            return Ok(IpEndpoint::new(addr, port));
+        }
+        Err(ADDRESS_FAMILY_NOT_SUPPORTED.into())
+    }
+}
+
+impl FromStr for IpAddrRange {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<IpAddrRange> {
+        let (start, end) = match s.find('-') {
+            Some(i) => (try!(IpAddr::from_str(&s[..i])), try!(IpAddr::from_str(&s[i + 1..]))),
+            None => {
+                let addr = try!(IpAddr::from_str(s));
+                (addr, addr)
+            }
+        };
+        match IpAddrRange::new(start, end) {
+            Some(range) => Ok(range),
+            None => Err(INVALID_ARGUMENT.into()),
+        }
+    }
+}
+
 #[test]
 fn test_lit() {
     assert_eq!(Lit('.').parse(".0".chars()).unwrap().0, ());
@@ -556,6 +725,18 @@ fn test_lladdr() {
         LlAddr::from_str("FF:ff:FF:fF:Ff:ff").unwrap(),
         LlAddr::new(255, 255, 255, 255, 255, 255)
     );
+    assert_eq!(
+        LlAddr::from_str("aa-bb-cc-dd-ee-ff").unwrap(),
+        LlAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff)
+    );
+    assert_eq!(
+        LlAddr::from_str("0011.2233.AABB").unwrap(),
+        LlAddr::new(0x00, 0x11, 0x22, 0x33, 0xaa, 0xbb)
+    );
+    assert!(LlAddr::from_str("0011.2233.aab").is_err());
+    assert!(LlAddr::from_str("0011.2233.aabb.").is_err());
+    assert!(LlAddr::from_str("0011:2233:aabb").is_err());
+    assert!(LlAddr::from_str("00:11:22:33:44").is_err());
 }
 
 #[test]
@@ -705,3 +886,118 @@ fn test_ipaddr() {
         )
     );
 }
+
+#[test]
+fn test_ip_network_v4() {
+    assert_eq!(
+        IpNetworkV4::from_str("10.0.0.0/8").unwrap(),
+        IpNetworkV4::from(IpAddrV4::new(10, 0, 0, 0), 8).unwrap()
+    );
+    assert_eq!(
+        IpNetworkV4::from_str("192.168.0.1/255.255.255.0").unwrap(),
+        IpNetworkV4::from(IpAddrV4::new(192, 168, 0, 1), 24).unwrap()
+    );
+    assert_eq!(
+        IpNetworkV4::from_str("192.168.0.1").unwrap(),
+        IpNetworkV4::from(IpAddrV4::new(192, 168, 0, 1), 32).unwrap()
+    );
+    assert_eq!(
+        IpNetworkV4::from_str("0.0.0.0/0").unwrap(),
+        IpNetworkV4::from(IpAddrV4::any(), 0).unwrap()
+    );
+    assert!(IpNetworkV4::from_str("10.0.0.0/33").is_err());
+    assert!(IpNetworkV4::from_str("10.0.0.0/").is_err());
+    assert!(IpNetworkV4::from_str("10.0.0.0/8/8").is_err());
+    assert!(IpNetworkV4::from_str("10.0.0.0/255.0.255.0").is_err());
+    assert!(IpNetworkV4::from_str("10.0.0/8").is_err());
+}
+
+#[test]
+fn test_ip_network_v6() {
+    assert_eq!(
+        IpNetworkV6::from_str("2001:db8::/32").unwrap(),
+        IpNetworkV6::from(IpAddrV6::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0), 32).unwrap()
+    );
+    assert_eq!(
+        IpNetworkV6::from_str("::1").unwrap(),
+        IpNetworkV6::from(IpAddrV6::loopback(), 128).unwrap()
+    );
+    assert_eq!(
+        IpNetworkV6::from_str("::ffff:10.0.0.0/104").unwrap(),
+        IpNetworkV6::from(IpAddrV6::v4_mapped(&IpAddrV4::new(10, 0, 0, 0)), 104).unwrap()
+    );
+    assert!(IpNetworkV6::from_str("2001:db8::/129").is_err());
+    assert_eq!(
+        IpNetworkV6::from_str("::/0").unwrap(),
+        IpNetworkV6::from(IpAddrV6::any(), 0).unwrap()
+    );
+    assert!(IpNetworkV6::from_str("2001:db8::%1/64").is_err());
+}
+
+#[test]
+fn test_ip_network() {
+    for s in &["10.0.0.0/8", "192.168.0.1/24", "2001:db8::/32", "fe80::1/64"] {
+        assert_eq!(IpNetwork::from_str(s).unwrap().to_string(), *s);
+    }
+    assert!(IpNetwork::from_str("10.0.0.0/8").unwrap() != IpNetwork::from_str("::a00:0/8").unwrap());
+    assert!(IpNetwork::from_str("example.com/8").is_err());
+}
+
+#[test]
+fn test_ip_addr_range() {
+    let range = IpAddrRange::from_str("10.0.0.5-10.0.0.20").unwrap();
+    assert_eq!(range.start(), IpAddr::V4(IpAddrV4::new(10, 0, 0, 5)));
+    assert_eq!(range.end(), IpAddr::V4(IpAddrV4::new(10, 0, 0, 20)));
+    assert_eq!(IpAddrRange::from_str("::1").unwrap().len(), 1);
+    assert_eq!(IpAddrRange::from_str("fe80::-fe80::ff").unwrap().len(), 256);
+    assert!(IpAddrRange::from_str("10.0.0.20-10.0.0.5").is_err());
+    assert!(IpAddrRange::from_str("10.0.0.5-::1").is_err());
+    assert!(IpAddrRange::from_str("10.0.0.5-").is_err());
+    assert!(IpAddrRange::from_str("10.0.0.5 - 10.0.0.20").is_err());
+}
+
+#[test]
+fn test_dec() {
+    assert_eq!(Dec16.parse("0".chars()).unwrap().0, 0);
+    assert_eq!(Dec16.parse("65535:".chars()).unwrap().0, 65535);
+    assert!(Dec16.parse("65536".chars()).is_err());
+    assert!(Dec16.parse(":1".chars()).is_err());
+    assert_eq!(Dec32.parse("4294967295".chars()).unwrap().0, 4294967295);
+    assert!(Dec32.parse("4294967296".chars()).is_err());
+    assert!(Dec32.parse("99999999999999999999".chars()).is_err());
+}
+
+#[test]
+fn test_ip_endpoint() {
+    use ip::{TcpEndpoint, UdpEndpoint};
+    use std::ffi::CString;
+
+    let ep = TcpEndpoint::from_str("192.168.0.1:80").unwrap();
+    assert_eq!(ep, TcpEndpoint::new(IpAddrV4::new(192, 168, 0, 1), 80));
+    assert_eq!(ep.to_string(), "192.168.0.1:80");
+
+    let ep = UdpEndpoint::from_str("[2001:db8::1]:53").unwrap();
+    assert_eq!(ep, UdpEndpoint::new(IpAddrV6::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1), 53));
+    assert_eq!(ep.to_string(), "[2001:db8::1]:53");
+
+    if let Ok(id) = if_nametoindex(&CString::new("lo").unwrap()) {
+        let ep = UdpEndpoint::from_str("[fe80::1%lo]:546").unwrap();
+        assert_eq!(ep.addr(), IpAddr::V6(IpAddrV6::with_scope_id(0xfe80, 0, 0, 0, 0, 0, 0, 1, id)));
+        assert_eq!(UdpEndpoint::from_str(&ep.to_string()).unwrap(), ep);
+    }
+
+    let ep = UdpEndpoint::from_str("[fe80::1%300]:546").unwrap();
+    assert_eq!(ep.addr(), IpAddr::V6(IpAddrV6::with_scope_id(0xfe80, 0, 0, 0, 0, 0, 0, 1, 300)));
+    assert_eq!(UdpEndpoint::from_str(&ep.to_string()).unwrap(), ep);
+
+    assert!(TcpEndpoint::from_str("192.168.0.1").is_err());
+    assert!(TcpEndpoint::from_str("192.168.0.1:65536").is_err());
+    assert!(TcpEndpoint::from_str("192.168.0.1:").is_err());
+    assert!(TcpEndpoint::from_str("::1:80").is_err());
+    assert!(TcpEndpoint::from_str("[::1]").is_err());
+    assert!(TcpEndpoint::from_str("[::1]:80x").is_err());
+    assert_eq!(
+        TcpEndpoint::from_str("[::ffff:1.2.3.4]:80").unwrap(),
+        TcpEndpoint::new(IpAddrV6::v4_mapped(&IpAddrV4::new(1, 2, 3, 4)), 80)
+    );
+}
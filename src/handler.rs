@@ -8,6 +8,11 @@ pub trait Complete<R, E>: Send + 'static {
     fn success(self, this: &mut ThreadIoContext, res: R);
 
     fn failure(self, this: &mut ThreadIoContext, err: E);
+
+    /// Returns true if the deadline of the operation is expired.
+    fn is_expired(&self) -> bool {
+        false
+    }
 }
 
 pub trait Handler<R, E>: Send + 'static {
@@ -25,6 +30,16 @@ pub trait Handler<R, E>: Send + 'static {
     fn wrap_timeout<W>(self, ctx: &Cancel, timeout: &Timeout, wrapper: W) -> Self::Output
     where
         W: FnOnce(&IoContext, Self::WrappedHandler);
+
+    /// Wraps the operation which is canceled by the `cancel` of the `ctx`, and has no timeout.
+    #[doc(hidden)]
+    fn wrap_cancel<W>(self, ctx: &Cancel, wrapper: W) -> Self::Output
+    where
+        Self: Sized,
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        self.wrap(ctx.as_ctx(), wrapper)
+    }
 }
 
 pub trait AsyncReadOp: Cancel + Send + 'static {
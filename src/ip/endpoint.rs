@@ -1,10 +1,13 @@
 use ffi::{AF_INET, AF_INET6, SockAddr, socklen_t, sockaddr, sockaddr_in, sockaddr_in6,
-          sockaddr_storage};
+          sockaddr_storage, if_indextoname, ADDRESS_FAMILY_NOT_SUPPORTED};
 use core::Endpoint;
 use ip::{IpProtocol, IpAddrV4, IpAddrV6, IpAddr};
 
+use std::io;
 use std::fmt;
 use std::mem;
+use std::net;
+use std::convert::TryFrom;
 use std::marker::PhantomData;
 
 /// The endpoint of internet protocol.
@@ -90,6 +93,16 @@ where
         u16::from_be(sin.sin_port)
     }
 
+    /// Returns a interface name of the scope id.
+    ///
+    /// Returns `None` if the endpoint has no scope id or the interface is not found.
+    pub fn scope_name(&self) -> Option<String> {
+        match self.addr() {
+            IpAddr::V6(ref addr) if addr.scope_id() != 0 => if_indextoname(addr.scope_id()).ok(),
+            _ => None,
+        }
+    }
+
     pub fn protocol(&self) -> P {
         if self.is_v4() {
             return P::v4();
@@ -147,17 +160,20 @@ impl<P: IpProtocol> fmt::Display for IpEndpoint<P> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.addr() {
             IpAddr::V4(addr) => write!(f, "{}:{}", addr, self.port()),
-            IpAddr::V6(addr) => write!(f, "[{}]:{}", addr, self.port()),
+            IpAddr::V6(addr) => {
+                // the scope id is not looked up, use the `scope_name` to get the interface name.
+                match addr.scope_id() {
+                    0 => write!(f, "[{}]:{}", addr, self.port()),
+                    id => write!(f, "[{}%{}]:{}", addr, id, self.port()),
+                }
+            }
         }
     }
 }
 
 impl<P: IpProtocol> fmt::Debug for IpEndpoint<P> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.addr() {
-            IpAddr::V4(addr) => write!(f, "{}:{}", addr, self.port()),
-            IpAddr::V6(addr) => write!(f, "[{}]:{}", addr, self.port()),
-        }
+        fmt::Display::fmt(self, f)
     }
 }
 
@@ -168,10 +184,10 @@ pub trait IntoEndpoint<P> {
 
 impl<P: IpProtocol> IntoEndpoint<P> for P {
     fn into_endpoint(self, port: u16) -> IpEndpoint<P> {
-        if &self == &P::v4() {
+        if self.family_type() == AF_INET {
             return IpEndpoint::from((IpAddrV4::any(), port));
         }
-        if &self == &P::v6() {
+        if self.family_type() == AF_INET6 {
             return IpEndpoint::from((IpAddrV6::any(), port));
         }
         unreachable!("Invalid protocol");
@@ -271,6 +287,72 @@ impl<P: IpProtocol> From<(IpAddrV6, u16)> for IpEndpoint<P> {
     }
 }
 
+impl<P: IpProtocol> From<net::SocketAddrV4> for IpEndpoint<P> {
+    fn from(sa: net::SocketAddrV4) -> Self {
+        IpEndpoint::from((IpAddrV4::from(*sa.ip()), sa.port()))
+    }
+}
+
+impl<P: IpProtocol> From<net::SocketAddrV6> for IpEndpoint<P> {
+    fn from(sa: net::SocketAddrV6) -> Self {
+        let addr = IpAddrV6::from(sa.ip().octets(), sa.scope_id());
+        let mut ep = IpEndpoint::from((addr, sa.port()));
+        unsafe {
+            let sin6 = &mut *(&mut ep.ss.sa as *mut _ as *mut sockaddr_in6);
+            sin6.sin6_flowinfo = sa.flowinfo().to_be();
+        }
+        ep
+    }
+}
+
+impl<P: IpProtocol> From<net::SocketAddr> for IpEndpoint<P> {
+    fn from(sa: net::SocketAddr) -> Self {
+        match sa {
+            net::SocketAddr::V4(sa) => sa.into(),
+            net::SocketAddr::V6(sa) => sa.into(),
+        }
+    }
+}
+
+impl<P: IpProtocol> From<IpEndpoint<P>> for net::SocketAddr {
+    fn from(ep: IpEndpoint<P>) -> Self {
+        match ep.addr() {
+            IpAddr::V4(addr) => net::SocketAddr::V4(net::SocketAddrV4::new(addr.into(), ep.port())),
+            IpAddr::V6(_) => net::SocketAddr::V6(net::SocketAddrV6::try_from(ep).unwrap()),
+        }
+    }
+}
+
+impl<P: IpProtocol> TryFrom<IpEndpoint<P>> for net::SocketAddrV4 {
+    type Error = io::Error;
+
+    fn try_from(ep: IpEndpoint<P>) -> io::Result<Self> {
+        match ep.addr() {
+            IpAddr::V4(addr) => Ok(net::SocketAddrV4::new(addr.into(), ep.port())),
+            IpAddr::V6(_) => Err(ADDRESS_FAMILY_NOT_SUPPORTED.into()),
+        }
+    }
+}
+
+impl<P: IpProtocol> TryFrom<IpEndpoint<P>> for net::SocketAddrV6 {
+    type Error = io::Error;
+
+    fn try_from(ep: IpEndpoint<P>) -> io::Result<Self> {
+        match ep.addr() {
+            IpAddr::V4(_) => Err(ADDRESS_FAMILY_NOT_SUPPORTED.into()),
+            IpAddr::V6(addr) => {
+                let sin6 = unsafe { &*(&ep.ss.sa as *const _ as *const sockaddr_in6) };
+                Ok(net::SocketAddrV6::new(
+                    addr.into(),
+                    ep.port(),
+                    u32::from_be(sin6.sin6_flowinfo),
+                    addr.scope_id(),
+                ))
+            }
+        }
+    }
+}
+
 #[test]
 fn test_endpoint_v4() {
     use ip::UdpEndpoint;
@@ -305,3 +387,44 @@ fn test_endpoint_cmp() {
     assert!(a < b);
     assert!(b < c);
 }
+
+#[test]
+fn test_endpoint_display() {
+    use ffi::if_nametoindex;
+    use ip::TcpEndpoint;
+    use std::ffi::CString;
+
+    let ep = TcpEndpoint::new(IpAddrV4::new(192, 168, 0, 1), 80);
+    assert_eq!(ep.to_string(), "192.168.0.1:80");
+    let ep = TcpEndpoint::new(IpAddrV6::loopback(), 443);
+    assert_eq!(ep.to_string(), "[::1]:443");
+    if let Ok(id) = if_nametoindex(&CString::new("lo").unwrap()) {
+        let ep = TcpEndpoint::new(IpAddrV6::with_scope_id(0xfe80, 0, 0, 0, 0, 0, 0, 1, id), 22);
+        assert_eq!(ep.to_string(), format!("[fe80::1%{}]:22", id));
+        assert_eq!(ep.scope_name(), Some("lo".to_string()));
+    }
+    let ep = TcpEndpoint::new(IpAddrV6::with_scope_id(0xfe80, 0, 0, 0, 0, 0, 0, 1, 0xFFFF), 22);
+    assert_eq!(format!("{:?}", ep), "[fe80::1%65535]:22");
+    assert_eq!(ep.scope_name(), None);
+    assert_eq!(TcpEndpoint::new(IpAddrV6::loopback(), 443).scope_name(), None);
+}
+
+#[test]
+fn test_endpoint_std_net() {
+    use ip::UdpEndpoint;
+
+    let sa: net::SocketAddr = "10.0.0.1:53".parse().unwrap();
+    let ep = UdpEndpoint::from(sa);
+    assert_eq!(ep, UdpEndpoint::new(IpAddrV4::new(10, 0, 0, 1), 53));
+    assert_eq!(net::SocketAddr::from(ep.clone()), sa);
+    assert!(net::SocketAddrV6::try_from(ep.clone()).is_err());
+    assert_eq!(net::SocketAddrV4::try_from(ep).unwrap().port(), 53);
+
+    let sa = net::SocketAddrV6::new("fe80::1".parse().unwrap(), 546, 0x12345, 2);
+    let ep = UdpEndpoint::from(sa);
+    assert_eq!(ep.addr(), IpAddr::V6(IpAddrV6::with_scope_id(0xfe80, 0, 0, 0, 0, 0, 0, 1, 2)));
+    assert_eq!(ep.port(), 546);
+    assert_eq!(net::SocketAddrV6::try_from(ep.clone()).unwrap(), sa);
+    assert_eq!(net::SocketAddr::from(ep.clone()), net::SocketAddr::V6(sa));
+    assert!(net::SocketAddrV4::try_from(ep).is_err());
+}
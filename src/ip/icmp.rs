@@ -1,4 +1,4 @@
-use ffi::{AF_INET, AF_INET6, AF_UNSPEC, SOCK_RAW, IPPROTO_ICMP, IPPROTO_ICMPV6};
+use ffi::{AF_INET, AF_INET6, AF_UNSPEC, SOCK_DGRAM, SOCK_RAW, IPPROTO_ICMP, IPPROTO_ICMPV6};
 use core::Protocol;
 use handler::Handler;
 use dgram_socket::DgramSocket;
@@ -12,9 +12,49 @@ use std::mem;
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub struct Icmp {
     family: i32,
+    socket_type: i32,
     protocol: i32,
 }
 
+impl Icmp {
+    /// Represents a unprivileged ICMP (ping socket).
+    ///
+    /// The socket is permitted to the users in the `net.ipv4.ping_group_range` sysctl.
+    /// The kernel manages the echo identifier, and receives only the echo replies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpProtocol, Icmp};
+    ///
+    /// assert!(Icmp::v4_dgram() != Icmp::v4());
+    /// ```
+    pub fn v4_dgram() -> Icmp {
+        Icmp {
+            family: AF_INET as i32,
+            socket_type: SOCK_DGRAM as i32,
+            protocol: IPPROTO_ICMP,
+        }
+    }
+
+    /// Represents a unprivileged ICMPv6 (ping socket).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpProtocol, Icmp};
+    ///
+    /// assert!(Icmp::v6_dgram() != Icmp::v6());
+    /// ```
+    pub fn v6_dgram() -> Icmp {
+        Icmp {
+            family: AF_INET6 as i32,
+            socket_type: SOCK_DGRAM as i32,
+            protocol: IPPROTO_ICMPV6,
+        }
+    }
+}
+
 impl Protocol for Icmp {
     type Endpoint = IpEndpoint<Self>;
 
@@ -25,7 +65,7 @@ impl Protocol for Icmp {
     }
 
     fn socket_type(&self) -> i32 {
-        SOCK_RAW as i32
+        self.socket_type
     }
 
     fn protocol_type(&self) -> i32 {
@@ -63,6 +103,7 @@ impl IpProtocol for Icmp {
     fn v4() -> Icmp {
         Icmp {
             family: AF_INET as i32,
+            socket_type: SOCK_RAW as i32,
             protocol: IPPROTO_ICMP,
         }
     }
@@ -81,6 +122,7 @@ impl IpProtocol for Icmp {
     fn v6() -> Icmp {
         Icmp {
             family: AF_INET6 as i32,
+            socket_type: SOCK_RAW as i32,
             protocol: IPPROTO_ICMPV6,
         }
     }
@@ -101,6 +143,7 @@ impl<'a> ResolverQuery<Icmp> for &'a str {
         ResolverIter::new(
             &Icmp {
                 family: AF_UNSPEC,
+                socket_type: SOCK_RAW as i32,
                 protocol: 0,
             },
             self.as_ref(),
@@ -124,6 +167,8 @@ fn test_icmp() {
     assert!(Icmp::v4() == Icmp::v4());
     assert!(Icmp::v6() == Icmp::v6());
     assert!(Icmp::v4() != Icmp::v6());
+    assert!(Icmp::v4_dgram() != Icmp::v4());
+    assert!(Icmp::v6_dgram() != Icmp::v6());
 }
 
 #[test]
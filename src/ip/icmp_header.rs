@@ -0,0 +1,211 @@
+use ffi::AF_INET6;
+use core::Protocol;
+use ip::Icmp;
+
+/// ICMP echo reply.
+pub const ICMP_ECHO_REPLY: u8 = 0;
+
+/// ICMP destination unreachable.
+pub const ICMP_DEST_UNREACH: u8 = 3;
+
+/// ICMP echo request.
+pub const ICMP_ECHO_REQUEST: u8 = 8;
+
+/// ICMP time exceeded.
+pub const ICMP_TIME_EXCEEDED: u8 = 11;
+
+/// ICMPv6 destination unreachable.
+pub const ICMPV6_DEST_UNREACH: u8 = 1;
+
+/// ICMPv6 packet too big.
+pub const ICMPV6_PACKET_TOO_BIG: u8 = 2;
+
+/// ICMPv6 time exceeded.
+pub const ICMPV6_TIME_EXCEEDED: u8 = 3;
+
+/// ICMPv6 echo request.
+pub const ICMPV6_ECHO_REQUEST: u8 = 128;
+
+/// ICMPv6 echo reply.
+pub const ICMPV6_ECHO_REPLY: u8 = 129;
+
+/// Returns a internet checksum (RFC 1071) of the bytes.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::ip::icmp_checksum;
+///
+/// assert_eq!(icmp_checksum(&[0x08, 0x00, 0x00, 0x00, 0x12, 0x34, 0x00, 0x01]), 0xe5ca);
+/// ```
+pub fn icmp_checksum(buf: &[u8]) -> u16 {
+    let mut sum = 0u32;
+    let mut it = buf.chunks(2);
+    while let Some(ch) = it.next() {
+        sum += if ch.len() == 2 {
+            (ch[0] as u32) << 8 | ch[1] as u32
+        } else {
+            (ch[0] as u32) << 8
+        };
+    }
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// The ICMP and ICMPv6 message header.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::ip::{IpProtocol, Icmp, IcmpHeader, ICMP_ECHO_REQUEST};
+///
+/// let buf = IcmpHeader::echo_request(&Icmp::v4(), 0x1234, 1).encode(&Icmp::v4(), b"ping");
+/// let (hdr, data) = IcmpHeader::decode(&buf).unwrap();
+/// assert_eq!(hdr.kind(), ICMP_ECHO_REQUEST);
+/// assert_eq!(hdr.identifier(), 0x1234);
+/// assert_eq!(hdr.sequence(), 1);
+/// assert_eq!(data, b"ping");
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct IcmpHeader {
+    bytes: [u8; 8],
+}
+
+impl IcmpHeader {
+    /// Returns a header of the type and code.
+    pub fn new(kind: u8, code: u8) -> IcmpHeader {
+        IcmpHeader { bytes: [kind, code, 0, 0, 0, 0, 0, 0] }
+    }
+
+    /// Returns a echo request header for the protocol.
+    pub fn echo_request(pro: &Icmp, ident: u16, seq: u16) -> IcmpHeader {
+        let kind = if pro.family_type() == AF_INET6 {
+            ICMPV6_ECHO_REQUEST
+        } else {
+            ICMP_ECHO_REQUEST
+        };
+        let mut hdr = IcmpHeader::new(kind, 0);
+        hdr.set_identifier(ident);
+        hdr.set_sequence(seq);
+        hdr
+    }
+
+    /// Returns a header and the rest of bytes.
+    ///
+    /// Returns `None` if the bytes are shorter than the header.
+    ///
+    /// The checksum is not verified, the kernel drops a received message that has a bad checksum.
+    /// An ICMPv4 message can be checked by `icmp_checksum(buf) == 0`.
+    pub fn decode(buf: &[u8]) -> Option<(IcmpHeader, &[u8])> {
+        if buf.len() < 8 {
+            return None;
+        }
+        let mut hdr = IcmpHeader::default();
+        hdr.bytes.copy_from_slice(&buf[..8]);
+        Some((hdr, &buf[8..]))
+    }
+
+    /// Returns a message of the protocol with the payload, the checksum is computed.
+    ///
+    /// The ICMPv6 checksum includes the IPv6 pseudo header, which is computed by the kernel.
+    pub fn encode(&self, pro: &Icmp, payload: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(8 + payload.len());
+        buf.extend_from_slice(&self.bytes);
+        buf.extend_from_slice(payload);
+        buf[2] = 0;
+        buf[3] = 0;
+        if pro.family_type() != AF_INET6 {
+            let sum = icmp_checksum(&buf);
+            buf[2] = (sum >> 8) as u8;
+            buf[3] = sum as u8;
+        }
+        buf
+    }
+
+    /// Returns a message type.
+    pub fn kind(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    /// Returns a message code.
+    pub fn code(&self) -> u8 {
+        self.bytes[1]
+    }
+
+    /// Returns a checksum.
+    pub fn checksum(&self) -> u16 {
+        (self.bytes[2] as u16) << 8 | self.bytes[3] as u16
+    }
+
+    /// Returns a echo identifier.
+    pub fn identifier(&self) -> u16 {
+        (self.bytes[4] as u16) << 8 | self.bytes[5] as u16
+    }
+
+    /// Sets a echo identifier.
+    pub fn set_identifier(&mut self, ident: u16) {
+        self.bytes[4] = (ident >> 8) as u8;
+        self.bytes[5] = ident as u8;
+    }
+
+    /// Returns a echo sequence number.
+    pub fn sequence(&self) -> u16 {
+        (self.bytes[6] as u16) << 8 | self.bytes[7] as u16
+    }
+
+    /// Sets a echo sequence number.
+    pub fn set_sequence(&mut self, seq: u16) {
+        self.bytes[6] = (seq >> 8) as u8;
+        self.bytes[7] = seq as u8;
+    }
+
+    /// Returns a next-hop MTU of ICMP fragmentation needed or ICMPv6 packet too big.
+    pub fn mtu(&self) -> u32 {
+        match self.kind() {
+            ICMPV6_PACKET_TOO_BIG => {
+                (self.bytes[4] as u32) << 24 | (self.bytes[5] as u32) << 16 |
+                    (self.bytes[6] as u32) << 8 | self.bytes[7] as u32
+            }
+            _ => (self.bytes[6] as u32) << 8 | self.bytes[7] as u32,
+        }
+    }
+
+    /// Returns true if the header is a echo reply.
+    pub fn is_echo_reply(&self) -> bool {
+        self.kind() == ICMP_ECHO_REPLY && self.code() == 0 ||
+            self.kind() == ICMPV6_ECHO_REPLY && self.code() == 0
+    }
+}
+
+#[test]
+fn test_icmp_checksum() {
+    assert_eq!(icmp_checksum(&[]), 0xffff);
+    assert_eq!(icmp_checksum(&[0xff, 0xff]), 0);
+    assert_eq!(icmp_checksum(&[0x01]), 0xfeff);
+
+    use ip::IpProtocol;
+
+    let buf = IcmpHeader::new(ICMP_ECHO_REPLY, 0).encode(&Icmp::v4(), b"abc");
+    assert_eq!(icmp_checksum(&buf), 0);
+
+    let buf = IcmpHeader::new(ICMPV6_DEST_UNREACH, 0).encode(&Icmp::v6(), b"abc");
+    assert_eq!(IcmpHeader::decode(&buf).unwrap().0.checksum(), 0);
+}
+
+#[test]
+fn test_icmp_header() {
+    use ip::IpProtocol;
+
+    let buf = IcmpHeader::echo_request(&Icmp::v6(), 1, 2).encode(&Icmp::v6(), &[]);
+    let (hdr, data) = IcmpHeader::decode(&buf).unwrap();
+    assert_eq!(hdr.kind(), ICMPV6_ECHO_REQUEST);
+    assert_eq!(hdr.checksum(), 0);
+    assert_eq!(hdr.identifier(), 1);
+    assert_eq!(hdr.sequence(), 2);
+    assert!(data.is_empty());
+    assert!(IcmpHeader::decode(&buf[..7]).is_none());
+    assert!(IcmpHeader::new(ICMP_ECHO_REPLY, 0).is_echo_reply());
+    assert!(!IcmpHeader::new(ICMP_ECHO_REQUEST, 0).is_echo_reply());
+}
@@ -44,56 +44,88 @@ fn sub_assign(bytes: &mut [u8], mut rhs: i64) {
     }
 }
 
+fn checked_offset(val: u128, rhs: i128, max: u128) -> Option<u128> {
+    let res = if rhs < 0 {
+        val.checked_sub(-rhs as u128)
+    } else {
+        val.checked_add(rhs as u128)
+    };
+    res.filter(|&res| res <= max)
+}
+
+/// Writes the IP-v6 address in RFC 5952 canonical text, or in the full form if `{:#}`.
 fn fmt_v6(bytes: &[u8; 16], f: &mut fmt::Formatter) -> fmt::Result {
-    let ar: &[u16; 8] = unsafe { mem::transmute(bytes) };
+    let mut ar = [0u16; 8];
+    for (i, it) in ar.iter_mut().enumerate() {
+        *it = (bytes[i * 2] as u16) << 8 | bytes[i * 2 + 1] as u16;
+    }
+
+    if f.alternate() {
+        return write!(
+            f,
+            "{:04x}:{:04x}:{:04x}:{:04x}:{:04x}:{:04x}:{:04x}:{:04x}",
+            ar[0],
+            ar[1],
+            ar[2],
+            ar[3],
+            ar[4],
+            ar[5],
+            ar[6],
+            ar[7]
+        );
+    }
+
+    // the IP-v4 mapped address is written with the dotted decimal notation.
+    let (len, v4) = if ar[..5].iter().all(|&e| e == 0) && ar[5] == 0xFFFF {
+        (6, true)
+    } else {
+        (8, false)
+    };
+
+    // finds the longest run of the zero fields, the first one if same length.
     let mut cnt = 0;
     let mut max_idx = 0;
     let mut max_cnt = 0;
-    for (i, e) in ar.iter().enumerate() {
-        if *e != 0 {
+    for (i, &e) in ar[..len].iter().enumerate() {
+        if e == 0 {
+            cnt += 1;
             if max_cnt < cnt {
-                max_idx = i - cnt;
+                max_idx = i + 1 - cnt;
                 max_cnt = cnt;
             }
-            cnt = 0;
         } else {
-            cnt += 1;
+            cnt = 0;
         }
     }
-    if max_cnt < cnt {
-        max_idx = ar.len() - cnt;
-        max_cnt = cnt;
-    }
-
-    if max_idx == 0 && max_cnt == 0 {
-        return write!(
-            f,
-            "{:x}:{:x}:{:x}:{:x}:{:x}:{:x}:{:x}:{:x}",
-            u16::from_be(ar[0]),
-            u16::from_be(ar[1]),
-            u16::from_be(ar[2]),
-            u16::from_be(ar[3]),
-            u16::from_be(ar[4]),
-            u16::from_be(ar[5]),
-            u16::from_be(ar[6]),
-            u16::from_be(ar[7])
-        );
-    }
 
-    if max_idx == 0 {
-        write!(f, ":")?;
+    // "::" must not be used to shorten just one zero field.
+    if max_cnt < 2 {
+        for (i, e) in ar[..len].iter().enumerate() {
+            if i != 0 {
+                write!(f, ":")?;
+            }
+            write!(f, "{:x}", e)?;
+        }
     } else {
-        for i in 0..max_idx {
-            write!(f, "{:x}:", u16::from_be(ar[i]))?;
+        for e in &ar[..max_idx] {
+            write!(f, "{:x}:", e)?;
+        }
+        if max_idx == 0 {
+            write!(f, ":")?;
+        }
+        for e in &ar[max_idx + max_cnt..len] {
+            write!(f, ":{:x}", e)?;
+        }
+        if max_idx + max_cnt == len {
+            write!(f, ":")?;
         }
     }
 
-    if max_idx + max_cnt == 8 {
-        write!(f, ":")?;
-    } else {
-        for i in max_idx + max_cnt..ar.len() {
-            write!(f, ":{:x}", u16::from_be(ar[i]))?;
+    if v4 {
+        if max_idx + max_cnt != len {
+            write!(f, ":")?;
         }
+        write!(f, "{}.{}.{}.{}", bytes[12], bytes[13], bytes[14], bytes[15])?;
     }
     Ok(())
 }
@@ -148,6 +180,123 @@ impl LlAddr {
     pub fn oui(&self) -> i32 {
         ((self.bytes[0] as i32 * 256 + self.bytes[1] as i32) * 256 + self.bytes[2] as i32)
     }
+
+    /// Returns a LL-address which is added `rhs`, or `None` if overflow occurred.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use asyncio::ip::LlAddr;
+    ///
+    /// let mac = LlAddr::new(0, 0, 0, 0, 0, 0xff);
+    /// assert_eq!(mac.checked_add(1), Some(LlAddr::new(0, 0, 0, 0, 1, 0)));
+    /// assert_eq!(mac.checked_add(-256), None);
+    /// ```
+    pub fn checked_add(&self, rhs: i64) -> Option<LlAddr> {
+        checked_offset(self.to_u64() as u128, rhs as i128, 0xFFFF_FFFF_FFFF)
+            .map(|addr| LlAddr::from_u64(addr as u64))
+    }
+
+    /// Returns a LL-address which is subtracted `rhs`, or `None` if overflow occurred.
+    pub fn checked_sub(&self, rhs: i64) -> Option<LlAddr> {
+        checked_offset(self.to_u64() as u128, -(rhs as i128), 0xFFFF_FFFF_FFFF)
+            .map(|addr| LlAddr::from_u64(addr as u64))
+    }
+
+    fn to_u64(&self) -> u64 {
+        self.bytes.iter().fold(0, |acc, &n| acc << 8 | n as u64)
+    }
+
+    fn from_u64(mut addr: u64) -> LlAddr {
+        let mut bytes = [0; 6];
+        for it in bytes.iter_mut().rev() {
+            *it = (addr & 0xFF) as u8;
+            addr >>= 8;
+        }
+        LlAddr { bytes: bytes }
+    }
+
+    /// Returns a EUI-64 identifier which is inserted `FF:FE` into the middle.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use asyncio::ip::LlAddr;
+    ///
+    /// let mac = LlAddr::new(0x00, 0x11, 0x22, 0x33, 0x44, 0x55);
+    /// assert_eq!(mac.to_eui64(), [0x00, 0x11, 0x22, 0xff, 0xfe, 0x33, 0x44, 0x55]);
+    /// ```
+    pub fn to_eui64(&self) -> [u8; 8] {
+        [
+            self.bytes[0],
+            self.bytes[1],
+            self.bytes[2],
+            0xFF,
+            0xFE,
+            self.bytes[3],
+            self.bytes[4],
+            self.bytes[5],
+        ]
+    }
+
+    /// Returns a modified EUI-64 interface identifier (RFC 4291 appendix A).
+    ///
+    /// The universal/local bit of EUI-64 is inverted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use asyncio::ip::LlAddr;
+    ///
+    /// let mac = LlAddr::new(0x00, 0x11, 0x22, 0x33, 0x44, 0x55);
+    /// assert_eq!(mac.interface_id(), [0x02, 0x11, 0x22, 0xff, 0xfe, 0x33, 0x44, 0x55]);
+    /// ```
+    pub fn interface_id(&self) -> [u8; 8] {
+        let mut iid = self.to_eui64();
+        iid[0] ^= 0x02;
+        iid
+    }
+
+    /// Returns a stateless autoconfiguration address (RFC 4862) in the 64 bits prefix.
+    ///
+    /// Returns `None` if the prefix length is not 64.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use asyncio::ip::{LlAddr, IpNetworkV6};
+    ///
+    /// let mac = LlAddr::new(0x00, 0x11, 0x22, 0x33, 0x44, 0x55);
+    /// let prefix: IpNetworkV6 = "2001:db8:1:2::/64".parse().unwrap();
+    /// assert_eq!(mac.slaac(&prefix).unwrap().to_string(), "2001:db8:1:2:211:22ff:fe33:4455");
+    /// assert!(mac.slaac(&"2001:db8::/48".parse().unwrap()).is_none());
+    /// ```
+    pub fn slaac(&self, prefix: &IpNetworkV6) -> Option<IpAddrV6> {
+        if prefix.prefix_len() != 64 {
+            return None;
+        }
+        let mut bytes = *prefix.network().as_bytes();
+        bytes[8..].copy_from_slice(&self.interface_id());
+        Some(bytes.into())
+    }
+
+    /// Returns a link-local address which is formed by the modified EUI-64 interface identifier.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use asyncio::ip::LlAddr;
+    ///
+    /// let mac = LlAddr::new(0x00, 0x11, 0x22, 0x33, 0x44, 0x55);
+    /// assert_eq!(mac.link_local().to_string(), "fe80::211:22ff:fe33:4455");
+    /// ```
+    pub fn link_local(&self) -> IpAddrV6 {
+        let mut bytes = [0; 16];
+        bytes[0] = 0xFE;
+        bytes[1] = 0x80;
+        bytes[8..].copy_from_slice(&self.interface_id());
+        bytes.into()
+    }
 }
 
 impl AddAssign<i64> for LlAddr {
@@ -373,7 +522,58 @@ impl IpAddrV4 {
     /// assert_eq!(IpAddrV4::new(10,0,0,1).to_u32(), 10*256*256*256+1);
     /// ```
     pub fn to_u32(&self) -> u32 {
-        unsafe { &*(self.bytes.as_ptr() as *const u32) }.to_be()
+        (self.bytes[0] as u32) << 24 | (self.bytes[1] as u32) << 16 | (self.bytes[2] as u32) << 8 |
+            self.bytes[3] as u32
+    }
+
+    /// Returns a IP-v4 address which is added `rhs`, or `None` if overflow occurred.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::IpAddrV4;
+    ///
+    /// assert_eq!(IpAddrV4::new(10,0,0,255).checked_add(1), Some(IpAddrV4::new(10,0,1,0)));
+    /// assert_eq!(IpAddrV4::new(255,255,255,255).checked_add(1), None);
+    /// assert_eq!(IpAddrV4::new(0,0,0,0).checked_add(-1), None);
+    /// ```
+    pub fn checked_add(&self, rhs: i64) -> Option<IpAddrV4> {
+        checked_offset(self.to_u32() as u128, rhs as i128, 0xFFFF_FFFF)
+            .map(|addr| IpAddrV4::from(addr as u32))
+    }
+
+    /// Returns a IP-v4 address which is subtracted `rhs`, or `None` if overflow occurred.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::IpAddrV4;
+    ///
+    /// assert_eq!(IpAddrV4::new(10,0,1,0).checked_sub(1), Some(IpAddrV4::new(10,0,0,255)));
+    /// assert_eq!(IpAddrV4::new(0,0,0,0).checked_sub(1), None);
+    /// ```
+    pub fn checked_sub(&self, rhs: i64) -> Option<IpAddrV4> {
+        checked_offset(self.to_u32() as u128, -(rhs as i128), 0xFFFF_FFFF)
+            .map(|addr| IpAddrV4::from(addr as u32))
+    }
+
+    /// Returns a domain name for the reverse DNS lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::IpAddrV4;
+    ///
+    /// assert_eq!(IpAddrV4::new(192,0,2,1).reverse_dns(), "1.2.0.192.in-addr.arpa");
+    /// ```
+    pub fn reverse_dns(&self) -> String {
+        format!(
+            "{}.{}.{}.{}.in-addr.arpa",
+            self.bytes[3],
+            self.bytes[2],
+            self.bytes[1],
+            self.bytes[0]
+        )
     }
 }
 
@@ -414,6 +614,12 @@ impl From<net::Ipv4Addr> for IpAddrV4 {
     }
 }
 
+impl From<IpAddrV4> for net::Ipv4Addr {
+    fn from(ip: IpAddrV4) -> Self {
+        ip.bytes.into()
+    }
+}
+
 impl From<u32> for IpAddrV4 {
     fn from(mut addr: u32) -> Self {
         let d = (addr & 0xFF) as u8;
@@ -427,6 +633,21 @@ impl From<u32> for IpAddrV4 {
 }
 
 /// Implements IP version 6 style addresses.
+///
+/// The `Display` writes the RFC 5952 canonical text, and the full form with `{:#}`.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::ip::{IpAddrV4, IpAddrV6};
+///
+/// let addr = IpAddrV6::new(0x2001,0xdb8,0,0,1,0,0,1);
+/// assert_eq!(format!("{}", addr), "2001:db8::1:0:0:1");
+/// assert_eq!(format!("{:#}", addr), "2001:0db8:0000:0000:0001:0000:0000:0001");
+///
+/// let addr = IpAddrV6::v4_mapped(&IpAddrV4::new(192,0,2,1));
+/// assert_eq!(format!("{}", addr), "::ffff:192.0.2.1");
+/// ```
 #[derive(Default, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
 pub struct IpAddrV6 {
     scope_id: u32,
@@ -649,6 +870,71 @@ impl IpAddrV6 {
         &self.bytes
     }
 
+    /// Returns `u128` in host byte order.
+    ///
+    /// # Examples
+    /// ```
+    /// use asyncio::ip::IpAddrV6;
+    ///
+    /// assert_eq!(IpAddrV6::new(1,0,0,0,0,0,0,2).to_u128(), 1 << 112 | 2);
+    /// ```
+    pub fn to_u128(&self) -> u128 {
+        self.bytes.iter().fold(0, |acc, &n| acc << 8 | n as u128)
+    }
+
+    /// Returns a IP-v6 address which is added `rhs`, or `None` if overflow occurred.
+    ///
+    /// The scope ID is preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::IpAddrV6;
+    ///
+    /// let addr = IpAddrV6::new(0xfe80,0,0,0,0,0,0,0xffff);
+    /// assert_eq!(addr.checked_add(1), Some(IpAddrV6::new(0xfe80,0,0,0,0,0,1,0)));
+    /// assert_eq!(IpAddrV6::any().checked_add(-1), None);
+    /// ```
+    pub fn checked_add(&self, rhs: i64) -> Option<IpAddrV6> {
+        checked_offset(self.to_u128(), rhs as i128, !0).map(|addr| {
+            let mut addr: IpAddrV6 = addr.into();
+            addr.scope_id = self.scope_id;
+            addr
+        })
+    }
+
+    /// Returns a IP-v6 address which is subtracted `rhs`, or `None` if overflow occurred.
+    ///
+    /// The scope ID is preserved.
+    pub fn checked_sub(&self, rhs: i64) -> Option<IpAddrV6> {
+        checked_offset(self.to_u128(), -(rhs as i128), !0).map(|addr| {
+            let mut addr: IpAddrV6 = addr.into();
+            addr.scope_id = self.scope_id;
+            addr
+        })
+    }
+
+    /// Returns a domain name for the reverse DNS lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::IpAddrV6;
+    ///
+    /// assert_eq!(
+    ///     IpAddrV6::new(0x2001,0xdb8,0,0,0,0,0,1).reverse_dns(),
+    ///     "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"
+    /// );
+    /// ```
+    pub fn reverse_dns(&self) -> String {
+        let mut name = String::with_capacity(72);
+        for &b in self.bytes.iter().rev() {
+            name.push_str(&format!("{:x}.{:x}.", b & 0xF, b >> 4));
+        }
+        name.push_str("ip6.arpa");
+        name
+    }
+
     /// Retruns a IP-v4 address if this is a convertable address.
     pub fn to_v4(&self) -> Option<IpAddrV4> {
         if self.is_v4_mapped() || self.is_v4_compatible() {
@@ -762,12 +1048,29 @@ impl From<[u8; 16]> for IpAddrV6 {
     }
 }
 
+impl From<u128> for IpAddrV6 {
+    fn from(mut addr: u128) -> Self {
+        let mut bytes = [0; 16];
+        for it in bytes.iter_mut().rev() {
+            *it = (addr & 0xFF) as u8;
+            addr >>= 8;
+        }
+        bytes.into()
+    }
+}
+
 impl From<net::Ipv6Addr> for IpAddrV6 {
     fn from(ip: net::Ipv6Addr) -> Self {
         ip.octets().into()
     }
 }
 
+impl From<IpAddrV6> for net::Ipv6Addr {
+    fn from(ip: IpAddrV6) -> Self {
+        ip.bytes.into()
+    }
+}
+
 /// Implements version-independent IP addresses.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum IpAddr {
@@ -807,6 +1110,30 @@ impl IpAddr {
             &IpAddr::V6(ref addr) => addr.as_bytes(),
         }
     }
+
+    /// Returns a IP address which is added `rhs`, or `None` if overflow occurred.
+    pub fn checked_add(&self, rhs: i64) -> Option<IpAddr> {
+        match self {
+            &IpAddr::V4(ref addr) => addr.checked_add(rhs).map(IpAddr::V4),
+            &IpAddr::V6(ref addr) => addr.checked_add(rhs).map(IpAddr::V6),
+        }
+    }
+
+    /// Returns a IP address which is subtracted `rhs`, or `None` if overflow occurred.
+    pub fn checked_sub(&self, rhs: i64) -> Option<IpAddr> {
+        match self {
+            &IpAddr::V4(ref addr) => addr.checked_sub(rhs).map(IpAddr::V4),
+            &IpAddr::V6(ref addr) => addr.checked_sub(rhs).map(IpAddr::V6),
+        }
+    }
+
+    /// Returns a domain name for the reverse DNS lookup.
+    pub fn reverse_dns(&self) -> String {
+        match self {
+            &IpAddr::V4(ref addr) => addr.reverse_dns(),
+            &IpAddr::V6(ref addr) => addr.reverse_dns(),
+        }
+    }
 }
 
 impl AddAssign<i64> for IpAddr {
@@ -830,8 +1157,8 @@ impl SubAssign<i64> for IpAddr {
 impl fmt::Display for IpAddr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            &IpAddr::V4(ref addr) => write!(f, "{}", addr),
-            &IpAddr::V6(ref addr) => write!(f, "{}", addr),
+            &IpAddr::V4(ref addr) => fmt::Display::fmt(addr, f),
+            &IpAddr::V6(ref addr) => fmt::Display::fmt(addr, f),
         }
     }
 }
@@ -869,6 +1196,15 @@ impl From<net::IpAddr> for IpAddr {
     }
 }
 
+impl From<IpAddr> for net::IpAddr {
+    fn from(ip: IpAddr) -> Self {
+        match ip {
+            IpAddr::V4(addr) => net::IpAddr::V4(addr.into()),
+            IpAddr::V6(addr) => net::IpAddr::V6(addr.into()),
+        }
+    }
+}
+
 pub trait IpProtocol: Protocol + Eq + fmt::Display {
     fn async_connect<F>(soc: &Self::Socket, ep: &IpEndpoint<Self>, handler: F) -> F::Output
     where
@@ -882,7 +1218,17 @@ pub trait IpProtocol: Protocol + Eq + fmt::Display {
 }
 
 mod network;
-pub use self::network::{IpNetworkV4, IpNetworkV6};
+pub use self::network::{IpNetwork, IpNetworkV4, IpNetworkV4Hosts, IpNetworkV4Subnets, IpNetworkV6,
+                        IpNetworkV6Hosts, IpNetworkV6Subnets};
+
+mod network_set;
+pub use self::network_set::{IpNetworkSet, IpNetworkSetIter};
+
+mod range;
+pub use self::range::{IpAddrRange, IpAddrRangeIter};
+
+mod prefix_table;
+pub use self::prefix_table::{PrefixTable, PrefixTableIter};
 
 mod endpoint;
 pub use self::endpoint::IpEndpoint;
@@ -895,6 +1241,19 @@ pub use self::resolver::{Passive, Resolver, ResolverIter, ResolverQuery};
 mod icmp;
 pub use self::icmp::{Icmp, IcmpEndpoint, IcmpResolver, IcmpSocket};
 
+mod icmp_header;
+pub use self::icmp_header::*;
+
+mod probe;
+
+mod pinger;
+pub use self::pinger::{Pinger, PingReply, PingStats};
+
+#[cfg(target_os = "linux")]
+mod trace;
+#[cfg(target_os = "linux")]
+pub use self::trace::{Tracer, TraceHop, TraceStatus};
+
 mod udp;
 pub use self::udp::{Udp, UdpEndpoint, UdpResolver, UdpSocket};
 
@@ -1029,17 +1388,62 @@ fn test_ipaddr_v6_format() {
     );
     assert_eq!(
         format!("{}", IpAddrV6::new(0, 2, 3, 4, 5, 6, 7, 8)),
-        "::2:3:4:5:6:7:8"
+        "0:2:3:4:5:6:7:8"
     );
     assert_eq!(
         format!("{}", IpAddrV6::new(1, 2, 3, 4, 5, 6, 7, 0)),
-        "1:2:3:4:5:6:7::"
+        "1:2:3:4:5:6:7:0"
     );
     assert_eq!(
         format!("{}", IpAddrV6::new(1, 2, 3, 4, 0, 6, 7, 8)),
-        "1:2:3:4::6:7:8"
+        "1:2:3:4:0:6:7:8"
     );
     assert_eq!(format!("{}", IpAddrV6::new(1, 0, 0, 0, 0, 0, 0, 8)), "1::8");
+    assert_eq!(format!("{}", IpAddrV6::new(0, 0, 3, 4, 5, 6, 7, 8)), "::3:4:5:6:7:8");
+    assert_eq!(format!("{}", IpAddrV6::new(1, 2, 3, 4, 5, 6, 0, 0)), "1:2:3:4:5:6::");
+    assert_eq!(format!("{}", IpAddrV6::new(1, 0, 0, 4, 0, 0, 7, 8)), "1::4:0:0:7:8");
+    assert_eq!(format!("{}", IpAddrV6::new(1, 0, 0, 4, 0, 0, 0, 8)), "1:0:0:4::8");
+    assert_eq!(
+        format!("{}", IpAddrV6::new(0x2001, 0xDB8, 0, 0, 0, 0xABCD, 0, 1)),
+        "2001:db8::abcd:0:1"
+    );
+    assert_eq!(
+        format!("{}", IpAddrV6::v4_mapped(&IpAddrV4::new(10, 0, 0, 1))),
+        "::ffff:10.0.0.1"
+    );
+    assert_eq!(format!("{}", IpAddrV6::new(0, 0, 0, 0, 0, 0xFFFF, 0, 0)), "::ffff:0.0.0.0");
+    assert_eq!(format!("{}", IpAddrV6::new(0, 0, 0, 0, 1, 0xFFFF, 0, 0)), "::1:ffff:0:0");
+    assert_eq!(format!("{:#}", IpAddrV6::any()), "0000:0000:0000:0000:0000:0000:0000:0000");
+    assert_eq!(
+        format!("{:#}", IpAddr::V6(IpAddrV6::loopback())),
+        "0000:0000:0000:0000:0000:0000:0000:0001"
+    );
+}
+
+#[test]
+fn test_reverse_dns() {
+    assert_eq!(IpAddr::V4(IpAddrV4::new(10, 1, 2, 3)).reverse_dns(), "3.2.1.10.in-addr.arpa");
+    assert_eq!(
+        IpAddr::V6(IpAddrV6::loopback()).reverse_dns(),
+        "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.ip6.arpa"
+    );
+}
+
+#[test]
+fn test_lladdr_eui64() {
+    let mac = LlAddr::new(0x02, 0xAA, 0xBB, 0xCC, 0xDD, 0xEE);
+    assert_eq!(mac.to_eui64(), [0x02, 0xAA, 0xBB, 0xFF, 0xFE, 0xCC, 0xDD, 0xEE]);
+    assert_eq!(mac.interface_id(), [0x00, 0xAA, 0xBB, 0xFF, 0xFE, 0xCC, 0xDD, 0xEE]);
+    assert_eq!(
+        mac.link_local(),
+        IpAddrV6::new(0xFE80, 0, 0, 0, 0x00AA, 0xBBFF, 0xFECC, 0xDDEE)
+    );
+    assert!(mac.link_local().is_link_local());
+    let prefix = IpNetworkV6::from(IpAddrV6::new(0x2001, 0xDB8, 0, 1, 2, 3, 4, 5), 64).unwrap();
+    assert_eq!(
+        mac.slaac(&prefix),
+        Some(IpAddrV6::new(0x2001, 0xDB8, 0, 1, 0x00AA, 0xBBFF, 0xFECC, 0xDDEE))
+    );
 }
 
 #[test]
@@ -1090,3 +1494,44 @@ fn test_ip_addr_as_bytes() {
     assert!(v6.as_bytes() == &bytes[..]);
     assert!(v6.as_bytes() != v4.as_bytes());
 }
+
+#[test]
+fn test_checked_offset() {
+    assert_eq!(checked_offset(0, 1, 0xFF), Some(1));
+    assert_eq!(checked_offset(0xFE, 1, 0xFF), Some(0xFF));
+    assert_eq!(checked_offset(0xFF, 1, 0xFF), None);
+    assert_eq!(checked_offset(1, -1, 0xFF), Some(0));
+    assert_eq!(checked_offset(0, -1, 0xFF), None);
+    assert_eq!(checked_offset(!0, 1, !0), None);
+    assert_eq!(checked_offset(!0, i64::min_value() as i128, !0), Some(!0 - (1 << 63)));
+    assert_eq!(checked_offset(0, -(i64::min_value() as i128), !0), Some(1 << 63));
+
+    let mac = LlAddr::new(0xff, 0xff, 0xff, 0xff, 0xff, 0xfe);
+    assert_eq!(mac.checked_add(1), Some(LlAddr::new(0xff, 0xff, 0xff, 0xff, 0xff, 0xff)));
+    assert_eq!(mac.checked_add(2), None);
+    assert_eq!(mac.checked_sub(-2), None);
+    assert_eq!(LlAddr::default().checked_sub(1), None);
+
+    let v6 = IpAddrV6::with_scope_id(0xfe80, 0, 0, 0, 0, 0, 0, 1, 2);
+    assert_eq!(v6.checked_sub(1).unwrap().scope_id(), 2);
+    assert_eq!(
+        IpAddr::V4(IpAddrV4::new(192, 168, 0, 1)).checked_sub(-1),
+        Some(IpAddr::V4(IpAddrV4::new(192, 168, 0, 2)))
+    );
+    assert_eq!(IpAddr::V6(IpAddrV6::any()).checked_sub(1), None);
+}
+
+#[test]
+fn test_ip_addr_std_net() {
+    let v4 = IpAddrV4::new(192, 168, 0, 1);
+    assert_eq!(net::Ipv4Addr::from(v4), net::Ipv4Addr::new(192, 168, 0, 1));
+    assert_eq!(IpAddrV4::from(net::Ipv4Addr::from(v4)), v4);
+
+    let v6 = IpAddrV6::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+    assert_eq!(net::Ipv6Addr::from(v6), net::Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+    let addr: IpAddrV6 = net::Ipv6Addr::from(v6).into();
+    assert_eq!(addr, v6);
+
+    assert_eq!(net::IpAddr::from(IpAddr::V4(v4)), net::IpAddr::V4(v4.into()));
+    assert_eq!(IpAddr::from(net::IpAddr::from(IpAddr::V6(v6))), IpAddr::V6(v6));
+}
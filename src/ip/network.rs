@@ -1,15 +1,14 @@
-use super::{IpAddrV4, IpAddrV6, fmt_v6};
+use super::{IpAddr, IpAddrV4, IpAddrV6, fmt_v6};
 
 use std::fmt;
-use std::mem;
-use std::cmp::Ordering;
+use std::cmp;
 
-fn make_netmask_v6(len: u8) -> [u64; 2] {
-    match len.cmp(&64) {
-        Ordering::Less => [(!((1u64 << (64 - len)) - 1)).to_be(), 0],
-        Ordering::Equal => [u64::max_value(), 0],
-        Ordering::Greater => [u64::max_value(), (!((1u64 << (128 - len)) - 1)).to_be()],
-    }
+fn make_netmask_v4(len: u8) -> u32 {
+    if len == 0 { 0 } else { !0 << (32 - len) }
+}
+
+fn make_netmask_v6(len: u8) -> u128 {
+    if len == 0 { 0 } else { !0 << (128 - len) }
 }
 
 fn prefix_len(addr: &[u8]) -> u8 {
@@ -67,7 +66,7 @@ fn prefix_len(addr: &[u8]) -> u8 {
 }
 
 /// Implements Network IP version 4 style addresses.
-#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct IpNetworkV4 {
     addr: IpAddrV4,
     len: u8,
@@ -97,8 +96,21 @@ impl IpNetworkV4 {
     }
 
     /// Returns new IpNetworkV4.
+    ///
+    /// The prefix length 0 is the default route `0.0.0.0/0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpAddrV4, IpNetworkV4};
+    ///
+    /// let default = IpNetworkV4::from(IpAddrV4::any(), 0).unwrap();
+    /// assert!(default.contains(&IpAddrV4::new(192, 168, 0, 1)));
+    ///
+    /// assert_eq!(IpNetworkV4::from(IpAddrV4::any(), 33), None);
+    /// ```
     pub fn from(addr: IpAddrV4, prefix_len: u16) -> Option<IpNetworkV4> {
-        if 1 <= prefix_len && prefix_len <= 32 {
+        if prefix_len <= 32 {
             Some(IpNetworkV4 {
                 addr: addr,
                 len: prefix_len as u8,
@@ -112,15 +124,15 @@ impl IpNetworkV4 {
     ///
     /// # Panics
     ///
-    /// Panics if len == 0 or len > 32
+    /// Panics if len > 32
     ///
     /// ```rust,no_run
     /// use asyncio::ip::{IpAddrV4, IpNetworkV4};
     ///
-    /// unsafe { IpNetworkV4::from_unchecked(IpAddrV4::any(), 0); } // panic!
+    /// unsafe { IpNetworkV4::from_unchecked(IpAddrV4::any(), 33); } // panic!
     /// ```
     pub unsafe fn from_unchecked(addr: IpAddrV4, prefix_len: u16) -> IpNetworkV4 {
-        assert!(1 <= prefix_len && prefix_len <= 32);
+        assert!(prefix_len <= 32);
         IpNetworkV4 {
             addr: addr,
             len: prefix_len as u8,
@@ -153,11 +165,7 @@ impl IpNetworkV4 {
     /// ```
 
     pub fn broadcast(&self) -> IpAddrV4 {
-        unsafe {
-            let lhs: u32 = mem::transmute(self.network().bytes);
-            let rhs: u32 = mem::transmute(self.netmask().bytes);
-            IpAddrV4 { bytes: mem::transmute(lhs | !rhs) }
-        }
+        (self.addr.to_u32() | !make_netmask_v4(self.len)).into()
     }
 
     /// Returns a canonical address.
@@ -172,22 +180,35 @@ impl IpNetworkV4 {
     /// ```
     pub fn canonical(&self) -> Self {
         IpNetworkV4 {
-            addr: unsafe {
-                let mask: u32 = mem::transmute(self.netmask().bytes);
-                let addr: &u32 = mem::transmute(&self.addr.bytes);
-                mem::transmute(*addr & mask)
-            },
+            addr: self.network(),
             len: self.len,
         }
     }
 
-    pub fn hosts(&self) -> (IpAddrV4, IpAddrV4) {
-        let mut addr = self.address();
-        addr += 1;
-        if self.is_host() {
-            (self.address(), addr)
+    /// Returns a iterator over the usable host addresses.
+    ///
+    /// The network and broadcast addresses are excluded, except for the /31 (RFC 3021) and /32.
+    /// Unlike this, `IpNetworkV6::hosts` includes all addresses, since IPv6 has no broadcast address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpAddrV4, IpNetworkV4};
+    ///
+    /// let net = IpNetworkV4::from(IpAddrV4::new(192, 168, 0, 0), 30).unwrap();
+    /// let hosts: Vec<_> = net.hosts().collect();
+    /// assert_eq!(hosts, vec![IpAddrV4::new(192, 168, 0, 1), IpAddrV4::new(192, 168, 0, 2)]);
+    /// ```
+    pub fn hosts(&self) -> IpNetworkV4Hosts {
+        let beg = self.network().to_u32() as u64;
+        let end = self.broadcast().to_u32() as u64 + 1;
+        if self.len >= 31 {
+            IpNetworkV4Hosts { beg: beg, end: end }
         } else {
-            (addr, self.broadcast())
+            IpNetworkV4Hosts {
+                beg: beg + 1,
+                end: end - 1,
+            }
         }
     }
 
@@ -199,13 +220,89 @@ impl IpNetworkV4 {
         if other.len >= self.len {
             false
         } else {
-            unsafe {
-                let mask: u32 = mem::transmute(other.netmask().bytes);
-                let lhs: u32 = mem::transmute_copy(&self.addr.bytes);
-                let rhs: u32 = mem::transmute_copy(&other.addr.bytes);
-                (lhs & mask) == (rhs & mask)
-            }
+            let mask = make_netmask_v4(other.len);
+            (self.addr.to_u32() & mask) == (other.addr.to_u32() & mask)
+        }
+    }
+
+    /// Returns true if the network contains the address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpAddrV4, IpNetworkV4};
+    ///
+    /// let net = IpNetworkV4::from(IpAddrV4::new(10, 0, 0, 0), 8).unwrap();
+    /// assert!(net.contains(&IpAddrV4::new(10, 1, 2, 3)));
+    /// assert!(!net.contains(&IpAddrV4::new(11, 0, 0, 0)));
+    /// ```
+    pub fn contains(&self, addr: &IpAddrV4) -> bool {
+        let mask = make_netmask_v4(self.len);
+        (self.addr.to_u32() & mask) == (addr.to_u32() & mask)
+    }
+
+    /// Returns true if the networks share any addresses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpAddrV4, IpNetworkV4};
+    ///
+    /// let net1 = IpNetworkV4::from(IpAddrV4::new(10, 0, 0, 0), 8).unwrap();
+    /// let net2 = IpNetworkV4::from(IpAddrV4::new(10, 10, 0, 0), 16).unwrap();
+    /// let net3 = IpNetworkV4::from(IpAddrV4::new(172, 16, 0, 0), 12).unwrap();
+    /// assert!(net1.overlaps(&net2) && net2.overlaps(&net1));
+    /// assert!(!net1.overlaps(&net3));
+    /// ```
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let mask = make_netmask_v4(cmp::min(self.len, other.len));
+        (self.addr.to_u32() & mask) == (other.addr.to_u32() & mask)
+    }
+
+    /// Returns a iterator over the subnets of the new prefix length.
+    ///
+    /// Returns `None` if the new prefix length is shorter than this or greater than 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpAddrV4, IpNetworkV4};
+    ///
+    /// let net = IpNetworkV4::from(IpAddrV4::new(10, 0, 0, 0), 8).unwrap();
+    /// let subnets: Vec<String> = net.subnets(10).unwrap().map(|net| net.to_string()).collect();
+    /// assert_eq!(subnets, ["10.0.0.0/10", "10.64.0.0/10", "10.128.0.0/10", "10.192.0.0/10"]);
+    /// ```
+    pub fn subnets(&self, new_prefix: u16) -> Option<IpNetworkV4Subnets> {
+        if new_prefix < self.len as u16 || new_prefix > 32 {
+            return None;
         }
+        Some(IpNetworkV4Subnets {
+            beg: self.network().to_u32() as u64,
+            end: self.broadcast().to_u32() as u64 + 1,
+            len: new_prefix as u8,
+        })
+    }
+
+    /// Returns a network of the prefix length shorter by one,
+    /// or `None` if the prefix length is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpAddrV4, IpNetworkV4};
+    ///
+    /// let net = IpNetworkV4::from(IpAddrV4::new(192, 168, 1, 0), 24).unwrap();
+    /// assert_eq!(net.supernet().unwrap().to_string(), "192.168.0.0/23");
+    /// ```
+    pub fn supernet(&self) -> Option<Self> {
+        if self.len == 0 {
+            return None;
+        }
+        let len = self.len - 1;
+        Some(IpNetworkV4 {
+            addr: (self.addr.to_u32() & make_netmask_v4(len)).into(),
+            len: len,
+        })
     }
 
     /// Returns a subnet mask.
@@ -219,7 +316,7 @@ impl IpNetworkV4 {
     /// assert_eq!(lo.netmask(), IpAddrV4::new(255,0,0,0));
     /// ```
     pub fn netmask(&self) -> IpAddrV4 {
-        (0xffffffff << (32 - self.len)).into()
+        make_netmask_v4(self.len).into()
     }
 
     /// Returns a network address.
@@ -233,11 +330,7 @@ impl IpNetworkV4 {
     /// assert_eq!(lo.network(), IpAddrV4::new(127,0,0,0));
     /// ```
     pub fn network(&self) -> IpAddrV4 {
-        unsafe {
-            let lhs: u32 = *(self.addr.bytes.as_ptr() as *const u32);
-            let rhs: u32 = mem::transmute(self.netmask().bytes);
-            IpAddrV4 { bytes: mem::transmute(lhs & rhs) }
-        }
+        (self.addr.to_u32() & make_netmask_v4(self.len)).into()
     }
 
     /// Returns a length of subnet mask.
@@ -261,7 +354,78 @@ impl fmt::Display for IpNetworkV4 {
     }
 }
 
+/// A iterator over the host addresses of `IpNetworkV4`.
+#[derive(Clone, Debug)]
+pub struct IpNetworkV4Hosts {
+    beg: u64,
+    end: u64,
+}
+
+impl Iterator for IpNetworkV4Hosts {
+    type Item = IpAddrV4;
+
+    fn next(&mut self) -> Option<IpAddrV4> {
+        if self.beg < self.end {
+            self.beg += 1;
+            Some(((self.beg - 1) as u32).into())
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end.saturating_sub(self.beg) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for IpNetworkV4Hosts {
+    fn next_back(&mut self) -> Option<IpAddrV4> {
+        if self.beg < self.end {
+            self.end -= 1;
+            Some((self.end as u32).into())
+        } else {
+            None
+        }
+    }
+}
+
+impl ExactSizeIterator for IpNetworkV4Hosts {}
+
+/// A iterator over the subnets of `IpNetworkV4`.
+#[derive(Clone, Debug)]
+pub struct IpNetworkV4Subnets {
+    beg: u64,
+    end: u64,
+    len: u8,
+}
+
+impl Iterator for IpNetworkV4Subnets {
+    type Item = IpNetworkV4;
+
+    fn next(&mut self) -> Option<IpNetworkV4> {
+        if self.beg < self.end {
+            let addr = self.beg as u32;
+            self.beg += 1 << (32 - self.len);
+            Some(IpNetworkV4 {
+                addr: addr.into(),
+                len: self.len,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.end.saturating_sub(self.beg) >> (32 - self.len)) as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for IpNetworkV4Subnets {}
+
 /// Implements Network IP version 6 style addresses.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct IpNetworkV6 {
     bytes: [u8; 16],
     len: u8,
@@ -291,8 +455,22 @@ impl IpNetworkV6 {
         }
     }
 
+    /// Returns new IpNetworkV6.
+    ///
+    /// The prefix length 0 is the default route `::/0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpAddrV6, IpNetworkV6};
+    ///
+    /// let default = IpNetworkV6::from(IpAddrV6::any(), 0).unwrap();
+    /// assert!(default.contains(&IpAddrV6::loopback()));
+    ///
+    /// assert_eq!(IpNetworkV6::from(IpAddrV6::any(), 129), None);
+    /// ```
     pub fn from(addr: IpAddrV6, prefix_len: u16) -> Option<Self> {
-        if 1 <= prefix_len && prefix_len <= 128 {
+        if prefix_len <= 128 {
             Some(IpNetworkV6 {
                 bytes: addr.bytes,
                 len: prefix_len as u8,
@@ -306,21 +484,25 @@ impl IpNetworkV6 {
     ///
     /// # Panics
     ///
-    /// Panics if len == 0 or len > 128
+    /// Panics if len > 128
     ///
     /// ```rust,no_run
     /// use asyncio::ip::{IpAddrV6, IpNetworkV6};
     ///
-    /// IpNetworkV6::from(IpAddrV6::loopback(), 0);  // panic!
+    /// unsafe { IpNetworkV6::from_unchecked(IpAddrV6::loopback(), 129); }  // panic!
     /// ```
     pub unsafe fn from_unchecked(addr: IpAddrV6, len: u8) -> Self {
-        assert!(1 <= len && len <= 128);
+        assert!(len <= 128);
         IpNetworkV6 {
             bytes: addr.bytes,
             len: len,
         }
     }
 
+    fn to_u128(&self) -> u128 {
+        self.address().to_u128()
+    }
+
     /// Returns a address.
     pub fn address(&self) -> IpAddrV6 {
         self.bytes.into()
@@ -328,22 +510,32 @@ impl IpNetworkV6 {
 
     pub fn canonical(&self) -> Self {
         IpNetworkV6 {
-            bytes: unsafe {
-                let mut lhs: [u64; 2] = make_netmask_v6(self.len);
-                let rhs: &[u64; 2] = mem::transmute(&self.bytes);
-                lhs[0] &= rhs[0];
-                lhs[1] &= rhs[1];
-                mem::transmute(lhs)
-            },
+            bytes: self.network().bytes,
             len: self.len,
         }
     }
 
-    pub fn hosts(&self) -> (IpAddrV6, IpAddrV6) {
-        let beg = self.network();
-        let mut end = beg;
-        end += 1 << (128 - self.len);
-        (beg, end)
+    /// Returns a iterator over the all addresses in the network.
+    ///
+    /// Unlike `IpNetworkV4::hosts`, the first and last addresses are included,
+    /// since IPv6 has no broadcast address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpAddrV6, IpNetworkV6};
+    ///
+    /// let net = IpNetworkV6::from(IpAddrV6::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0), 126).unwrap();
+    /// assert_eq!(net.hosts().count(), 4);
+    /// assert_eq!(net.hosts().last(), Some(IpAddrV6::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 3)));
+    /// ```
+    pub fn hosts(&self) -> IpNetworkV6Hosts {
+        let beg = self.network().to_u128();
+        IpNetworkV6Hosts {
+            beg: beg,
+            end: beg | !make_netmask_v6(self.len),
+            done: false,
+        }
     }
 
     pub fn is_host(&self) -> bool {
@@ -354,16 +546,74 @@ impl IpNetworkV6 {
         if other.len >= self.len {
             false
         } else {
-            unsafe {
-                let mask: [u64; 2] = make_netmask_v6(other.len);
-                let lhs: &[u64; 2] = mem::transmute(&self.bytes);
-                let rhs: &[u64; 2] = mem::transmute(&other.bytes);
-                ((lhs[0] & mask[0]) == (rhs[0] & mask[0])) &&
-                    ((lhs[1] & mask[1]) == (rhs[1] & mask[1]))
-            }
+            let mask = make_netmask_v6(other.len);
+            (self.to_u128() & mask) == (other.to_u128() & mask)
         }
     }
 
+    /// Returns true if the network contains the address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpAddrV6, IpNetworkV6};
+    ///
+    /// let net = IpNetworkV6::from(IpAddrV6::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0), 32).unwrap();
+    /// assert!(net.contains(&IpAddrV6::new(0x2001, 0xdb8, 1, 0, 0, 0, 0, 1)));
+    /// assert!(!net.contains(&IpAddrV6::loopback()));
+    /// ```
+    pub fn contains(&self, addr: &IpAddrV6) -> bool {
+        let mask = make_netmask_v6(self.len);
+        (self.to_u128() & mask) == (addr.to_u128() & mask)
+    }
+
+    /// Returns true if the networks share any addresses.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let mask = make_netmask_v6(cmp::min(self.len, other.len));
+        (self.to_u128() & mask) == (other.to_u128() & mask)
+    }
+
+    /// Returns a iterator over the subnets of the new prefix length.
+    ///
+    /// Returns `None` if the new prefix length is shorter than this or greater than 128.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpAddrV6, IpNetworkV6};
+    ///
+    /// let net = IpNetworkV6::from(IpAddrV6::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0), 32).unwrap();
+    /// let mut it = net.subnets(48).unwrap();
+    /// assert_eq!(it.next().unwrap().to_string(), "2001:db8::/48");
+    /// assert_eq!(it.next().unwrap().to_string(), "2001:db8:1::/48");
+    /// ```
+    pub fn subnets(&self, new_prefix: u16) -> Option<IpNetworkV6Subnets> {
+        if new_prefix < self.len as u16 || new_prefix > 128 {
+            return None;
+        }
+        let beg = self.network().to_u128();
+        Some(IpNetworkV6Subnets {
+            beg: beg,
+            end: beg | !make_netmask_v6(self.len),
+            len: new_prefix as u8,
+            done: false,
+        })
+    }
+
+    /// Returns a network of the prefix length shorter by one,
+    /// or `None` if the prefix length is 0.
+    pub fn supernet(&self) -> Option<Self> {
+        if self.len == 0 {
+            return None;
+        }
+        let len = self.len - 1;
+        let addr: IpAddrV6 = (self.to_u128() & make_netmask_v6(len)).into();
+        Some(IpNetworkV6 {
+            bytes: addr.bytes,
+            len: len,
+        })
+    }
+
     /// Returns a subnet mask.
     ///
     /// # Examples
@@ -375,10 +625,7 @@ impl IpNetworkV6 {
     /// assert_eq!(lo.netmask(), IpAddrV6::new(0xffff,0xffff,0xffff,0xffff,0,0,0,0));
     /// ```
     pub fn netmask(&self) -> IpAddrV6 {
-        IpAddrV6 {
-            scope_id: 0,
-            bytes: unsafe { mem::transmute(make_netmask_v6(self.len)) },
-        }
+        make_netmask_v6(self.len).into()
     }
 
     /// Returns a network address.
@@ -392,16 +639,7 @@ impl IpNetworkV6 {
     /// assert_eq!(lo.network(), IpAddrV6::any());
     /// ```
     pub fn network(&self) -> IpAddrV6 {
-        unsafe {
-            let mut lhs: [u64; 2] = make_netmask_v6(self.len);
-            let rhs: &[u64; 2] = mem::transmute(&self.bytes);
-            lhs[0] &= rhs[0];
-            lhs[1] &= rhs[1];
-            IpAddrV6 {
-                scope_id: 0,
-                bytes: mem::transmute(lhs),
-            }
-        }
+        (self.to_u128() & make_netmask_v6(self.len)).into()
     }
 
 
@@ -428,6 +666,226 @@ impl fmt::Display for IpNetworkV6 {
     }
 }
 
+/// A iterator over the addresses of `IpNetworkV6`.
+#[derive(Clone, Debug)]
+pub struct IpNetworkV6Hosts {
+    beg: u128,
+    end: u128,
+    done: bool,
+}
+
+impl Iterator for IpNetworkV6Hosts {
+    type Item = IpAddrV6;
+
+    fn next(&mut self) -> Option<IpAddrV6> {
+        if self.done {
+            return None;
+        }
+        let addr = self.beg;
+        if self.beg == self.end {
+            self.done = true;
+        } else {
+            self.beg += 1;
+        }
+        Some(addr.into())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            (0, Some(0))
+        } else if self.end - self.beg < usize::max_value() as u128 {
+            let len = (self.end - self.beg) as usize + 1;
+            (len, Some(len))
+        } else {
+            (usize::max_value(), None)
+        }
+    }
+}
+
+impl DoubleEndedIterator for IpNetworkV6Hosts {
+    fn next_back(&mut self) -> Option<IpAddrV6> {
+        if self.done {
+            return None;
+        }
+        let addr = self.end;
+        if self.beg == self.end {
+            self.done = true;
+        } else {
+            self.end -= 1;
+        }
+        Some(addr.into())
+    }
+}
+
+/// A iterator over the subnets of `IpNetworkV6`.
+#[derive(Clone, Debug)]
+pub struct IpNetworkV6Subnets {
+    beg: u128,
+    end: u128,
+    len: u8,
+    done: bool,
+}
+
+impl Iterator for IpNetworkV6Subnets {
+    type Item = IpNetworkV6;
+
+    fn next(&mut self) -> Option<IpNetworkV6> {
+        if self.done {
+            return None;
+        }
+        let addr: IpAddrV6 = self.beg.into();
+        let last = self.beg | !make_netmask_v6(self.len);
+        if last == self.end {
+            self.done = true;
+        } else {
+            self.beg = last + 1;
+        }
+        Some(IpNetworkV6 {
+            bytes: addr.bytes,
+            len: self.len,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = (self.end - self.beg).checked_shr(128 - self.len as u32).unwrap_or(0);
+        if self.done {
+            (0, Some(0))
+        } else if n < usize::max_value() as u128 {
+            let len = n as usize + 1;
+            (len, Some(len))
+        } else {
+            (usize::max_value(), None)
+        }
+    }
+}
+
+/// The `len()` panics if the number of subnets overflows a `usize`.
+impl ExactSizeIterator for IpNetworkV6Subnets {}
+
+/// Implements version-independent IP networks.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::ip::{IpAddr, IpNetwork};
+///
+/// let net: IpNetwork = "2001:db8::/32".parse().unwrap();
+/// assert!(net.contains(&"2001:db8::1".parse().unwrap()));
+/// assert!(!net.contains(&"192.0.2.1".parse().unwrap()));
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum IpNetwork {
+    V4(IpNetworkV4),
+    V6(IpNetworkV6),
+}
+
+impl IpNetwork {
+    /// Returns a address.
+    pub fn address(&self) -> IpAddr {
+        match self {
+            &IpNetwork::V4(ref net) => IpAddr::V4(net.address()),
+            &IpNetwork::V6(ref net) => IpAddr::V6(net.address()),
+        }
+    }
+
+    /// Returns a network address.
+    pub fn network(&self) -> IpAddr {
+        match self {
+            &IpNetwork::V4(ref net) => IpAddr::V4(net.network()),
+            &IpNetwork::V6(ref net) => IpAddr::V6(net.network()),
+        }
+    }
+
+    /// Returns a subnet mask.
+    pub fn netmask(&self) -> IpAddr {
+        match self {
+            &IpNetwork::V4(ref net) => IpAddr::V4(net.netmask()),
+            &IpNetwork::V6(ref net) => IpAddr::V6(net.netmask()),
+        }
+    }
+
+    /// Returns a length of subnet mask.
+    pub fn prefix_len(&self) -> u16 {
+        match self {
+            &IpNetwork::V4(ref net) => net.prefix_len(),
+            &IpNetwork::V6(ref net) => net.prefix_len(),
+        }
+    }
+
+    /// Returns a canonical address.
+    pub fn canonical(&self) -> Self {
+        match self {
+            &IpNetwork::V4(ref net) => IpNetwork::V4(net.canonical()),
+            &IpNetwork::V6(ref net) => IpNetwork::V6(net.canonical()),
+        }
+    }
+
+    /// Return true if this is a host address.
+    pub fn is_host(&self) -> bool {
+        match self {
+            &IpNetwork::V4(ref net) => net.is_host(),
+            &IpNetwork::V6(ref net) => net.is_host(),
+        }
+    }
+
+    /// Returns true if this is a subnet of the other network in the same version.
+    pub fn is_subnet_of(&self, other: &Self) -> bool {
+        match (self, other) {
+            (&IpNetwork::V4(ref lhs), &IpNetwork::V4(ref rhs)) => lhs.is_subnet_of(rhs),
+            (&IpNetwork::V6(ref lhs), &IpNetwork::V6(ref rhs)) => lhs.is_subnet_of(rhs),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the network contains the address of the same version.
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        match (self, addr) {
+            (&IpNetwork::V4(ref net), &IpAddr::V4(ref addr)) => net.contains(addr),
+            (&IpNetwork::V6(ref net), &IpAddr::V6(ref addr)) => net.contains(addr),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the networks of the same version share any addresses.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        match (self, other) {
+            (&IpNetwork::V4(ref lhs), &IpNetwork::V4(ref rhs)) => lhs.overlaps(rhs),
+            (&IpNetwork::V6(ref lhs), &IpNetwork::V6(ref rhs)) => lhs.overlaps(rhs),
+            _ => false,
+        }
+    }
+
+    /// Returns a network of the prefix length shorter by one,
+    /// or `None` if the prefix length is 0.
+    pub fn supernet(&self) -> Option<Self> {
+        match self {
+            &IpNetwork::V4(ref net) => net.supernet().map(IpNetwork::V4),
+            &IpNetwork::V6(ref net) => net.supernet().map(IpNetwork::V6),
+        }
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &IpNetwork::V4(ref net) => fmt::Display::fmt(net, f),
+            &IpNetwork::V6(ref net) => fmt::Display::fmt(net, f),
+        }
+    }
+}
+
+impl From<IpNetworkV4> for IpNetwork {
+    fn from(net: IpNetworkV4) -> Self {
+        IpNetwork::V4(net)
+    }
+}
+
+impl From<IpNetworkV6> for IpNetwork {
+    fn from(net: IpNetworkV6) -> Self {
+        IpNetwork::V6(net)
+    }
+}
+
 #[test]
 fn test_prefix_len() {
     assert_eq!(prefix_len(&[255, 255, 255, 0]), 24);
@@ -469,17 +927,69 @@ fn test_ip_network_canonical_v4() {
 
 #[test]
 fn test_ip_network_v4_hosts() {
-    let (ip1, ip2) = IpNetworkV4::from(IpAddrV4::new(192, 168, 0, 0), 24)
+    let mut it = IpNetworkV4::from(IpAddrV4::new(192, 168, 0, 0), 24)
         .unwrap()
         .hosts();
-    assert_eq!(ip1, IpAddrV4::new(192, 168, 0, 1));
-    assert_eq!(ip2, IpAddrV4::new(192, 168, 0, 255));
+    assert_eq!(it.len(), 254);
+    assert_eq!(it.next(), Some(IpAddrV4::new(192, 168, 0, 1)));
+    assert_eq!(it.next_back(), Some(IpAddrV4::new(192, 168, 0, 254)));
+    assert_eq!(it.len(), 252);
+
+    let net = IpNetworkV4::from(IpAddrV4::new(10, 0, 0, 1), 31).unwrap();
+    assert_eq!(net.hosts().collect::<Vec<_>>(), vec![IpAddrV4::new(10, 0, 0, 0), IpAddrV4::new(10, 0, 0, 1)]);
+    let net = IpNetworkV4::from(IpAddrV4::new(10, 0, 0, 1), 32).unwrap();
+    assert_eq!(net.hosts().collect::<Vec<_>>(), vec![IpAddrV4::new(10, 0, 0, 1)]);
+    let net = IpNetworkV4::from(IpAddrV4::new(255, 255, 255, 255), 32).unwrap();
+    assert_eq!(net.hosts().count(), 1);
+    let net = IpNetworkV4::from(IpAddrV4::new(128, 0, 0, 0), 1).unwrap();
+    assert_eq!(net.hosts().len(), (1 << 31) - 2);
+    assert_eq!(net.hosts().next_back(), Some(IpAddrV4::new(255, 255, 255, 254)));
+    let net = IpNetworkV4::from(IpAddrV4::new(10, 0, 0, 1), 0).unwrap();
+    assert_eq!(net.hosts().len(), (1 << 32) - 2);
+    assert_eq!(net.hosts().next(), Some(IpAddrV4::new(0, 0, 0, 1)));
+    assert_eq!(net.subnets(1).unwrap().len(), 2);
+}
+
+#[test]
+fn test_ip_network_v4_subnets() {
+    let net = IpNetworkV4::from(IpAddrV4::new(192, 168, 0, 1), 24).unwrap();
+    assert!(net.subnets(23).is_none());
+    assert!(net.subnets(33).is_none());
+    assert_eq!(net.subnets(24).unwrap().collect::<Vec<_>>(), vec![net.canonical()]);
+    let subnets: Vec<_> = net.subnets(26).unwrap().collect();
+    assert_eq!(subnets.len(), 4);
+    assert_eq!(subnets[3], IpNetworkV4::from(IpAddrV4::new(192, 168, 0, 192), 26).unwrap());
+    assert_eq!(net.subnets(32).unwrap().len(), 256);
+    assert!(subnets.iter().all(|sub| sub.is_subnet_of(&net)));
+    assert_eq!(
+        IpNetworkV4::from(IpAddrV4::new(255, 0, 0, 0), 8).unwrap().subnets(9).unwrap().last(),
+        IpNetworkV4::from(IpAddrV4::new(255, 128, 0, 0), 9)
+    );
+
+    assert_eq!(subnets[3].supernet().unwrap().supernet(), Some(net.canonical()));
+    assert_eq!(
+        IpNetworkV4::from(IpAddrV4::new(128, 0, 0, 0), 1).unwrap().supernet(),
+        IpNetworkV4::from(IpAddrV4::any(), 0)
+    );
+    assert_eq!(IpNetworkV4::from(IpAddrV4::any(), 0).unwrap().supernet(), None);
+}
+
+#[test]
+fn test_ip_network_v4_contains() {
+    let net = IpNetworkV4::from(IpAddrV4::new(172, 16, 0, 0), 12).unwrap();
+    assert!(net.contains(&IpAddrV4::new(172, 16, 0, 0)));
+    assert!(net.contains(&IpAddrV4::new(172, 31, 255, 255)));
+    assert!(!net.contains(&IpAddrV4::new(172, 32, 0, 0)));
+
+    let sub = IpNetworkV4::from(IpAddrV4::new(172, 31, 0, 0), 16).unwrap();
+    assert!(net.overlaps(&sub) && sub.overlaps(&net) && net.overlaps(&net));
+    assert!(!sub.overlaps(&IpNetworkV4::from(IpAddrV4::new(172, 30, 0, 0), 16).unwrap()));
 }
 
 #[test]
 #[should_panic]
 fn test_panic_ip_network_v4() {
-    unsafe { IpNetworkV4::from_unchecked(IpAddrV4::loopback(), 0) };
+    unsafe { IpNetworkV4::from_unchecked(IpAddrV4::loopback(), 33) };
 }
 
 #[test]
@@ -543,7 +1053,7 @@ fn test_ip_network_v6_short() {
 #[test]
 #[should_panic]
 fn test_panic_ip_network_v6() {
-    unsafe { IpNetworkV6::from_unchecked(IpAddrV6::loopback(), 0) };
+    unsafe { IpNetworkV6::from_unchecked(IpAddrV6::loopback(), 129) };
 }
 
 #[test]
@@ -554,3 +1064,75 @@ fn test_ip_network_v6_format() {
     let ip = IpNetworkV6::from(IpAddrV6::new(0xdead, 0xbeaf, 0, 0, 0, 0, 0, 0), 32).unwrap();
     assert_eq!(format!("{}", ip), "dead:beaf::/32");
 }
+
+#[test]
+fn test_ip_network_v6_hosts() {
+    let net = IpNetworkV6::from(IpAddrV6::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0x10), 124).unwrap();
+    let hosts: Vec<_> = net.hosts().collect();
+    assert_eq!(hosts.len(), 16);
+    assert_eq!(hosts[0], IpAddrV6::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0x10));
+    assert_eq!(hosts[15], IpAddrV6::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0x1f));
+    assert_eq!(net.hosts().size_hint(), (16, Some(16)));
+
+    let net = IpNetworkV6::from(IpAddrV6::loopback(), 128).unwrap();
+    assert_eq!(net.hosts().collect::<Vec<_>>(), vec![IpAddrV6::loopback()]);
+
+    let net = IpNetworkV6::from(IpAddrV6::new(0x8000, 0, 0, 0, 0, 0, 0, 0), 1).unwrap();
+    assert_eq!(net.hosts().size_hint(), (usize::max_value(), None));
+    assert_eq!(
+        net.hosts().next_back(),
+        Some(IpAddrV6::new(0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff))
+    );
+}
+
+#[test]
+fn test_ip_network_v6_subnets() {
+    let net = IpNetworkV6::from(IpAddrV6::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0), 32).unwrap();
+    assert!(net.subnets(31).is_none());
+    assert!(net.subnets(129).is_none());
+    assert_eq!(net.subnets(34).unwrap().count(), 4);
+    assert_eq!(net.subnets(34).unwrap().len(), 4);
+    assert_eq!(net.subnets(48).unwrap().len(), 0x10000);
+    let mut it = net.subnets(48).unwrap();
+    it.nth(0xfffe);
+    assert_eq!(it.len(), 1);
+    it.next();
+    assert_eq!(it.len(), 0);
+    assert_eq!(net.subnets(48).unwrap().nth(0xffff).unwrap().to_string(), "2001:db8:ffff::/48");
+    assert_eq!(net.subnets(48).unwrap().nth(0x10000), None);
+
+    let net = IpNetworkV6::from(IpAddrV6::new(0xffff, 0, 0, 0, 0, 0, 0, 0), 16).unwrap();
+    assert_eq!(net.subnets(17).unwrap().count(), 2);
+    assert_eq!(net.supernet().unwrap().to_string(), "fffe::/15");
+
+    let net = IpNetworkV6::from(IpAddrV6::loopback(), 0).unwrap();
+    assert_eq!(net.canonical().to_string(), "::/0");
+    assert_eq!(net.subnets(1).unwrap().count(), 2);
+    assert_eq!(net.subnets(0).unwrap().len(), 1);
+    assert_eq!(net.subnets(128).unwrap().size_hint(), (usize::max_value(), None));
+    assert_eq!(net.hosts().next(), Some(IpAddrV6::any()));
+    assert_eq!(net.supernet(), None);
+}
+
+#[test]
+fn test_ip_network_v6_contains() {
+    let net = IpNetworkV6::from(IpAddrV6::new(0xfe80, 0, 0, 0, 0, 0, 0, 0), 10).unwrap();
+    assert!(net.contains(&IpAddrV6::new(0xfebf, 0, 0, 0, 0, 0, 0, 1)));
+    assert!(!net.contains(&IpAddrV6::new(0xfec0, 0, 0, 0, 0, 0, 0, 1)));
+    let sub = IpNetworkV6::from(IpAddrV6::new(0xfe80, 0, 0, 0, 0, 0, 0, 0), 64).unwrap();
+    assert!(net.overlaps(&sub) && sub.overlaps(&net));
+    assert!(sub.is_subnet_of(&net) && !net.is_subnet_of(&sub));
+}
+
+#[test]
+fn test_ip_network() {
+    let v4 = IpNetwork::from(IpNetworkV4::from(IpAddrV4::new(10, 0, 0, 1), 8).unwrap());
+    let v6 = IpNetwork::from(IpNetworkV6::from(IpAddrV6::loopback(), 64).unwrap());
+    assert_eq!(v4.to_string(), "10.0.0.1/8");
+    assert_eq!(v4.canonical().to_string(), "10.0.0.0/8");
+    assert_eq!(v6.network(), IpAddr::V6(IpAddrV6::any()));
+    assert!(v4.contains(&IpAddr::V4(IpAddrV4::new(10, 2, 3, 4))));
+    assert!(!v6.contains(&IpAddr::V4(IpAddrV4::new(10, 2, 3, 4))));
+    assert!(!v4.overlaps(&v6));
+    assert_eq!(v4.supernet().unwrap().prefix_len(), 7);
+}
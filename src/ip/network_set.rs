@@ -0,0 +1,523 @@
+use ip::{IpAddr, IpNetwork, IpNetworkV4, IpNetworkV6};
+
+use std::cmp::{self, Ordering};
+use std::slice;
+use std::iter::FromIterator;
+
+type Range = (u128, u128);
+
+fn range_of(net: &IpNetwork) -> (Range, bool) {
+    match net {
+        &IpNetwork::V4(ref net) => {
+            let range = (
+                net.network().to_u32() as u128,
+                net.broadcast().to_u32() as u128,
+            );
+            (range, true)
+        }
+        &IpNetwork::V6(ref net) => {
+            let beg = net.network().to_u128();
+            ((beg, beg | !net.netmask().to_u128()), false)
+        }
+    }
+}
+
+fn normalize(ranges: &mut Vec<Range>) {
+    ranges.sort();
+    let mut vec: Vec<Range> = Vec::with_capacity(ranges.len());
+    for (beg, end) in ranges.drain(..) {
+        if let Some(last) = vec.last_mut() {
+            // merges the overlapped and adjacent ranges.
+            if last.1 == u128::max_value() || beg <= last.1 + 1 {
+                last.1 = cmp::max(last.1, end);
+                continue;
+            }
+        }
+        vec.push((beg, end));
+    }
+    *ranges = vec;
+}
+
+fn union(lhs: &[Range], rhs: &[Range]) -> Vec<Range> {
+    let mut vec = Vec::with_capacity(lhs.len() + rhs.len());
+    vec.extend_from_slice(lhs);
+    vec.extend_from_slice(rhs);
+    normalize(&mut vec);
+    vec
+}
+
+/// Returns a number of the leading ranges which satisfy the predicate.
+fn partition<F>(ranges: &[Range], pred: F) -> usize
+where
+    F: Fn(&Range) -> bool,
+{
+    match ranges.binary_search_by(|r| if pred(r) { Ordering::Less } else { Ordering::Greater }) {
+        Ok(i) | Err(i) => i,
+    }
+}
+
+fn insert(ranges: &mut Vec<Range>, (beg, end): Range) {
+    // the ranges in `i..j` are overlapped or adjacent to the new range.
+    let i = partition(ranges, |r| r.1 < beg && r.1 + 1 < beg);
+    let j = partition(ranges, |r| r.0 <= end.saturating_add(1));
+    if i < j {
+        ranges[i] = (cmp::min(beg, ranges[i].0), cmp::max(end, ranges[j - 1].1));
+        ranges.drain(i + 1..j);
+    } else {
+        ranges.insert(i, (beg, end));
+    }
+}
+
+fn remove(ranges: &mut Vec<Range>, (beg, end): Range) {
+    // the ranges in `i..j` are overlapped to the removed range.
+    let i = partition(ranges, |r| r.1 < beg);
+    let j = partition(ranges, |r| r.0 <= end);
+    if i < j {
+        let (first, last) = (ranges[i], ranges[j - 1]);
+        let mut rest = Vec::with_capacity(2);
+        if first.0 < beg {
+            rest.push((first.0, beg - 1));
+        }
+        if last.1 > end {
+            rest.push((end + 1, last.1));
+        }
+        ranges.splice(i..j, rest);
+    }
+}
+
+fn intersection(lhs: &[Range], rhs: &[Range]) -> Vec<Range> {
+    let mut vec = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < lhs.len() && j < rhs.len() {
+        let beg = cmp::max(lhs[i].0, rhs[j].0);
+        let end = cmp::min(lhs[i].1, rhs[j].1);
+        if beg <= end {
+            vec.push((beg, end));
+        }
+        if lhs[i].1 < rhs[j].1 { i += 1 } else { j += 1 }
+    }
+    vec
+}
+
+fn difference(lhs: &[Range], rhs: &[Range]) -> Vec<Range> {
+    let mut vec = Vec::new();
+    let mut j = 0;
+    for &(beg, end) in lhs {
+        while j < rhs.len() && rhs[j].1 < beg {
+            j += 1;
+        }
+        let mut cur = Some(beg);
+        for &(rbeg, rend) in rhs[j..].iter().take_while(|r| r.0 <= end) {
+            let beg = cur.unwrap();
+            if rbeg > beg {
+                vec.push((beg, rbeg - 1));
+            }
+            if rend >= end {
+                cur = None;
+                break;
+            }
+            cur = Some(cmp::max(beg, rend + 1));
+        }
+        if let Some(beg) = cur {
+            vec.push((beg, end));
+        }
+    }
+    vec
+}
+
+fn contains(ranges: &[Range], addr: u128) -> bool {
+    match ranges.binary_search_by(|r| r.0.cmp(&addr)) {
+        Ok(_) => true,
+        Err(0) => false,
+        Err(i) => ranges[i - 1].1 >= addr,
+    }
+}
+
+/// Returns a number of the addresses minus one in the prefix of `k` host bits.
+pub fn host_mask(k: u32) -> u128 {
+    if k == 128 { !0 } else { (1 << k) - 1 }
+}
+
+/// Returns a number of host bits of the largest prefix which starts at `beg` and ends by `end`.
+pub fn host_bits(beg: u128, end: u128, bits: u32) -> u32 {
+    let mut k = cmp::min(beg.trailing_zeros(), bits);
+    while k > 0 && end - beg < host_mask(k) {
+        k -= 1;
+    }
+    k
+}
+
+/// The set of IP addresses, represented by the minimal CIDR prefixes.
+///
+/// The adjacent and overlapped prefixes are aggregated automatically.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::ip::{IpNetwork, IpNetworkSet};
+///
+/// let mut set = IpNetworkSet::new();
+/// set.insert("192.168.0.0/24".parse::<IpNetwork>().unwrap());
+/// set.insert("192.168.1.0/24".parse::<IpNetwork>().unwrap());
+/// set.insert("192.168.1.128/25".parse::<IpNetwork>().unwrap());
+///
+/// let nets: Vec<String> = set.iter().map(|net| net.to_string()).collect();
+/// assert_eq!(nets, ["192.168.0.0/23"]);
+/// assert!(set.contains(&"192.168.1.1".parse().unwrap()));
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct IpNetworkSet {
+    v4: Vec<Range>,
+    v6: Vec<Range>,
+}
+
+impl IpNetworkSet {
+    /// Returns a empty set.
+    pub fn new() -> IpNetworkSet {
+        IpNetworkSet::default()
+    }
+
+    /// Returns true if the set contains no addresses.
+    pub fn is_empty(&self) -> bool {
+        self.v4.is_empty() && self.v6.is_empty()
+    }
+
+    /// Returns a number of the aggregated prefixes.
+    ///
+    /// This takes O(n) time, the prefixes are not stored but computed from the address ranges.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Removes all addresses.
+    pub fn clear(&mut self) {
+        self.v4.clear();
+        self.v6.clear();
+    }
+
+    /// Adds the all addresses in the network.
+    pub fn insert<T>(&mut self, net: T)
+    where
+        T: Into<IpNetwork>,
+    {
+        match range_of(&net.into()) {
+            (range, true) => insert(&mut self.v4, range),
+            (range, false) => insert(&mut self.v6, range),
+        }
+    }
+
+    /// Removes the all addresses in the network.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpNetwork, IpNetworkSet};
+    ///
+    /// let mut set = IpNetworkSet::new();
+    /// set.insert("10.0.0.0/8".parse::<IpNetwork>().unwrap());
+    /// set.remove("10.128.0.0/9".parse::<IpNetwork>().unwrap());
+    /// set.remove("10.0.0.0/10".parse::<IpNetwork>().unwrap());
+    ///
+    /// let nets: Vec<String> = set.iter().map(|net| net.to_string()).collect();
+    /// assert_eq!(nets, ["10.64.0.0/10"]);
+    /// ```
+    pub fn remove<T>(&mut self, net: T)
+    where
+        T: Into<IpNetwork>,
+    {
+        match range_of(&net.into()) {
+            (range, true) => remove(&mut self.v4, range),
+            (range, false) => remove(&mut self.v6, range),
+        }
+    }
+
+    /// Returns true if the set contains the address.
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        match addr {
+            &IpAddr::V4(ref addr) => contains(&self.v4, addr.to_u32() as u128),
+            &IpAddr::V6(ref addr) => contains(&self.v6, addr.to_u128()),
+        }
+    }
+
+    /// Returns true if the set contains the all addresses in the network.
+    pub fn contains_network(&self, net: &IpNetwork) -> bool {
+        match range_of(net) {
+            (range, true) => difference(&[range], &self.v4).is_empty(),
+            (range, false) => difference(&[range], &self.v6).is_empty(),
+        }
+    }
+
+    /// Returns a set of the addresses in either set.
+    pub fn union(&self, other: &IpNetworkSet) -> IpNetworkSet {
+        IpNetworkSet {
+            v4: union(&self.v4, &other.v4),
+            v6: union(&self.v6, &other.v6),
+        }
+    }
+
+    /// Returns a set of the addresses in both sets.
+    pub fn intersection(&self, other: &IpNetworkSet) -> IpNetworkSet {
+        IpNetworkSet {
+            v4: intersection(&self.v4, &other.v4),
+            v6: intersection(&self.v6, &other.v6),
+        }
+    }
+
+    /// Returns a set of the addresses in this set, but not in the other set.
+    pub fn difference(&self, other: &IpNetworkSet) -> IpNetworkSet {
+        IpNetworkSet {
+            v4: difference(&self.v4, &other.v4),
+            v6: difference(&self.v6, &other.v6),
+        }
+    }
+
+    /// Returns a set of the addresses in the supernet, but not in this set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpNetwork, IpNetworkSet};
+    ///
+    /// let mut set = IpNetworkSet::new();
+    /// set.insert("192.168.0.0/25".parse::<IpNetwork>().unwrap());
+    /// let rest = set.complement(&"192.168.0.0/23".parse().unwrap());
+    ///
+    /// let nets: Vec<String> = rest.iter().map(|net| net.to_string()).collect();
+    /// assert_eq!(nets, ["192.168.0.128/25", "192.168.1.0/24"]);
+    /// ```
+    pub fn complement(&self, supernet: &IpNetwork) -> IpNetworkSet {
+        match range_of(supernet) {
+            (range, true) => IpNetworkSet {
+                v4: difference(&[range], &self.v4),
+                v6: Vec::new(),
+            },
+            (range, false) => IpNetworkSet {
+                v4: Vec::new(),
+                v6: difference(&[range], &self.v6),
+            },
+        }
+    }
+
+    /// Returns a iterator over the aggregated prefixes in ascending order, IPv4 first.
+    pub fn iter(&self) -> IpNetworkSetIter<'_> {
+        IpNetworkSetIter {
+            v4: self.v4.iter(),
+            v6: self.v6.iter(),
+            cur: None,
+        }
+    }
+}
+
+impl<T: Into<IpNetwork>> FromIterator<T> for IpNetworkSet {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut set = IpNetworkSet::new();
+        for net in iter {
+            match range_of(&net.into()) {
+                (range, true) => set.v4.push(range),
+                (range, false) => set.v6.push(range),
+            }
+        }
+        normalize(&mut set.v4);
+        normalize(&mut set.v6);
+        set
+    }
+}
+
+impl<'a> IntoIterator for &'a IpNetworkSet {
+    type Item = IpNetwork;
+
+    type IntoIter = IpNetworkSetIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A iterator over the prefixes of `IpNetworkSet`.
+#[derive(Clone, Debug)]
+pub struct IpNetworkSetIter<'a> {
+    v4: slice::Iter<'a, Range>,
+    v6: slice::Iter<'a, Range>,
+    cur: Option<(u128, u128, u32)>,
+}
+
+impl<'a> Iterator for IpNetworkSetIter<'a> {
+    type Item = IpNetwork;
+
+    fn next(&mut self) -> Option<IpNetwork> {
+        loop {
+            if let Some((beg, end, bits)) = self.cur {
+                let k = host_bits(beg, end, bits);
+                let last = beg + host_mask(k);
+                self.cur = if last == end {
+                    None
+                } else {
+                    Some((last + 1, end, bits))
+                };
+                let len = (bits - k) as u16;
+                return Some(if bits == 32 {
+                    IpNetwork::V4(IpNetworkV4::from((beg as u32).into(), len).unwrap())
+                } else {
+                    IpNetwork::V6(IpNetworkV6::from(beg.into(), len).unwrap())
+                });
+            }
+            self.cur = match self.v4.next() {
+                Some(&(beg, end)) => Some((beg, end, 32)),
+                None => {
+                    match self.v6.next() {
+                        Some(&(beg, end)) => Some((beg, end, 128)),
+                        None => return None,
+                    }
+                }
+            };
+        }
+    }
+}
+
+#[test]
+fn test_normalize() {
+    let mut vec = vec![(10, 20), (0, 4), (5, 5), (15, 30), (40, 50)];
+    normalize(&mut vec);
+    assert_eq!(vec, vec![(0, 5), (10, 30), (40, 50)]);
+
+    let mut vec = vec![(10, u128::max_value()), (0, 20), (30, 40)];
+    normalize(&mut vec);
+    assert_eq!(vec, vec![(0, u128::max_value())]);
+}
+
+#[test]
+fn test_range_ops() {
+    let lhs = [(0, 10), (20, 30), (40, 50)];
+    let rhs = [(5, 25), (45, 60)];
+    assert_eq!(union(&lhs, &rhs), vec![(0, 30), (40, 60)]);
+    assert_eq!(intersection(&lhs, &rhs), vec![(5, 10), (20, 25), (45, 50)]);
+    assert_eq!(difference(&lhs, &rhs), vec![(0, 4), (26, 30), (40, 44)]);
+    assert_eq!(difference(&rhs, &lhs), vec![(11, 19), (51, 60)]);
+    assert_eq!(difference(&[(0, 100)], &[(0, 100)]), vec![]);
+    assert_eq!(difference(&[(0, 10), (12, 20)], &[(5, 15)]), vec![(0, 4), (16, 20)]);
+    assert_eq!(
+        difference(&[(0, u128::max_value())], &[(1, u128::max_value() - 1)]),
+        vec![(0, 0), (u128::max_value(), u128::max_value())]
+    );
+}
+
+#[test]
+fn test_insert_remove() {
+    let mut vec = vec![(0, 4), (10, 20), (40, 50)];
+    insert(&mut vec, (25, 30));
+    assert_eq!(vec, vec![(0, 4), (10, 20), (25, 30), (40, 50)]);
+    insert(&mut vec, (5, 9));
+    assert_eq!(vec, vec![(0, 20), (25, 30), (40, 50)]);
+    insert(&mut vec, (21, 45));
+    assert_eq!(vec, vec![(0, 50)]);
+    insert(&mut vec, (60, u128::max_value()));
+    assert_eq!(vec, vec![(0, 50), (60, u128::max_value())]);
+
+    remove(&mut vec, (51, 59));
+    assert_eq!(vec, vec![(0, 50), (60, u128::max_value())]);
+    remove(&mut vec, (10, 20));
+    assert_eq!(vec, vec![(0, 9), (21, 50), (60, u128::max_value())]);
+    remove(&mut vec, (5, 70));
+    assert_eq!(vec, vec![(0, 4), (71, u128::max_value())]);
+    remove(&mut vec, (0, u128::max_value()));
+    assert_eq!(vec, vec![]);
+}
+
+#[test]
+fn test_host_bits() {
+    assert_eq!(host_bits(0, 0xffffffff, 32), 32);
+    assert_eq!(host_bits(0, u128::max_value(), 128), 128);
+    assert_eq!(host_bits(0, 0x7fffffff, 32), 31);
+    assert_eq!(host_bits(0x0a000000, 0x0affffff, 32), 24);
+    assert_eq!(host_bits(0x0a000001, 0x0affffff, 32), 0);
+    assert_eq!(host_bits(0x0a000000, 0x0a0000fe, 32), 7);
+}
+
+#[test]
+fn test_ip_network_set() {
+    use std::str::FromStr;
+
+    let net = |s| IpNetwork::from_str(s).unwrap();
+    let set: IpNetworkSet = vec![
+        net("10.0.0.0/9"),
+        net("10.128.0.0/9"),
+        net("2001:db8::/33"),
+        net("2001:db8:8000::/33"),
+        net("192.168.0.1"),
+        net("192.168.0.2"),
+    ].into_iter()
+        .collect();
+    let nets: Vec<_> = set.iter().map(|net| net.to_string()).collect();
+    assert_eq!(
+        nets,
+        [
+            "10.0.0.0/8",
+            "192.168.0.1/32",
+            "192.168.0.2/32",
+            "2001:db8::/32",
+        ]
+    );
+    assert_eq!(set.len(), 4);
+    assert!(set.contains(&"10.255.255.255".parse().unwrap()));
+    assert!(!set.contains(&"11.0.0.0".parse().unwrap()));
+    assert!(!set.contains(&"192.168.0.3".parse().unwrap()));
+    assert!(set.contains(&"2001:db8:ffff::1".parse().unwrap()));
+    assert!(!set.contains(&"::ffff:10.0.0.1".parse().unwrap()));
+    assert!(set.contains_network(&net("10.1.0.0/16")));
+    assert!(!set.contains_network(&net("10.0.0.0/7")));
+
+    let other: IpNetworkSet = vec![net("10.64.0.0/10"), net("172.16.0.0/12")]
+        .into_iter()
+        .collect();
+    let nets: Vec<_> = set.intersection(&other).iter().map(|net| net.to_string()).collect();
+    assert_eq!(nets, ["10.64.0.0/10"]);
+    let nets: Vec<_> = set.difference(&other)
+        .iter()
+        .take(2)
+        .map(|net| net.to_string())
+        .collect();
+    assert_eq!(nets, ["10.0.0.0/10", "10.128.0.0/9"]);
+    assert_eq!(set.union(&other).len(), 5);
+    assert_eq!(set.union(&other).difference(&other).union(&other), set.union(&other));
+
+    let all = set.complement(&net("128.0.0.0/1"));
+    assert!(!all.contains(&"192.168.0.1".parse().unwrap()));
+    assert!(all.contains(&"192.168.0.3".parse().unwrap()));
+    assert!(all.contains(&"255.255.255.255".parse().unwrap()));
+    assert!(all.complement(&net("128.0.0.0/1")).iter().eq(
+        vec![net("192.168.0.1"), net("192.168.0.2")],
+    ));
+
+    let mut set = set;
+    set.remove(net("0.0.0.0/1"));
+    set.remove(net("192.168.0.0/24"));
+    set.remove(net("2001:db8:1::/48"));
+    assert_eq!(set.iter().next(), Some(net("2001:db8::/48")));
+    assert_eq!(set.len(), 16);
+    set.clear();
+    assert!(set.is_empty());
+}
+
+#[test]
+fn test_ip_network_set_default_route() {
+    use std::str::FromStr;
+
+    let net = |s| IpNetwork::from_str(s).unwrap();
+    let set: IpNetworkSet = vec![net("0.0.0.0/1"), net("128.0.0.0/1"), net("::/0")]
+        .into_iter()
+        .collect();
+    assert!(set.iter().eq(vec![net("0.0.0.0/0"), net("::/0")]));
+    assert!(set.contains(&"255.255.255.255".parse().unwrap()));
+    assert!(set.contains(&"ffff::1".parse().unwrap()));
+    assert!(set.contains_network(&net("::/0")));
+
+    let rest = IpNetworkSet::new().complement(&net("::/0"));
+    assert!(rest.iter().eq(vec![net("::/0")]));
+    let mut set = set;
+    set.remove(net("::/0"));
+    set.remove(net("0.0.0.0/0"));
+    assert!(set.is_empty());
+}
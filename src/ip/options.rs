@@ -1,8 +1,10 @@
-use ffi::{IPPROTO_IP, IPPROTO_IPV6, IPPROTO_TCP, IP_ADD_MEMBERSHIP, IP_DROP_MEMBERSHIP,
-          IP_MULTICAST_IF, IP_TTL, IP_MULTICAST_TTL, IPV6_UNICAST_HOPS, IP_MULTICAST_LOOP,
-          IPV6_JOIN_GROUP, IPV6_LEAVE_GROUP, IPV6_MULTICAST_IF, IPV6_MULTICAST_HOPS,
-          IPV6_MULTICAST_LOOP, IPV6_V6ONLY, TCP_NODELAY, gethostname, in_addr, in6_addr, ip_mreq,
-          ipv6_mreq};
+use ffi::{AF_INET, AF_INET6, IPPROTO_IP, IPPROTO_IPV6, IPPROTO_TCP, IP_ADD_MEMBERSHIP,
+          IP_DROP_MEMBERSHIP, IP_MULTICAST_IF, IP_TTL, IP_MULTICAST_TTL, IPV6_UNICAST_HOPS,
+          IP_MULTICAST_LOOP, IPV6_JOIN_GROUP, IPV6_LEAVE_GROUP, IPV6_MULTICAST_IF,
+          IPV6_MULTICAST_HOPS, IPV6_MULTICAST_LOOP, IPV6_V6ONLY, TCP_NODELAY, gethostname, in_addr,
+          in6_addr, ip_mreq, ipv6_mreq};
+#[cfg(target_os = "linux")]
+use ffi::{IP_MTU_DISCOVER, IPV6_MTU_DISCOVER, IP_PMTUDISC_DO, IP_PMTUDISC_DONT};
 use core::{GetSocketOption, SetSocketOption, SocketOption, IoContext};
 use ip::{IpAddr, IpAddrV4, IpAddrV6, IpProtocol, Tcp};
 
@@ -201,20 +203,20 @@ impl UnicastHops {
 
 impl<P: IpProtocol> SocketOption<P> for UnicastHops {
     fn level(&self, pro: &P) -> i32 {
-        if pro == &P::v4() {
+        if pro.family_type() == AF_INET {
             return IPPROTO_IP.into();
         }
-        if pro == &P::v6() {
+        if pro.family_type() == AF_INET6 {
             return IPPROTO_IPV6.into();
         }
         unreachable!("Invalid ip version")
     }
 
     fn name(&self, pro: &P) -> i32 {
-        if pro == &P::v4() {
+        if pro.family_type() == AF_INET {
             return IP_TTL;
         }
-        if pro == &P::v6() {
+        if pro.family_type() == AF_INET6 {
             return IPV6_UNICAST_HOPS;
         }
         unreachable!("Invalid ip version")
@@ -225,6 +227,88 @@ impl<P: IpProtocol> GetSocketOption<P> for UnicastHops {}
 
 impl<P: IpProtocol> SetSocketOption<P> for UnicastHops {}
 
+/// Socket option for get/set the path MTU discovery, which sets the don't fragment bit to outgoing packets.
+///
+/// Implements the IPPROTO_IP/IP_MTU_DISCOVER or IPPROTO_IPV6/IPV6_MTU_DISCOVER socket option.
+///
+/// # Examples
+/// Setting the option:
+///
+/// ```
+/// use asyncio::*;
+/// use asyncio::ip::*;
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let soc = UdpSocket::new(ctx, Udp::v4()).unwrap();
+///
+/// soc.set_option(MtuDiscover::new(true)).unwrap();
+/// ```
+///
+/// Getting the option:
+///
+/// ```
+/// use asyncio::*;
+/// use asyncio::ip::*;
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let soc = UdpSocket::new(ctx, Udp::v4()).unwrap();
+///
+/// let opt: MtuDiscover = soc.get_option().unwrap();
+/// let is_set: bool = opt.get();
+/// ```
+#[cfg(target_os = "linux")]
+#[derive(Default, Clone)]
+pub struct MtuDiscover(i32);
+
+#[cfg(target_os = "linux")]
+impl MtuDiscover {
+    /// Returns a option, `true` is IP_PMTUDISC_DO and `false` is IP_PMTUDISC_DONT.
+    pub fn new(on: bool) -> MtuDiscover {
+        MtuDiscover(if on { IP_PMTUDISC_DO } else { IP_PMTUDISC_DONT })
+    }
+
+    /// Returns true if the don't fragment bit is always set.
+    ///
+    /// The IP_PMTUDISC_WANT and IP_PMTUDISC_PROBE modes are returned as `false`.
+    pub fn get(&self) -> bool {
+        self.0 == IP_PMTUDISC_DO
+    }
+
+    /// Sets the IP_PMTUDISC_DO if `on` is true, otherwise sets the IP_PMTUDISC_DONT.
+    pub fn set(&mut self, on: bool) {
+        self.0 = if on { IP_PMTUDISC_DO } else { IP_PMTUDISC_DONT }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl<P: IpProtocol> SocketOption<P> for MtuDiscover {
+    fn level(&self, pro: &P) -> i32 {
+        if pro.family_type() == AF_INET {
+            return IPPROTO_IP.into();
+        }
+        if pro.family_type() == AF_INET6 {
+            return IPPROTO_IPV6.into();
+        }
+        unreachable!("Invalid ip version")
+    }
+
+    fn name(&self, pro: &P) -> i32 {
+        if pro.family_type() == AF_INET {
+            return IP_MTU_DISCOVER;
+        }
+        if pro.family_type() == AF_INET6 {
+            return IPV6_MTU_DISCOVER;
+        }
+        unreachable!("Invalid ip version")
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl<P: IpProtocol> GetSocketOption<P> for MtuDiscover {}
+
+#[cfg(target_os = "linux")]
+impl<P: IpProtocol> SetSocketOption<P> for MtuDiscover {}
+
 /// Socket option determining whether outgoing multicast packets will be received on the same socket
 /// if it is a member of the multicast group.
 ///
@@ -274,20 +358,20 @@ impl MulticastEnableLoopback {
 
 impl<P: IpProtocol> SocketOption<P> for MulticastEnableLoopback {
     fn level(&self, pro: &P) -> i32 {
-        if pro == &P::v4() {
+        if pro.family_type() == AF_INET {
             return IPPROTO_IP.into();
         }
-        if pro == &P::v6() {
+        if pro.family_type() == AF_INET6 {
             return IPPROTO_IPV6.into();
         }
         unreachable!("Invalid ip version")
     }
 
     fn name(&self, pro: &P) -> i32 {
-        if pro == &P::v4() {
+        if pro.family_type() == AF_INET {
             return IP_MULTICAST_LOOP;
         }
-        if pro == &P::v6() {
+        if pro.family_type() == AF_INET6 {
             return IPV6_MULTICAST_LOOP;
         }
         unreachable!("Invalid ip version")
@@ -346,20 +430,20 @@ impl MulticastHops {
 
 impl<P: IpProtocol> SocketOption<P> for MulticastHops {
     fn level(&self, pro: &P) -> i32 {
-        if pro == &P::v4() {
+        if pro.family_type() == AF_INET {
             return IPPROTO_IP.into();
         }
-        if pro == &P::v6() {
+        if pro.family_type() == AF_INET6 {
             return IPPROTO_IPV6.into();
         }
         unreachable!("Invalid ip version")
     }
 
     fn name(&self, pro: &P) -> i32 {
-        if pro == &P::v4() {
+        if pro.family_type() == AF_INET {
             return IP_MULTICAST_TTL;
         }
-        if pro == &P::v6() {
+        if pro.family_type() == AF_INET6 {
             return IPV6_MULTICAST_HOPS;
         }
         unreachable!("Invalid ip version")
@@ -424,20 +508,20 @@ impl MulticastJoinGroup {
 
 impl<P: IpProtocol> SocketOption<P> for MulticastJoinGroup {
     fn level(&self, pro: &P) -> i32 {
-        if pro == &P::v4() {
+        if pro.family_type() == AF_INET {
             return IPPROTO_IP.into();
         }
-        if pro == &P::v6() {
+        if pro.family_type() == AF_INET6 {
             return IPPROTO_IPV6.into();
         }
         unreachable!("Invalid ip version")
     }
 
     fn name(&self, pro: &P) -> i32 {
-        if pro == &P::v4() {
+        if pro.family_type() == AF_INET {
             return IP_ADD_MEMBERSHIP;
         }
-        if pro == &P::v6() {
+        if pro.family_type() == AF_INET6 {
             return IPV6_JOIN_GROUP;
         }
         unreachable!("Invalid ip version")
@@ -512,20 +596,20 @@ impl MulticastLeaveGroup {
 
 impl<P: IpProtocol> SocketOption<P> for MulticastLeaveGroup {
     fn level(&self, pro: &P) -> i32 {
-        if pro == &P::v4() {
+        if pro.family_type() == AF_INET {
             return IPPROTO_IP;
         }
-        if pro == &P::v6() {
+        if pro.family_type() == AF_INET6 {
             return IPPROTO_IPV6;
         }
         unreachable!("Invalid ip version")
     }
 
     fn name(&self, pro: &P) -> i32 {
-        if pro == &P::v4() {
+        if pro.family_type() == AF_INET {
             return IP_DROP_MEMBERSHIP;
         }
-        if pro == &P::v6() {
+        if pro.family_type() == AF_INET6 {
             return IPV6_LEAVE_GROUP;
         }
         unreachable!("Invalid ip version")
@@ -585,20 +669,20 @@ impl OutboundInterface {
 
 impl<P: IpProtocol> SocketOption<P> for OutboundInterface {
     fn level(&self, pro: &P) -> i32 {
-        if pro == &P::v4() {
+        if pro.family_type() == AF_INET {
             return IPPROTO_IP;
         }
-        if pro == &P::v6() {
+        if pro.family_type() == AF_INET6 {
             return IPPROTO_IPV6;
         }
         unreachable!("Invalid ip version")
     }
 
     fn name(&self, pro: &P) -> i32 {
-        if pro == &P::v4() {
+        if pro.family_type() == AF_INET {
             return IP_MULTICAST_IF;
         }
-        if pro == &P::v6() {
+        if pro.family_type() == AF_INET6 {
             return IPV6_MULTICAST_IF;
         }
         unreachable!("Invalid ip version")
@@ -0,0 +1,424 @@
+use ffi::{AsRawFd, AF_INET, SOCK_RAW, TIMED_OUT};
+use core::{AsIoContext, Exec, IoContext, Protocol, Socket, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+use ip::{Icmp, IcmpEndpoint, IcmpHeader, IcmpSocket};
+use ip::probe::{Probe, ProbeTable, Prober, cancel_probes, start_probe};
+
+use std::io;
+use std::cmp;
+use std::process;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+const PAYLOAD: &'static [u8] = b"asyncio pinger 0123456789abcdefghijklmnopqrstuvwxyz012";
+
+/// The result of a echo request.
+#[derive(Clone, Debug)]
+pub struct PingReply {
+    seq: u16,
+    len: usize,
+    rtt: Duration,
+    ep: IcmpEndpoint,
+}
+
+impl PingReply {
+    /// Returns a sequence number of the echo reply.
+    pub fn sequence(&self) -> u16 {
+        self.seq
+    }
+
+    /// Returns a length of the echo reply, including the ICMP header.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns a round-trip time.
+    pub fn rtt(&self) -> Duration {
+        self.rtt
+    }
+
+    /// Returns a endpoint of the replied host.
+    pub fn endpoint(&self) -> &IcmpEndpoint {
+        &self.ep
+    }
+}
+
+/// The round-trip time statistics of the `Pinger`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PingStats {
+    transmitted: u64,
+    received: u64,
+    min: Duration,
+    max: Duration,
+    sum: Duration,
+}
+
+impl PingStats {
+    fn add(&mut self, rtt: Duration) {
+        if self.received == 0 {
+            self.min = rtt;
+            self.max = rtt;
+        } else {
+            self.min = cmp::min(self.min, rtt);
+            self.max = cmp::max(self.max, rtt);
+        }
+        self.sum += rtt;
+        self.received += 1;
+    }
+
+    /// Returns a number of the transmitted echo requests.
+    pub fn transmitted(&self) -> u64 {
+        self.transmitted
+    }
+
+    /// Returns a number of the received echo replies.
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    /// Returns a packet loss ratio in the range of `0.0` to `1.0`.
+    pub fn loss(&self) -> f64 {
+        if self.transmitted == 0 {
+            0.0
+        } else {
+            1.0 - self.received as f64 / self.transmitted as f64
+        }
+    }
+
+    /// Returns a minimum round-trip time.
+    pub fn min_rtt(&self) -> Option<Duration> {
+        if self.received == 0 { None } else { Some(self.min) }
+    }
+
+    /// Returns a average round-trip time.
+    pub fn avg_rtt(&self) -> Option<Duration> {
+        if self.received == 0 {
+            None
+        } else {
+            Some(self.sum / self.received as u32)
+        }
+    }
+
+    /// Returns a maximum round-trip time.
+    pub fn max_rtt(&self) -> Option<Duration> {
+        if self.received == 0 { None } else { Some(self.max) }
+    }
+}
+
+/// Provides the ICMP echo request/reply (ping) functionality.
+///
+/// The pinger issues the echo requests with the sequence numbers, and waits for each reply until its timeout expired.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io;
+/// use std::sync::Arc;
+/// use asyncio::{IoContext, wrap};
+/// use asyncio::ip::{Icmp, IcmpEndpoint, IpAddrV4, Pinger, PingReply};
+///
+/// fn on_ping(pinger: Arc<Pinger>, res: io::Result<PingReply>) {
+///     match res {
+///         Ok(reply) => println!("seq={} time={:?}", reply.sequence(), reply.rtt()),
+///         Err(err) => println!("{}", err),
+///     }
+/// }
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let pinger = Arc::new(Pinger::new(ctx, Icmp::v4_dgram()).unwrap());
+/// pinger.async_ping(&IcmpEndpoint::new(IpAddrV4::loopback(), 0), wrap(&pinger, on_ping));
+/// ctx.run();
+/// ```
+pub struct Pinger {
+    soc: IcmpSocket,
+    ident: u16,
+    seq: AtomicUsize,
+    timeout: Mutex<Duration>,
+    stats: Mutex<PingStats>,
+    probes: ProbeTable<(usize, IcmpEndpoint)>,
+}
+
+impl Pinger {
+    /// Returns a pinger of the ICMP protocol.
+    ///
+    /// The `Icmp::v4()` and `Icmp::v6()` require the privilege to open raw sockets,
+    /// the `Icmp::v4_dgram()` and `Icmp::v6_dgram()` does not.
+    pub fn new(ctx: &IoContext, pro: Icmp) -> io::Result<Pinger> {
+        let soc = IcmpSocket::new(ctx, pro)?;
+        let ident = (process::id() as u16).wrapping_add(soc.as_raw_fd() as u16);
+        Ok(Pinger {
+            soc: soc,
+            ident: ident,
+            seq: AtomicUsize::new(0),
+            timeout: Mutex::new(Duration::new(1, 0)),
+            stats: Mutex::default(),
+            probes: ProbeTable::new(),
+        })
+    }
+
+    /// Returns a timeout of each echo request. Default is 1 second.
+    pub fn get_timeout(&self) -> Duration {
+        *self.timeout.lock().unwrap()
+    }
+
+    /// Sets a timeout of each echo request.
+    pub fn set_timeout(&self, timeout: Duration) {
+        *self.timeout.lock().unwrap() = timeout
+    }
+
+    /// Returns a snapshot of the statistics.
+    pub fn stats(&self) -> PingStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Sends a echo request to the endpoint, and asynchronously waits for the echo reply.
+    ///
+    /// Each echo request has own timer, so multiple requests may be outstanding at the same time.
+    /// If the reply was not received within the timeout, the handler is called with `TimedOut`.
+    pub fn async_ping<F>(&self, ep: &IcmpEndpoint, handler: F) -> F::Output
+    where
+        F: Handler<PingReply, io::Error>,
+    {
+        handler.wrap_cancel(self, move |ctx, handler| {
+            ctx.do_dispatch(PingStart {
+                pinger: self,
+                ep: ep.clone(),
+                handler: handler,
+            })
+        })
+    }
+}
+
+unsafe impl AsIoContext for Pinger {
+    fn as_ctx(&self) -> &IoContext {
+        self.soc.as_ctx()
+    }
+}
+
+impl Cancel for Pinger {
+    fn cancel(&self) {
+        cancel_probes(self)
+    }
+}
+
+unsafe impl Send for Pinger {}
+
+unsafe impl Sync for Pinger {}
+
+impl Prober for Pinger {
+    type Message = (usize, IcmpEndpoint);
+
+    fn icmp(&self) -> &IcmpSocket {
+        &self.soc
+    }
+
+    fn probes(&self) -> &ProbeTable<Self::Message> {
+        &self.probes
+    }
+
+    fn parse(&self, buf: &[u8], ep: IcmpEndpoint) -> Option<(u16, Self::Message)> {
+        let pro = self.soc.protocol();
+        let len = buf.len();
+        let buf = if pro.family_type() == AF_INET && pro.socket_type() == SOCK_RAW {
+            // the raw IPv4 socket receives the IP header too.
+            let ihl = buf.first().map_or(0, |&ch| (ch & 0x0f) as usize * 4);
+            &buf[cmp::min(ihl, buf.len())..]
+        } else {
+            buf
+        };
+        match IcmpHeader::decode(buf) {
+            Some((ref hdr, _)) if hdr.is_echo_reply() &&
+                                      (pro.socket_type() != SOCK_RAW ||
+                                           hdr.identifier() == self.ident) => {
+                Some((hdr.sequence(), (len, ep)))
+            }
+            _ => None,
+        }
+    }
+}
+
+struct PingStart<F> {
+    pinger: *const Pinger,
+    ep: IcmpEndpoint,
+    handler: F,
+}
+
+unsafe impl<F> Send for PingStart<F> {}
+
+impl<F> Exec for PingStart<F>
+where
+    F: Complete<PingReply, io::Error>,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        let pinger = unsafe { &*self.pinger };
+        let seq = pinger.seq.fetch_add(1, Ordering::SeqCst) as u16;
+        let msg = IcmpHeader::echo_request(pinger.soc.protocol(), pinger.ident, seq)
+            .encode(pinger.soc.protocol(), PAYLOAD);
+        let start = Instant::now();
+        if let Err(err) = pinger.soc.nonblocking_send_to(&msg, 0, &self.ep) {
+            return self.handler.failure(this, err);
+        }
+        pinger.stats.lock().unwrap().transmitted += 1;
+        start_probe(
+            pinger,
+            this,
+            seq,
+            pinger.get_timeout(),
+            Box::new(PingProbe {
+                pinger: pinger,
+                seq: seq,
+                start: start,
+                handler: self.handler,
+            }),
+        )
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.call(this)
+    }
+}
+
+struct PingProbe<F> {
+    pinger: *const Pinger,
+    seq: u16,
+    start: Instant,
+    handler: F,
+}
+
+unsafe impl<F> Send for PingProbe<F> {}
+
+impl<F> Probe<(usize, IcmpEndpoint)> for PingProbe<F>
+where
+    F: Complete<PingReply, io::Error>,
+{
+    fn reply(self: Box<Self>, this: &mut ThreadIoContext, (len, ep): (usize, IcmpEndpoint)) {
+        let pinger = unsafe { &*self.pinger };
+        let rtt = self.start.elapsed();
+        pinger.stats.lock().unwrap().add(rtt);
+        let PingProbe { seq, handler, .. } = *self;
+        handler.success(
+            this,
+            PingReply {
+                seq: seq,
+                len: len,
+                rtt: rtt,
+                ep: ep,
+            },
+        )
+    }
+
+    fn timeout(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.handler.failure(this, TIMED_OUT.into())
+    }
+
+    fn failure(self: Box<Self>, this: &mut ThreadIoContext, err: io::Error) {
+        self.handler.failure(this, err)
+    }
+}
+
+#[test]
+fn test_ping_stats() {
+    let mut stats = PingStats::default();
+    assert_eq!(stats.loss(), 0.0);
+    assert_eq!(stats.avg_rtt(), None);
+    stats.transmitted = 4;
+    stats.add(Duration::from_millis(10));
+    stats.add(Duration::from_millis(30));
+    assert_eq!(stats.received(), 2);
+    assert_eq!(stats.loss(), 0.5);
+    assert_eq!(stats.min_rtt(), Some(Duration::from_millis(10)));
+    assert_eq!(stats.avg_rtt(), Some(Duration::from_millis(20)));
+    assert_eq!(stats.max_rtt(), Some(Duration::from_millis(30)));
+}
+
+#[test]
+fn test_pinger_loopback() {
+    use std::sync::Arc;
+    use handler::wrap;
+    use ip::{IpAddrV4, IpProtocol};
+
+    let ctx = &IoContext::new().unwrap();
+    let pinger = match Pinger::new(ctx, Icmp::v4_dgram()).or_else(|_| Pinger::new(ctx, Icmp::v4())) {
+        Ok(pinger) => Arc::new(pinger),
+        Err(_) => return, // requires ping_group_range or CAP_NET_RAW
+    };
+    let ep = IcmpEndpoint::new(IpAddrV4::loopback(), 0);
+    for _ in 0..3 {
+        ctx.restart();
+        pinger.async_ping(&ep, wrap(&pinger, |_, res: io::Result<PingReply>| {
+            let reply = res.unwrap();
+            assert!(reply.endpoint().addr().is_loopback());
+        }));
+        ctx.run();
+    }
+    let stats = pinger.stats();
+    assert_eq!(stats.transmitted(), 3);
+    assert_eq!(stats.received(), 3);
+}
+
+#[test]
+#[ignore] // requires ping_group_range or CAP_NET_RAW, and TEST-NET-3 is not replied
+fn test_pinger_timeout() {
+    use std::sync::Arc;
+    use handler::wrap;
+    use ip::{IpAddrV4, IpProtocol};
+
+    let ctx = &IoContext::new().unwrap();
+    let pinger = match Pinger::new(ctx, Icmp::v4_dgram()).or_else(|_| Pinger::new(ctx, Icmp::v4())) {
+        Ok(pinger) => Arc::new(pinger),
+        Err(_) => return, // requires ping_group_range or CAP_NET_RAW
+    };
+    pinger.set_timeout(Duration::from_millis(50));
+    // TEST-NET-3 is never replied.
+    let ep = IcmpEndpoint::new(IpAddrV4::new(203, 0, 113, 1), 0);
+    pinger.async_ping(&ep, wrap(&pinger, |_, res: io::Result<PingReply>| {
+        // the network unreachable is also acceptable.
+        assert!(res.is_err());
+    }));
+    ctx.run();
+    assert_eq!(pinger.stats().received(), 0);
+}
+
+#[test]
+fn test_pinger_concurrent() {
+    use std::sync::Arc;
+    use handler::wrap;
+    use ip::{IpAddrV4, IpProtocol};
+
+    let ctx = &IoContext::new().unwrap();
+    let pinger = match Pinger::new(ctx, Icmp::v4_dgram()).or_else(|_| Pinger::new(ctx, Icmp::v4())) {
+        Ok(pinger) => Arc::new(pinger),
+        Err(_) => return, // requires ping_group_range or CAP_NET_RAW
+    };
+    let ep = IcmpEndpoint::new(IpAddrV4::loopback(), 0);
+    for _ in 0..3 {
+        pinger.async_ping(&ep, wrap(&pinger, |_, res: io::Result<PingReply>| {
+            assert!(res.unwrap().endpoint().addr().is_loopback());
+        }));
+    }
+    ctx.run();
+    assert_eq!(pinger.stats().received(), 3);
+}
+
+#[test]
+#[ignore] // requires ping_group_range or CAP_NET_RAW, and TEST-NET-3 is not replied
+fn test_pinger_cancel() {
+    use std::sync::Arc;
+    use handler::wrap;
+    use ip::{IpAddrV4, IpProtocol};
+
+    let ctx = &IoContext::new().unwrap();
+    let pinger = match Pinger::new(ctx, Icmp::v4_dgram()).or_else(|_| Pinger::new(ctx, Icmp::v4())) {
+        Ok(pinger) => Arc::new(pinger),
+        Err(_) => return, // requires ping_group_range or CAP_NET_RAW
+    };
+    pinger.set_timeout(Duration::new(60, 0));
+    let ep = IcmpEndpoint::new(IpAddrV4::new(203, 0, 113, 1), 0);
+    pinger.async_ping(&ep, wrap(&pinger, |_, res: io::Result<PingReply>| {
+        assert!(res.is_err());
+    }));
+    let cancel = pinger.clone();
+    ctx.post(move |_| cancel.cancel());
+    ctx.run();
+}
@@ -0,0 +1,408 @@
+use ip::{IpAddr, IpNetwork, IpNetworkV4, IpNetworkV6};
+
+use std::cmp;
+use std::mem;
+
+fn make_mask(len: u8) -> u128 {
+    if len == 0 { 0 } else { !0 << (128 - len) }
+}
+
+fn bit(key: u128, pos: u8) -> usize {
+    (key >> (127 - pos) & 1) as usize
+}
+
+/// Returns a left-aligned key and prefix length, and true if IPv4.
+fn key_of(net: &IpNetwork) -> (u128, u8, bool) {
+    match net {
+        &IpNetwork::V4(ref net) => {
+            let key = (net.network().to_u32() as u128) << 96;
+            (key, net.prefix_len() as u8, true)
+        }
+        &IpNetwork::V6(ref net) => (net.network().to_u128(), net.prefix_len() as u8, false),
+    }
+}
+
+fn network_of(key: u128, len: u8, is_v4: bool) -> IpNetwork {
+    if is_v4 {
+        IpNetwork::V4(IpNetworkV4::from(((key >> 96) as u32).into(), len as u16).unwrap())
+    } else {
+        IpNetwork::V6(IpNetworkV6::from(key.into(), len as u16).unwrap())
+    }
+}
+
+struct Node<T> {
+    key: u128,
+    len: u8,
+    value: Option<T>,
+    child: [Option<Box<Node<T>>>; 2],
+}
+
+impl<T> Node<T> {
+    fn new(key: u128, len: u8, value: Option<T>) -> Box<Node<T>> {
+        Box::new(Node {
+            key: key,
+            len: len,
+            value: value,
+            child: [None, None],
+        })
+    }
+}
+
+fn insert<T>(slot: &mut Option<Box<Node<T>>>, key: u128, len: u8, value: T) -> Option<T> {
+    let mut node = match slot.take() {
+        Some(node) => node,
+        None => {
+            *slot = Some(Node::new(key, len, Some(value)));
+            return None;
+        }
+    };
+    let diff = ((node.key ^ key).leading_zeros()) as u8;
+    let common = cmp::min(diff, cmp::min(node.len, len));
+    let old = if common == node.len && common == len {
+        mem::replace(&mut node.value, Some(value))
+    } else if common == node.len {
+        insert(&mut node.child[bit(key, common)], key, len, value)
+    } else if common == len {
+        let mut new = Node::new(key, len, Some(value));
+        let pos = bit(node.key, common);
+        new.child[pos] = Some(node);
+        node = new;
+        None
+    } else {
+        let mut branch = Node::new(key & make_mask(common), common, None);
+        branch.child[bit(key, common)] = Some(Node::new(key, len, Some(value)));
+        let pos = bit(node.key, common);
+        branch.child[pos] = Some(node);
+        node = branch;
+        None
+    };
+    *slot = Some(node);
+    old
+}
+
+fn remove<T>(slot: &mut Option<Box<Node<T>>>, key: u128, len: u8) -> Option<T> {
+    let mut node = match slot.take() {
+        Some(node) => node,
+        None => return None,
+    };
+    let old = if node.len > len || (node.key ^ key) & make_mask(node.len) != 0 {
+        None
+    } else if node.len == len {
+        node.value.take()
+    } else {
+        remove(&mut node.child[bit(key, node.len)], key, len)
+    };
+    // removes the branch node which is no longer needed.
+    *slot = if node.value.is_some() {
+        Some(node)
+    } else {
+        match (node.child[0].take(), node.child[1].take()) {
+            (None, None) => None,
+            (Some(child), None) |
+            (None, Some(child)) => Some(child),
+            (lhs, rhs) => {
+                node.child = [lhs, rhs];
+                Some(node)
+            }
+        }
+    };
+    old
+}
+
+fn find<T>(mut node: Option<&Box<Node<T>>>, key: u128, len: u8) -> Option<&Box<Node<T>>> {
+    while let Some(n) = node {
+        if n.len > len || (n.key ^ key) & make_mask(n.len) != 0 {
+            break;
+        }
+        if n.len == len {
+            return Some(n);
+        }
+        node = n.child[bit(key, n.len)].as_ref();
+    }
+    None
+}
+
+/// The routing table which maps the IP prefixes to the values,
+/// with the longest prefix match lookup.
+///
+/// The prefixes are stored in a path-compressed binary (Patricia) trie,
+/// so the lookup takes at most 32 or 128 steps regardless of the number of prefixes.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::ip::{IpNetwork, PrefixTable};
+///
+/// let mut table = PrefixTable::new();
+/// table.insert("10.0.0.0/8".parse::<IpNetwork>().unwrap(), "eth0");
+/// table.insert("10.1.0.0/16".parse::<IpNetwork>().unwrap(), "eth1");
+///
+/// let (net, iface) = table.longest_match(&"10.1.2.3".parse().unwrap()).unwrap();
+/// assert_eq!(net.to_string(), "10.1.0.0/16");
+/// assert_eq!(*iface, "eth1");
+/// assert_eq!(table.longest_match(&"10.2.0.1".parse().unwrap()).unwrap().1, &"eth0");
+/// assert!(table.longest_match(&"192.168.0.1".parse().unwrap()).is_none());
+/// ```
+pub struct PrefixTable<T> {
+    v4: Option<Box<Node<T>>>,
+    v6: Option<Box<Node<T>>>,
+    len: usize,
+}
+
+impl<T> PrefixTable<T> {
+    /// Returns a empty table.
+    pub fn new() -> PrefixTable<T> {
+        PrefixTable {
+            v4: None,
+            v6: None,
+            len: 0,
+        }
+    }
+
+    /// Returns a number of the prefixes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the table has no prefixes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all prefixes.
+    pub fn clear(&mut self) {
+        self.v4 = None;
+        self.v6 = None;
+        self.len = 0;
+    }
+
+    /// Inserts a value of the prefix, and returns the old value if the prefix was present.
+    ///
+    /// The host bits of the network are ignored.
+    pub fn insert<N>(&mut self, net: N, value: T) -> Option<T>
+    where
+        N: Into<IpNetwork>,
+    {
+        let (key, len, is_v4) = key_of(&net.into());
+        let root = if is_v4 { &mut self.v4 } else { &mut self.v6 };
+        let old = insert(root, key, len, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Removes a value of the prefix, and returns it if the prefix was present.
+    pub fn remove(&mut self, net: &IpNetwork) -> Option<T> {
+        let (key, len, is_v4) = key_of(net);
+        let root = if is_v4 { &mut self.v4 } else { &mut self.v6 };
+        let old = remove(root, key, len);
+        if old.is_some() {
+            self.len -= 1;
+        }
+        old
+    }
+
+    /// Returns a value of the exactly matched prefix.
+    pub fn get(&self, net: &IpNetwork) -> Option<&T> {
+        let (key, len, is_v4) = key_of(net);
+        let root = if is_v4 { &self.v4 } else { &self.v6 };
+        find(root.as_ref(), key, len).and_then(|node| node.value.as_ref())
+    }
+
+    /// Returns a mutable value of the exactly matched prefix.
+    pub fn get_mut(&mut self, net: &IpNetwork) -> Option<&mut T> {
+        let (key, len, is_v4) = key_of(net);
+        let mut node = if is_v4 { &mut self.v4 } else { &mut self.v6 };
+        while let Some(n) = { node } {
+            if n.len > len || (n.key ^ key) & make_mask(n.len) != 0 {
+                break;
+            }
+            if n.len == len {
+                return n.value.as_mut();
+            }
+            node = &mut n.child[bit(key, n.len)];
+        }
+        None
+    }
+
+    /// Returns a longest prefix and the value which contains the address.
+    pub fn longest_match(&self, addr: &IpAddr) -> Option<(IpNetwork, &T)> {
+        let (mut node, key, bits, is_v4) = match addr {
+            &IpAddr::V4(ref addr) => (self.v4.as_ref(), (addr.to_u32() as u128) << 96, 32, true),
+            &IpAddr::V6(ref addr) => (self.v6.as_ref(), addr.to_u128(), 128, false),
+        };
+        let mut found = None;
+        while let Some(n) = node {
+            if (n.key ^ key) & make_mask(n.len) != 0 {
+                break;
+            }
+            if let Some(ref value) = n.value {
+                found = Some((n.key, n.len, value));
+            }
+            if n.len == bits {
+                break;
+            }
+            node = n.child[bit(key, n.len)].as_ref();
+        }
+        found.map(|(key, len, value)| (network_of(key, len, is_v4), value))
+    }
+
+    /// Returns a iterator over the prefixes and values in ascending order, IPv4 first.
+    pub fn iter(&self) -> PrefixTableIter<'_, T> {
+        let mut stack = Vec::new();
+        if let Some(ref node) = self.v6 {
+            stack.push((&**node, false));
+        }
+        if let Some(ref node) = self.v4 {
+            stack.push((&**node, true));
+        }
+        PrefixTableIter { stack: stack }
+    }
+}
+
+impl<T> Default for PrefixTable<T> {
+    fn default() -> Self {
+        PrefixTable::new()
+    }
+}
+
+/// A iterator over the prefixes and values of `PrefixTable`.
+pub struct PrefixTableIter<'a, T: 'a> {
+    stack: Vec<(&'a Node<T>, bool)>,
+}
+
+impl<'a, T> Iterator for PrefixTableIter<'a, T> {
+    type Item = (IpNetwork, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, is_v4)) = self.stack.pop() {
+            for child in node.child.iter().rev() {
+                if let Some(ref child) = *child {
+                    self.stack.push((&**child, is_v4));
+                }
+            }
+            if let Some(ref value) = node.value {
+                return Some((network_of(node.key, node.len, is_v4), value));
+            }
+        }
+        None
+    }
+}
+
+#[test]
+fn test_prefix_table() {
+    use std::str::FromStr;
+
+    let net = |s| IpNetwork::from_str(s).unwrap();
+    let addr = |s| IpAddr::from_str(s).unwrap();
+    let mut table = PrefixTable::new();
+    assert_eq!(table.insert(net("10.0.0.0/8"), 1), None);
+    assert_eq!(table.insert(net("10.1.0.0/16"), 2), None);
+    assert_eq!(table.insert(net("10.1.2.0/24"), 3), None);
+    assert_eq!(table.insert(net("10.128.0.0/9"), 4), None);
+    assert_eq!(table.insert(net("0.0.0.0/1"), 5), None);
+    assert_eq!(table.insert(net("2001:db8::/32"), 6), None);
+    assert_eq!(table.insert(net("10.1.2.3/8"), 10), Some(1));
+    assert_eq!(table.len(), 6);
+
+    assert_eq!(table.longest_match(&addr("10.1.2.3")), Some((net("10.1.2.0/24"), &3)));
+    assert_eq!(table.longest_match(&addr("10.1.3.3")), Some((net("10.1.0.0/16"), &2)));
+    assert_eq!(table.longest_match(&addr("10.200.0.1")), Some((net("10.128.0.0/9"), &4)));
+    assert_eq!(table.longest_match(&addr("10.2.0.1")), Some((net("10.0.0.0/8"), &10)));
+    assert_eq!(table.longest_match(&addr("11.0.0.1")), Some((net("0.0.0.0/1"), &5)));
+    assert_eq!(table.longest_match(&addr("192.168.0.1")), None);
+    assert_eq!(table.longest_match(&addr("2001:db8::1")), Some((net("2001:db8::/32"), &6)));
+    assert_eq!(table.longest_match(&addr("::ffff:10.0.0.1")), None);
+
+    assert_eq!(table.get(&net("10.1.0.0/16")), Some(&2));
+    assert_eq!(table.get(&net("10.1.0.0/17")), None);
+    assert_eq!(table.get(&net("10.0.0.0/7")), None);
+    *table.get_mut(&net("10.1.2.0/24")).unwrap() = 30;
+    assert!(table.get_mut(&net("10.1.3.0/24")).is_none());
+
+    let nets: Vec<_> = table.iter().map(|(net, &value)| (net.to_string(), value)).collect();
+    assert_eq!(
+        nets,
+        [
+            ("0.0.0.0/1".to_string(), 5),
+            ("10.0.0.0/8".to_string(), 10),
+            ("10.1.0.0/16".to_string(), 2),
+            ("10.1.2.0/24".to_string(), 30),
+            ("10.128.0.0/9".to_string(), 4),
+            ("2001:db8::/32".to_string(), 6),
+        ]
+    );
+
+    assert_eq!(table.remove(&net("10.1.0.0/16")), Some(2));
+    assert_eq!(table.remove(&net("10.1.0.0/16")), None);
+    assert_eq!(table.remove(&net("10.1.0.0/17")), None);
+    assert_eq!(table.longest_match(&addr("10.1.3.3")), Some((net("10.0.0.0/8"), &10)));
+    assert_eq!(table.longest_match(&addr("10.1.2.3")), Some((net("10.1.2.0/24"), &30)));
+    assert_eq!(table.remove(&net("10.0.0.0/8")), Some(10));
+    assert_eq!(table.longest_match(&addr("10.1.3.3")), Some((net("0.0.0.0/1"), &5)));
+    assert_eq!(table.len(), 4);
+    table.clear();
+    assert!(table.is_empty());
+    assert_eq!(table.iter().count(), 0);
+}
+
+#[test]
+fn test_prefix_table_hosts() {
+    use ip::{IpAddrV4, IpAddrV6};
+
+    let mut table = PrefixTable::new();
+    for i in 0..256 {
+        let addr = IpAddrV4::new(192, 168, 0, i as u8);
+        table.insert(IpNetworkV4::from(addr, 32).unwrap(), i);
+    }
+    table.insert(
+        IpNetworkV6::from(IpAddrV6::loopback(), 128).unwrap(),
+        256,
+    );
+    assert_eq!(table.len(), 257);
+    for i in 0..256 {
+        let addr = IpAddr::V4(IpAddrV4::new(192, 168, 0, i as u8));
+        assert_eq!(table.longest_match(&addr).unwrap().1, &i);
+    }
+    assert_eq!(
+        table.longest_match(&IpAddr::V6(IpAddrV6::loopback())).unwrap().1,
+        &256
+    );
+    assert!(table.iter().map(|(_, &i)| i).eq(0..257));
+    for i in 0..256 {
+        let addr = IpAddrV4::new(192, 168, 0, i as u8);
+        assert_eq!(table.remove(&IpNetworkV4::from(addr, 32).unwrap().into()), Some(i));
+    }
+    assert_eq!(table.len(), 1);
+    assert!(table.v4.is_none());
+}
+
+#[test]
+fn test_prefix_table_default_route() {
+    use std::str::FromStr;
+
+    let net = |s| IpNetwork::from_str(s).unwrap();
+    let addr = |s| IpAddr::from_str(s).unwrap();
+    let mut table = PrefixTable::new();
+    assert_eq!(table.insert(net("10.0.0.0/8"), 1), None);
+    assert_eq!(table.insert(net("0.0.0.0/0"), 2), None);
+    assert_eq!(table.insert(net("2001:db8::/32"), 3), None);
+    assert_eq!(table.insert(net("::/0"), 4), None);
+    assert_eq!(table.insert(net("192.168.0.1/0"), 5), Some(2));
+
+    assert_eq!(table.longest_match(&addr("10.1.2.3")), Some((net("10.0.0.0/8"), &1)));
+    assert_eq!(table.longest_match(&addr("192.168.0.1")), Some((net("0.0.0.0/0"), &5)));
+    assert_eq!(table.longest_match(&addr("2001:db8::1")), Some((net("2001:db8::/32"), &3)));
+    assert_eq!(table.longest_match(&addr("::1")), Some((net("::/0"), &4)));
+    assert_eq!(table.get(&net("::/0")), Some(&4));
+
+    let nets: Vec<_> = table.iter().map(|(net, _)| net.to_string()).collect();
+    assert_eq!(nets, ["0.0.0.0/0", "10.0.0.0/8", "::/0", "2001:db8::/32"]);
+
+    assert_eq!(table.remove(&net("0.0.0.0/0")), Some(5));
+    assert_eq!(table.longest_match(&addr("192.168.0.1")), None);
+    assert_eq!(table.remove(&net("::/0")), Some(4));
+    assert_eq!(table.longest_match(&addr("::1")), None);
+    assert_eq!(table.len(), 2);
+}
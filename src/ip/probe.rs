@@ -0,0 +1,316 @@
+use ffi::{Timeout, OPERATION_CANCELED};
+use core::{AsIoContext, Exec, IoContext, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+use ip::{IcmpEndpoint, IcmpSocket};
+use SteadyTimer;
+
+use std::io;
+use std::mem;
+use std::slice;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use libc;
+
+/// A probe in flight, which is completed by the response or the expiry of its own timer.
+pub trait Probe<M>: Send + 'static {
+    fn reply(self: Box<Self>, this: &mut ThreadIoContext, msg: M);
+
+    fn timeout(self: Box<Self>, this: &mut ThreadIoContext);
+
+    fn failure(self: Box<Self>, this: &mut ThreadIoContext, err: io::Error);
+}
+
+/// The sender of the probes, which receives the responses by the ICMP socket.
+pub trait Prober: Sync + 'static {
+    type Message: Send + 'static;
+
+    fn icmp(&self) -> &IcmpSocket;
+
+    fn probes(&self) -> &ProbeTable<Self::Message>;
+
+    /// Returns the key of the probe and the message, if the received bytes are the response of it.
+    fn parse(&self, buf: &[u8], ep: IcmpEndpoint) -> Option<(u16, Self::Message)>;
+}
+
+struct Pending<M> {
+    id: usize,
+    timer: Box<SteadyTimer>,
+    probe: Box<Probe<M>>,
+}
+
+struct ProbeState<M> {
+    pending: HashMap<u16, Pending<M>>,
+    next_id: usize,
+    receiving: bool,
+}
+
+/// The probes in flight keyed by the sequence number or the port.
+///
+/// A receive loop on the ICMP socket demultiplexes the responses to the probes,
+/// and runs while any probe is in flight.
+pub struct ProbeTable<M> {
+    state: Mutex<ProbeState<M>>,
+}
+
+impl<M> ProbeTable<M>
+where
+    M: Send + 'static,
+{
+    pub fn new() -> Self {
+        ProbeTable {
+            state: Mutex::new(ProbeState {
+                pending: HashMap::new(),
+                next_id: 0,
+                receiving: false,
+            }),
+        }
+    }
+}
+
+fn copy_error(err: &io::Error) -> io::Error {
+    match err.raw_os_error() {
+        Some(code) => io::Error::from_raw_os_error(code),
+        None => err.kind().into(),
+    }
+}
+
+/// Starts the timer of the probe which has been sent, and the receive loop if it is not running.
+///
+/// Must be called on the thread which runs the `IoContext`.
+pub fn start_probe<P>(
+    prober: &P,
+    this: &mut ThreadIoContext,
+    key: u16,
+    timeout: Duration,
+    probe: Box<Probe<P::Message>>,
+) where
+    P: Prober,
+{
+    let timer = Box::new(SteadyTimer::new(prober.icmp().as_ctx()));
+    let (old, receive) = {
+        let mut state = prober.probes().state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id = id.wrapping_add(1);
+        timer.expires_from_now(timeout);
+        timer.async_wait(ProbeTimeout {
+            prober: prober,
+            key: key,
+            id: id,
+        });
+        let old = state.pending.insert(
+            key,
+            Pending {
+                id: id,
+                timer: timer,
+                probe: probe,
+            },
+        );
+        (old, !mem::replace(&mut state.receiving, true))
+    };
+    if let Some(old) = old {
+        // the key has wrapped around while the old probe is in flight.
+        old.timer.cancel();
+        old.probe.timeout(this);
+    }
+    if receive {
+        ProbeRecv {
+            prober: prober,
+            buf: vec![0; 1500],
+        }.receive()
+    }
+}
+
+struct ProbeCanceled<M> {
+    probe: Box<Probe<M>>,
+}
+
+impl<M> Exec for ProbeCanceled<M>
+where
+    M: Send + 'static,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        self.probe.failure(this, OPERATION_CANCELED.into())
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.call(this)
+    }
+
+    fn outstanding_work(&self, _: &IoContext) {}
+}
+
+/// Fails all probes in flight with `OPERATION_CANCELED`, and stops the receive loop.
+pub fn cancel_probes<P>(prober: &P)
+where
+    P: Prober,
+{
+    let pending: Vec<_> = prober
+        .probes()
+        .state
+        .lock()
+        .unwrap()
+        .pending
+        .drain()
+        .map(|(_, pending)| pending)
+        .collect();
+    let ctx = prober.icmp().as_ctx();
+    for pending in pending {
+        pending.timer.cancel();
+        ctx.do_post(ProbeCanceled { probe: pending.probe });
+    }
+    if ThreadIoContext::callstack(ctx).is_none() {
+        ctx.as_reactor().interrupt();
+    }
+    prober.icmp().cancel();
+}
+
+struct ProbeTimeout<P> {
+    prober: *const P,
+    key: u16,
+    id: usize,
+}
+
+unsafe impl<P> Send for ProbeTimeout<P> {}
+
+impl<P> Handler<(), io::Error> for ProbeTimeout<P>
+where
+    P: Prober,
+{
+    type Output = ();
+
+    type WrappedHandler = Self;
+
+    fn wrap<W>(self, ctx: &IoContext, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        wrapper(ctx, self)
+    }
+
+    fn wrap_timeout<W>(self, ctx: &Cancel, _: &Timeout, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        wrapper(ctx.as_ctx(), self)
+    }
+}
+
+impl<P> Complete<(), io::Error> for ProbeTimeout<P>
+where
+    P: Prober,
+{
+    fn success(self, this: &mut ThreadIoContext, _: ()) {
+        let prober = unsafe { &*self.prober };
+        let (pending, idle) = {
+            let mut state = prober.probes().state.lock().unwrap();
+            let pending = match state.pending.get(&self.key) {
+                Some(pending) if pending.id == self.id => state.pending.remove(&self.key),
+                _ => None,
+            };
+            (pending, state.pending.is_empty())
+        };
+        if let Some(pending) = pending {
+            if idle {
+                // stops the receive loop, since no probe is in flight.
+                prober.icmp().cancel();
+            }
+            pending.probe.timeout(this);
+        }
+        this.decrease_outstanding_work();
+    }
+
+    fn failure(self, this: &mut ThreadIoContext, _: io::Error) {
+        this.decrease_outstanding_work();
+    }
+}
+
+struct ProbeRecv<P> {
+    prober: *const P,
+    buf: Vec<u8>,
+}
+
+unsafe impl<P> Send for ProbeRecv<P> {}
+
+impl<P> ProbeRecv<P>
+where
+    P: Prober,
+{
+    fn receive(mut self) {
+        let prober = unsafe { &*self.prober };
+        let buf = unsafe { slice::from_raw_parts_mut(self.buf.as_mut_ptr(), self.buf.len()) };
+        prober.icmp().async_receive_from(buf, 0, self)
+    }
+}
+
+impl<P> Handler<(usize, IcmpEndpoint), io::Error> for ProbeRecv<P>
+where
+    P: Prober,
+{
+    type Output = ();
+
+    type WrappedHandler = Self;
+
+    fn wrap<W>(self, ctx: &IoContext, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        wrapper(ctx, self)
+    }
+
+    fn wrap_timeout<W>(self, ctx: &Cancel, _: &Timeout, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        wrapper(ctx.as_ctx(), self)
+    }
+}
+
+impl<P> Complete<(usize, IcmpEndpoint), io::Error> for ProbeRecv<P>
+where
+    P: Prober,
+{
+    fn success(self, this: &mut ThreadIoContext, (len, ep): (usize, IcmpEndpoint)) {
+        let prober = unsafe { &*self.prober };
+        let msg = prober.parse(&self.buf[..len], ep);
+        let (done, next) = {
+            let mut state = prober.probes().state.lock().unwrap();
+            let done = msg.and_then(|(key, msg)| {
+                state.pending.remove(&key).map(|pending| (pending, msg))
+            });
+            state.receiving = !state.pending.is_empty();
+            (done, state.receiving)
+        };
+        if let Some((pending, msg)) = done {
+            pending.timer.cancel();
+            pending.probe.reply(this, msg);
+        }
+        if next {
+            self.receive();
+        }
+        this.decrease_outstanding_work();
+    }
+
+    fn failure(self, this: &mut ThreadIoContext, err: io::Error) {
+        let prober = unsafe { &*self.prober };
+        // the loop is canceled when it is idle or by the `cancel_probes`, and the probes started after it remain.
+        let retry = err.raw_os_error() == Some(libc::ECANCELED) && !this.as_ctx().stopped();
+        let (failed, next) = {
+            let mut state = prober.probes().state.lock().unwrap();
+            if retry && !state.pending.is_empty() {
+                (Vec::new(), true)
+            } else {
+                state.receiving = false;
+                (state.pending.drain().map(|(_, pending)| pending).collect(), false)
+            }
+        };
+        for pending in failed {
+            pending.timer.cancel();
+            pending.probe.failure(this, copy_error(&err));
+        }
+        if next {
+            self.receive();
+        }
+        this.decrease_outstanding_work();
+    }
+}
@@ -0,0 +1,289 @@
+use ip::{IpAddr, IpAddrV4, IpAddrV6, IpNetwork, IpNetworkV4, IpNetworkV6};
+use super::network_set::{host_bits, host_mask};
+
+use std::fmt;
+
+fn to_u128(addr: &IpAddr) -> u128 {
+    match addr {
+        &IpAddr::V4(ref addr) => addr.to_u32() as u128,
+        &IpAddr::V6(ref addr) => addr.to_u128(),
+    }
+}
+
+fn from_u128(addr: u128, base: &IpAddr) -> IpAddr {
+    match base {
+        &IpAddr::V4(_) => IpAddr::V4(IpAddrV4::from(addr as u32)),
+        &IpAddr::V6(ref base) => {
+            let mut addr: IpAddrV6 = addr.into();
+            addr.set_scope_id(base.scope_id());
+            IpAddr::V6(addr)
+        }
+    }
+}
+
+/// Implements the inclusive range of IP addresses.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::ip::{IpAddr, IpAddrV4, IpAddrRange};
+///
+/// let range: IpAddrRange = "10.0.0.5-10.0.0.20".parse().unwrap();
+/// assert_eq!(range.len(), 16);
+/// assert!(range.contains(&IpAddr::V4(IpAddrV4::new(10,0,0,10))));
+///
+/// let nets: Vec<String> = range.to_networks().iter().map(|net| net.to_string()).collect();
+/// assert_eq!(nets, ["10.0.0.5/32", "10.0.0.6/31", "10.0.0.8/29", "10.0.0.16/30", "10.0.0.20/32"]);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct IpAddrRange {
+    start: IpAddr,
+    end: IpAddr,
+}
+
+impl IpAddrRange {
+    /// Returns a range from `start` to `end` inclusive.
+    ///
+    /// Returns `None` if the address families differ or `start` is greater than `end`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::{IpAddr, IpAddrV4, IpAddrV6, IpAddrRange};
+    ///
+    /// let a = IpAddr::V4(IpAddrV4::new(192,168,0,1));
+    /// let b = IpAddr::V4(IpAddrV4::new(192,168,0,100));
+    /// assert!(IpAddrRange::new(a, b).is_some());
+    /// assert!(IpAddrRange::new(b, a).is_none());
+    /// assert!(IpAddrRange::new(a, IpAddr::V6(IpAddrV6::loopback())).is_none());
+    /// ```
+    pub fn new(start: IpAddr, end: IpAddr) -> Option<IpAddrRange> {
+        match (&start, &end) {
+            (&IpAddr::V4(_), &IpAddr::V4(_)) |
+            (&IpAddr::V6(_), &IpAddr::V6(_)) if to_u128(&start) <= to_u128(&end) => Some(
+                IpAddrRange {
+                    start: start,
+                    end: end,
+                },
+            ),
+            _ => None,
+        }
+    }
+
+    /// Returns the first address.
+    pub fn start(&self) -> IpAddr {
+        self.start
+    }
+
+    /// Returns the last address.
+    pub fn end(&self) -> IpAddr {
+        self.end
+    }
+
+    /// Returns a number of addresses.
+    ///
+    /// The whole IP-v6 address space saturates at `u128::max_value()`.
+    pub fn len(&self) -> u128 {
+        (to_u128(&self.end) - to_u128(&self.start)).saturating_add(1)
+    }
+
+    /// Returns true if this contains the address.
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        match (&self.start, addr) {
+            (&IpAddr::V4(_), &IpAddr::V4(_)) |
+            (&IpAddr::V6(_), &IpAddr::V6(_)) => {
+                let addr = to_u128(addr);
+                to_u128(&self.start) <= addr && addr <= to_u128(&self.end)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns a iterator over the addresses.
+    pub fn iter(&self) -> IpAddrRangeIter {
+        IpAddrRangeIter {
+            base: self.start,
+            beg: to_u128(&self.start),
+            end: to_u128(&self.end),
+            done: false,
+        }
+    }
+
+    /// Returns the minimal list of prefixes which covers exactly this range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::IpAddrRange;
+    ///
+    /// let range: IpAddrRange = "2001:db8::-2001:db8::1:ffff".parse().unwrap();
+    /// let nets: Vec<String> = range.to_networks().iter().map(|net| net.to_string()).collect();
+    /// assert_eq!(nets, ["2001:db8::/111"]);
+    /// ```
+    pub fn to_networks(&self) -> Vec<IpNetwork> {
+        let (is_v4, bits) = match self.start {
+            IpAddr::V4(_) => (true, 32),
+            IpAddr::V6(_) => (false, 128),
+        };
+        let mut vec = Vec::new();
+        let mut beg = to_u128(&self.start);
+        let end = to_u128(&self.end);
+        loop {
+            let k = host_bits(beg, end, bits);
+            let len = (bits - k) as u16;
+            vec.push(if is_v4 {
+                IpNetwork::V4(IpNetworkV4::from((beg as u32).into(), len).unwrap())
+            } else {
+                IpNetwork::V6(IpNetworkV6::from(beg.into(), len).unwrap())
+            });
+            let last = beg + host_mask(k);
+            if last == end {
+                return vec;
+            }
+            beg = last + 1;
+        }
+    }
+}
+
+impl fmt::Display for IpAddrRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+impl From<IpNetwork> for IpAddrRange {
+    fn from(net: IpNetwork) -> Self {
+        let (start, end) = match net {
+            IpNetwork::V4(net) => (IpAddr::V4(net.network()), IpAddr::V4(net.broadcast())),
+            IpNetwork::V6(net) => {
+                let beg = net.network().to_u128();
+                let end: IpAddrV6 = (beg | !net.netmask().to_u128()).into();
+                (IpAddr::V6(net.network()), IpAddr::V6(end))
+            }
+        };
+        IpAddrRange {
+            start: start,
+            end: end,
+        }
+    }
+}
+
+impl IntoIterator for IpAddrRange {
+    type Item = IpAddr;
+    type IntoIter = IpAddrRangeIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a IpAddrRange {
+    type Item = IpAddr;
+    type IntoIter = IpAddrRangeIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A iterator over the addresses of `IpAddrRange`.
+#[derive(Clone, Debug)]
+pub struct IpAddrRangeIter {
+    base: IpAddr,
+    beg: u128,
+    end: u128,
+    done: bool,
+}
+
+impl Iterator for IpAddrRangeIter {
+    type Item = IpAddr;
+
+    fn next(&mut self) -> Option<IpAddr> {
+        if self.done {
+            return None;
+        }
+        let addr = from_u128(self.beg, &self.base);
+        if self.beg == self.end {
+            self.done = true;
+        } else {
+            self.beg += 1;
+        }
+        Some(addr)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            return (0, Some(0));
+        }
+        let len = self.end - self.beg;
+        if len < usize::max_value() as u128 {
+            (len as usize + 1, Some(len as usize + 1))
+        } else {
+            (usize::max_value(), None)
+        }
+    }
+}
+
+impl DoubleEndedIterator for IpAddrRangeIter {
+    fn next_back(&mut self) -> Option<IpAddr> {
+        if self.done {
+            return None;
+        }
+        let addr = from_u128(self.end, &self.base);
+        if self.beg == self.end {
+            self.done = true;
+        } else {
+            self.end -= 1;
+        }
+        Some(addr)
+    }
+}
+
+#[test]
+fn test_ip_addr_range() {
+    let a = IpAddr::V4(IpAddrV4::new(10, 0, 0, 254));
+    let b = IpAddr::V4(IpAddrV4::new(10, 0, 1, 1));
+    let range = IpAddrRange::new(a, b).unwrap();
+    assert_eq!(range.len(), 4);
+    assert_eq!(range.start(), a);
+    assert_eq!(range.end(), b);
+    assert_eq!(range.to_string(), "10.0.0.254-10.0.1.1");
+    let addrs: Vec<_> = range.iter().map(|addr| addr.to_string()).collect();
+    assert_eq!(addrs, ["10.0.0.254", "10.0.0.255", "10.0.1.0", "10.0.1.1"]);
+    assert_eq!(range.iter().rev().next(), Some(b));
+    assert_eq!(range.iter().size_hint(), (4, Some(4)));
+    assert!(!range.contains(&IpAddr::V4(IpAddrV4::new(10, 0, 1, 2))));
+    assert!(!range.contains(&IpAddr::V6(IpAddrV6::any())));
+
+    let single = IpAddrRange::new(a, a).unwrap();
+    assert_eq!(single.len(), 1);
+    assert_eq!(single.iter().collect::<Vec<_>>(), [a]);
+    assert_eq!(single.iter().rev().collect::<Vec<_>>(), [a]);
+}
+
+#[test]
+fn test_ip_addr_range_networks() {
+    let all = IpAddrRange::new(
+        IpAddr::V4(IpAddrV4::any()),
+        IpAddr::V4(IpAddrV4::new(255, 255, 255, 255)),
+    ).unwrap();
+    assert_eq!(all.len(), 1 << 32);
+    let nets: Vec<_> = all.to_networks().iter().map(|net| net.to_string()).collect();
+    assert_eq!(nets, ["0.0.0.0/0"]);
+
+    let all = IpAddrRange::new(
+        IpAddr::V6(IpAddrV6::any()),
+        IpAddr::V6((!0 as u128).into()),
+    ).unwrap();
+    assert_eq!(all.len(), u128::max_value());
+    assert_eq!(all.to_networks(), [IpNetworkV6::from(IpAddrV6::any(), 0).unwrap().into()]);
+    assert_eq!(all.iter().next_back(), Some(IpAddr::V6((!0 as u128).into())));
+
+    let net: IpNetwork = IpNetworkV4::from(IpAddrV4::new(192, 168, 1, 1), 24).unwrap().into();
+    let range = IpAddrRange::from(net);
+    assert_eq!(range.to_string(), "192.168.1.0-192.168.1.255");
+    assert_eq!(range.to_networks(), [net.canonical()]);
+
+    let net: IpNetwork = IpNetworkV6::from(IpAddrV6::loopback(), 127).unwrap().into();
+    assert_eq!(IpAddrRange::from(net).to_string(), "::-::1");
+}
@@ -0,0 +1,712 @@
+use ffi::{Timeout, AF_INET, IPPROTO_ICMP, IPPROTO_UDP, HOST_UNREACHABLE, MESSAGE_SIZE};
+use core::{AsIoContext, Exec, IoContext, Protocol, Socket, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+use ip::{IpAddr, IpProtocol, Icmp, IcmpEndpoint, IcmpHeader, IcmpSocket, MtuDiscover,
+         UnicastHops, Udp, UdpEndpoint, UdpSocket, ICMP_DEST_UNREACH, ICMP_TIME_EXCEEDED,
+         ICMPV6_DEST_UNREACH, ICMPV6_PACKET_TOO_BIG, ICMPV6_TIME_EXCEEDED};
+use ip::probe::{Probe, ProbeTable, Prober, cancel_probes, start_probe};
+
+use std::io;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use libc;
+
+const PORT_BASE: u16 = 33434;
+
+const PORT_RANGE: usize = 0x1000;
+
+const PROBE_SIZE: usize = 60;
+
+const MAX_HOPS: u8 = 64;
+
+/// The packet sizes of the path MTU probes, in descending order (RFC 1191).
+const MTU_PLATEAUS: &'static [usize] = &[
+    65535,
+    32000,
+    17914,
+    9000,
+    8166,
+    4352,
+    2002,
+    1500,
+    1492,
+    1280,
+    1006,
+    576,
+    508,
+    296,
+    68,
+];
+
+fn header_len(ep: &UdpEndpoint) -> usize {
+    if ep.is_v4() { 20 + 8 } else { 40 + 8 }
+}
+
+fn min_mtu(ep: &UdpEndpoint) -> usize {
+    if ep.is_v4() { 68 } else { 1280 }
+}
+
+fn next_plateau(size: usize, min: usize) -> Option<usize> {
+    MTU_PLATEAUS.iter().cloned().find(|&mtu| mtu < size && mtu >= min)
+}
+
+fn skip_ipv4_header(buf: &[u8], protocol: i32) -> Option<&[u8]> {
+    let ihl = match buf.first() {
+        Some(&ch) => (ch & 0x0f) as usize * 4,
+        None => return None,
+    };
+    if ihl < 20 || buf.len() < ihl || buf[9] != protocol as u8 {
+        return None;
+    }
+    Some(&buf[ihl..])
+}
+
+/// The status of a probe.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum TraceStatus {
+    /// No response was received within the timeout.
+    TimedOut,
+
+    /// The probe was discarded by a intermediate router.
+    TimeExceeded,
+
+    /// The probe reached to the destination host.
+    Reached,
+
+    /// The probe was rejected by the code of destination unreachable.
+    Unreachable(u8),
+
+    /// The probe was too big to forward, with the next-hop MTU.
+    PacketTooBig(u32),
+}
+
+/// The result of a probe.
+#[derive(Clone, Debug)]
+pub struct TraceHop {
+    ttl: u8,
+    size: usize,
+    addr: Option<IpAddr>,
+    rtt: Option<Duration>,
+    status: TraceStatus,
+}
+
+impl TraceHop {
+    /// Returns a time-to-live (hop limit) of the probe.
+    pub fn ttl(&self) -> u8 {
+        self.ttl
+    }
+
+    /// Returns a packet size of the probe, including the IP and UDP headers.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns a address of the responded host, or `None` if timed out.
+    pub fn addr(&self) -> Option<&IpAddr> {
+        self.addr.as_ref()
+    }
+
+    /// Returns a round-trip time, or `None` if timed out.
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt
+    }
+
+    /// Returns a status of the probe.
+    pub fn status(&self) -> TraceStatus {
+        self.status
+    }
+}
+
+/// Provides the traceroute and the path MTU discovery functionality.
+///
+/// The tracer sends UDP probes to the unused ports of the destination, and receives
+/// the ICMP time exceeded and destination unreachable messages by a raw ICMP socket.
+/// It requires the privilege to open raw sockets.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io;
+/// use std::sync::Arc;
+/// use asyncio::{IoContext, wrap};
+/// use asyncio::ip::{IpProtocol, IpAddrV4, Tracer, TraceHop, Udp, UdpEndpoint};
+///
+/// fn on_trace(tracer: Arc<Tracer>, res: io::Result<Vec<TraceHop>>) {
+///     for hop in res.unwrap() {
+///         println!("{} {:?} {:?}", hop.ttl(), hop.addr(), hop.rtt());
+///     }
+/// }
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let tracer = Arc::new(Tracer::new(ctx, Udp::v4()).unwrap());
+/// let ep = UdpEndpoint::new(IpAddrV4::new(192, 0, 2, 1), 0);
+/// tracer.async_trace(&ep, 30, wrap(&tracer, on_trace));
+/// ctx.run();
+/// ```
+pub struct Tracer {
+    udp: UdpSocket,
+    icmp: IcmpSocket,
+    sport: u16,
+    port: AtomicUsize,
+    pmtudisc: MtuDiscover,
+    sending: Mutex<()>,
+    timeout: Mutex<Duration>,
+    probes: ProbeTable<(TraceStatus, IcmpEndpoint)>,
+}
+
+impl Tracer {
+    /// Returns a tracer of the UDP protocol.
+    pub fn new(ctx: &IoContext, pro: Udp) -> io::Result<Tracer> {
+        let icmp = if pro.family_type() == AF_INET {
+            IcmpSocket::new(ctx, Icmp::v4())?
+        } else {
+            IcmpSocket::new(ctx, Icmp::v6())?
+        };
+        let udp = UdpSocket::new(ctx, pro)?;
+        udp.bind(&UdpEndpoint::new(pro, 0))?;
+        let sport = udp.local_endpoint()?.port();
+        let pmtudisc = udp.get_option()?;
+        Ok(Tracer {
+            udp: udp,
+            icmp: icmp,
+            sport: sport,
+            port: AtomicUsize::new(0),
+            pmtudisc: pmtudisc,
+            sending: Mutex::new(()),
+            timeout: Mutex::new(Duration::new(1, 0)),
+            probes: ProbeTable::new(),
+        })
+    }
+
+    /// Returns a timeout of each probe. Default is 1 second.
+    pub fn get_timeout(&self) -> Duration {
+        *self.timeout.lock().unwrap()
+    }
+
+    /// Sets a timeout of each probe.
+    pub fn set_timeout(&self, timeout: Duration) {
+        *self.timeout.lock().unwrap() = timeout
+    }
+
+    /// Sends a probe of the TTL and the packet size to the destination,
+    /// and asynchronously waits for the ICMP response.
+    ///
+    /// Each probe has own timer, so multiple probes may be outstanding at the same time.
+    /// If no response was received within the timeout, the handler is called with
+    /// the `TraceStatus::TimedOut` hop.
+    pub fn async_probe<F>(&self, ep: &UdpEndpoint, ttl: u8, size: usize, handler: F) -> F::Output
+    where
+        F: Handler<TraceHop, io::Error>,
+    {
+        handler.wrap_cancel(self, move |ctx, handler| {
+            ctx.do_dispatch(TraceStart {
+                tracer: self,
+                ep: ep.clone(),
+                ttl: ttl,
+                size: size,
+                dont_fragment: false,
+                handler: handler,
+            })
+        })
+    }
+
+    /// Sends the probes with increasing TTL from 1 to `max_hops`,
+    /// and asynchronously collects the hops until the destination responded.
+    pub fn async_trace<F>(&self, ep: &UdpEndpoint, max_hops: u8, handler: F) -> F::Output
+    where
+        F: Handler<Vec<TraceHop>, io::Error>,
+    {
+        handler.wrap_cancel(self, move |_, handler| {
+            TraceOp {
+                tracer: self,
+                ep: ep.clone(),
+                max_hops: max_hops,
+                hops: Vec::new(),
+                handler: handler,
+            }.probe(1)
+        })
+    }
+
+    /// Sends the probes with the don't fragment bit and decreasing sizes,
+    /// and asynchronously finds the largest packet size which reaches to the destination.
+    ///
+    /// The don't fragment bit is set only to these probes, and the other probes are sent as before.
+    pub fn async_path_mtu<F>(&self, ep: &UdpEndpoint, handler: F) -> F::Output
+    where
+        F: Handler<usize, io::Error>,
+    {
+        handler.wrap_cancel(self, move |_, handler| {
+            PathMtuOp {
+                tracer: self,
+                ep: ep.clone(),
+                size: MTU_PLATEAUS[0],
+                handler: handler,
+            }.probe()
+        })
+    }
+
+    /// Sends a probe with the TTL and the don't fragment bit, and returns the destination port of it.
+    fn send_probe(&self, ep: &UdpEndpoint, ttl: u8, size: usize, dont_fragment: bool) -> io::Result<u16> {
+        let port = PORT_BASE + (self.port.fetch_add(1, Ordering::SeqCst) % PORT_RANGE) as u16;
+        let buf = vec![0; size.saturating_sub(header_len(ep))];
+        // the options are shared by the probes, which may be sent from the other threads.
+        let _sending = self.sending.lock().unwrap();
+        self.udp.set_option(UnicastHops::new(ttl))?;
+        if dont_fragment {
+            self.udp.set_option(MtuDiscover::new(true))?;
+        }
+        let res = self.udp.nonblocking_send_to(
+            &buf,
+            0,
+            &UdpEndpoint::new(ep.addr(), port),
+        );
+        if dont_fragment {
+            self.udp.set_option(self.pmtudisc.clone())?;
+        }
+        res?;
+        Ok(port)
+    }
+
+    fn status(&self, buf: &[u8]) -> Option<(u16, TraceStatus)> {
+        let is_v4 = self.icmp.protocol().family_type() == AF_INET;
+        let buf = if is_v4 {
+            // the raw IPv4 socket receives the IP header too.
+            match skip_ipv4_header(buf, IPPROTO_ICMP) {
+                Some(buf) => buf,
+                None => return None,
+            }
+        } else {
+            buf
+        };
+        let (hdr, data) = match IcmpHeader::decode(buf) {
+            Some(res) => res,
+            None => return None,
+        };
+        // the ICMP error message contains the IP header and the UDP header of the probe.
+        let udp = if is_v4 {
+            match skip_ipv4_header(data, IPPROTO_UDP) {
+                Some(udp) => udp,
+                None => return None,
+            }
+        } else if data.len() >= 40 && data[6] == IPPROTO_UDP as u8 {
+            &data[40..]
+        } else {
+            return None;
+        };
+        if udp.len() < 4 || (udp[0] as u16) << 8 | udp[1] as u16 != self.sport {
+            return None;
+        }
+        let port = (udp[2] as u16) << 8 | udp[3] as u16;
+        Some((
+            port,
+            match (is_v4, hdr.kind(), hdr.code()) {
+                (true, ICMP_TIME_EXCEEDED, _) |
+                (false, ICMPV6_TIME_EXCEEDED, _) => TraceStatus::TimeExceeded,
+                (true, ICMP_DEST_UNREACH, 3) |
+                (false, ICMPV6_DEST_UNREACH, 4) => TraceStatus::Reached,
+                (true, ICMP_DEST_UNREACH, 4) |
+                (false, ICMPV6_PACKET_TOO_BIG, _) => TraceStatus::PacketTooBig(hdr.mtu()),
+                (true, ICMP_DEST_UNREACH, code) |
+                (false, ICMPV6_DEST_UNREACH, code) => TraceStatus::Unreachable(code),
+                _ => return None,
+            },
+        ))
+    }
+}
+
+unsafe impl AsIoContext for Tracer {
+    fn as_ctx(&self) -> &IoContext {
+        self.udp.as_ctx()
+    }
+}
+
+impl Cancel for Tracer {
+    fn cancel(&self) {
+        cancel_probes(self)
+    }
+}
+
+unsafe impl Send for Tracer {}
+
+unsafe impl Sync for Tracer {}
+
+impl Prober for Tracer {
+    type Message = (TraceStatus, IcmpEndpoint);
+
+    fn icmp(&self) -> &IcmpSocket {
+        &self.icmp
+    }
+
+    fn probes(&self) -> &ProbeTable<Self::Message> {
+        &self.probes
+    }
+
+    fn parse(&self, buf: &[u8], ep: IcmpEndpoint) -> Option<(u16, Self::Message)> {
+        self.status(buf).map(|(port, status)| (port, (status, ep)))
+    }
+}
+
+struct TraceStart<F> {
+    tracer: *const Tracer,
+    ep: UdpEndpoint,
+    ttl: u8,
+    size: usize,
+    dont_fragment: bool,
+    handler: F,
+}
+
+unsafe impl<F> Send for TraceStart<F> {}
+
+impl<F> Exec for TraceStart<F>
+where
+    F: Complete<TraceHop, io::Error>,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        let tracer = unsafe { &*self.tracer };
+        let start = Instant::now();
+        let port = match tracer.send_probe(&self.ep, self.ttl, self.size, self.dont_fragment) {
+            Ok(port) => port,
+            Err(err) => return self.handler.failure(this, err),
+        };
+        start_probe(
+            tracer,
+            this,
+            port,
+            tracer.get_timeout(),
+            Box::new(TraceProbe {
+                ttl: self.ttl,
+                size: self.size,
+                start: start,
+                handler: self.handler,
+            }),
+        )
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.call(this)
+    }
+}
+
+struct TraceProbe<F> {
+    ttl: u8,
+    size: usize,
+    start: Instant,
+    handler: F,
+}
+
+impl<F> Probe<(TraceStatus, IcmpEndpoint)> for TraceProbe<F>
+where
+    F: Complete<TraceHop, io::Error>,
+{
+    fn reply(self: Box<Self>, this: &mut ThreadIoContext, (status, ep): (TraceStatus, IcmpEndpoint)) {
+        let hop = TraceHop {
+            ttl: self.ttl,
+            size: self.size,
+            addr: Some(ep.addr()),
+            rtt: Some(self.start.elapsed()),
+            status: status,
+        };
+        self.handler.success(this, hop)
+    }
+
+    fn timeout(self: Box<Self>, this: &mut ThreadIoContext) {
+        let hop = TraceHop {
+            ttl: self.ttl,
+            size: self.size,
+            addr: None,
+            rtt: None,
+            status: TraceStatus::TimedOut,
+        };
+        self.handler.success(this, hop)
+    }
+
+    fn failure(self: Box<Self>, this: &mut ThreadIoContext, err: io::Error) {
+        self.handler.failure(this, err)
+    }
+}
+
+struct TraceOp<F> {
+    tracer: *const Tracer,
+    ep: UdpEndpoint,
+    max_hops: u8,
+    hops: Vec<TraceHop>,
+    handler: F,
+}
+
+unsafe impl<F> Send for TraceOp<F> {}
+
+impl<F> TraceOp<F>
+where
+    F: Complete<Vec<TraceHop>, io::Error>,
+{
+    fn probe(self, ttl: u8) {
+        let tracer = unsafe { &*self.tracer };
+        let ep = self.ep.clone();
+        tracer.async_probe(&ep, ttl, PROBE_SIZE, self)
+    }
+}
+
+impl<F> Handler<TraceHop, io::Error> for TraceOp<F>
+where
+    F: Complete<Vec<TraceHop>, io::Error>,
+{
+    type Output = ();
+
+    type WrappedHandler = Self;
+
+    fn wrap<W>(self, ctx: &IoContext, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        wrapper(ctx, self)
+    }
+
+    fn wrap_timeout<W>(self, ctx: &Cancel, _: &Timeout, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        wrapper(ctx.as_ctx(), self)
+    }
+}
+
+impl<F> Complete<TraceHop, io::Error> for TraceOp<F>
+where
+    F: Complete<Vec<TraceHop>, io::Error>,
+{
+    fn success(mut self, this: &mut ThreadIoContext, hop: TraceHop) {
+        let ttl = hop.ttl;
+        let next = match hop.status {
+            TraceStatus::TimedOut |
+            TraceStatus::TimeExceeded => ttl < self.max_hops,
+            _ => false,
+        };
+        self.hops.push(hop);
+        if next {
+            self.probe(ttl + 1);
+            this.decrease_outstanding_work();
+        } else {
+            let TraceOp { hops, handler, .. } = self;
+            handler.success(this, hops)
+        }
+    }
+
+    fn failure(self, this: &mut ThreadIoContext, err: io::Error) {
+        self.handler.failure(this, err)
+    }
+}
+
+struct PathMtuOp<F> {
+    tracer: *const Tracer,
+    ep: UdpEndpoint,
+    size: usize,
+    handler: F,
+}
+
+unsafe impl<F> Send for PathMtuOp<F> {}
+
+impl<F> PathMtuOp<F>
+where
+    F: Complete<usize, io::Error>,
+{
+    fn probe(self) {
+        let tracer = unsafe { &*self.tracer };
+        let ep = self.ep.clone();
+        let size = self.size;
+        self.wrap(tracer.as_ctx(), move |ctx, handler| {
+            ctx.do_dispatch(TraceStart {
+                tracer: tracer,
+                ep: ep,
+                ttl: MAX_HOPS,
+                size: size,
+                dont_fragment: true,
+                handler: handler,
+            })
+        })
+    }
+
+    fn retry(mut self, this: &mut ThreadIoContext, size: Option<usize>) {
+        match size {
+            Some(size) => {
+                self.size = size;
+                self.probe();
+                this.decrease_outstanding_work();
+            }
+            None => self.handler.failure(this, MESSAGE_SIZE.into()),
+        }
+    }
+}
+
+impl<F> Handler<TraceHop, io::Error> for PathMtuOp<F>
+where
+    F: Complete<usize, io::Error>,
+{
+    type Output = ();
+
+    type WrappedHandler = Self;
+
+    fn wrap<W>(self, ctx: &IoContext, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        wrapper(ctx, self)
+    }
+
+    fn wrap_timeout<W>(self, ctx: &Cancel, _: &Timeout, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        wrapper(ctx.as_ctx(), self)
+    }
+}
+
+impl<F> Complete<TraceHop, io::Error> for PathMtuOp<F>
+where
+    F: Complete<usize, io::Error>,
+{
+    fn success(self, this: &mut ThreadIoContext, hop: TraceHop) {
+        let min = min_mtu(&self.ep);
+        match hop.status {
+            TraceStatus::Reached => {
+                let size = self.size;
+                self.handler.success(this, size)
+            }
+            TraceStatus::PacketTooBig(mtu) if (mtu as usize) < self.size &&
+                                                  mtu as usize >= min => {
+                self.retry(this, Some(mtu as usize))
+            }
+            TraceStatus::PacketTooBig(_) |
+            TraceStatus::TimedOut => {
+                // the large probe may be dropped silently by a black hole router.
+                let size = next_plateau(self.size, min);
+                self.retry(this, size)
+            }
+            TraceStatus::TimeExceeded |
+            TraceStatus::Unreachable(_) => self.handler.failure(this, HOST_UNREACHABLE.into()),
+        }
+    }
+
+    fn failure(self, this: &mut ThreadIoContext, err: io::Error) {
+        if err.raw_os_error() == Some(libc::EMSGSIZE) {
+            // the probe is larger than the MTU of the local interface or the cached path MTU.
+            let size = next_plateau(self.size, min_mtu(&self.ep));
+            self.retry(this, size)
+        } else {
+            self.handler.failure(this, err)
+        }
+    }
+}
+
+#[test]
+fn test_next_plateau() {
+    assert_eq!(next_plateau(65536, 68), Some(65535));
+    assert_eq!(next_plateau(65535, 68), Some(32000));
+    assert_eq!(next_plateau(1500, 68), Some(1492));
+    assert_eq!(next_plateau(1400, 68), Some(1280));
+    assert_eq!(next_plateau(1400, 1280), Some(1280));
+    assert_eq!(next_plateau(1280, 1280), None);
+    assert_eq!(next_plateau(68, 68), None);
+}
+
+#[test]
+fn test_tracer_loopback() {
+    use std::sync::Arc;
+    use handler::wrap;
+    use ip::IpAddrV4;
+
+    let ctx = &IoContext::new().unwrap();
+    let tracer = match Tracer::new(ctx, Udp::v4()) {
+        Ok(tracer) => Arc::new(tracer),
+        Err(_) => return, // requires CAP_NET_RAW
+    };
+    let ep = UdpEndpoint::new(IpAddrV4::loopback(), 0);
+    tracer.async_trace(&ep, 4, wrap(&tracer, |_, res: io::Result<Vec<TraceHop>>| {
+        let hops = res.unwrap();
+        assert_eq!(hops.len(), 1);
+        assert_eq!(hops[0].ttl(), 1);
+        assert_eq!(hops[0].status(), TraceStatus::Reached);
+        assert_eq!(hops[0].addr(), Some(&IpAddr::V4(IpAddrV4::loopback())));
+        assert!(hops[0].rtt().is_some());
+    }));
+    ctx.run();
+}
+
+#[test]
+fn test_tracer_path_mtu() {
+    use std::sync::Arc;
+    use handler::wrap;
+    use ip::IpAddrV4;
+
+    let ctx = &IoContext::new().unwrap();
+    let tracer = match Tracer::new(ctx, Udp::v4()) {
+        Ok(tracer) => Arc::new(tracer),
+        Err(_) => return, // requires CAP_NET_RAW
+    };
+    let ep = UdpEndpoint::new(IpAddrV4::loopback(), 0);
+    tracer.async_path_mtu(&ep, wrap(&tracer, |_, res: io::Result<usize>| {
+        // the MTU of loopback interface is 65536 or smaller.
+        let mtu = res.unwrap();
+        assert!(mtu >= 1280 && MTU_PLATEAUS.contains(&mtu));
+    }));
+    ctx.run();
+}
+
+#[test]
+fn test_tracer_timeout() {
+    use std::sync::Arc;
+    use handler::wrap;
+    use ip::IpAddrV4;
+
+    let ctx = &IoContext::new().unwrap();
+    let tracer = match Tracer::new(ctx, Udp::v4()) {
+        Ok(tracer) => Arc::new(tracer),
+        Err(_) => return, // requires CAP_NET_RAW
+    };
+    tracer.set_timeout(Duration::from_millis(50));
+    // TEST-NET-3 is never replied, but the first hop router may reply.
+    let ep = UdpEndpoint::new(IpAddrV4::new(203, 0, 113, 1), 0);
+    tracer.async_probe(&ep, 1, PROBE_SIZE, wrap(&tracer, |_, res: io::Result<TraceHop>| {
+        if let Ok(hop) = res {
+            assert_eq!(hop.ttl(), 1);
+            assert!(hop.status() == TraceStatus::TimedOut || hop.status() == TraceStatus::TimeExceeded);
+            assert_eq!(hop.addr().is_none(), hop.status() == TraceStatus::TimedOut);
+        }
+    }));
+    ctx.run();
+}
+
+#[test]
+fn test_tracer_concurrent() {
+    use std::sync::Arc;
+    use std::sync::atomic::ATOMIC_USIZE_INIT;
+    use handler::wrap;
+    use ip::IpAddrV4;
+
+    static REACHED: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    let ctx = &IoContext::new().unwrap();
+    let tracer = match Tracer::new(ctx, Udp::v4()) {
+        Ok(tracer) => Arc::new(tracer),
+        Err(_) => return, // requires CAP_NET_RAW
+    };
+    tracer.set_timeout(Duration::from_millis(200));
+    // the result depends on the network, the first hop router may reply or the network may be unreachable.
+    let ep = UdpEndpoint::new(IpAddrV4::new(203, 0, 113, 1), 0);
+    tracer.async_probe(&ep, 1, PROBE_SIZE, wrap(&tracer, |_, _: io::Result<TraceHop>| {}));
+    // the probe in flight does not cancel the timeouts of the others.
+    let ep = UdpEndpoint::new(IpAddrV4::loopback(), 0);
+    tracer.async_path_mtu(&ep, wrap(&tracer, |_, res: io::Result<usize>| {
+        res.unwrap();
+    }));
+    for _ in 0..3 {
+        tracer.async_probe(&ep, 1, PROBE_SIZE, wrap(&tracer, |_, res: io::Result<TraceHop>| {
+            assert_eq!(res.unwrap().status(), TraceStatus::Reached);
+            REACHED.fetch_add(1, Ordering::SeqCst);
+        }));
+    }
+    ctx.run();
+    assert_eq!(REACHED.load(Ordering::SeqCst), 3);
+    // the don't fragment bit of the path MTU probes is restored.
+    let pmtudisc: MtuDiscover = tracer.udp.get_option().unwrap();
+    assert!(!pmtudisc.get());
+}
@@ -24,6 +24,12 @@ extern crate libc;
 #[cfg(feature = "openssl-sys")]
 extern crate openssl_sys;
 
+#[cfg(feature = "serde")]
+extern crate serde;
+
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_test;
+
 #[cfg(feature = "termios")]
 extern crate termios;
 
@@ -62,6 +68,14 @@ pub mod clock;
 pub type SteadyTimer = clock::WaitableTimer<clock::SteadyClock>;
 pub type SystemTimer = clock::WaitableTimer<clock::SystemClock>;
 
+mod deadline;
+pub use self::deadline::{DeadlineHandler, with_deadline, with_timeout};
+
+mod sync;
+pub use self::sync::{AsyncMutex, AsyncMutexGuard, AsyncCondvar, AsyncRwLock, AsyncRwLockReadGuard,
+                     AsyncRwLockWriteGuard, AsyncSemaphore, AsyncSemaphorePermit, AsyncBarrier, AsyncEvent,
+                     Promise, Channel, SyncSender, TrySendError};
+
 mod streambuf;
 pub use self::streambuf::*;
 
@@ -87,8 +101,20 @@ pub mod ip;
 
 mod from_str;
 
+#[cfg(feature = "serde")]
+mod serialize;
+
 pub mod posix;
 
+#[cfg(unix)]
+pub mod process;
+
+#[cfg(target_os = "linux")]
+pub mod netlink;
+
+#[cfg(target_os = "linux")]
+pub mod packet;
+
 #[cfg(unix)]
 mod signal_set;
 #[cfg(unix)]
@@ -0,0 +1,221 @@
+use ffi::{sockaddr, sockaddr_nl, socklen_t, SockAddr, AF_NETLINK, SOCK_DGRAM};
+use core::{Endpoint, Protocol};
+use dgram_socket::DgramSocket;
+
+use std::fmt;
+use std::mem;
+
+/// Routing/device hook protocol number.
+pub const NETLINK_ROUTE: i32 = 0;
+
+/// The netlink protocol.
+///
+/// # Examples
+/// Subscribe to the IPv4 and IPv6 address change notifications.
+///
+/// ```rust,no_run
+/// use asyncio::IoContext;
+/// use asyncio::netlink::*;
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let soc = NetlinkSocket::new(ctx, Netlink::route()).unwrap();
+/// soc.bind(&NetlinkEndpoint::new(0, RTMGRP_IPV4_IFADDR | RTMGRP_IPV6_IFADDR)).unwrap();
+///
+/// let mut buf = [0; 8192];
+/// let len = soc.receive(&mut buf, 0).unwrap();
+/// for msg in RtnlMessages::new(&buf[..len]) {
+///     match msg {
+///         RtnlMessage::NewAddr(addr) => println!("added {:?}", addr.address()),
+///         RtnlMessage::DelAddr(addr) => println!("removed {:?}", addr.address()),
+///         _ => (),
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Netlink {
+    protocol: i32,
+}
+
+impl Netlink {
+    /// Returns a netlink protocol of the given family (ex. `NETLINK_ROUTE`).
+    pub fn new(protocol: i32) -> Netlink {
+        Netlink { protocol: protocol }
+    }
+
+    /// Represents a rtnetlink (`NETLINK_ROUTE`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::Endpoint;
+    /// use asyncio::netlink::{Netlink, NetlinkEndpoint};
+    ///
+    /// assert_eq!(Netlink::route(), NetlinkEndpoint::new(0, 0).protocol());
+    /// ```
+    pub fn route() -> Netlink {
+        Netlink::new(NETLINK_ROUTE)
+    }
+}
+
+impl Protocol for Netlink {
+    type Endpoint = NetlinkEndpoint;
+
+    type Socket = NetlinkSocket;
+
+    fn family_type(&self) -> i32 {
+        AF_NETLINK
+    }
+
+    fn socket_type(&self) -> i32 {
+        SOCK_DGRAM
+    }
+
+    fn protocol_type(&self) -> i32 {
+        self.protocol
+    }
+
+    unsafe fn uninitialized(&self) -> Self::Endpoint {
+        NetlinkEndpoint::with_protocol(*self, 0, 0)
+    }
+}
+
+impl fmt::Display for Netlink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Netlink({})", self.protocol)
+    }
+}
+
+/// The endpoint of netlink socket.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct NetlinkEndpoint {
+    snl: SockAddr<sockaddr_nl>,
+    protocol: i32,
+}
+
+impl NetlinkEndpoint {
+    /// Returns a rtnetlink endpoint from port-id and multicast groups mask.
+    ///
+    /// The port-id `0` means the kernel (or auto-assigned when binding).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::netlink::{NetlinkEndpoint, RTMGRP_LINK};
+    ///
+    /// let ep = NetlinkEndpoint::new(0, RTMGRP_LINK);
+    /// assert_eq!(ep.pid(), 0);
+    /// assert_eq!(ep.groups(), RTMGRP_LINK);
+    /// ```
+    pub fn new(pid: u32, groups: u32) -> NetlinkEndpoint {
+        NetlinkEndpoint::with_protocol(Netlink::route(), pid, groups)
+    }
+
+    /// Returns a endpoint of the given netlink protocol.
+    pub fn with_protocol(pro: Netlink, pid: u32, groups: u32) -> NetlinkEndpoint {
+        let mut snl: sockaddr_nl = unsafe { mem::zeroed() };
+        snl.nl_family = AF_NETLINK as u16;
+        snl.nl_pid = pid;
+        snl.nl_groups = groups;
+        NetlinkEndpoint {
+            snl: SockAddr {
+                sa: snl,
+                sa_len: mem::size_of::<sockaddr_nl>() as u8,
+            },
+            protocol: pro.protocol,
+        }
+    }
+
+    /// Returns a port-id.
+    pub fn pid(&self) -> u32 {
+        self.snl.sa.nl_pid
+    }
+
+    /// Returns a multicast groups mask.
+    pub fn groups(&self) -> u32 {
+        self.snl.sa.nl_groups
+    }
+}
+
+impl Endpoint<Netlink> for NetlinkEndpoint {
+    fn protocol(&self) -> Netlink {
+        Netlink::new(self.protocol)
+    }
+
+    fn as_ptr(&self) -> *const sockaddr {
+        &self.snl.sa as *const _ as *const _
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut sockaddr {
+        &mut self.snl.sa as *mut _ as *mut _
+    }
+
+    fn capacity(&self) -> socklen_t {
+        self.snl.capacity() as socklen_t
+    }
+
+    fn size(&self) -> socklen_t {
+        self.snl.size() as socklen_t
+    }
+
+    unsafe fn resize(&mut self, size: socklen_t) {
+        debug_assert!(size <= self.capacity());
+        self.snl.resize(size as u8)
+    }
+}
+
+impl fmt::Debug for NetlinkEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}/{:x}", self.protocol(), self.pid(), self.groups())
+    }
+}
+
+/// The netlink socket type.
+pub type NetlinkSocket = DgramSocket<Netlink>;
+
+mod rtnl;
+pub use self::rtnl::*;
+
+#[test]
+fn test_netlink_endpoint() {
+    let ep = NetlinkEndpoint::new(10, RTMGRP_IPV4_IFADDR);
+    assert_eq!(ep.pid(), 10);
+    assert_eq!(ep.groups(), RTMGRP_IPV4_IFADDR);
+    assert_eq!(ep, NetlinkEndpoint::new(10, RTMGRP_IPV4_IFADDR));
+    assert!(ep != NetlinkEndpoint::new(10, RTMGRP_IPV6_IFADDR));
+    assert_eq!(ep.protocol(), Netlink::route());
+}
+
+#[test]
+fn test_netlink_dump_links() {
+    use core::IoContext;
+
+    let ctx = &IoContext::new().unwrap();
+    let soc = NetlinkSocket::new(ctx, Netlink::route()).unwrap();
+    soc.bind(&NetlinkEndpoint::new(0, 0)).unwrap();
+    let req = RtnlRequest::get_link(1);
+    soc.send_to(req.as_bytes(), 0, &NetlinkEndpoint::new(0, 0))
+        .unwrap();
+
+    let mut buf = [0; 16384];
+    let mut found = false;
+    loop {
+        let len = soc.receive(&mut buf, 0).unwrap();
+        let mut done = false;
+        for msg in RtnlMessages::new(&buf[..len]) {
+            match msg {
+                RtnlMessage::NewLink(link) => {
+                    if link.name() == Some("lo") {
+                        found = true;
+                    }
+                }
+                RtnlMessage::Done => done = true,
+                RtnlMessage::Error(err) => panic!("{}", err),
+                _ => (),
+            }
+        }
+        if done {
+            break;
+        }
+    }
+    assert!(found);
+}
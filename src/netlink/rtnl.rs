@@ -0,0 +1,644 @@
+use ffi::{AF_INET, AF_INET6};
+use ip::{IpAddr, IpAddrV4, IpAddrV6, LlAddr};
+
+use std::ptr;
+use std::str;
+
+/// Multicast group of the link notifications.
+pub const RTMGRP_LINK: u32 = 0x0001;
+
+/// Multicast group of the IPv4 address notifications.
+pub const RTMGRP_IPV4_IFADDR: u32 = 0x0010;
+
+/// Multicast group of the IPv4 route notifications.
+pub const RTMGRP_IPV4_ROUTE: u32 = 0x0040;
+
+/// Multicast group of the IPv6 address notifications.
+pub const RTMGRP_IPV6_IFADDR: u32 = 0x0100;
+
+/// Multicast group of the IPv6 route notifications.
+pub const RTMGRP_IPV6_ROUTE: u32 = 0x0400;
+
+const NLMSG_NOOP: u16 = 1;
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+
+const RTM_NEWLINK: u16 = 16;
+const RTM_DELLINK: u16 = 17;
+const RTM_GETLINK: u16 = 18;
+const RTM_NEWADDR: u16 = 20;
+const RTM_DELADDR: u16 = 21;
+const RTM_GETADDR: u16 = 22;
+const RTM_NEWROUTE: u16 = 24;
+const RTM_DELROUTE: u16 = 25;
+const RTM_GETROUTE: u16 = 26;
+
+const NLM_F_REQUEST: u16 = 0x001;
+const NLM_F_DUMP: u16 = 0x300;
+
+const IFLA_ADDRESS: u16 = 1;
+const IFLA_IFNAME: u16 = 3;
+const IFLA_MTU: u16 = 4;
+
+const IFA_ADDRESS: u16 = 1;
+const IFA_LOCAL: u16 = 2;
+const IFA_LABEL: u16 = 3;
+
+const RTA_DST: u16 = 1;
+const RTA_OIF: u16 = 4;
+const RTA_GATEWAY: u16 = 5;
+const RTA_PRIORITY: u16 = 6;
+const RTA_TABLE: u16 = 15;
+
+const NLMSG_HDRLEN: usize = 16;
+const IFINFOMSG_LEN: usize = 16;
+const IFADDRMSG_LEN: usize = 8;
+const RTMSG_LEN: usize = 12;
+const RTATTR_HDRLEN: usize = 4;
+
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn get_u16(buf: &[u8], off: usize) -> u16 {
+    debug_assert!(off + 2 <= buf.len());
+    unsafe { ptr::read_unaligned(buf[off..].as_ptr() as *const u16) }
+}
+
+fn get_u32(buf: &[u8], off: usize) -> u32 {
+    debug_assert!(off + 4 <= buf.len());
+    unsafe { ptr::read_unaligned(buf[off..].as_ptr() as *const u32) }
+}
+
+fn put_u16(buf: &mut Vec<u8>, val: u16) {
+    buf.extend_from_slice(&[val as u8, (val >> 8) as u8]);
+    if cfg!(target_endian = "big") {
+        let len = buf.len();
+        buf[len - 2..].reverse();
+    }
+}
+
+fn put_u32(buf: &mut Vec<u8>, val: u32) {
+    put_u16(buf, if cfg!(target_endian = "big") { (val >> 16) as u16 } else { val as u16 });
+    put_u16(buf, if cfg!(target_endian = "big") { val as u16 } else { (val >> 16) as u16 });
+}
+
+/// Decodes the address, and gives the interface `index` to the link-local IPv6 address as the scope id.
+fn ip_addr(family: i32, data: &[u8], index: u32) -> Option<IpAddr> {
+    match family {
+        AF_INET if data.len() == 4 => {
+            Some(IpAddr::V4(IpAddrV4::new(data[0], data[1], data[2], data[3])))
+        }
+        AF_INET6 if data.len() == 16 => {
+            let mut bytes = [0; 16];
+            bytes.copy_from_slice(data);
+            let mut addr = IpAddrV6::from(bytes, 0);
+            if addr.is_link_local() || addr.is_multicast_link_local() {
+                addr.set_scope_id(index);
+            }
+            Some(IpAddr::V6(addr))
+        }
+        _ => None,
+    }
+}
+
+fn name(data: &[u8]) -> Option<String> {
+    let len = data.iter().position(|&ch| ch == 0).unwrap_or(data.len());
+    str::from_utf8(&data[..len]).ok().map(String::from)
+}
+
+/// An iterator over the route attributes (rtattr).
+#[derive(Clone)]
+struct RtAttrs<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Iterator for RtAttrs<'a> {
+    type Item = (u16, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.len() < RTATTR_HDRLEN {
+            return None;
+        }
+        let len = get_u16(self.buf, 0) as usize;
+        let kind = get_u16(self.buf, 2);
+        if len < RTATTR_HDRLEN || len > self.buf.len() {
+            return None;
+        }
+        let data = &self.buf[RTATTR_HDRLEN..len];
+        self.buf = &self.buf[align(len).min(self.buf.len())..];
+        Some((kind, data))
+    }
+}
+
+/// A rtnetlink request message.
+///
+/// # Examples
+/// Dumps all IPv4 addresses.
+///
+/// ```rust,no_run
+/// use asyncio::IoContext;
+/// use asyncio::netlink::*;
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let soc = NetlinkSocket::new(ctx, Netlink::route()).unwrap();
+/// let req = RtnlRequest::get_addr(2 /* AF_INET */, 1);
+/// soc.send_to(req.as_bytes(), 0, &NetlinkEndpoint::new(0, 0)).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct RtnlRequest {
+    buf: Vec<u8>,
+}
+
+impl RtnlRequest {
+    fn dump(kind: u16, seq: u32, body: &[u8]) -> RtnlRequest {
+        let mut buf = Vec::with_capacity(NLMSG_HDRLEN + body.len());
+        put_u32(&mut buf, (NLMSG_HDRLEN + body.len()) as u32);
+        put_u16(&mut buf, kind);
+        put_u16(&mut buf, NLM_F_REQUEST | NLM_F_DUMP);
+        put_u32(&mut buf, seq);
+        put_u32(&mut buf, 0);
+        buf.extend_from_slice(body);
+        RtnlRequest { buf: buf }
+    }
+
+    /// Returns a RTM_GETLINK request for dumping all links.
+    pub fn get_link(seq: u32) -> RtnlRequest {
+        RtnlRequest::dump(RTM_GETLINK, seq, &[0; IFINFOMSG_LEN])
+    }
+
+    /// Returns a RTM_GETADDR request for dumping addresses.
+    ///
+    /// The `family` is AF_INET, AF_INET6 or 0 (AF_UNSPEC) for all families.
+    pub fn get_addr(family: i32, seq: u32) -> RtnlRequest {
+        let mut body = [0; IFADDRMSG_LEN];
+        body[0] = family as u8;
+        RtnlRequest::dump(RTM_GETADDR, seq, &body)
+    }
+
+    /// Returns a RTM_GETROUTE request for dumping routes.
+    ///
+    /// The `family` is AF_INET, AF_INET6 or 0 (AF_UNSPEC) for all families.
+    pub fn get_route(family: i32, seq: u32) -> RtnlRequest {
+        let mut body = [0; RTMSG_LEN];
+        body[0] = family as u8;
+        RtnlRequest::dump(RTM_GETROUTE, seq, &body)
+    }
+
+    /// Returns the encoded message.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+}
+
+/// The link information of RTM_NEWLINK/RTM_DELLINK.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LinkMessage {
+    index: u32,
+    flags: u32,
+    name: Option<String>,
+    lladdr: Option<LlAddr>,
+    mtu: Option<u32>,
+}
+
+impl LinkMessage {
+    fn parse(buf: &[u8]) -> Option<LinkMessage> {
+        if buf.len() < IFINFOMSG_LEN {
+            return None;
+        }
+        let mut msg = LinkMessage {
+            index: get_u32(buf, 4),
+            flags: get_u32(buf, 8),
+            name: None,
+            lladdr: None,
+            mtu: None,
+        };
+        for (kind, data) in (RtAttrs { buf: &buf[IFINFOMSG_LEN..] }) {
+            match kind {
+                IFLA_IFNAME => msg.name = name(data),
+                IFLA_ADDRESS if data.len() == 6 => {
+                    msg.lladdr = Some(LlAddr::new(data[0], data[1], data[2], data[3], data[4], data[5]))
+                }
+                IFLA_MTU if data.len() == 4 => msg.mtu = Some(get_u32(data, 0)),
+                _ => (),
+            }
+        }
+        Some(msg)
+    }
+
+    /// Returns a interface index.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Returns a interface flags (IFF_UP, IFF_RUNNING, ...).
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Returns a interface name.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|s| s.as_str())
+    }
+
+    /// Returns a Link-layer address if the interface is a ethernet-like device.
+    pub fn lladdr(&self) -> Option<LlAddr> {
+        self.lladdr
+    }
+
+    /// Returns a MTU.
+    pub fn mtu(&self) -> Option<u32> {
+        self.mtu
+    }
+}
+
+/// The address information of RTM_NEWADDR/RTM_DELADDR.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddrMessage {
+    family: i32,
+    prefix_len: u8,
+    scope: u8,
+    index: u32,
+    address: Option<IpAddr>,
+    local: Option<IpAddr>,
+    label: Option<String>,
+}
+
+impl AddrMessage {
+    fn parse(buf: &[u8]) -> Option<AddrMessage> {
+        if buf.len() < IFADDRMSG_LEN {
+            return None;
+        }
+        let mut msg = AddrMessage {
+            family: buf[0] as i32,
+            prefix_len: buf[1],
+            scope: buf[3],
+            index: get_u32(buf, 4),
+            address: None,
+            local: None,
+            label: None,
+        };
+        for (kind, data) in (RtAttrs { buf: &buf[IFADDRMSG_LEN..] }) {
+            match kind {
+                IFA_ADDRESS => msg.address = ip_addr(msg.family, data, msg.index),
+                IFA_LOCAL => msg.local = ip_addr(msg.family, data, msg.index),
+                IFA_LABEL => msg.label = name(data),
+                _ => (),
+            }
+        }
+        Some(msg)
+    }
+
+    /// Returns a address family (AF_INET or AF_INET6).
+    pub fn family(&self) -> i32 {
+        self.family
+    }
+
+    /// Returns a length of prefix.
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Returns a address scope.
+    pub fn scope(&self) -> u8 {
+        self.scope
+    }
+
+    /// Returns a interface index.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Returns a interface address.
+    ///
+    /// For point-to-point interfaces, this is the destination address.
+    pub fn address(&self) -> Option<IpAddr> {
+        self.address
+    }
+
+    /// Returns a local address.
+    pub fn local(&self) -> Option<IpAddr> {
+        self.local
+    }
+
+    /// Returns a label (IPv4 only).
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_ref().map(|s| s.as_str())
+    }
+}
+
+/// The route information of RTM_NEWROUTE/RTM_DELROUTE.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RouteMessage {
+    family: i32,
+    dst_len: u8,
+    table: u32,
+    protocol: u8,
+    scope: u8,
+    kind: u8,
+    dst: Option<IpAddr>,
+    gateway: Option<IpAddr>,
+    oif: Option<u32>,
+    priority: Option<u32>,
+}
+
+impl RouteMessage {
+    fn parse(buf: &[u8]) -> Option<RouteMessage> {
+        if buf.len() < RTMSG_LEN {
+            return None;
+        }
+        let mut msg = RouteMessage {
+            family: buf[0] as i32,
+            dst_len: buf[1],
+            table: buf[4] as u32,
+            protocol: buf[5],
+            scope: buf[6],
+            kind: buf[7],
+            dst: None,
+            gateway: None,
+            oif: None,
+            priority: None,
+        };
+        let attrs = RtAttrs { buf: &buf[RTMSG_LEN..] };
+        // the output interface is the scope of the link-local addresses, which may follow them.
+        let oif = attrs.clone().find(|&(kind, data)| kind == RTA_OIF && data.len() == 4);
+        let index = oif.map_or(0, |(_, data)| get_u32(data, 0));
+        for (kind, data) in attrs {
+            match kind {
+                RTA_DST => msg.dst = ip_addr(msg.family, data, index),
+                RTA_GATEWAY => msg.gateway = ip_addr(msg.family, data, index),
+                RTA_OIF if data.len() == 4 => msg.oif = Some(get_u32(data, 0)),
+                RTA_PRIORITY if data.len() == 4 => msg.priority = Some(get_u32(data, 0)),
+                RTA_TABLE if data.len() == 4 => msg.table = get_u32(data, 0),
+                _ => (),
+            }
+        }
+        Some(msg)
+    }
+
+    /// Returns a address family (AF_INET or AF_INET6).
+    pub fn family(&self) -> i32 {
+        self.family
+    }
+
+    /// Returns a length of destination prefix.
+    pub fn dst_len(&self) -> u8 {
+        self.dst_len
+    }
+
+    /// Returns a routing table id.
+    pub fn table(&self) -> u32 {
+        self.table
+    }
+
+    /// Returns a routing protocol (RTPROT_*).
+    pub fn protocol(&self) -> u8 {
+        self.protocol
+    }
+
+    /// Returns a route scope (RT_SCOPE_*).
+    pub fn scope(&self) -> u8 {
+        self.scope
+    }
+
+    /// Returns a route type (RTN_*).
+    pub fn kind(&self) -> u8 {
+        self.kind
+    }
+
+    /// Returns a destination address, or `None` for the default route.
+    pub fn dst(&self) -> Option<IpAddr> {
+        self.dst
+    }
+
+    /// Returns a gateway address.
+    pub fn gateway(&self) -> Option<IpAddr> {
+        self.gateway
+    }
+
+    /// Returns a output interface index.
+    pub fn oif(&self) -> Option<u32> {
+        self.oif
+    }
+
+    /// Returns a route priority (metric).
+    pub fn priority(&self) -> Option<u32> {
+        self.priority
+    }
+}
+
+/// A decoded rtnetlink message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RtnlMessage {
+    NewLink(LinkMessage),
+    DelLink(LinkMessage),
+    NewAddr(AddrMessage),
+    DelAddr(AddrMessage),
+    NewRoute(RouteMessage),
+    DelRoute(RouteMessage),
+    /// The end of a multipart (dump) message.
+    Done,
+    /// The error or acknowledgement (errno is `0`).
+    Error(i32),
+    /// The unsupported message type.
+    Other(u16),
+}
+
+/// An iterator over the rtnetlink messages in a received buffer.
+pub struct RtnlMessages<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> RtnlMessages<'a> {
+    pub fn new(buf: &'a [u8]) -> RtnlMessages<'a> {
+        RtnlMessages { buf: buf }
+    }
+}
+
+impl<'a> Iterator for RtnlMessages<'a> {
+    type Item = RtnlMessage;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.buf.len() < NLMSG_HDRLEN {
+                return None;
+            }
+            let len = get_u32(self.buf, 0) as usize;
+            let kind = get_u16(self.buf, 4);
+            if len < NLMSG_HDRLEN || len > self.buf.len() {
+                return None;
+            }
+            let body = &self.buf[NLMSG_HDRLEN..len];
+            self.buf = &self.buf[align(len).min(self.buf.len())..];
+            let msg = match kind {
+                NLMSG_NOOP => continue,
+                NLMSG_DONE => Some(RtnlMessage::Done),
+                NLMSG_ERROR if body.len() >= 4 => {
+                    Some(RtnlMessage::Error(-(get_u32(body, 0) as i32)))
+                }
+                RTM_NEWLINK => LinkMessage::parse(body).map(RtnlMessage::NewLink),
+                RTM_DELLINK => LinkMessage::parse(body).map(RtnlMessage::DelLink),
+                RTM_NEWADDR => AddrMessage::parse(body).map(RtnlMessage::NewAddr),
+                RTM_DELADDR => AddrMessage::parse(body).map(RtnlMessage::DelAddr),
+                RTM_NEWROUTE => RouteMessage::parse(body).map(RtnlMessage::NewRoute),
+                RTM_DELROUTE => RouteMessage::parse(body).map(RtnlMessage::DelRoute),
+                _ => Some(RtnlMessage::Other(kind)),
+            };
+            if let Some(msg) = msg {
+                return Some(msg);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_rtnl_request() {
+    let req = RtnlRequest::get_addr(AF_INET, 7);
+    let buf = req.as_bytes();
+    assert_eq!(buf.len(), NLMSG_HDRLEN + IFADDRMSG_LEN);
+    assert_eq!(get_u32(buf, 0) as usize, buf.len());
+    assert_eq!(get_u16(buf, 4), RTM_GETADDR);
+    assert_eq!(get_u16(buf, 6), NLM_F_REQUEST | NLM_F_DUMP);
+    assert_eq!(get_u32(buf, 8), 7);
+    assert_eq!(buf[NLMSG_HDRLEN] as i32, AF_INET);
+}
+
+#[test]
+fn test_rtnl_newaddr() {
+    let mut buf = Vec::new();
+    put_u32(&mut buf, (NLMSG_HDRLEN + IFADDRMSG_LEN + 8 + 8) as u32);
+    put_u16(&mut buf, RTM_NEWADDR);
+    put_u16(&mut buf, 0);
+    put_u32(&mut buf, 0);
+    put_u32(&mut buf, 0);
+    buf.extend_from_slice(&[AF_INET as u8, 24, 0, 0]);
+    put_u32(&mut buf, 2);
+    put_u16(&mut buf, 8);
+    put_u16(&mut buf, IFA_LOCAL);
+    buf.extend_from_slice(&[192, 168, 0, 1]);
+    put_u16(&mut buf, 7);
+    put_u16(&mut buf, IFA_LABEL);
+    buf.extend_from_slice(&[b'e', b'0', 0, 0]);
+    put_u32(&mut buf, NLMSG_HDRLEN as u32);
+    put_u16(&mut buf, NLMSG_DONE);
+    put_u16(&mut buf, 0);
+    put_u32(&mut buf, 0);
+    put_u32(&mut buf, 0);
+
+    let mut it = RtnlMessages::new(&buf);
+    match it.next() {
+        Some(RtnlMessage::NewAddr(addr)) => {
+            assert_eq!(addr.family(), AF_INET);
+            assert_eq!(addr.prefix_len(), 24);
+            assert_eq!(addr.index(), 2);
+            assert_eq!(addr.local(), Some(IpAddr::V4(IpAddrV4::new(192, 168, 0, 1))));
+            assert_eq!(addr.address(), None);
+            assert_eq!(addr.label(), Some("e0"));
+        }
+        msg => panic!("{:?}", msg),
+    }
+    assert_eq!(it.next(), Some(RtnlMessage::Done));
+    assert_eq!(it.next(), None);
+}
+
+#[test]
+fn test_rtnl_newaddr_link_local() {
+    let mut buf = Vec::new();
+    put_u32(&mut buf, (NLMSG_HDRLEN + IFADDRMSG_LEN + 20) as u32);
+    put_u16(&mut buf, RTM_NEWADDR);
+    put_u16(&mut buf, 0);
+    put_u32(&mut buf, 0);
+    put_u32(&mut buf, 0);
+    buf.extend_from_slice(&[AF_INET6 as u8, 64, 0, 253]);
+    put_u32(&mut buf, 3);
+    put_u16(&mut buf, 20);
+    put_u16(&mut buf, IFA_ADDRESS);
+    buf.extend_from_slice(&[0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+    match RtnlMessages::new(&buf).next() {
+        Some(RtnlMessage::NewAddr(addr)) => {
+            let mut lladdr = IpAddrV6::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+            lladdr.set_scope_id(3);
+            assert_eq!(addr.address(), Some(IpAddr::V6(lladdr)));
+        }
+        msg => panic!("{:?}", msg),
+    }
+}
+
+#[test]
+fn test_rtnl_addr_notification() {
+    use core::IoContext;
+    use netlink::{Netlink, NetlinkEndpoint, NetlinkSocket};
+    use spawn;
+    use std::sync::mpsc;
+    use libc;
+
+    const NLM_F_ACK: u16 = 0x004;
+    const NLM_F_EXCL: u16 = 0x200;
+    const NLM_F_CREATE: u16 = 0x400;
+
+    fn request(soc: &NetlinkSocket, kind: u16, flags: u16, index: u32) -> i32 {
+        let mut buf = Vec::new();
+        put_u32(&mut buf, (NLMSG_HDRLEN + IFADDRMSG_LEN + 8) as u32);
+        put_u16(&mut buf, kind);
+        put_u16(&mut buf, NLM_F_REQUEST | NLM_F_ACK | flags);
+        put_u32(&mut buf, 1);
+        put_u32(&mut buf, 0);
+        buf.extend_from_slice(&[AF_INET as u8, 32, 0, 0]);
+        put_u32(&mut buf, index);
+        put_u16(&mut buf, 8);
+        put_u16(&mut buf, IFA_LOCAL);
+        buf.extend_from_slice(&[198, 18, 0, 1]);
+        soc.send_to(&buf, 0, &NetlinkEndpoint::new(0, 0)).unwrap();
+        let mut buf = [0; 8192];
+        loop {
+            let len = soc.receive(&mut buf, 0).unwrap();
+            for msg in RtnlMessages::new(&buf[..len]) {
+                if let RtnlMessage::Error(err) = msg {
+                    return err;
+                }
+            }
+        }
+    }
+
+    let ctx = &IoContext::new().unwrap();
+    let mon = NetlinkSocket::new(ctx, Netlink::route()).unwrap();
+    mon.bind(&NetlinkEndpoint::new(0, RTMGRP_IPV4_IFADDR)).unwrap();
+    let soc = NetlinkSocket::new(ctx, Netlink::route()).unwrap();
+    soc.bind(&NetlinkEndpoint::new(0, 0)).unwrap();
+    let index = unsafe { libc::if_nametoindex(b"lo\0".as_ptr() as *const _) };
+    match request(&soc, RTM_NEWADDR, NLM_F_CREATE | NLM_F_EXCL, index) {
+        0 => (),
+        // changing the addresses requires CAP_NET_ADMIN.
+        libc::EPERM => return,
+        err => panic!("{}", err),
+    }
+    assert_eq!(request(&soc, RTM_DELADDR, 0, index), 0);
+
+    let (tx, rx) = mpsc::channel();
+    spawn(ctx, move |coro| {
+        let local = Some(IpAddr::V4(IpAddrV4::new(198, 18, 0, 1)));
+        let mut buf = [0; 8192];
+        let mut events = Vec::new();
+        while events.len() < 2 {
+            let len = mon.async_receive(&mut buf, 0, coro.wrap()).unwrap();
+            for msg in RtnlMessages::new(&buf[..len]) {
+                match msg {
+                    RtnlMessage::NewAddr(ref addr) if addr.local() == local => events.push(msg.clone()),
+                    RtnlMessage::DelAddr(ref addr) if addr.local() == local => events.push(msg.clone()),
+                    _ => (),
+                }
+            }
+        }
+        tx.send(events).unwrap();
+    }).unwrap();
+    ctx.run();
+    let events = rx.recv().unwrap();
+    match (events[0].clone(), events[1].clone()) {
+        (RtnlMessage::NewAddr(new), RtnlMessage::DelAddr(del)) => {
+            assert_eq!(new.index(), index);
+            assert_eq!(del.index(), index);
+        }
+        msg => panic!("{:?}", msg),
+    }
+}
@@ -0,0 +1,315 @@
+use ffi::{sockaddr, sockaddr_ll, socklen_t, if_nametoindex, SockAddr, AF_PACKET, SOCK_DGRAM,
+          SOCK_RAW};
+use core::{Endpoint, Protocol};
+use dgram_socket::DgramSocket;
+use ip::LlAddr;
+
+use std::io;
+use std::fmt;
+use std::mem;
+use std::ffi::CString;
+
+/// Every packet.
+pub const ETH_P_ALL: u16 = 0x0003;
+
+/// Internet Protocol version 4 packet.
+pub const ETH_P_IP: u16 = 0x0800;
+
+/// Address Resolution Protocol packet.
+pub const ETH_P_ARP: u16 = 0x0806;
+
+/// Internet Protocol version 6 packet.
+pub const ETH_P_IPV6: u16 = 0x86DD;
+
+/// Link Layer Discovery Protocol packet.
+pub const ETH_P_LLDP: u16 = 0x88CC;
+
+/// The packet addressed to the local host.
+pub const PACKET_HOST: u8 = 0;
+
+/// The physical-layer broadcast packet.
+pub const PACKET_BROADCAST: u8 = 1;
+
+/// The physical-layer multicast packet.
+pub const PACKET_MULTICAST: u8 = 2;
+
+/// The packet addressed to another host (promiscuous mode).
+pub const PACKET_OTHERHOST: u8 = 3;
+
+/// The packet originating from the local host (looped back).
+pub const PACKET_OUTGOING: u8 = 4;
+
+/// Returns a interface index from the interface name.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::packet::interface_index;
+///
+/// assert!(interface_index("lo").unwrap() > 0);
+/// ```
+pub fn interface_index(name: &str) -> io::Result<u32> {
+    let name = CString::new(name)?;
+    Ok(if_nametoindex(&name)?)
+}
+
+/// The link-layer packet protocol (AF_PACKET).
+///
+/// # Examples
+/// Receive the LLDP frames.
+///
+/// ```rust,no_run
+/// use asyncio::*;
+/// use asyncio::ip::LlAddr;
+/// use asyncio::packet::*;
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let pro = PacketProtocol::raw(ETH_P_LLDP);
+/// let soc = PacketSocket::new(ctx, pro).unwrap();
+/// soc.bind(&PacketEndpoint::new(pro, interface_index("eth0").unwrap(), LlAddr::default()))
+///     .unwrap();
+///
+/// let mut buf = [0; 1518];
+/// let (len, ep) = soc.receive_from(&mut buf, 0).unwrap();
+/// println!("{} bytes from {}", len, ep.lladdr());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Hash)]
+pub struct PacketProtocol {
+    socket_type: i32,
+    ethertype: u16,
+}
+
+impl PacketProtocol {
+    /// Represents a SOCK_RAW packet protocol, the frames include the link-level header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::packet::{PacketProtocol, ETH_P_ARP};
+    ///
+    /// assert_eq!(PacketProtocol::raw(ETH_P_ARP).ethertype(), ETH_P_ARP);
+    /// ```
+    pub fn raw(ethertype: u16) -> PacketProtocol {
+        PacketProtocol {
+            socket_type: SOCK_RAW,
+            ethertype: ethertype,
+        }
+    }
+
+    /// Represents a SOCK_DGRAM packet protocol, the link-level header is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::packet::{PacketProtocol, ETH_P_IP};
+    ///
+    /// assert!(PacketProtocol::dgram(ETH_P_IP) != PacketProtocol::raw(ETH_P_IP));
+    /// ```
+    pub fn dgram(ethertype: u16) -> PacketProtocol {
+        PacketProtocol {
+            socket_type: SOCK_DGRAM,
+            ethertype: ethertype,
+        }
+    }
+
+    /// Returns a ethertype in host byte order.
+    pub fn ethertype(&self) -> u16 {
+        self.ethertype
+    }
+}
+
+impl Protocol for PacketProtocol {
+    type Endpoint = PacketEndpoint;
+
+    type Socket = PacketSocket;
+
+    fn family_type(&self) -> i32 {
+        AF_PACKET
+    }
+
+    fn socket_type(&self) -> i32 {
+        self.socket_type
+    }
+
+    fn protocol_type(&self) -> i32 {
+        self.ethertype.to_be() as i32
+    }
+
+    unsafe fn uninitialized(&self) -> Self::Endpoint {
+        PacketEndpoint::new(*self, 0, LlAddr::default())
+    }
+}
+
+impl fmt::Display for PacketProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.socket_type {
+            SOCK_RAW => write!(f, "PacketRaw(0x{:04x})", self.ethertype),
+            _ => write!(f, "PacketDgram(0x{:04x})", self.ethertype),
+        }
+    }
+}
+
+/// The endpoint of packet socket.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct PacketEndpoint {
+    sll: SockAddr<sockaddr_ll>,
+    socket_type: i32,
+}
+
+impl PacketEndpoint {
+    /// Returns a packet endpoint from interface index and link-layer address.
+    ///
+    /// The interface index `0` means any interfaces when binding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asyncio::ip::LlAddr;
+    /// use asyncio::packet::{PacketEndpoint, PacketProtocol, ETH_P_ALL};
+    ///
+    /// let mac = LlAddr::new(0x00, 0x11, 0x22, 0x33, 0x44, 0x55);
+    /// let ep = PacketEndpoint::new(PacketProtocol::raw(ETH_P_ALL), 1, mac);
+    /// assert_eq!(ep.ifindex(), 1);
+    /// assert_eq!(ep.lladdr(), mac);
+    /// assert_eq!(ep.ethertype(), ETH_P_ALL);
+    /// ```
+    pub fn new(pro: PacketProtocol, ifindex: u32, lladdr: LlAddr) -> PacketEndpoint {
+        let mut sll: sockaddr_ll = unsafe { mem::zeroed() };
+        sll.sll_family = AF_PACKET as u16;
+        sll.sll_protocol = pro.ethertype.to_be();
+        sll.sll_ifindex = ifindex as i32;
+        sll.sll_halen = 6;
+        sll.sll_addr[..6].copy_from_slice(lladdr.as_bytes());
+        PacketEndpoint {
+            sll: SockAddr {
+                sa: sll,
+                sa_len: mem::size_of::<sockaddr_ll>() as u8,
+            },
+            socket_type: pro.socket_type,
+        }
+    }
+
+    /// Returns a interface index.
+    pub fn ifindex(&self) -> u32 {
+        self.sll.sa.sll_ifindex as u32
+    }
+
+    /// Returns a link-layer address.
+    ///
+    /// When the hardware address is not ethernet-like, returns the zero address.
+    pub fn lladdr(&self) -> LlAddr {
+        if self.sll.sa.sll_halen != 6 {
+            return LlAddr::default();
+        }
+        let a = &self.sll.sa.sll_addr;
+        LlAddr::new(a[0], a[1], a[2], a[3], a[4], a[5])
+    }
+
+    /// Returns a ethertype in host byte order.
+    pub fn ethertype(&self) -> u16 {
+        u16::from_be(self.sll.sa.sll_protocol)
+    }
+
+    /// Returns a packet type (ex. `PACKET_HOST`) of the received packet.
+    pub fn pkttype(&self) -> u8 {
+        self.sll.sa.sll_pkttype
+    }
+
+    /// Returns a ARP hardware type (ex. `ARPHRD_ETHER` is 1) of the received packet.
+    pub fn hatype(&self) -> u16 {
+        self.sll.sa.sll_hatype
+    }
+}
+
+impl Endpoint<PacketProtocol> for PacketEndpoint {
+    fn protocol(&self) -> PacketProtocol {
+        PacketProtocol {
+            socket_type: self.socket_type,
+            ethertype: self.ethertype(),
+        }
+    }
+
+    fn as_ptr(&self) -> *const sockaddr {
+        &self.sll.sa as *const _ as *const _
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut sockaddr {
+        &mut self.sll.sa as *mut _ as *mut _
+    }
+
+    fn capacity(&self) -> socklen_t {
+        self.sll.capacity() as socklen_t
+    }
+
+    fn size(&self) -> socklen_t {
+        self.sll.size() as socklen_t
+    }
+
+    unsafe fn resize(&mut self, size: socklen_t) {
+        debug_assert!(size <= self.capacity());
+        self.sll.resize(size as u8)
+    }
+}
+
+impl fmt::Debug for PacketEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}%{}/0x{:04x}",
+            self.lladdr(),
+            self.ifindex(),
+            self.ethertype()
+        )
+    }
+}
+
+/// The packet socket type.
+pub type PacketSocket = DgramSocket<PacketProtocol>;
+
+mod options;
+pub use self::options::*;
+
+mod ring;
+pub use self::ring::*;
+
+#[test]
+fn test_packet_endpoint() {
+    let pro = PacketProtocol::dgram(ETH_P_ARP);
+    let mac = LlAddr::new(1, 2, 3, 4, 5, 6);
+    let ep = PacketEndpoint::new(pro, 2, mac);
+    assert_eq!(ep.protocol(), pro);
+    assert_eq!(ep.ifindex(), 2);
+    assert_eq!(ep.lladdr(), mac);
+    assert_eq!(ep.ethertype(), ETH_P_ARP);
+    assert_eq!(ep, PacketEndpoint::new(pro, 2, mac));
+    assert!(ep != PacketEndpoint::new(pro, 3, mac));
+}
+
+#[test]
+fn test_packet_loopback() {
+    use core::IoContext;
+
+    let ctx = &IoContext::new().unwrap();
+    let ifindex = interface_index("lo").unwrap();
+    let pro = PacketProtocol::dgram(0x88B5);
+    let rx = match PacketSocket::new(ctx, pro) {
+        Ok(soc) => soc,
+        Err(_) => return, // requires CAP_NET_RAW
+    };
+    rx.bind(&PacketEndpoint::new(pro, ifindex, LlAddr::default()))
+        .unwrap();
+    let tx = PacketSocket::new(ctx, pro).unwrap();
+    let ep = PacketEndpoint::new(pro, ifindex, LlAddr::default());
+    assert_eq!(tx.send_to(b"hello packet", 0, &ep).unwrap(), 12);
+
+    let mut buf = [0; 64];
+    loop {
+        let (len, ep) = rx.receive_from(&mut buf, 0).unwrap();
+        if ep.pkttype() != PACKET_OUTGOING {
+            assert_eq!(&buf[..len], b"hello packet");
+            assert_eq!(ep.ifindex(), ifindex);
+            assert_eq!(ep.ethertype(), 0x88B5);
+            break;
+        }
+    }
+}
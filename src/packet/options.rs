@@ -0,0 +1,128 @@
+use ffi::{packet_mreq, SOL_PACKET, PACKET_ADD_MEMBERSHIP, PACKET_DROP_MEMBERSHIP,
+          PACKET_MR_MULTICAST, PACKET_MR_PROMISC, PACKET_MR_ALLMULTI};
+use core::{SetSocketOption, SocketOption};
+use ip::LlAddr;
+use packet::PacketProtocol;
+
+use std::mem;
+use libc::c_void;
+
+/// Socket option to add or drop a packet membership on a specified interface.
+///
+/// Implements the SOL_PACKET/PACKET_ADD_MEMBERSHIP or SOL_PACKET/PACKET_DROP_MEMBERSHIP socket option.
+///
+/// The membership is released automatically when the socket is closed.
+///
+/// # Examples
+/// Setting the promiscuous mode:
+///
+/// ```rust,no_run
+/// use asyncio::*;
+/// use asyncio::packet::*;
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let soc = PacketSocket::new(ctx, PacketProtocol::raw(ETH_P_ALL)).unwrap();
+/// let ifindex = interface_index("eth0").unwrap();
+///
+/// soc.set_option(PacketMembership::promiscuous(ifindex)).unwrap();
+/// ```
+///
+/// Leaving the LLDP multicast group:
+///
+/// ```rust,no_run
+/// use asyncio::*;
+/// use asyncio::ip::LlAddr;
+/// use asyncio::packet::*;
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let soc = PacketSocket::new(ctx, PacketProtocol::raw(ETH_P_LLDP)).unwrap();
+/// let ifindex = interface_index("eth0").unwrap();
+/// let lldp = LlAddr::new(0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e);
+///
+/// soc.set_option(PacketMembership::multicast(ifindex, lldp).leave()).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct PacketMembership {
+    mreq: packet_mreq,
+    name: i32,
+}
+
+impl PacketMembership {
+    fn new(ifindex: u32, kind: i32) -> PacketMembership {
+        let mut mreq: packet_mreq = unsafe { mem::zeroed() };
+        mreq.mr_ifindex = ifindex as i32;
+        mreq.mr_type = kind as u16;
+        PacketMembership {
+            mreq: mreq,
+            name: PACKET_ADD_MEMBERSHIP,
+        }
+    }
+
+    /// Returns a membership for receiving all packets on the interface.
+    pub fn promiscuous(ifindex: u32) -> PacketMembership {
+        Self::new(ifindex, PACKET_MR_PROMISC)
+    }
+
+    /// Returns a membership for receiving all multicast packets on the interface.
+    pub fn all_multicast(ifindex: u32) -> PacketMembership {
+        Self::new(ifindex, PACKET_MR_ALLMULTI)
+    }
+
+    /// Returns a membership for receiving the link-layer multicast group on the interface.
+    pub fn multicast(ifindex: u32, group: LlAddr) -> PacketMembership {
+        let mut opt = Self::new(ifindex, PACKET_MR_MULTICAST);
+        opt.mreq.mr_alen = 6;
+        opt.mreq.mr_address[..6].copy_from_slice(group.as_bytes());
+        opt
+    }
+
+    /// Turns into the option to drop the membership.
+    pub fn leave(mut self) -> PacketMembership {
+        self.name = PACKET_DROP_MEMBERSHIP;
+        self
+    }
+
+    /// Returns a interface index.
+    pub fn ifindex(&self) -> u32 {
+        self.mreq.mr_ifindex as u32
+    }
+}
+
+impl SocketOption<PacketProtocol> for PacketMembership {
+    fn level(&self, _: &PacketProtocol) -> i32 {
+        SOL_PACKET
+    }
+
+    fn name(&self, _: &PacketProtocol) -> i32 {
+        self.name
+    }
+
+    fn capacity(&self) -> u32 {
+        mem::size_of::<packet_mreq>() as u32
+    }
+}
+
+impl SetSocketOption<PacketProtocol> for PacketMembership {
+    fn as_ptr(&self) -> *const c_void {
+        &self.mreq as *const _ as *const _
+    }
+}
+
+#[test]
+fn test_packet_membership() {
+    use core::IoContext;
+    use packet::{interface_index, PacketSocket, ETH_P_ALL};
+
+    let ctx = &IoContext::new().unwrap();
+    let soc = match PacketSocket::new(ctx, PacketProtocol::raw(ETH_P_ALL)) {
+        Ok(soc) => soc,
+        Err(_) => return, // requires CAP_NET_RAW
+    };
+    let ifindex = interface_index("lo").unwrap();
+    let group = LlAddr::new(0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e);
+    soc.set_option(PacketMembership::all_multicast(ifindex))
+        .unwrap();
+    soc.set_option(PacketMembership::all_multicast(ifindex).leave())
+        .unwrap();
+    assert_eq!(PacketMembership::multicast(ifindex, group).ifindex(), ifindex);
+}
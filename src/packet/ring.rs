@@ -0,0 +1,410 @@
+use ffi::{AsRawFd, SystemError, Timeout, readable, sockaddr_ll, tpacket_req, tpacket2_hdr,
+          SOL_PACKET, PACKET_RX_RING, PACKET_VERSION, TPACKET_V2, TP_STATUS_KERNEL, TP_STATUS_USER,
+          WOULD_BLOCK};
+use core::{Exec, Perform, SetSocketOption, SocketOption, ThreadIoContext};
+use handler::{Handler, Complete, AsyncReadOp};
+use packet::{PacketProtocol, PacketSocket};
+
+use std::io;
+use std::ptr;
+use std::mem;
+use std::slice;
+use std::marker::PhantomData;
+use std::sync::atomic::{fence, Ordering};
+use std::time::Duration;
+use libc::{self, c_void};
+
+const TPACKET_ALIGNMENT: usize = 16;
+
+fn tpacket_align(len: usize) -> usize {
+    (len + TPACKET_ALIGNMENT - 1) & !(TPACKET_ALIGNMENT - 1)
+}
+
+fn frame_ready(hdr: *const tpacket2_hdr) -> bool {
+    let status = unsafe { ptr::read_volatile(&(*hdr).tp_status) };
+    fence(Ordering::Acquire);
+    status & TP_STATUS_USER != 0
+}
+
+#[derive(Clone)]
+struct PacketVersion(i32);
+
+impl SocketOption<PacketProtocol> for PacketVersion {
+    fn level(&self, _: &PacketProtocol) -> i32 {
+        SOL_PACKET
+    }
+
+    fn name(&self, _: &PacketProtocol) -> i32 {
+        PACKET_VERSION
+    }
+}
+
+impl SetSocketOption<PacketProtocol> for PacketVersion {
+    fn as_ptr(&self) -> *const c_void {
+        &self.0 as *const _ as *const _
+    }
+}
+
+#[derive(Clone)]
+struct RxRing(tpacket_req);
+
+impl SocketOption<PacketProtocol> for RxRing {
+    fn level(&self, _: &PacketProtocol) -> i32 {
+        SOL_PACKET
+    }
+
+    fn name(&self, _: &PacketProtocol) -> i32 {
+        PACKET_RX_RING
+    }
+}
+
+impl SetSocketOption<PacketProtocol> for RxRing {
+    fn as_ptr(&self) -> *const c_void {
+        &self.0 as *const _ as *const _
+    }
+}
+
+struct RingWait<F> {
+    soc: *const PacketSocket,
+    hdr: *const tpacket2_hdr,
+    handler: F,
+}
+
+unsafe impl<F> Send for RingWait<F> {}
+
+impl<F> Exec for RingWait<F>
+where
+    F: Complete<(), io::Error>,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        let soc = unsafe { &*self.soc };
+        soc.add_read_op(this, Box::new(self), SystemError::default())
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        let soc = unsafe { &*self.soc };
+        soc.add_read_op(this, self, SystemError::default())
+    }
+}
+
+impl<F> Complete<(), io::Error> for RingWait<F>
+where
+    F: Complete<(), io::Error>,
+{
+    fn success(self, this: &mut ThreadIoContext, res: ()) {
+        let soc = unsafe { &*self.soc };
+        soc.next_read_op(this);
+        self.handler.success(this, res)
+    }
+
+    fn failure(self, this: &mut ThreadIoContext, err: io::Error) {
+        let soc = unsafe { &*self.soc };
+        soc.next_read_op(this);
+        self.handler.failure(this, err)
+    }
+}
+
+impl<F> Perform for RingWait<F>
+where
+    F: Complete<(), io::Error>,
+{
+    fn perform(self: Box<Self>, this: &mut ThreadIoContext, err: SystemError) {
+        if err == SystemError::default() {
+            if frame_ready(self.hdr) {
+                self.success(this, ())
+            } else {
+                let soc = unsafe { &*self.soc };
+                soc.add_read_op(this, self, WOULD_BLOCK)
+            }
+        } else {
+            self.failure(this, err.into())
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
+}
+
+/// The memory mapped receive ring (PACKET_RX_RING) of packet socket.
+///
+/// The frames are stored by the kernel directly, without copying by the recv system call.
+/// A packet socket can be attached only one receive ring.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use asyncio::*;
+/// use asyncio::packet::*;
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let pro = PacketProtocol::raw(ETH_P_ALL);
+/// let soc = PacketSocket::new(ctx, pro).unwrap();
+/// let mut ring = PacketRxRing::new(&soc, 2048, 1024).unwrap();
+/// soc.bind(&PacketEndpoint::new(pro, interface_index("eth0").unwrap(), Default::default()))
+///     .unwrap();
+///
+/// loop {
+///     let frame = ring.receive().unwrap();
+///     println!("{} bytes", frame.len());
+/// }
+/// ```
+pub struct PacketRxRing<'a> {
+    soc: &'a PacketSocket,
+    ptr: *mut u8,
+    size: usize,
+    block_size: usize,
+    frame_size: usize,
+    frames_per_block: usize,
+    frame_nr: usize,
+    head: usize,
+}
+
+impl<'a> PacketRxRing<'a> {
+    /// Attaches a receive ring of `frame_nr` frames to the socket.
+    ///
+    /// The `frame_size` is the capacity of each frame including the TPACKET_V2 header.
+    pub fn new(soc: &'a PacketSocket, frame_size: usize, frame_nr: usize) -> io::Result<Self> {
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let frame_size = tpacket_align(frame_size);
+        if frame_size <= tpacket_align(mem::size_of::<tpacket2_hdr>()) || frame_nr == 0 {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        }
+        let block_size = (frame_size + page_size - 1) / page_size * page_size;
+        let frames_per_block = block_size / frame_size;
+        let block_nr = (frame_nr + frames_per_block - 1) / frames_per_block;
+        let frame_nr = block_nr * frames_per_block;
+
+        soc.set_option(PacketVersion(TPACKET_V2))?;
+        soc.set_option(RxRing(tpacket_req {
+            tp_block_size: block_size as u32,
+            tp_block_nr: block_nr as u32,
+            tp_frame_size: frame_size as u32,
+            tp_frame_nr: frame_nr as u32,
+        }))?;
+
+        let size = block_size * block_nr;
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                soc.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(PacketRxRing {
+            soc: soc,
+            ptr: ptr as *mut u8,
+            size: size,
+            block_size: block_size,
+            frame_size: frame_size,
+            frames_per_block: frames_per_block,
+            frame_nr: frame_nr,
+            head: 0,
+        })
+    }
+
+    /// Returns a number of frames in the ring.
+    pub fn frame_nr(&self) -> usize {
+        self.frame_nr
+    }
+
+    fn header(&self) -> *mut tpacket2_hdr {
+        let block = self.head / self.frames_per_block;
+        let frame = self.head % self.frames_per_block;
+        unsafe {
+            self.ptr.add(block * self.block_size + frame * self.frame_size) as *mut tpacket2_hdr
+        }
+    }
+
+    fn ready(&self) -> bool {
+        frame_ready(self.header())
+    }
+
+    /// Returns a next frame if the kernel has already stored it.
+    ///
+    /// The frame is returned to the kernel when it is dropped.
+    pub fn try_receive(&mut self) -> Option<PacketFrame<'_>> {
+        if !self.ready() {
+            return None;
+        }
+        let hdr = self.header();
+        self.head = (self.head + 1) % self.frame_nr;
+        Some(PacketFrame {
+            hdr: hdr,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Blocks the current thread until a next frame is stored by the kernel.
+    ///
+    /// It does not use the reactor, so use the `async_wait` in the handlers and the coroutines.
+    pub fn receive(&mut self) -> io::Result<PacketFrame<'_>> {
+        while !self.ready() {
+            readable(self.soc, &Timeout::max())?;
+        }
+        Ok(self.try_receive().unwrap())
+    }
+
+    /// Asynchronously waits until a next frame is stored by the kernel, and then it can be taken by the `try_receive`.
+    ///
+    /// The wait is canceled by the `cancel` of the socket.
+    pub fn async_wait<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<(), io::Error>,
+    {
+        handler.wrap_cancel(self.soc, |ctx, handler| {
+            ctx.do_dispatch(RingWait {
+                soc: self.soc,
+                hdr: self.header(),
+                handler: handler,
+            })
+        })
+    }
+}
+
+impl<'a> Drop for PacketRxRing<'a> {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr as *mut c_void, self.size) };
+    }
+}
+
+/// A frame borrowed from the `PacketRxRing`.
+pub struct PacketFrame<'a> {
+    hdr: *mut tpacket2_hdr,
+    _marker: PhantomData<&'a mut ()>,
+}
+
+impl<'a> PacketFrame<'a> {
+    fn hdr(&self) -> &tpacket2_hdr {
+        unsafe { &*self.hdr }
+    }
+
+    fn sll(&self) -> &sockaddr_ll {
+        unsafe {
+            &*((self.hdr as *const u8).add(tpacket_align(mem::size_of::<tpacket2_hdr>())) as *const sockaddr_ll)
+        }
+    }
+
+    /// Returns a captured bytes.
+    pub fn data(&self) -> &[u8] {
+        let hdr = self.hdr();
+        unsafe {
+            slice::from_raw_parts(
+                (self.hdr as *const u8).add(hdr.tp_mac as usize),
+                hdr.tp_snaplen as usize,
+            )
+        }
+    }
+
+    /// Returns a original length of the packet.
+    ///
+    /// It may be larger than `data().len()` if the frame was truncated.
+    pub fn len(&self) -> usize {
+        self.hdr().tp_len as usize
+    }
+
+    /// Returns a receive timestamp since the UNIX epoch.
+    pub fn timestamp(&self) -> Duration {
+        let hdr = self.hdr();
+        Duration::new(hdr.tp_sec as u64, hdr.tp_nsec)
+    }
+
+    /// Returns a interface index of the received packet.
+    pub fn ifindex(&self) -> u32 {
+        self.sll().sll_ifindex as u32
+    }
+
+    /// Returns a packet type (ex. `PACKET_HOST`) of the received packet.
+    pub fn pkttype(&self) -> u8 {
+        self.sll().sll_pkttype
+    }
+
+    /// Returns a ethertype in host byte order.
+    pub fn ethertype(&self) -> u16 {
+        u16::from_be(self.sll().sll_protocol)
+    }
+}
+
+impl<'a> Drop for PacketFrame<'a> {
+    fn drop(&mut self) {
+        fence(Ordering::Release);
+        unsafe { ptr::write_volatile(&mut (*self.hdr).tp_status, TP_STATUS_KERNEL) };
+    }
+}
+
+#[test]
+fn test_packet_rx_ring() {
+    use core::IoContext;
+    use ip::LlAddr;
+    use packet::{interface_index, PacketEndpoint, PACKET_OUTGOING};
+
+    let ctx = &IoContext::new().unwrap();
+    let ifindex = interface_index("lo").unwrap();
+    let pro = PacketProtocol::dgram(0x88B6);
+    let rx = match PacketSocket::new(ctx, pro) {
+        Ok(soc) => soc,
+        Err(_) => return, // requires CAP_NET_RAW
+    };
+    let mut ring = PacketRxRing::new(&rx, 2048, 64).unwrap();
+    assert!(ring.frame_nr() >= 64);
+    assert!(ring.try_receive().is_none());
+    rx.bind(&PacketEndpoint::new(pro, ifindex, LlAddr::default()))
+        .unwrap();
+
+    let tx = PacketSocket::new(ctx, pro).unwrap();
+    let ep = PacketEndpoint::new(pro, ifindex, LlAddr::default());
+    for _ in 0..4 {
+        tx.send_to(b"hello ring", 0, &ep).unwrap();
+    }
+    let mut count = 0;
+    while count < 4 {
+        let frame = ring.receive().unwrap();
+        if frame.pkttype() != PACKET_OUTGOING {
+            assert_eq!(frame.data(), b"hello ring");
+            assert_eq!(frame.len(), 10);
+            assert_eq!(frame.ifindex(), ifindex);
+            assert_eq!(frame.ethertype(), 0x88B6);
+            count += 1;
+        }
+    }
+}
+
+#[cfg(feature = "context")]
+#[test]
+fn test_packet_rx_ring_async() {
+    use core::{AsIoContext, IoContext};
+    use ip::LlAddr;
+    use packet::{interface_index, PacketEndpoint, PACKET_OUTGOING};
+    use strand::spawn;
+
+    let ctx = &IoContext::new().unwrap();
+    let ifindex = interface_index("lo").unwrap();
+    let pro = PacketProtocol::dgram(0x88B6);
+    if PacketSocket::new(ctx, pro).is_err() {
+        return; // requires CAP_NET_RAW
+    }
+    spawn(ctx, move |coro| {
+        let rx = PacketSocket::new(coro.as_ctx(), pro).unwrap();
+        let mut ring = PacketRxRing::new(&rx, 2048, 64).unwrap();
+        rx.bind(&PacketEndpoint::new(pro, ifindex, LlAddr::default()))
+            .unwrap();
+        let tx = PacketSocket::new(coro.as_ctx(), pro).unwrap();
+        let ep = PacketEndpoint::new(pro, ifindex, LlAddr::default());
+        tx.send_to(b"hello async ring", 0, &ep).unwrap();
+        loop {
+            ring.async_wait(coro.wrap()).unwrap();
+            let frame = ring.try_receive().unwrap();
+            if frame.pkttype() != PACKET_OUTGOING {
+                assert_eq!(frame.data(), b"hello async ring");
+                break;
+            }
+        }
+    }).unwrap();
+    ctx.run();
+}
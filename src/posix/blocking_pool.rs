@@ -0,0 +1,248 @@
+use ffi::OPERATION_CANCELED;
+use core::{AsIoContext, IoContext, Exec, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+
+use std::io;
+use std::mem;
+use std::thread;
+use std::time::Duration;
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+
+/// A blocking operation, which runs on the blocking pool.
+///
+/// The operation must own the all resources which it uses,
+/// since it may run after the object which started it has been dropped or canceled.
+pub trait Blocking: Send + 'static {
+    type Output: Send + 'static;
+
+    fn run(self) -> io::Result<Self::Output>;
+}
+
+const MAX_THREADS: usize = 16;
+
+const IDLE_TIMEOUT: u64 = 10;
+
+struct PoolState {
+    jobs: VecDeque<Box<FnOnce() + Send>>,
+    threads: usize,
+    idle: usize,
+}
+
+struct Pool {
+    state: Mutex<PoolState>,
+    condvar: Condvar,
+}
+
+lazy_static! {
+    static ref POOL: Pool = Pool {
+        state: Mutex::new(PoolState {
+            jobs: VecDeque::new(),
+            threads: 0,
+            idle: 0,
+        }),
+        condvar: Condvar::new(),
+    };
+}
+
+fn worker() {
+    let mut state = POOL.state.lock().unwrap();
+    loop {
+        if let Some(job) = state.jobs.pop_front() {
+            drop(state);
+            job();
+            state = POOL.state.lock().unwrap();
+            continue;
+        }
+        state.idle += 1;
+        let (st, res) = POOL
+            .condvar
+            .wait_timeout(state, Duration::from_secs(IDLE_TIMEOUT))
+            .unwrap();
+        state = st;
+        state.idle -= 1;
+        if res.timed_out() && state.jobs.is_empty() {
+            state.threads -= 1;
+            return;
+        }
+    }
+}
+
+/// Runs the `job` on a thread of the pool, which grows up to `MAX_THREADS` and shrinks when idle.
+fn spawn<F>(job: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    let mut state = POOL.state.lock().unwrap();
+    state.jobs.push_back(Box::new(job));
+    if state.idle > 0 || state.threads >= MAX_THREADS {
+        POOL.condvar.notify_one();
+        return;
+    }
+    state.threads += 1;
+    if let Err(_) = thread::Builder::new()
+        .name("asyncio-blocking".to_string())
+        .spawn(worker)
+    {
+        state.threads -= 1;
+        if state.threads == 0 {
+            // runs the job on the caller thread, since no thread can pick up it.
+            let job = state.jobs.pop_back().unwrap();
+            drop(state);
+            job();
+        }
+    }
+}
+
+/// The handler of the operation in flight, which is taken by the completion or the cancellation.
+struct Slot<R, F> {
+    handler: Mutex<Option<F>>,
+    _marker: PhantomData<fn(R)>,
+}
+
+impl<R, F> Slot<R, F> {
+    fn take(&self) -> Option<F> {
+        self.handler.lock().unwrap().take()
+    }
+}
+
+trait PendingOp: Send + Sync {
+    fn cancel(&self, ctx: &IoContext);
+}
+
+impl<R, F> PendingOp for Slot<R, F>
+where
+    R: Send + 'static,
+    F: Complete<R, io::Error>,
+{
+    fn cancel(&self, ctx: &IoContext) {
+        if let Some(handler) = self.take() {
+            ctx.do_post(BlockingDone {
+                res: Err(OPERATION_CANCELED.into()),
+                slot: Arc::new(Slot {
+                    handler: Mutex::new(Some(handler)),
+                    _marker: PhantomData,
+                }),
+            })
+        }
+    }
+}
+
+/// The operations in flight on the blocking pool, which can be canceled.
+///
+/// The cancellation completes the handlers with `OPERATION_CANCELED` immediately,
+/// and the operations which are still running are detached and their results are discarded.
+pub struct BlockingOps {
+    ops: Mutex<Vec<Weak<PendingOp>>>,
+}
+
+impl BlockingOps {
+    pub fn new() -> Self {
+        BlockingOps { ops: Mutex::new(Vec::new()) }
+    }
+
+    fn push(&self, op: Weak<PendingOp>) {
+        let mut ops = self.ops.lock().unwrap();
+        ops.retain(|op| op.upgrade().is_some());
+        ops.push(op);
+    }
+
+    /// Cancels the all operations in flight.
+    pub fn cancel(&self, ctx: &IoContext) {
+        let ops = mem::replace(&mut *self.ops.lock().unwrap(), Vec::new());
+        for op in ops {
+            if let Some(op) = op.upgrade() {
+                op.cancel(ctx);
+            }
+        }
+        if ThreadIoContext::callstack(ctx).is_none() {
+            ctx.as_reactor().interrupt();
+        }
+    }
+}
+
+struct BlockingOp<B: Blocking, F> {
+    op: B,
+    slot: Arc<Slot<B::Output, F>>,
+}
+
+impl<B, F> Exec for BlockingOp<B, F>
+where
+    B: Blocking,
+    F: Complete<B::Output, io::Error>,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        let ctx = this.as_ctx().clone();
+        let BlockingOp { op, slot } = self;
+        if slot.handler.lock().unwrap().is_none() {
+            // has been canceled before started.
+            return;
+        }
+        spawn(move || {
+            let res = op.run();
+            ctx.do_post(BlockingDone {
+                res: res,
+                slot: slot,
+            });
+            ctx.as_reactor().interrupt();
+        })
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.call(this)
+    }
+}
+
+struct BlockingDone<R, F> {
+    res: io::Result<R>,
+    slot: Arc<Slot<R, F>>,
+}
+
+impl<R, F> Exec for BlockingDone<R, F>
+where
+    R: Send + 'static,
+    F: Complete<R, io::Error>,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        // the handler has been taken if the operation was canceled.
+        if let Some(handler) = self.slot.take() {
+            match self.res {
+                Ok(res) => handler.success(this, res),
+                Err(err) => handler.failure(this, err),
+            }
+        }
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.call(this)
+    }
+
+    fn outstanding_work(&self, _: &IoContext) {}
+}
+
+/// Runs the `op` on the blocking pool, and completes the wrapped `handler` on the `IoContext`
+/// unless the `ops` are canceled.
+pub fn blocking_op<B, F>(ctx: &IoContext, ops: &BlockingOps, op: B, handler: F)
+where
+    B: Blocking,
+    F: Complete<B::Output, io::Error>,
+{
+    let slot = Arc::new(Slot {
+        handler: Mutex::new(Some(handler)),
+        _marker: PhantomData,
+    });
+    let pending: Arc<PendingOp> = slot.clone();
+    ops.push(Arc::downgrade(&pending));
+    ctx.do_dispatch(BlockingOp { op: op, slot: slot })
+}
+
+/// Runs the `op` on the blocking pool, and completes the `handler` on the `IoContext`
+/// unless the `ops` are canceled.
+pub fn async_blocking_op<B, F>(soc: &Cancel, ops: &BlockingOps, op: B, handler: F) -> F::Output
+where
+    B: Blocking,
+    F: Handler<B::Output, io::Error>,
+{
+    handler.wrap_cancel(soc, move |ctx, handler| blocking_op(ctx, ops, op, handler))
+}
@@ -0,0 +1,285 @@
+use ffi::{AsRawFd, RawFd, SystemError, INTERRUPTED, IN_PROGRESS, OPERATION_CANCELED,
+          WOULD_BLOCK, eventfd, read, readable, write};
+use reactor::SocketImpl;
+use core::{AsIoContext, IoContext, Exec, Perform, ThreadIoContext, Cancel};
+use handler::{Handler, Complete, AsyncReadOp};
+
+use std::io;
+use std::time::Duration;
+
+fn read_count<S>(soc: &S) -> Result<u64, SystemError>
+where
+    S: AsRawFd,
+{
+    let mut buf = [0; 8];
+    read(soc, &mut buf)?;
+    Ok(u64::from_ne_bytes(buf))
+}
+
+struct EventWait<F> {
+    ev: *const EventNotifier,
+    handler: F,
+}
+
+unsafe impl<F> Send for EventWait<F> {}
+
+impl<F> Exec for EventWait<F>
+where
+    F: Complete<u64, io::Error>,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        let ev = unsafe { &*self.ev };
+        ev.add_read_op(this, Box::new(self), SystemError::default())
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        let ev = unsafe { &*self.ev };
+        ev.add_read_op(this, self, SystemError::default())
+    }
+}
+
+impl<F> Complete<u64, io::Error> for EventWait<F>
+where
+    F: Complete<u64, io::Error>,
+{
+    fn success(self, this: &mut ThreadIoContext, res: u64) {
+        let ev = unsafe { &*self.ev };
+        ev.next_read_op(this);
+        self.handler.success(this, res)
+    }
+
+    fn failure(self, this: &mut ThreadIoContext, err: io::Error) {
+        let ev = unsafe { &*self.ev };
+        ev.next_read_op(this);
+        self.handler.failure(this, err)
+    }
+}
+
+impl<F> Perform for EventWait<F>
+where
+    F: Complete<u64, io::Error>,
+{
+    fn perform(self: Box<Self>, this: &mut ThreadIoContext, err: SystemError) {
+        if err == SystemError::default() {
+            let ev = unsafe { &*self.ev };
+            while !this.as_ctx().stopped() {
+                match read_count(ev) {
+                    Ok(count) => return self.success(this, count),
+                    Err(IN_PROGRESS) | Err(WOULD_BLOCK) => {
+                        return ev.add_read_op(this, self, WOULD_BLOCK)
+                    }
+                    Err(INTERRUPTED) => (),
+                    Err(err) => return self.failure(this, err.into()),
+                }
+            }
+            self.failure(this, OPERATION_CANCELED.into())
+        } else {
+            self.failure(this, err.into())
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
+}
+
+/// Provides a cross-thread event notification by the eventfd.
+///
+/// The `notify` only writes to the eventfd, so it can be called from any thread, including the
+/// threads which does not run the `IoContext` and the signal handlers.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::thread;
+/// use std::sync::Arc;
+/// use asyncio::{IoContext, wrap};
+/// use asyncio::posix::EventNotifier;
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let ev = Arc::new(EventNotifier::new(ctx).unwrap());
+/// ev.async_wait(wrap(&ev, |_, res: io::Result<u64>| {
+///     assert_eq!(res.unwrap(), 3);
+/// }));
+///
+/// let notifier = ev.clone();
+/// thread::spawn(move || notifier.notify(3).unwrap()).join().unwrap();
+/// ctx.run();
+/// ```
+pub struct EventNotifier {
+    pimpl: Box<SocketImpl<()>>,
+}
+
+impl EventNotifier {
+    /// Returns a `EventNotifier` which yields the accumulated count, and resets it to 0.
+    pub fn new(ctx: &IoContext) -> io::Result<Self> {
+        let fd = eventfd(0, false)?;
+        Ok(EventNotifier { pimpl: SocketImpl::new(ctx, fd, ()) })
+    }
+
+    /// Returns a `EventNotifier` in the semaphore mode, which yields 1 and decrements the count for each wait.
+    pub fn semaphore(ctx: &IoContext) -> io::Result<Self> {
+        let fd = eventfd(0, true)?;
+        Ok(EventNotifier { pimpl: SocketImpl::new(ctx, fd, ()) })
+    }
+
+    /// Adds `n` to the count, and wakes up the waiters.
+    ///
+    /// Returns a `WouldBlock` error if the count would overflow.
+    pub fn notify(&self, n: u64) -> io::Result<()> {
+        write(self, &n.to_ne_bytes())?;
+        Ok(())
+    }
+
+    /// Asynchronously waits until the count is greater than 0.
+    pub fn async_wait<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<u64, io::Error>,
+    {
+        handler.wrap_cancel(self, |ctx, handler| {
+            ctx.do_dispatch(EventWait {
+                ev: self,
+                handler: handler,
+            })
+        })
+    }
+
+    /// Returns the count without blocking, or `None` if it is 0.
+    pub fn try_wait(&self) -> io::Result<Option<u64>> {
+        match read_count(self) {
+            Ok(count) => Ok(Some(count)),
+            Err(WOULD_BLOCK) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Blocks until the count is greater than 0.
+    pub fn wait(&self) -> io::Result<u64> {
+        if self.as_ctx().stopped() {
+            return Err(OPERATION_CANCELED.into());
+        }
+        loop {
+            match read_count(self) {
+                Ok(count) => return Ok(count),
+                Err(WOULD_BLOCK) => {
+                    if let Err(err) = readable(self, &self.pimpl.timeout) {
+                        return Err(err.into());
+                    }
+                }
+                Err(INTERRUPTED) if !self.as_ctx().stopped() => (),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.pimpl.timeout.get()
+    }
+
+    pub fn set_timeout(&self, timeout: Duration) -> io::Result<()> {
+        Ok(self.pimpl.timeout.set(timeout)?)
+    }
+}
+
+unsafe impl Send for EventNotifier {}
+
+unsafe impl Sync for EventNotifier {}
+
+unsafe impl AsIoContext for EventNotifier {
+    fn as_ctx(&self) -> &IoContext {
+        self.pimpl.as_ctx()
+    }
+}
+
+impl AsRawFd for EventNotifier {
+    fn as_raw_fd(&self) -> RawFd {
+        self.pimpl.as_raw_fd()
+    }
+}
+
+impl Cancel for EventNotifier {
+    fn cancel(&self) {
+        self.pimpl.cancel()
+    }
+
+    fn cancel_expired(&self) {
+        self.pimpl.cancel_expired()
+    }
+}
+
+impl AsyncReadOp for EventNotifier {
+    fn add_read_op(&self, this: &mut ThreadIoContext, op: Box<Perform>, err: SystemError) {
+        self.pimpl.add_read_op(this, op, err)
+    }
+
+    fn next_read_op(&self, this: &mut ThreadIoContext) {
+        self.pimpl.next_read_op(this)
+    }
+}
+
+#[test]
+fn test_event_notifier() {
+    let ctx = &IoContext::new().unwrap();
+    let ev = EventNotifier::new(ctx).unwrap();
+    assert_eq!(ev.try_wait().unwrap(), None);
+    ev.notify(1).unwrap();
+    ev.notify(2).unwrap();
+    assert_eq!(ev.try_wait().unwrap(), Some(3));
+    assert_eq!(ev.try_wait().unwrap(), None);
+    assert_eq!(
+        ev.notify(!0).unwrap_err().kind(),
+        io::ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn test_event_notifier_semaphore() {
+    let ctx = &IoContext::new().unwrap();
+    let ev = EventNotifier::semaphore(ctx).unwrap();
+    ev.notify(2).unwrap();
+    assert_eq!(ev.wait().unwrap(), 1);
+    assert_eq!(ev.wait().unwrap(), 1);
+    assert_eq!(ev.try_wait().unwrap(), None);
+}
+
+#[test]
+fn test_event_notifier_async() {
+    use handler::wrap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let ctx = &IoContext::new().unwrap();
+    let ev = Arc::new(EventNotifier::semaphore(ctx).unwrap());
+    for _ in 0..3 {
+        ev.async_wait(wrap(&ev, |_, res: io::Result<u64>| {
+            assert_eq!(res.unwrap(), 1);
+            COUNT.fetch_add(1, Ordering::SeqCst);
+        }));
+    }
+    let notifier = ev.clone();
+    let thrd = thread::spawn(move || for _ in 0..3 {
+        notifier.notify(1).unwrap();
+    });
+    ctx.run();
+    thrd.join().unwrap();
+    assert_eq!(COUNT.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_event_notifier_cancel() {
+    use handler::wrap;
+    use std::sync::Arc;
+
+    let ctx = &IoContext::new().unwrap();
+    let ev = Arc::new(EventNotifier::new(ctx).unwrap());
+    ev.async_wait(wrap(&ev, |_, res: io::Result<u64>| {
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(::libc::ECANCELED));
+    }));
+    let cancel = ev.clone();
+    ctx.post(move |_| cancel.cancel());
+    ctx.run();
+}
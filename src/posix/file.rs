@@ -0,0 +1,665 @@
+use ffi::{AsRawFd, RawFd, CONNECTION_ABORTED};
+use core::{AsIoContext, IoContext, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+use stream::Stream;
+use super::blocking_pool::{Blocking, BlockingOps, async_blocking_op, blocking_op};
+
+use std::io::{self, Read, Write, Seek, SeekFrom};
+use std::fs::{File, Metadata, OpenOptions};
+use std::path::Path;
+use std::slice;
+use std::sync::Arc;
+use std::os::unix::fs::FileExt;
+
+struct ReadAt {
+    file: Arc<File>,
+    len: usize,
+    offset: u64,
+}
+
+impl Blocking for ReadAt {
+    type Output = Vec<u8>;
+
+    fn run(self) -> io::Result<Vec<u8>> {
+        let mut buf = vec![0; self.len];
+        let len = self.file.read_at(&mut buf, self.offset)?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+}
+
+struct WriteAt {
+    file: Arc<File>,
+    buf: Vec<u8>,
+    offset: u64,
+}
+
+impl Blocking for WriteAt {
+    type Output = usize;
+
+    fn run(self) -> io::Result<usize> {
+        self.file.write_at(&self.buf, self.offset)
+    }
+}
+
+struct ReadSome {
+    file: Arc<File>,
+    len: usize,
+}
+
+impl Blocking for ReadSome {
+    type Output = Vec<u8>;
+
+    fn run(self) -> io::Result<Vec<u8>> {
+        let mut buf = vec![0; self.len];
+        let len = read_some(&mut &*self.file, &mut buf)?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+}
+
+struct WriteSome {
+    file: Arc<File>,
+    buf: Vec<u8>,
+}
+
+impl Blocking for WriteSome {
+    type Output = usize;
+
+    fn run(self) -> io::Result<usize> {
+        (&*self.file).write(&self.buf)
+    }
+}
+
+struct Fsync {
+    file: Arc<File>,
+    data_only: bool,
+}
+
+impl Blocking for Fsync {
+    type Output = ();
+
+    fn run(self) -> io::Result<()> {
+        if self.data_only {
+            self.file.sync_data()
+        } else {
+            self.file.sync_all()
+        }
+    }
+}
+
+/// Copies the data which was read on the blocking pool into the buffer of the caller,
+/// on the thread which runs the `IoContext`.
+struct ReadInto<F> {
+    buf: *mut u8,
+    handler: F,
+}
+
+unsafe impl<F: Send> Send for ReadInto<F> {}
+
+impl<F> Complete<Vec<u8>, io::Error> for ReadInto<F>
+where
+    F: Complete<usize, io::Error>,
+{
+    fn success(self, this: &mut ThreadIoContext, data: Vec<u8>) {
+        let buf = unsafe { slice::from_raw_parts_mut(self.buf, data.len()) };
+        buf.copy_from_slice(&data);
+        self.handler.success(this, data.len())
+    }
+
+    fn failure(self, this: &mut ThreadIoContext, err: io::Error) {
+        self.handler.failure(this, err)
+    }
+}
+
+/// Returns the file, or the duplicate of it if the operations in flight still hold it.
+fn into_std(file: Arc<File>) -> io::Result<File> {
+    Arc::try_unwrap(file).or_else(|file| file.try_clone())
+}
+
+/// Reads as the `Stream`, which reports the end of file by the `CONNECTION_ABORTED` like as the sockets.
+fn read_some(file: &mut &File, buf: &mut [u8]) -> io::Result<usize> {
+    match file.read(buf)? {
+        0 if buf.len() > 0 => Err(CONNECTION_ABORTED.into()),
+        len => Ok(len),
+    }
+}
+
+/// Provides a random-access file.
+///
+/// The regular files are always readable and writable for the reactor,
+/// so the asynchronous operations run on an internal blocking thread pool,
+/// and complete the handler on the `IoContext`.
+/// The operations hold the file and copy the data, so the file may be dropped while they are in flight.
+/// The `cancel` completes the operations in flight with the `OperationCanceled` error,
+/// and the data which they read later is discarded.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::sync::Arc;
+/// use asyncio::{IoContext, wrap};
+/// use asyncio::posix::RandomAccessFile;
+///
+/// let path = std::env::temp_dir().join(format!("asyncio-doc-raf-{}", std::process::id()));
+/// let ctx = &IoContext::new().unwrap();
+/// let file = Arc::new(RandomAccessFile::create(ctx, &path).unwrap());
+///
+/// file.async_write_at(6, b"world", wrap(&file, |file: Arc<RandomAccessFile>, res: io::Result<usize>| {
+///     assert_eq!(res.unwrap(), 5);
+///     assert_eq!(file.size().unwrap(), 11);
+/// }));
+/// ctx.run();
+/// std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct RandomAccessFile {
+    ctx: IoContext,
+    file: Arc<File>,
+    ops: BlockingOps,
+}
+
+impl RandomAccessFile {
+    /// Returns a `RandomAccessFile` which takes the ownership of the `file`.
+    pub fn new(ctx: &IoContext, file: File) -> Self {
+        RandomAccessFile {
+            ctx: ctx.clone(),
+            file: Arc::new(file),
+            ops: BlockingOps::new(),
+        }
+    }
+
+    /// Opens a file in read-only mode.
+    pub fn open<P>(ctx: &IoContext, path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Self::new(ctx, File::open(path)?))
+    }
+
+    /// Opens a file in read-write mode, and creates it if it does not exist.
+    pub fn create<P>(ctx: &IoContext, path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(path)?;
+        Ok(Self::new(ctx, file))
+    }
+
+    /// Asynchronously reads at the `offset`. The result 0 means the end of file.
+    ///
+    /// The data is copied into the `buf` when the handler is called,
+    /// so the `buf` must be valid until the operation completes.
+    pub fn async_read_at<F>(&self, offset: u64, buf: &mut [u8], handler: F) -> F::Output
+    where
+        F: Handler<usize, io::Error>,
+    {
+        let op = ReadAt {
+            file: self.file.clone(),
+            len: buf.len(),
+            offset: offset,
+        };
+        let buf = buf.as_mut_ptr();
+        handler.wrap_cancel(self, move |ctx, handler| {
+            blocking_op(
+                ctx,
+                &self.ops,
+                op,
+                ReadInto {
+                    buf: buf,
+                    handler: handler,
+                },
+            )
+        })
+    }
+
+    /// Asynchronously writes at the `offset`.
+    pub fn async_write_at<F>(&self, offset: u64, buf: &[u8], handler: F) -> F::Output
+    where
+        F: Handler<usize, io::Error>,
+    {
+        let op = WriteAt {
+            file: self.file.clone(),
+            buf: buf.to_vec(),
+            offset: offset,
+        };
+        async_blocking_op(self, &self.ops, op, handler)
+    }
+
+    /// Asynchronously flushes the data and the metadata to the disk by `fsync`.
+    pub fn async_fsync<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<(), io::Error>,
+    {
+        let op = Fsync {
+            file: self.file.clone(),
+            data_only: false,
+        };
+        async_blocking_op(self, &self.ops, op, handler)
+    }
+
+    /// Asynchronously flushes the data to the disk by `fdatasync`.
+    pub fn async_fdatasync<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<(), io::Error>,
+    {
+        let op = Fsync {
+            file: self.file.clone(),
+            data_only: true,
+        };
+        async_blocking_op(self, &self.ops, op, handler)
+    }
+
+    /// Reads at the `offset` in the caller thread. The result 0 means the end of file.
+    pub fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.file.read_at(buf, offset)
+    }
+
+    /// Writes at the `offset` in the caller thread.
+    pub fn write_at(&self, offset: u64, buf: &[u8]) -> io::Result<usize> {
+        self.file.write_at(buf, offset)
+    }
+
+    pub fn fsync(&self) -> io::Result<()> {
+        self.file.sync_all()
+    }
+
+    pub fn fdatasync(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    pub fn metadata(&self) -> io::Result<Metadata> {
+        self.file.metadata()
+    }
+
+    /// Returns the size of the file.
+    pub fn size(&self) -> io::Result<u64> {
+        Ok(self.file.metadata()?.len())
+    }
+
+    /// Truncates or extends the file.
+    pub fn set_size(&self, size: u64) -> io::Result<()> {
+        self.file.set_len(size)
+    }
+
+    /// Converts into the `std` file.
+    ///
+    /// Returns the duplicate of the file if the asynchronous operations are still in flight.
+    pub fn into_std(self) -> io::Result<File> {
+        into_std(self.file)
+    }
+}
+
+unsafe impl AsIoContext for RandomAccessFile {
+    fn as_ctx(&self) -> &IoContext {
+        &self.ctx
+    }
+}
+
+impl Cancel for RandomAccessFile {
+    fn cancel(&self) {
+        self.ops.cancel(&self.ctx)
+    }
+}
+
+impl AsRawFd for RandomAccessFile {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+/// Provides a sequential file, which reads and writes at the current file position.
+///
+/// The asynchronous operations run on an internal blocking thread pool like as the `RandomAccessFile`,
+/// so the program must ensure that the stream performs no other operation until the operation completes.
+/// The pool is shared by all files and has at most 16 threads, so the reads of the FIFOs or the terminals
+/// which block for a long time may exhaust it and delay the operations of the other files.
+/// Use the `ReadPipe` and the `WritePipe` for the FIFOs, which are driven by the reactor.
+/// The end of file is reported by the `ConnectionAborted` error like as the sockets.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::sync::Arc;
+/// use asyncio::{IoContext, Stream, StreamBuf, wrap};
+/// use asyncio::posix::StreamFile;
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let file = Arc::new(StreamFile::open(ctx, "Cargo.toml").unwrap());
+/// let mut sbuf = StreamBuf::new();
+/// file.async_read_until(&mut sbuf, "\n", wrap(&file, |_, res: io::Result<usize>| {
+///     assert_eq!(res.unwrap(), 10);
+/// }));
+/// ctx.run();
+/// assert_eq!(&sbuf.as_bytes()[..10], b"[package]\n");
+/// ```
+pub struct StreamFile {
+    ctx: IoContext,
+    file: Arc<File>,
+    ops: BlockingOps,
+}
+
+impl StreamFile {
+    /// Returns a `StreamFile` which takes the ownership of the `file`.
+    pub fn new(ctx: &IoContext, file: File) -> Self {
+        StreamFile {
+            ctx: ctx.clone(),
+            file: Arc::new(file),
+            ops: BlockingOps::new(),
+        }
+    }
+
+    /// Opens a file in read-only mode.
+    pub fn open<P>(ctx: &IoContext, path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Self::new(ctx, File::open(path)?))
+    }
+
+    /// Opens a file in write-only mode, and truncates it or creates it if it does not exist.
+    pub fn create<P>(ctx: &IoContext, path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Self::new(ctx, File::create(path)?))
+    }
+
+    /// Opens a file in append mode, and creates it if it does not exist.
+    pub fn append<P>(ctx: &IoContext, path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = OpenOptions::new().append(true).create(true).open(path)?;
+        Ok(Self::new(ctx, file))
+    }
+
+    /// Asynchronously flushes the data and the metadata to the disk by `fsync`.
+    pub fn async_fsync<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<(), io::Error>,
+    {
+        let op = Fsync {
+            file: self.file.clone(),
+            data_only: false,
+        };
+        async_blocking_op(self, &self.ops, op, handler)
+    }
+
+    /// Asynchronously flushes the data to the disk by `fdatasync`.
+    pub fn async_fdatasync<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<(), io::Error>,
+    {
+        let op = Fsync {
+            file: self.file.clone(),
+            data_only: true,
+        };
+        async_blocking_op(self, &self.ops, op, handler)
+    }
+
+    pub fn read_some(&self, buf: &mut [u8]) -> io::Result<usize> {
+        read_some(&mut &*self.file, buf)
+    }
+
+    pub fn write_some(&self, buf: &[u8]) -> io::Result<usize> {
+        (&*self.file).write(buf)
+    }
+
+    pub fn seek(&self, pos: SeekFrom) -> io::Result<u64> {
+        (&*self.file).seek(pos)
+    }
+
+    pub fn fsync(&self) -> io::Result<()> {
+        self.file.sync_all()
+    }
+
+    pub fn fdatasync(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    pub fn metadata(&self) -> io::Result<Metadata> {
+        self.file.metadata()
+    }
+
+    /// Converts into the `std` file.
+    ///
+    /// Returns the duplicate of the file if the asynchronous operations are still in flight.
+    pub fn into_std(self) -> io::Result<File> {
+        into_std(self.file)
+    }
+}
+
+unsafe impl AsIoContext for StreamFile {
+    fn as_ctx(&self) -> &IoContext {
+        &self.ctx
+    }
+}
+
+impl AsRawFd for StreamFile {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+/// The operations which are still running on the blocking pool are detached,
+/// and the handlers complete with the `OperationCanceled` error immediately.
+impl Cancel for StreamFile {
+    fn cancel(&self) {
+        self.ops.cancel(&self.ctx)
+    }
+}
+
+impl io::Read for StreamFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&*self.file).read(buf)
+    }
+}
+
+impl io::Write for StreamFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self.file).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Stream for StreamFile {
+    type Error = io::Error;
+
+    fn async_read_some<F>(&self, buf: &[u8], handler: F) -> F::Output
+    where
+        F: Handler<usize, Self::Error>,
+    {
+        let op = ReadSome {
+            file: self.file.clone(),
+            len: buf.len(),
+        };
+        let buf = buf.as_ptr() as *mut u8;
+        handler.wrap_cancel(self, move |ctx, handler| {
+            blocking_op(
+                ctx,
+                &self.ops,
+                op,
+                ReadInto {
+                    buf: buf,
+                    handler: handler,
+                },
+            )
+        })
+    }
+
+    fn async_write_some<F>(&self, buf: &[u8], handler: F) -> F::Output
+    where
+        F: Handler<usize, Self::Error>,
+    {
+        let op = WriteSome {
+            file: self.file.clone(),
+            buf: buf.to_vec(),
+        };
+        async_blocking_op(self, &self.ops, op, handler)
+    }
+
+    #[doc(hidden)]
+    fn wrap_timeout<F, G, W>(&self, handler: F, wrapper: W) -> F::Output
+    where
+        F: Handler<usize, Self::Error, WrappedHandler = G>,
+        G: Complete<usize, Self::Error>,
+        W: FnOnce(&IoContext, G),
+    {
+        handler.wrap(&self.ctx, wrapper)
+    }
+}
+
+#[cfg(test)]
+fn temp_path(name: &str) -> ::std::path::PathBuf {
+    use std::env;
+    use std::process;
+
+    env::temp_dir().join(format!("asyncio-{}-{}", name, process::id()))
+}
+
+#[test]
+fn test_random_access_file() {
+    use handler::wrap;
+    use std::fs;
+    use std::sync::Arc;
+
+    static mut BUF: [u8; 16] = [0; 16];
+
+    let path = temp_path("raf");
+    let ctx = &IoContext::new().unwrap();
+    let file = Arc::new(RandomAccessFile::create(ctx, &path).unwrap());
+    assert_eq!(file.write_at(0, b"hello world").unwrap(), 11);
+    file.async_write_at(6, b"rusty", wrap(&file, |file: Arc<RandomAccessFile>, res: io::Result<usize>| {
+        assert_eq!(res.unwrap(), 5);
+        file.async_read_at(0, unsafe { &mut BUF }, wrap(&file, |file: Arc<RandomAccessFile>, res: io::Result<usize>| {
+            assert_eq!(res.unwrap(), 11);
+            assert_eq!(unsafe { &BUF[..11] }, b"hello rusty");
+            file.async_read_at(11, unsafe { &mut BUF }, wrap(&file, |file: Arc<RandomAccessFile>, res: io::Result<usize>| {
+                assert_eq!(res.unwrap(), 0);
+                file.async_fdatasync(wrap(&file, |_, res: io::Result<()>| res.unwrap()));
+            }));
+        }));
+    }));
+    ctx.run();
+    assert_eq!(file.size().unwrap(), 11);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_random_access_file_error() {
+    use handler::wrap;
+    use std::fs;
+    use std::sync::Arc;
+
+    let path = temp_path("raf-ro");
+    fs::write(&path, b"readonly").unwrap();
+    let ctx = &IoContext::new().unwrap();
+    let file = Arc::new(RandomAccessFile::open(ctx, &path).unwrap());
+    file.async_write_at(0, b"x", wrap(&file, |_, res: io::Result<usize>| {
+        assert!(res.is_err());
+    }));
+    ctx.run();
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_stream_file() {
+    use handler::wrap;
+    use std::fs;
+    use std::sync::Arc;
+
+    static mut BUF: [u8; 16] = [0; 16];
+
+    let path = temp_path("stream-file");
+    let ctx = &IoContext::new().unwrap();
+    let file = Arc::new(StreamFile::create(ctx, &path).unwrap());
+    file.async_write_some(b"line1\nline2\n", wrap(&file, |file: Arc<StreamFile>, res: io::Result<usize>| {
+        assert_eq!(res.unwrap(), 12);
+        file.async_fsync(wrap(&file, |_, res: io::Result<()>| res.unwrap()));
+    }));
+    ctx.run();
+
+    ctx.restart();
+    let file = Arc::new(StreamFile::open(ctx, &path).unwrap());
+    file.async_read_some(unsafe { &BUF }, wrap(&file, |file: Arc<StreamFile>, res: io::Result<usize>| {
+        assert_eq!(res.unwrap(), 12);
+        assert_eq!(unsafe { &BUF[..12] }, b"line1\nline2\n");
+        file.async_read_some(unsafe { &BUF }, wrap(&file, |_, res: io::Result<usize>| {
+            assert_eq!(res.unwrap_err().kind(), io::ErrorKind::ConnectionAborted);
+        }));
+    }));
+    ctx.run();
+    assert_eq!(file.seek(SeekFrom::Start(6)).unwrap(), 6);
+    let mut buf = [0; 16];
+    assert_eq!(file.read_some(&mut buf).unwrap(), 6);
+    assert_eq!(&buf[..6], b"line2\n");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_random_access_file_drop() {
+    use handler::wrap;
+    use std::fs;
+    use SteadyTimer;
+
+    static mut BUF: [u8; 16] = [0; 16];
+
+    let path = temp_path("raf-drop");
+    fs::write(&path, b"hello world").unwrap();
+    let ctx = &IoContext::new().unwrap();
+    let owner = Arc::new(SteadyTimer::new(ctx));
+    let file = RandomAccessFile::open(ctx, &path).unwrap();
+    file.async_read_at(6, unsafe { &mut BUF }, wrap(&owner, |_, res: io::Result<usize>| {
+        assert_eq!(res.unwrap(), 5);
+        assert_eq!(unsafe { &BUF[..5] }, b"world");
+    }));
+    // the operation holds the file until it completes.
+    drop(file);
+    ctx.run();
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_stream_file_cancel() {
+    use handler::wrap;
+    use std::ffi::CString;
+    use std::fs;
+    use std::time::Duration;
+    use libc;
+    use SteadyTimer;
+
+    static mut BUF: [u8; 16] = [0; 16];
+
+    let path = temp_path("stream-file-fifo");
+    let cpath = CString::new(path.to_str().unwrap()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(cpath.as_ptr(), 0o600) }, 0);
+    let ctx = &IoContext::new().unwrap();
+    // the read of the FIFO blocks until the data is written.
+    let fifo = OpenOptions::new().read(true).write(true).open(&path).unwrap();
+    let file = Arc::new(StreamFile::new(ctx, fifo));
+    file.async_read_some(unsafe { &BUF }, wrap(&file, |_, res: io::Result<usize>| {
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::ECANCELED));
+    }));
+    let timer = Arc::new(SteadyTimer::new(ctx));
+    timer.expires_from_now(Duration::from_millis(10));
+    let cancel = file.clone();
+    timer.async_wait(wrap(&timer, move |_, res: io::Result<()>| {
+        res.unwrap();
+        cancel.cancel();
+    }));
+    ctx.run();
+
+    // completes the detached read, and the data is discarded.
+    file.write_some(b"data").unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(unsafe { &BUF[..4] }, &[0; 4]);
+}
@@ -0,0 +1,532 @@
+use ffi::{AsRawFd, RawFd, SystemError, INTERRUPTED, IN_PROGRESS, OPERATION_CANCELED,
+          WOULD_BLOCK, inotify_init, inotify_add_watch, inotify_rm_watch, read, readable};
+use reactor::SocketImpl;
+use core::{AsIoContext, IoContext, Exec, Perform, ThreadIoContext, Cancel};
+use handler::{Handler, Complete, AsyncReadOp};
+
+use std::io;
+use std::mem;
+use std::ptr;
+use std::ffi::{CString, OsStr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::os::unix::ffi::OsStrExt;
+use libc;
+
+bitflags! {
+    /// The set of events to watch, and the flags for `FileWatcher::add_watch`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct WatchMask: u32 {
+        /// File was accessed.
+        const ACCESS = libc::IN_ACCESS;
+
+        /// File was modified.
+        const MODIFY = libc::IN_MODIFY;
+
+        /// Metadata changed.
+        const ATTRIB = libc::IN_ATTRIB;
+
+        /// File opened for writing was closed.
+        const CLOSE_WRITE = libc::IN_CLOSE_WRITE;
+
+        /// File not opened for writing was closed.
+        const CLOSE_NOWRITE = libc::IN_CLOSE_NOWRITE;
+
+        /// File was opened.
+        const OPEN = libc::IN_OPEN;
+
+        /// File moved out of watched directory.
+        const MOVED_FROM = libc::IN_MOVED_FROM;
+
+        /// File moved into watched directory.
+        const MOVED_TO = libc::IN_MOVED_TO;
+
+        /// File or directory created in watched directory.
+        const CREATE = libc::IN_CREATE;
+
+        /// File or directory deleted from watched directory.
+        const DELETE = libc::IN_DELETE;
+
+        /// Watched file or directory was itself deleted.
+        const DELETE_SELF = libc::IN_DELETE_SELF;
+
+        /// Watched file or directory was itself moved.
+        const MOVE_SELF = libc::IN_MOVE_SELF;
+
+        /// Both of `CLOSE_WRITE` and `CLOSE_NOWRITE`.
+        const CLOSE = libc::IN_CLOSE;
+
+        /// Both of `MOVED_FROM` and `MOVED_TO`.
+        const MOVE = libc::IN_MOVE;
+
+        /// All of the above events.
+        const ALL_EVENTS = libc::IN_ALL_EVENTS;
+
+        /// Only watch the path if it is a directory.
+        const ONLYDIR = libc::IN_ONLYDIR;
+
+        /// Don't dereference the path if it is a symbolic link.
+        const DONT_FOLLOW = libc::IN_DONT_FOLLOW;
+
+        /// Don't generate events for children after they have been unlinked.
+        const EXCL_UNLINK = libc::IN_EXCL_UNLINK;
+
+        /// Add the events to the existing watch instead of replacing it.
+        const MASK_ADD = libc::IN_MASK_ADD;
+
+        /// Remove the watch after one event.
+        const ONESHOT = libc::IN_ONESHOT;
+    }
+}
+
+/// The identifier of a watch, which is returned by `FileWatcher::add_watch`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WatchDescriptor(i32);
+
+/// The kind of a `FileEvent`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileEventKind {
+    Access,
+    Modify,
+    Attrib,
+    CloseWrite,
+    CloseNoWrite,
+    Open,
+    Create,
+    Delete,
+    DeleteSelf,
+    MoveSelf,
+
+    /// A file was moved between the watched directories.
+    ///
+    /// The event has the destination, and this has the source.
+    Move {
+        from: WatchDescriptor,
+        from_name: Option<PathBuf>,
+    },
+
+    /// A file was moved out of the watched directory, which did not pair with a `MovedTo`.
+    MovedFrom { cookie: u32 },
+
+    /// A file was moved into the watched directory, which did not pair with a `MovedFrom`.
+    MovedTo { cookie: u32 },
+
+    /// The file system containing the watched object was unmounted.
+    Unmount,
+
+    /// The watch was removed.
+    Ignored,
+
+    /// The event queue overflowed, and some events were lost.
+    Overflow,
+}
+
+/// A file system event which was read from the `FileWatcher`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileEvent {
+    /// The watch of this event. It is meaningless for `FileEventKind::Overflow`.
+    pub wd: WatchDescriptor,
+
+    pub kind: FileEventKind,
+
+    /// The name of the file relative to the watched directory, if the event is for a child.
+    pub name: Option<PathBuf>,
+
+    /// True if the subject of this event is a directory.
+    pub is_dir: bool,
+}
+
+fn event_kind(mask: u32, cookie: u32) -> FileEventKind {
+    if mask & libc::IN_Q_OVERFLOW != 0 {
+        FileEventKind::Overflow
+    } else if mask & libc::IN_ACCESS != 0 {
+        FileEventKind::Access
+    } else if mask & libc::IN_MODIFY != 0 {
+        FileEventKind::Modify
+    } else if mask & libc::IN_ATTRIB != 0 {
+        FileEventKind::Attrib
+    } else if mask & libc::IN_CLOSE_WRITE != 0 {
+        FileEventKind::CloseWrite
+    } else if mask & libc::IN_CLOSE_NOWRITE != 0 {
+        FileEventKind::CloseNoWrite
+    } else if mask & libc::IN_OPEN != 0 {
+        FileEventKind::Open
+    } else if mask & libc::IN_CREATE != 0 {
+        FileEventKind::Create
+    } else if mask & libc::IN_DELETE != 0 {
+        FileEventKind::Delete
+    } else if mask & libc::IN_DELETE_SELF != 0 {
+        FileEventKind::DeleteSelf
+    } else if mask & libc::IN_MOVE_SELF != 0 {
+        FileEventKind::MoveSelf
+    } else if mask & libc::IN_MOVED_FROM != 0 {
+        FileEventKind::MovedFrom { cookie: cookie }
+    } else if mask & libc::IN_MOVED_TO != 0 {
+        FileEventKind::MovedTo { cookie: cookie }
+    } else if mask & libc::IN_UNMOUNT != 0 {
+        FileEventKind::Unmount
+    } else {
+        FileEventKind::Ignored
+    }
+}
+
+/// Decodes the `inotify_event` records, and pairs the `MovedFrom` and `MovedTo` by the cookie.
+fn decode_events(buf: &[u8]) -> Vec<FileEvent> {
+    let mut events = Vec::new();
+    let mut moved_from: Vec<(u32, usize)> = Vec::new();
+    let mut pos = 0;
+    while pos + mem::size_of::<libc::inotify_event>() <= buf.len() {
+        let ev: libc::inotify_event =
+            unsafe { ptr::read_unaligned(buf[pos..].as_ptr() as *const libc::inotify_event) };
+        pos += mem::size_of::<libc::inotify_event>();
+        let name = &buf[pos..(pos + ev.len as usize).min(buf.len())];
+        pos += ev.len as usize;
+        let name = match name.iter().position(|&ch| ch == 0).unwrap_or(name.len()) {
+            0 => None,
+            len => Some(PathBuf::from(OsStr::from_bytes(&name[..len]))),
+        };
+        let event = FileEvent {
+            wd: WatchDescriptor(ev.wd),
+            kind: event_kind(ev.mask, ev.cookie),
+            name: name,
+            is_dir: ev.mask & libc::IN_ISDIR != 0,
+        };
+        match event.kind {
+            FileEventKind::MovedFrom { cookie } => moved_from.push((cookie, events.len())),
+            FileEventKind::MovedTo { cookie } => {
+                if let Some(i) = moved_from.iter().position(|&(c, _)| c == cookie) {
+                    let (_, idx) = moved_from.remove(i);
+                    let from = mem::replace(&mut events[idx], event);
+                    events[idx].kind = FileEventKind::Move {
+                        from: from.wd,
+                        from_name: from.name,
+                    };
+                    continue;
+                }
+            }
+            _ => (),
+        }
+        events.push(event);
+    }
+    events
+}
+
+struct ReadEvents<F> {
+    watcher: *const FileWatcher,
+    handler: F,
+}
+
+unsafe impl<F> Send for ReadEvents<F> {}
+
+impl<F> Exec for ReadEvents<F>
+where
+    F: Complete<Vec<FileEvent>, io::Error>,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        let watcher = unsafe { &*self.watcher };
+        watcher.add_read_op(this, Box::new(self), SystemError::default())
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        let watcher = unsafe { &*self.watcher };
+        watcher.add_read_op(this, self, SystemError::default())
+    }
+}
+
+impl<F> Complete<Vec<FileEvent>, io::Error> for ReadEvents<F>
+where
+    F: Complete<Vec<FileEvent>, io::Error>,
+{
+    fn success(self, this: &mut ThreadIoContext, res: Vec<FileEvent>) {
+        let watcher = unsafe { &*self.watcher };
+        watcher.next_read_op(this);
+        self.handler.success(this, res)
+    }
+
+    fn failure(self, this: &mut ThreadIoContext, err: io::Error) {
+        let watcher = unsafe { &*self.watcher };
+        watcher.next_read_op(this);
+        self.handler.failure(this, err)
+    }
+}
+
+impl<F> Perform for ReadEvents<F>
+where
+    F: Complete<Vec<FileEvent>, io::Error>,
+{
+    fn perform(self: Box<Self>, this: &mut ThreadIoContext, err: SystemError) {
+        if err == SystemError::default() {
+            let watcher = unsafe { &*self.watcher };
+            let mut buf = [0; BUFFER_SIZE];
+            while !this.as_ctx().stopped() {
+                match read(watcher, &mut buf) {
+                    Ok(len) => return self.success(this, decode_events(&buf[..len])),
+                    Err(IN_PROGRESS) | Err(WOULD_BLOCK) => {
+                        return watcher.add_read_op(this, self, WOULD_BLOCK)
+                    }
+                    Err(INTERRUPTED) => (),
+                    Err(err) => return self.failure(this, err.into()),
+                }
+            }
+            self.failure(this, OPERATION_CANCELED.into())
+        } else {
+            self.failure(this, err.into())
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
+}
+
+/// Enough to read at least one event with the longest name.
+const BUFFER_SIZE: usize = 4096 + libc::PATH_MAX as usize;
+
+/// Watches the file system events by the inotify.
+///
+/// # Examples
+///
+/// ```
+/// use std::fs::{self, File};
+/// use asyncio::IoContext;
+/// use asyncio::posix::{FileWatcher, WatchMask, FileEventKind};
+///
+/// let dir = std::env::temp_dir().join(format!("asyncio-doc-watch-{}", std::process::id()));
+/// fs::create_dir_all(&dir).unwrap();
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let watcher = FileWatcher::new(ctx).unwrap();
+/// watcher.add_watch(&dir, WatchMask::CREATE).unwrap();
+///
+/// File::create(dir.join("a.txt")).unwrap();
+/// let events = watcher.read_events().unwrap();
+/// assert_eq!(events[0].kind, FileEventKind::Create);
+/// assert_eq!(events[0].name.as_ref().unwrap().to_str(), Some("a.txt"));
+/// fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub struct FileWatcher {
+    pimpl: Box<SocketImpl<()>>,
+}
+
+impl FileWatcher {
+    pub fn new(ctx: &IoContext) -> io::Result<Self> {
+        let fd = inotify_init()?;
+        Ok(FileWatcher { pimpl: SocketImpl::new(ctx, fd, ()) })
+    }
+
+    /// Adds a new watch, or modifies an existing watch of the same path.
+    pub fn add_watch<P>(&self, path: P, mask: WatchMask) -> io::Result<WatchDescriptor>
+    where
+        P: AsRef<Path>,
+    {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())?;
+        Ok(WatchDescriptor(inotify_add_watch(self, &path, mask.bits())?))
+    }
+
+    /// Removes a watch. Then a `FileEventKind::Ignored` event is generated for the watch.
+    pub fn remove_watch(&self, wd: WatchDescriptor) -> io::Result<()> {
+        Ok(inotify_rm_watch(self, wd.0)?)
+    }
+
+    /// Asynchronously reads the events.
+    ///
+    /// The `MovedFrom` and `MovedTo` events which were read at the same time are paired into a `Move`.
+    pub fn async_read_events<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<Vec<FileEvent>, io::Error>,
+    {
+        handler.wrap_cancel(self, |ctx, handler| {
+            ctx.do_dispatch(ReadEvents {
+                watcher: self,
+                handler: handler,
+            })
+        })
+    }
+
+    /// Blocks until the events are available, and reads them.
+    pub fn read_events(&self) -> io::Result<Vec<FileEvent>> {
+        if self.as_ctx().stopped() {
+            return Err(OPERATION_CANCELED.into());
+        }
+        let mut buf = [0; BUFFER_SIZE];
+        loop {
+            match read(self, &mut buf) {
+                Ok(len) => return Ok(decode_events(&buf[..len])),
+                Err(WOULD_BLOCK) => {
+                    if let Err(err) = readable(self, &self.pimpl.timeout) {
+                        return Err(err.into());
+                    }
+                }
+                Err(INTERRUPTED) if !self.as_ctx().stopped() => (),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.pimpl.timeout.get()
+    }
+
+    pub fn set_timeout(&self, timeout: Duration) -> io::Result<()> {
+        Ok(self.pimpl.timeout.set(timeout)?)
+    }
+}
+
+unsafe impl Send for FileWatcher {}
+
+unsafe impl Sync for FileWatcher {}
+
+unsafe impl AsIoContext for FileWatcher {
+    fn as_ctx(&self) -> &IoContext {
+        self.pimpl.as_ctx()
+    }
+}
+
+impl AsRawFd for FileWatcher {
+    fn as_raw_fd(&self) -> RawFd {
+        self.pimpl.as_raw_fd()
+    }
+}
+
+impl Cancel for FileWatcher {
+    fn cancel(&self) {
+        self.pimpl.cancel()
+    }
+
+    fn cancel_expired(&self) {
+        self.pimpl.cancel_expired()
+    }
+}
+
+impl AsyncReadOp for FileWatcher {
+    fn add_read_op(&self, this: &mut ThreadIoContext, op: Box<Perform>, err: SystemError) {
+        self.pimpl.add_read_op(this, op, err)
+    }
+
+    fn next_read_op(&self, this: &mut ThreadIoContext) {
+        self.pimpl.next_read_op(this)
+    }
+}
+
+#[cfg(test)]
+fn temp_dir(name: &str) -> PathBuf {
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    let dir = env::temp_dir().join(format!("asyncio-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_decode_events() {
+    fn record(buf: &mut Vec<u8>, wd: i32, mask: u32, cookie: u32, name: &str) {
+        let len = if name.is_empty() { 0 } else { (name.len() + 16) & !15 };
+        let ev = libc::inotify_event {
+            wd: wd,
+            mask: mask,
+            cookie: cookie,
+            len: len as u32,
+        };
+        let bytes: [u8; 16] = unsafe { mem::transmute(ev) };
+        buf.extend_from_slice(&bytes);
+        buf.extend_from_slice(name.as_bytes());
+        buf.resize(buf.len() + len - name.len(), 0);
+    }
+
+    let mut buf = Vec::new();
+    record(&mut buf, 1, libc::IN_MOVED_FROM, 7, "a");
+    record(&mut buf, 1, libc::IN_CREATE | libc::IN_ISDIR, 0, "dir");
+    record(&mut buf, 2, libc::IN_MOVED_TO, 7, "b");
+    record(&mut buf, 2, libc::IN_MOVED_TO, 8, "c");
+    record(&mut buf, -1, libc::IN_Q_OVERFLOW, 0, "");
+    let events = decode_events(&buf);
+    assert_eq!(events.len(), 4);
+    assert_eq!(
+        events[0],
+        FileEvent {
+            wd: WatchDescriptor(2),
+            kind: FileEventKind::Move {
+                from: WatchDescriptor(1),
+                from_name: Some(PathBuf::from("a")),
+            },
+            name: Some(PathBuf::from("b")),
+            is_dir: false,
+        }
+    );
+    assert_eq!(events[1].kind, FileEventKind::Create);
+    assert!(events[1].is_dir);
+    assert_eq!(events[2].kind, FileEventKind::MovedTo { cookie: 8 });
+    assert_eq!(events[3].kind, FileEventKind::Overflow);
+    assert_eq!(events[3].name, None);
+}
+
+#[test]
+fn test_file_watcher() {
+    use std::fs::{self, File};
+    use std::io::Write;
+
+    let dir = temp_dir("watch");
+    let ctx = &IoContext::new().unwrap();
+    let watcher = FileWatcher::new(ctx).unwrap();
+    let wd = watcher
+        .add_watch(
+            &dir,
+            WatchMask::CREATE | WatchMask::MODIFY | WatchMask::MOVE | WatchMask::DELETE,
+        )
+        .unwrap();
+
+    File::create(dir.join("a")).unwrap().write_all(b"hello").unwrap();
+    fs::rename(dir.join("a"), dir.join("b")).unwrap();
+    fs::remove_file(dir.join("b")).unwrap();
+
+    let mut kinds = Vec::new();
+    while kinds.len() < 4 {
+        for ev in watcher.read_events().unwrap() {
+            assert_eq!(ev.wd, wd);
+            kinds.push((ev.kind, ev.name.unwrap()));
+        }
+    }
+    assert_eq!(
+        kinds,
+        vec![
+            (FileEventKind::Create, PathBuf::from("a")),
+            (FileEventKind::Modify, PathBuf::from("a")),
+            (
+                FileEventKind::Move {
+                    from: wd,
+                    from_name: Some(PathBuf::from("a")),
+                },
+                PathBuf::from("b")
+            ),
+            (FileEventKind::Delete, PathBuf::from("b")),
+        ]
+    );
+
+    watcher.remove_watch(wd).unwrap();
+    assert_eq!(watcher.read_events().unwrap()[0].kind, FileEventKind::Ignored);
+    assert!(watcher.remove_watch(wd).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_file_watcher_async() {
+    use handler::wrap;
+    use std::fs::{self, File};
+    use std::sync::Arc;
+
+    let dir = temp_dir("watch-async");
+    let ctx = &IoContext::new().unwrap();
+    let watcher = Arc::new(FileWatcher::new(ctx).unwrap());
+    watcher.add_watch(&dir, WatchMask::CREATE).unwrap();
+    watcher.async_read_events(wrap(&watcher, |_, res: io::Result<Vec<FileEvent>>| {
+        let events = res.unwrap();
+        assert_eq!(events[0].kind, FileEventKind::Create);
+        assert_eq!(events[0].name, Some(PathBuf::from("x")));
+    }));
+    File::create(dir.join("x")).unwrap();
+    ctx.run();
+    fs::remove_dir_all(&dir).unwrap();
+}
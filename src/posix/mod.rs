@@ -9,6 +9,26 @@ use stream::Stream;
 use std::io;
 use std::time::Duration;
 
+mod pipe;
+pub use self::pipe::{pipe, ReadPipe, WritePipe};
+#[cfg(target_os = "linux")]
+pub use self::pipe::PipeCapacity;
+
+mod blocking_pool;
+
+mod file;
+pub use self::file::{RandomAccessFile, StreamFile};
+
+#[cfg(target_os = "linux")]
+mod file_watcher;
+#[cfg(target_os = "linux")]
+pub use self::file_watcher::{FileWatcher, WatchMask, WatchDescriptor, FileEvent, FileEventKind};
+
+#[cfg(target_os = "linux")]
+mod event_notifier;
+#[cfg(target_os = "linux")]
+pub use self::event_notifier::EventNotifier;
+
 /// Typedef for the typical usage of a stream-oriented descriptor.
 pub struct StreamDescriptor {
     pimpl: Box<SocketImpl<()>>,
@@ -67,6 +87,10 @@ impl Cancel for StreamDescriptor {
     fn cancel(&self) {
         self.pimpl.cancel()
     }
+
+    fn cancel_expired(&self) {
+        self.pimpl.cancel_expired()
+    }
 }
 
 impl AsyncReadOp for StreamDescriptor {
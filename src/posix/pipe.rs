@@ -0,0 +1,469 @@
+use ffi::{AsRawFd, RawFd, SystemError, BAD_DESCRIPTOR, ioctl, dup, pipe as pipe2};
+use reactor::SocketImpl;
+use core::{AsIoContext, IoContext, Perform, ThreadIoContext, Cancel};
+use handler::{Handler, AsyncReadOp, AsyncWriteOp, Complete, Failure};
+use read_ops::{Read, async_read_op, blocking_read_op, nonblocking_read_op};
+use write_ops::{Write, async_write_op, blocking_write_op, nonblocking_write_op};
+use socket_base::NonBlockingIo;
+use stream::Stream;
+
+use std::io;
+use std::time::Duration;
+use std::os::unix::io::{FromRawFd, IntoRawFd};
+
+fn from_std<T>(ctx: &IoContext, io: T) -> io::Result<Box<SocketImpl<()>>>
+where
+    T: IntoRawFd,
+{
+    let pimpl = SocketImpl::new(ctx, io.into_raw_fd(), ());
+    ioctl(&*pimpl, &mut NonBlockingIo::new(true))?;
+    Ok(pimpl)
+}
+
+fn into_std<T>(pimpl: Box<SocketImpl<()>>) -> io::Result<T>
+where
+    T: FromRawFd,
+{
+    ioctl(&*pimpl, &mut NonBlockingIo::new(false))?;
+    let fd = dup(pimpl.as_raw_fd())?;
+    Ok(unsafe { T::from_raw_fd(fd) })
+}
+
+/// Returns a connected pair of the read end and the write end of a anonymous pipe.
+///
+/// The both ends are non-blocking and close-on-exec.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::IoContext;
+/// use asyncio::posix::pipe;
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let (rx, tx) = pipe(ctx).unwrap();
+/// assert_eq!(tx.write_some(b"hello").unwrap(), 5);
+///
+/// let mut buf = [0; 16];
+/// assert_eq!(rx.read_some(&mut buf).unwrap(), 5);
+/// assert_eq!(&buf[..5], b"hello");
+/// ```
+pub fn pipe(ctx: &IoContext) -> io::Result<(ReadPipe, WritePipe)> {
+    let (rfd, wfd) = pipe2()?;
+    Ok((
+        ReadPipe { pimpl: SocketImpl::new(ctx, rfd, ()) },
+        WritePipe { pimpl: SocketImpl::new(ctx, wfd, ()) },
+    ))
+}
+
+/// Provides the capacity of a pipe, which is shared by the both ends.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::IoContext;
+/// use asyncio::posix::{pipe, PipeCapacity};
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let (rx, tx) = pipe(ctx).unwrap();
+/// let size = tx.set_capacity(65536).unwrap();
+/// assert_eq!(rx.capacity().unwrap(), size);
+/// ```
+#[cfg(target_os = "linux")]
+pub trait PipeCapacity: AsRawFd {
+    /// Returns the capacity of the pipe.
+    fn capacity(&self) -> io::Result<usize> {
+        use ffi::pipe_size;
+
+        Ok(pipe_size(self.as_raw_fd())?)
+    }
+
+    /// Sets the capacity of the pipe, and returns the actual capacity rounded up by the kernel.
+    fn set_capacity(&self, size: usize) -> io::Result<usize> {
+        use ffi::set_pipe_size;
+
+        Ok(set_pipe_size(self.as_raw_fd(), size)?)
+    }
+}
+
+/// The read end of a pipe.
+pub struct ReadPipe {
+    pimpl: Box<SocketImpl<()>>,
+}
+
+impl ReadPipe {
+    /// Returns a `ReadPipe` which takes the ownership of the file descriptor.
+    ///
+    /// The `io` is such as a `ChildStdout`, a `ChildStderr` or a `File` opened from a FIFO,
+    /// and it is set to the non-blocking mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::process::{Command, Stdio};
+    /// use asyncio::IoContext;
+    /// use asyncio::posix::ReadPipe;
+    ///
+    /// let ctx = &IoContext::new().unwrap();
+    /// let mut child = Command::new("echo").arg("hello").stdout(Stdio::piped()).spawn().unwrap();
+    /// let rx = ReadPipe::from_std(ctx, child.stdout.take().unwrap()).unwrap();
+    ///
+    /// let mut buf = [0; 16];
+    /// assert_eq!(rx.read_some(&mut buf).unwrap(), 6);
+    /// child.wait().unwrap();
+    /// ```
+    pub fn from_std<T>(ctx: &IoContext, io: T) -> io::Result<Self>
+    where
+        T: IntoRawFd,
+    {
+        Ok(ReadPipe { pimpl: from_std(ctx, io)? })
+    }
+
+    /// Returns a `ReadPipe` which takes the ownership of the file descriptor.
+    ///
+    /// # Safety
+    ///
+    /// The `fd` must be a open read end of a pipe or a FIFO in the non-blocking mode,
+    /// and it must not be owned by the others since it is closed when the `ReadPipe` is dropped.
+    pub unsafe fn from_raw_fd(ctx: &IoContext, fd: RawFd) -> Self {
+        ReadPipe { pimpl: SocketImpl::new(ctx, fd, ()) }
+    }
+
+    /// Converts into a blocking `std` type, such as a `File` or a `Stdio`.
+    pub fn into_std<T>(self) -> io::Result<T>
+    where
+        T: FromRawFd,
+    {
+        into_std(self.pimpl)
+    }
+
+    pub fn nonblocking_read_some(&self, buf: &mut [u8]) -> io::Result<usize> {
+        nonblocking_read_op(self, buf, Read::new())
+    }
+
+    pub fn read_some(&self, buf: &mut [u8]) -> io::Result<usize> {
+        blocking_read_op(self, buf, &self.pimpl.timeout, Read::new())
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.pimpl.timeout.get()
+    }
+
+    pub fn set_timeout(&self, timeout: Duration) -> io::Result<()> {
+        Ok(self.pimpl.timeout.set(timeout)?)
+    }
+}
+
+unsafe impl AsIoContext for ReadPipe {
+    fn as_ctx(&self) -> &IoContext {
+        self.pimpl.as_ctx()
+    }
+}
+
+impl AsRawFd for ReadPipe {
+    fn as_raw_fd(&self) -> RawFd {
+        self.pimpl.as_raw_fd()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl PipeCapacity for ReadPipe {}
+
+impl Cancel for ReadPipe {
+    fn cancel(&self) {
+        self.pimpl.cancel()
+    }
+
+    fn cancel_expired(&self) {
+        self.pimpl.cancel_expired()
+    }
+}
+
+impl AsyncReadOp for ReadPipe {
+    fn add_read_op(&self, this: &mut ThreadIoContext, op: Box<Perform>, err: SystemError) {
+        self.pimpl.add_read_op(this, op, err)
+    }
+
+    fn next_read_op(&self, this: &mut ThreadIoContext) {
+        self.pimpl.next_read_op(this)
+    }
+}
+
+impl io::Read for ReadPipe {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_some(buf)
+    }
+}
+
+unsafe impl Send for ReadPipe {}
+
+unsafe impl Sync for ReadPipe {}
+
+impl Stream for ReadPipe {
+    type Error = io::Error;
+
+    fn async_read_some<F>(&self, buf: &[u8], handler: F) -> F::Output
+    where
+        F: Handler<usize, Self::Error>,
+    {
+        async_read_op(self, buf, &self.pimpl.timeout, handler, Read::new())
+    }
+
+    /// Always fails with the bad descriptor error, since the read end is not writable.
+    fn async_write_some<F>(&self, _: &[u8], handler: F) -> F::Output
+    where
+        F: Handler<usize, Self::Error>,
+    {
+        handler.wrap(self.as_ctx(), |ctx, handler| {
+            ctx.do_dispatch(Failure::new(BAD_DESCRIPTOR, handler))
+        })
+    }
+
+    #[doc(hidden)]
+    fn wrap_timeout<F, G, W>(&self, handler: F, wrapper: W) -> F::Output
+    where
+        F: Handler<usize, Self::Error, WrappedHandler = G>,
+        G: Complete<usize, Self::Error>,
+        W: FnOnce(&IoContext, G),
+    {
+        handler.wrap_timeout(self, &self.pimpl.timeout, wrapper)
+    }
+}
+
+/// The write end of a pipe.
+pub struct WritePipe {
+    pimpl: Box<SocketImpl<()>>,
+}
+
+impl WritePipe {
+    /// Returns a `WritePipe` which takes the ownership of the file descriptor.
+    ///
+    /// The `io` is such as a `ChildStdin` or a `File` opened from a FIFO,
+    /// and it is set to the non-blocking mode.
+    pub fn from_std<T>(ctx: &IoContext, io: T) -> io::Result<Self>
+    where
+        T: IntoRawFd,
+    {
+        Ok(WritePipe { pimpl: from_std(ctx, io)? })
+    }
+
+    /// Returns a `WritePipe` which takes the ownership of the file descriptor.
+    ///
+    /// # Safety
+    ///
+    /// The `fd` must be a open write end of a pipe or a FIFO in the non-blocking mode,
+    /// and it must not be owned by the others since it is closed when the `WritePipe` is dropped.
+    pub unsafe fn from_raw_fd(ctx: &IoContext, fd: RawFd) -> Self {
+        WritePipe { pimpl: SocketImpl::new(ctx, fd, ()) }
+    }
+
+    /// Converts into a blocking `std` type, such as a `File` or a `Stdio`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::process::{Command, Stdio};
+    /// use asyncio::IoContext;
+    /// use asyncio::posix::pipe;
+    ///
+    /// let ctx = &IoContext::new().unwrap();
+    /// let (rx, tx) = pipe(ctx).unwrap();
+    /// let mut child = Command::new("echo").arg("hello").stdout(tx.into_std::<Stdio>().unwrap()).spawn().unwrap();
+    /// child.wait().unwrap();
+    ///
+    /// let mut buf = [0; 16];
+    /// assert_eq!(rx.read_some(&mut buf).unwrap(), 6);
+    /// ```
+    pub fn into_std<T>(self) -> io::Result<T>
+    where
+        T: FromRawFd,
+    {
+        into_std(self.pimpl)
+    }
+
+    pub fn nonblocking_write_some(&self, buf: &[u8]) -> io::Result<usize> {
+        nonblocking_write_op(self, buf, Write::new())
+    }
+
+    pub fn write_some(&self, buf: &[u8]) -> io::Result<usize> {
+        blocking_write_op(self, buf, &self.pimpl.timeout, Write::new())
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.pimpl.timeout.get()
+    }
+
+    pub fn set_timeout(&self, timeout: Duration) -> io::Result<()> {
+        Ok(self.pimpl.timeout.set(timeout)?)
+    }
+}
+
+unsafe impl AsIoContext for WritePipe {
+    fn as_ctx(&self) -> &IoContext {
+        self.pimpl.as_ctx()
+    }
+}
+
+impl AsRawFd for WritePipe {
+    fn as_raw_fd(&self) -> RawFd {
+        self.pimpl.as_raw_fd()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl PipeCapacity for WritePipe {}
+
+impl Cancel for WritePipe {
+    fn cancel(&self) {
+        self.pimpl.cancel()
+    }
+
+    fn cancel_expired(&self) {
+        self.pimpl.cancel_expired()
+    }
+}
+
+impl AsyncWriteOp for WritePipe {
+    fn add_write_op(&self, this: &mut ThreadIoContext, op: Box<Perform>, err: SystemError) {
+        self.pimpl.add_write_op(this, op, err)
+    }
+
+    fn next_write_op(&self, this: &mut ThreadIoContext) {
+        self.pimpl.next_write_op(this)
+    }
+}
+
+impl io::Write for WritePipe {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_some(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+unsafe impl Send for WritePipe {}
+
+unsafe impl Sync for WritePipe {}
+
+impl Stream for WritePipe {
+    type Error = io::Error;
+
+    /// Always fails with the bad descriptor error, since the write end is not readable.
+    fn async_read_some<F>(&self, _: &[u8], handler: F) -> F::Output
+    where
+        F: Handler<usize, Self::Error>,
+    {
+        handler.wrap(self.as_ctx(), |ctx, handler| {
+            ctx.do_dispatch(Failure::new(BAD_DESCRIPTOR, handler))
+        })
+    }
+
+    fn async_write_some<F>(&self, buf: &[u8], handler: F) -> F::Output
+    where
+        F: Handler<usize, Self::Error>,
+    {
+        async_write_op(self, buf, &self.pimpl.timeout, handler, Write::new())
+    }
+
+    #[doc(hidden)]
+    fn wrap_timeout<F, G, W>(&self, handler: F, wrapper: W) -> F::Output
+    where
+        F: Handler<usize, Self::Error, WrappedHandler = G>,
+        G: Complete<usize, Self::Error>,
+        W: FnOnce(&IoContext, G),
+    {
+        handler.wrap_timeout(self, &self.pimpl.timeout, wrapper)
+    }
+}
+
+#[test]
+fn test_pipe_async() {
+    use handler::wrap;
+    use std::sync::Arc;
+
+    let ctx = &IoContext::new().unwrap();
+    let (rx, tx) = pipe(ctx).unwrap();
+    let rx = Arc::new(rx);
+    let tx = Arc::new(tx);
+    static mut BUF: [u8; 16] = [0; 16];
+    rx.async_read_some(unsafe { &BUF }, wrap(&rx, |_, res: io::Result<usize>| {
+        assert_eq!(res.unwrap(), 5);
+        assert_eq!(unsafe { &BUF[..5] }, b"hello");
+    }));
+    tx.async_write_some(b"hello", wrap(&tx, |_, res: io::Result<usize>| {
+        assert_eq!(res.unwrap(), 5);
+    }));
+    tx.async_read_some(unsafe { &BUF }, wrap(&tx, |_, res: io::Result<usize>| {
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(::libc::EBADF));
+    }));
+    ctx.run();
+}
+
+#[test]
+fn test_pipe_eof() {
+    let ctx = &IoContext::new().unwrap();
+    let (rx, tx) = pipe(ctx).unwrap();
+    assert_eq!(
+        rx.nonblocking_read_some(&mut [0; 16]).unwrap_err().kind(),
+        io::ErrorKind::WouldBlock
+    );
+    drop(tx);
+    assert_eq!(
+        rx.read_some(&mut [0; 16]).unwrap_err().kind(),
+        io::ErrorKind::ConnectionAborted
+    );
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_pipe_capacity() {
+    let ctx = &IoContext::new().unwrap();
+    let (rx, tx) = pipe(ctx).unwrap();
+    assert_eq!(tx.set_capacity(4096).unwrap(), 4096);
+    assert_eq!(rx.capacity().unwrap(), 4096);
+    let buf = [0; 8192];
+    assert_eq!(tx.nonblocking_write_some(&buf).unwrap(), 4096);
+    assert_eq!(
+        tx.nonblocking_write_some(&buf).unwrap_err().kind(),
+        io::ErrorKind::WouldBlock
+    );
+}
+
+#[test]
+fn test_pipe_fifo() {
+    use std::env;
+    use std::ffi::CString;
+    use std::fs::{self, File, OpenOptions};
+    use std::io::Write as IoWrite;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let path = env::temp_dir().join(format!("asyncio-fifo-{}", ::std::process::id()));
+    let cpath = CString::new(path.as_os_str().as_bytes()).unwrap();
+    assert_eq!(unsafe { ::libc::mkfifo(cpath.as_ptr(), 0o600) }, 0);
+
+    let ctx = &IoContext::new().unwrap();
+    let file = OpenOptions::new()
+        .read(true)
+        .custom_flags(::libc::O_NONBLOCK)
+        .open(&path)
+        .unwrap();
+    let rx = ReadPipe::from_std(ctx, file).unwrap();
+    let tx = WritePipe::from_std(ctx, OpenOptions::new().write(true).open(&path).unwrap()).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(tx.write_some(b"fifo").unwrap(), 4);
+    let mut buf = [0; 16];
+    assert_eq!(rx.read_some(&mut buf).unwrap(), 4);
+    assert_eq!(&buf[..4], b"fifo");
+
+    let mut file: File = tx.into_std().unwrap();
+    file.write_all(b"std").unwrap();
+    drop(file);
+    assert_eq!(rx.read_some(&mut buf).unwrap(), 3);
+    assert_eq!(
+        rx.read_some(&mut buf).unwrap_err().kind(),
+        io::ErrorKind::ConnectionAborted
+    );
+}
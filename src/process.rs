@@ -0,0 +1,776 @@
+use ffi::{SystemError, Signal, Timeout, INTERRUPTED, INVALID_ARGUMENT, OPERATION_CANCELED,
+          WOULD_BLOCK, kill, waitpid};
+use reactor::SocketImpl;
+use core::{AsIoContext, IoContext, Exec, Perform, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+use posix::StreamDescriptor;
+use signal_set::SignalSet;
+use socket_base::NonBlockingIo;
+
+use std::io;
+use std::mem;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::sync::{Arc, Mutex};
+use std::os::unix::io::IntoRawFd;
+use std::os::unix::process::ExitStatusExt;
+use libc::pid_t;
+
+pub use std::process::{ExitStatus, Stdio};
+
+fn into_descriptor<T>(ctx: &IoContext, io: Option<T>) -> io::Result<Option<StreamDescriptor>>
+where
+    T: IntoRawFd,
+{
+    match io {
+        Some(io) => {
+            let fd = io.into_raw_fd();
+            let desc = unsafe { StreamDescriptor::from_raw_fd(ctx, fd) };
+            desc.io_control(&mut NonBlockingIo::new(true))?;
+            Ok(Some(desc))
+        }
+        None => Ok(None),
+    }
+}
+
+/// A process builder, which spawns the child process on the `IoContext`.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::IoContext;
+/// use asyncio::process::{Command, Stdio};
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let child = Command::new("echo").arg("hello").stdout(Stdio::piped()).spawn(ctx).unwrap();
+///
+/// let mut buf = [0; 16];
+/// let len = child.stdout.as_ref().unwrap().read_some(&mut buf).unwrap();
+/// assert_eq!(&buf[..len], b"hello\n");
+/// assert!(child.wait().unwrap().success());
+/// ```
+pub struct Command {
+    cmd: process::Command,
+}
+
+impl Command {
+    /// Returns a new `Command` for launching the `program`.
+    pub fn new<S>(program: S) -> Command
+    where
+        S: AsRef<OsStr>,
+    {
+        Command { cmd: process::Command::new(program) }
+    }
+
+    /// Adds an argument to pass to the program.
+    pub fn arg<S>(&mut self, arg: S) -> &mut Command
+    where
+        S: AsRef<OsStr>,
+    {
+        self.cmd.arg(arg);
+        self
+    }
+
+    /// Adds multiple arguments to pass to the program.
+    pub fn args<I, S>(&mut self, args: I) -> &mut Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.cmd.args(args);
+        self
+    }
+
+    /// Inserts or updates an environment variable.
+    pub fn env<K, V>(&mut self, key: K, val: V) -> &mut Command
+    where
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.cmd.env(key, val);
+        self
+    }
+
+    /// Inserts or updates multiple environment variables.
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Command
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.cmd.envs(vars);
+        self
+    }
+
+    /// Removes an environment variable.
+    pub fn env_remove<K>(&mut self, key: K) -> &mut Command
+    where
+        K: AsRef<OsStr>,
+    {
+        self.cmd.env_remove(key);
+        self
+    }
+
+    /// Clears the entire environment for the child process.
+    pub fn env_clear(&mut self) -> &mut Command {
+        self.cmd.env_clear();
+        self
+    }
+
+    /// Sets the working directory for the child process.
+    pub fn current_dir<P>(&mut self, dir: P) -> &mut Command
+    where
+        P: AsRef<Path>,
+    {
+        self.cmd.current_dir(dir);
+        self
+    }
+
+    /// Sets the configuration for the child process's standard input.
+    pub fn stdin<T>(&mut self, cfg: T) -> &mut Command
+    where
+        T: Into<Stdio>,
+    {
+        self.cmd.stdin(cfg);
+        self
+    }
+
+    /// Sets the configuration for the child process's standard output.
+    pub fn stdout<T>(&mut self, cfg: T) -> &mut Command
+    where
+        T: Into<Stdio>,
+    {
+        self.cmd.stdout(cfg);
+        self
+    }
+
+    /// Sets the configuration for the child process's standard error.
+    pub fn stderr<T>(&mut self, cfg: T) -> &mut Command
+    where
+        T: Into<Stdio>,
+    {
+        self.cmd.stderr(cfg);
+        self
+    }
+
+    /// Spawns the child process.
+    ///
+    /// The piped standard streams are registered to the `ctx` as non-blocking `StreamDescriptor`s.
+    pub fn spawn(&mut self, ctx: &IoContext) -> io::Result<Child> {
+        let mut child = self.cmd.spawn()?;
+        let pid = child.id() as pid_t;
+        let stdin = into_descriptor(ctx, child.stdin.take());
+        let stdout = into_descriptor(ctx, child.stdout.take());
+        let stderr = into_descriptor(ctx, child.stderr.take());
+        let waiter = Waiter::new(ctx, pid);
+        match (waiter, stdin, stdout, stderr) {
+            (Ok(waiter), Ok(stdin), Ok(stdout), Ok(stderr)) => Ok(Child {
+                pid: pid,
+                status: Mutex::new(None),
+                waiter: waiter,
+                stdin: stdin,
+                stdout: stdout,
+                stderr: stderr,
+            }),
+            (waiter, stdin, stdout, stderr) => {
+                // kills and reaps the child process, since the caller can not wait for it.
+                let _ = child.kill();
+                let _ = child.wait();
+                let err = waiter.err().or(stdin.err()).or(stdout.err()).or(stderr.err());
+                Err(err.unwrap())
+            }
+        }
+    }
+}
+
+enum Waiter {
+    #[cfg(target_os = "linux")]
+    Pidfd(Box<SocketImpl<()>>),
+    Reaper(IoContext),
+}
+
+impl Waiter {
+    #[cfg(target_os = "linux")]
+    fn new(ctx: &IoContext, pid: pid_t) -> io::Result<Waiter> {
+        use ffi::pidfd_open;
+
+        match pidfd_open(pid) {
+            Ok(fd) => Ok(Waiter::Pidfd(SocketImpl::new(ctx, fd, ()))),
+            Err(_) => {
+                Reaper::get()?;
+                Ok(Waiter::Reaper(ctx.clone()))
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn new(ctx: &IoContext, _: pid_t) -> io::Result<Waiter> {
+        Reaper::get()?;
+        Ok(Waiter::Reaper(ctx.clone()))
+    }
+}
+
+/// A child process, which was spawned by the `Command`.
+///
+/// The child process is not killed nor reaped when the `Child` is dropped.
+pub struct Child {
+    pid: pid_t,
+    status: Mutex<Option<ExitStatus>>,
+    waiter: Waiter,
+
+    /// The handle for writing to the child's standard input, if it has been piped.
+    pub stdin: Option<StreamDescriptor>,
+
+    /// The handle for reading from the child's standard output, if it has been piped.
+    pub stdout: Option<StreamDescriptor>,
+
+    /// The handle for reading from the child's standard error, if it has been piped.
+    pub stderr: Option<StreamDescriptor>,
+}
+
+impl Child {
+    /// Returns the OS-assigned process identifier.
+    pub fn id(&self) -> u32 {
+        self.pid as u32
+    }
+
+    /// Sends a `SIGKILL` to the child process.
+    pub fn kill(&self) -> io::Result<()> {
+        self.signal(Signal::SIGKILL)
+    }
+
+    /// Sends a `SIGTERM` to the child process.
+    pub fn terminate(&self) -> io::Result<()> {
+        self.signal(Signal::SIGTERM)
+    }
+
+    /// Sends a signal to the child process.
+    ///
+    /// Returns a `InvalidInput` error if the child process has already been reaped.
+    pub fn signal(&self, sig: Signal) -> io::Result<()> {
+        if self.status.lock().unwrap().is_some() {
+            return Err(INVALID_ARGUMENT.into());
+        }
+        Ok(kill(self.pid, sig)?)
+    }
+
+    /// Returns the exit status if the child process has exited, without blocking.
+    pub fn try_wait(&self) -> io::Result<Option<ExitStatus>> {
+        self.reap(true)
+    }
+
+    /// Blocks until the child process exits, and returns the exit status.
+    pub fn wait(&self) -> io::Result<ExitStatus> {
+        loop {
+            if let Some(status) = self.reap(false)? {
+                return Ok(status);
+            }
+        }
+    }
+
+    /// Asynchronously waits for the child process to exit.
+    ///
+    /// On Linux it watches a pidfd of the child process. Otherwise, or when pidfd is not available,
+    /// it waits a `SIGCHLD` by a process-wide reaper thread shared with all children in the process.
+    /// In this case, the `SIGCHLD` must be blocked in all threads of the process, since the reaper blocks it
+    /// only in the thread which has spawned the first `Child` and in the reaper thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use std::sync::Arc;
+    /// use asyncio::{IoContext, wrap};
+    /// use asyncio::process::{Child, Command, ExitStatus};
+    ///
+    /// let ctx = &IoContext::new().unwrap();
+    /// let child = Arc::new(Command::new("sh").args(&["-c", "exit 3"]).spawn(ctx).unwrap());
+    /// child.async_wait(wrap(&child, |_: Arc<Child>, res: io::Result<ExitStatus>| {
+    ///     assert_eq!(res.unwrap().code(), Some(3));
+    /// }));
+    /// ctx.run();
+    /// ```
+    pub fn async_wait<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<ExitStatus, io::Error>,
+    {
+        handler.wrap_cancel(self, |ctx, handler| match self.waiter {
+            #[cfg(target_os = "linux")]
+            Waiter::Pidfd(_) => {
+                ctx.do_dispatch(ChildWait {
+                    child: self,
+                    handler: handler,
+                })
+            }
+            Waiter::Reaper(_) => {
+                ctx.do_dispatch(ChildReap {
+                    child: self,
+                    handler: handler,
+                })
+            }
+        })
+    }
+
+    fn reap(&self, nohang: bool) -> io::Result<Option<ExitStatus>> {
+        if let Some(status) = *self.status.lock().unwrap() {
+            return Ok(Some(status));
+        }
+        loop {
+            match waitpid(self.pid, nohang) {
+                Ok(Some(raw)) => {
+                    let status = ExitStatus::from_raw(raw);
+                    *self.status.lock().unwrap() = Some(status);
+                    return Ok(Some(status));
+                }
+                Ok(None) => return Ok(None),
+                Err(INTERRUPTED) => (),
+                Err(err) => {
+                    // the child process has been reaped by the other thread.
+                    return match *self.status.lock().unwrap() {
+                        Some(status) => Ok(Some(status)),
+                        None => Err(err.into()),
+                    };
+                }
+            }
+        }
+    }
+}
+
+unsafe impl Send for Child {}
+
+unsafe impl Sync for Child {}
+
+unsafe impl AsIoContext for Child {
+    fn as_ctx(&self) -> &IoContext {
+        match self.waiter {
+            #[cfg(target_os = "linux")]
+            Waiter::Pidfd(ref pimpl) => pimpl.as_ctx(),
+            Waiter::Reaper(ref ctx) => ctx,
+        }
+    }
+}
+
+impl Cancel for Child {
+    fn cancel(&self) {
+        match self.waiter {
+            #[cfg(target_os = "linux")]
+            Waiter::Pidfd(ref pimpl) => pimpl.cancel(),
+            Waiter::Reaper(_) => Reaper::cancel(self),
+        }
+    }
+}
+
+impl Drop for Child {
+    fn drop(&mut self) {
+        // the reaper thread must not touch the child after dropped.
+        if let Waiter::Reaper(_) = self.waiter {
+            Reaper::cancel(self)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+struct ChildWait<F> {
+    child: *const Child,
+    handler: F,
+}
+
+#[cfg(target_os = "linux")]
+unsafe impl<F> Send for ChildWait<F> {}
+
+#[cfg(target_os = "linux")]
+impl<F> ChildWait<F> {
+    fn pidfd(&self) -> &SocketImpl<()> {
+        match unsafe { &(*self.child).waiter } {
+            &Waiter::Pidfd(ref pimpl) => pimpl,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl<F> Exec for ChildWait<F>
+where
+    F: Complete<ExitStatus, io::Error>,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        let pidfd = self.pidfd() as *const SocketImpl<()>;
+        unsafe { &*pidfd }.add_read_op(this, Box::new(self), SystemError::default())
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        let pidfd = self.pidfd() as *const SocketImpl<()>;
+        unsafe { &*pidfd }.add_read_op(this, self, SystemError::default())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl<F> Complete<ExitStatus, io::Error> for ChildWait<F>
+where
+    F: Complete<ExitStatus, io::Error>,
+{
+    fn success(self, this: &mut ThreadIoContext, res: ExitStatus) {
+        self.pidfd().next_read_op(this);
+        self.handler.success(this, res)
+    }
+
+    fn failure(self, this: &mut ThreadIoContext, err: io::Error) {
+        self.pidfd().next_read_op(this);
+        self.handler.failure(this, err)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl<F> Perform for ChildWait<F>
+where
+    F: Complete<ExitStatus, io::Error>,
+{
+    fn perform(self: Box<Self>, this: &mut ThreadIoContext, err: SystemError) {
+        if err == SystemError::default() {
+            let child = unsafe { &*self.child };
+            if this.as_ctx().stopped() {
+                return self.failure(this, OPERATION_CANCELED.into());
+            }
+            match child.reap(true) {
+                Ok(Some(status)) => self.success(this, status),
+                Ok(None) => {
+                    let pidfd = self.pidfd() as *const SocketImpl<()>;
+                    unsafe { &*pidfd }.add_read_op(this, self, WOULD_BLOCK)
+                }
+                Err(err) => self.failure(this, err),
+            }
+        } else {
+            self.failure(this, err.into())
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
+}
+
+trait ReapOp: Send + 'static {
+    fn child(&self) -> *const Child;
+
+    fn complete(self: Box<Self>, this: &mut ThreadIoContext, res: io::Result<ExitStatus>);
+}
+
+struct ChildReap<F> {
+    child: *const Child,
+    handler: F,
+}
+
+unsafe impl<F> Send for ChildReap<F> {}
+
+impl<F> ReapOp for ChildReap<F>
+where
+    F: Complete<ExitStatus, io::Error>,
+{
+    fn child(&self) -> *const Child {
+        self.child
+    }
+
+    fn complete(self: Box<Self>, this: &mut ThreadIoContext, res: io::Result<ExitStatus>) {
+        let ChildReap { handler, .. } = *self;
+        match res {
+            Ok(status) => handler.success(this, status),
+            Err(err) => handler.failure(this, err),
+        }
+    }
+}
+
+impl<F> Exec for ChildReap<F>
+where
+    F: Complete<ExitStatus, io::Error>,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        let child = unsafe { &*self.child };
+        let reaper = match Reaper::get() {
+            Ok(reaper) => reaper,
+            Err(err) => return self.handler.failure(this, err),
+        };
+        // the child is reaped under the lock, so that the SIGCHLD received before registering is not missed.
+        let mut waiters = reaper.waiters.lock().unwrap();
+        match child.reap(true) {
+            Ok(Some(status)) => {
+                drop(waiters);
+                self.handler.success(this, status)
+            }
+            Ok(None) => waiters.push(Box::new(self)),
+            Err(err) => {
+                drop(waiters);
+                self.handler.failure(this, err)
+            }
+        }
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.call(this)
+    }
+}
+
+struct ReapComplete {
+    op: Box<ReapOp>,
+    res: io::Result<ExitStatus>,
+}
+
+impl Exec for ReapComplete {
+    fn call(self, this: &mut ThreadIoContext) {
+        self.op.complete(this, self.res)
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.call(this)
+    }
+
+    fn outstanding_work(&self, _: &IoContext) {}
+}
+
+/// The process-wide waiter of `SIGCHLD`.
+///
+/// The signal is consumed by only one `SignalSet` in the process, so that a single reaper waits it on own thread
+/// and completes the waiters on the `IoContext` of each child.
+struct Reaper {
+    sig: SignalSet,
+    waiters: Mutex<Vec<Box<ReapOp>>>,
+}
+
+lazy_static! {
+    static ref REAPER: Mutex<Option<Arc<Reaper>>> = Mutex::new(None);
+}
+
+impl Reaper {
+    fn get() -> io::Result<Arc<Reaper>> {
+        let mut global = REAPER.lock().unwrap();
+        if let Some(ref reaper) = *global {
+            return Ok(reaper.clone());
+        }
+        let ctx = IoContext::new()?;
+        let sig = SignalSet::new(&ctx)?;
+        // blocks the SIGCHLD in this thread, the reaper thread inherits the signal mask.
+        sig.add(Signal::SIGCHLD)?;
+        let reaper = Arc::new(Reaper {
+            sig: sig,
+            waiters: Mutex::new(Vec::new()),
+        });
+        let this = reaper.clone();
+        thread::Builder::new()
+            .name("asyncio-reaper".to_string())
+            .spawn(move || {
+                this.sig.async_wait(ReaperWait { reaper: &*this });
+                this.sig.as_ctx().run();
+            })?;
+        *global = Some(reaper.clone());
+        Ok(reaper)
+    }
+
+    fn cancel(child: &Child) {
+        let reaper = match *REAPER.lock().unwrap() {
+            Some(ref reaper) => reaper.clone(),
+            None => return,
+        };
+        let canceled: Vec<_> = {
+            let mut waiters = reaper.waiters.lock().unwrap();
+            let (canceled, pending) = mem::replace(&mut *waiters, Vec::new())
+                .into_iter()
+                .partition(|op| op.child() == child as *const _);
+            *waiters = pending;
+            canceled
+        };
+        if canceled.is_empty() {
+            return;
+        }
+        let ctx = child.as_ctx();
+        for op in canceled {
+            ctx.do_post(ReapComplete {
+                op: op,
+                res: Err(OPERATION_CANCELED.into()),
+            });
+        }
+        if ThreadIoContext::callstack(ctx).is_none() {
+            ctx.as_reactor().interrupt();
+        }
+    }
+
+    fn reap(&self, err: Option<io::Error>) {
+        let mut waiters = self.waiters.lock().unwrap();
+        let mut pending = Vec::new();
+        for op in mem::replace(&mut *waiters, Vec::new()) {
+            let child = unsafe { &*op.child() };
+            let res = match err {
+                Some(ref err) => Err(io::Error::new(err.kind(), err.to_string())),
+                None => {
+                    match child.reap(true) {
+                        Ok(Some(status)) => Ok(status),
+                        Ok(None) => {
+                            pending.push(op);
+                            continue;
+                        }
+                        Err(err) => Err(err),
+                    }
+                }
+            };
+            let ctx = child.as_ctx().clone();
+            ctx.do_post(ReapComplete { op: op, res: res });
+            ctx.as_reactor().interrupt();
+        }
+        *waiters = pending;
+    }
+}
+
+unsafe impl Send for Reaper {}
+
+unsafe impl Sync for Reaper {}
+
+struct ReaperWait {
+    reaper: *const Reaper,
+}
+
+unsafe impl Send for ReaperWait {}
+
+impl Handler<Signal, io::Error> for ReaperWait {
+    type Output = ();
+
+    type WrappedHandler = Self;
+
+    fn wrap<W>(self, ctx: &IoContext, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        wrapper(ctx, self)
+    }
+
+    fn wrap_timeout<W>(self, ctx: &Cancel, _: &Timeout, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        wrapper(ctx.as_ctx(), self)
+    }
+}
+
+impl Complete<Signal, io::Error> for ReaperWait {
+    fn success(self, this: &mut ThreadIoContext, _: Signal) {
+        let reaper = unsafe { &*self.reaper };
+        reaper.reap(None);
+        reaper.sig.async_wait(self);
+        this.decrease_outstanding_work();
+    }
+
+    fn failure(self, this: &mut ThreadIoContext, err: io::Error) {
+        // the reaper thread exits, the following waiters are registered to a new reaper.
+        let reaper = unsafe { &*self.reaper };
+        *REAPER.lock().unwrap() = None;
+        reaper.reap(Some(err));
+        this.decrease_outstanding_work();
+    }
+}
+
+#[test]
+fn test_process_output() {
+    use std::sync::Arc;
+
+    let ctx = &IoContext::new().unwrap();
+    let child = Arc::new(
+        Command::new("sh")
+            .args(&["-c", "echo hello; exit 3"])
+            .stdout(Stdio::piped())
+            .spawn(ctx)
+            .unwrap(),
+    );
+    let mut buf = [0; 16];
+    let len = child.stdout.as_ref().unwrap().read_some(&mut buf).unwrap();
+    assert_eq!(&buf[..len], b"hello\n");
+    child.async_wait(::handler::wrap(&child, |_, res: io::Result<ExitStatus>| {
+        assert_eq!(res.unwrap().code(), Some(3));
+    }));
+    ctx.run();
+    assert_eq!(child.try_wait().unwrap().unwrap().code(), Some(3));
+}
+
+#[test]
+fn test_process_kill() {
+    use std::sync::Arc;
+
+    let ctx = &IoContext::new().unwrap();
+    let child = Arc::new(Command::new("sleep").arg("10").spawn(ctx).unwrap());
+    assert!(child.try_wait().unwrap().is_none());
+    child.async_wait(::handler::wrap(&child, |_, res: io::Result<ExitStatus>| {
+        assert_eq!(res.unwrap().signal(), Some(Signal::SIGKILL as i32));
+    }));
+    child.kill().unwrap();
+    ctx.run();
+    assert!(child.kill().is_err());
+}
+
+#[test]
+fn test_process_stdin() {
+    let ctx = &IoContext::new().unwrap();
+    let mut child = Command::new("cat")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn(ctx)
+        .unwrap();
+    {
+        let stdin = child.stdin.take().unwrap();
+        assert_eq!(stdin.write_some(b"hello").unwrap(), 5);
+    }
+    let mut buf = [0; 16];
+    let len = child.stdout.as_ref().unwrap().read_some(&mut buf).unwrap();
+    assert_eq!(&buf[..len], b"hello");
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn test_process_cancel() {
+    use std::sync::Arc;
+
+    let ctx = &IoContext::new().unwrap();
+    let child = Arc::new(Command::new("sleep").arg("10").spawn(ctx).unwrap());
+    child.async_wait(::handler::wrap(&child, |_, res: io::Result<ExitStatus>| {
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(::libc::ECANCELED));
+    }));
+    let cancel = child.clone();
+    ctx.post(move |_| cancel.cancel());
+    ctx.run();
+    child.kill().unwrap();
+    assert!(child.wait().unwrap().signal().is_some());
+}
+
+#[test]
+fn test_process_reaper() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    // the children of the different contexts are reaped by the single process-wide reaper.
+    let count = Arc::new(AtomicUsize::new(0));
+    let threads: Vec<_> = (0..2)
+        .map(|i| {
+            let count = count.clone();
+            thread::spawn(move || {
+                let ctx = &IoContext::new().unwrap();
+                let mut child = Command::new("sh")
+                    .args(&["-c", &format!("exit {}", i + 3)])
+                    .spawn(ctx)
+                    .unwrap();
+                child.waiter = Waiter::Reaper(ctx.clone());
+                let child = Arc::new(child);
+                child.async_wait(::handler::wrap(&child, move |_, res: io::Result<ExitStatus>| {
+                    assert_eq!(res.unwrap().code(), Some(i + 3));
+                    count.fetch_add(1, Ordering::SeqCst);
+                }));
+                ctx.run();
+            })
+        })
+        .collect();
+    // the SIGCHLD may be discarded by the main thread of the test harness, which does not block it.
+    while count.load(Ordering::SeqCst) < 2 {
+        Reaper::get().unwrap().reap(None);
+        thread::sleep(Duration::from_millis(10));
+    }
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
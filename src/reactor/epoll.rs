@@ -5,15 +5,16 @@ use timer::TimerQueue;
 
 use std::io;
 use std::mem;
+use std::cell::UnsafeCell;
 use std::sync::Mutex;
 use std::collections::{HashSet, VecDeque};
-use std::ops::{Deref, DerefMut};
+use std::ops::Deref;
 use std::hash::{Hash, Hasher};
 use libc::{self, epoll_event, epoll_create1, epoll_ctl, epoll_wait, EPOLLIN, EPOLLOUT, EPOLLERR,
            EPOLLHUP, EPOLLET, EPOLL_CLOEXEC, EPOLL_CTL_ADD, EPOLL_CTL_DEL};
 
 fn dispatch_socket(ev: &epoll_event, this: &mut ThreadIoContext) {
-    let eev = unsafe { &mut *(ev.u64 as *mut Epoll) };
+    let eev = unsafe { &*(ev.u64 as *const Epoll) };
     if (ev.events & (EPOLLERR | EPOLLHUP) as u32) != 0 {
         let err = sock_error(eev);
         this.as_ctx().clone().as_reactor().cancel_ops_nolock(
@@ -24,14 +25,16 @@ fn dispatch_socket(ev: &epoll_event, this: &mut ThreadIoContext) {
         return;
     }
     if (ev.events & EPOLLIN as u32) as u32 != 0 {
-        if let Some(op) = eev.input.queue.pop_front() {
-            eev.input.blocked = true;
+        let input = unsafe { &mut *eev.input.get() };
+        if let Some(op) = input.queue.pop_front() {
+            input.blocked = true;
             this.push(op, SystemError::default());
         }
     }
     if (ev.events & EPOLLOUT as u32) as u32 != 0 {
-        if let Some(op) = eev.output.queue.pop_front() {
-            eev.output.blocked = true;
+        let output = unsafe { &mut *eev.output.get() };
+        if let Some(op) = output.queue.pop_front() {
+            output.blocked = true;
             this.push(op, SystemError::default());
         }
     }
@@ -47,6 +50,8 @@ fn dispatch_intr(ev: &epoll_event, _: &mut ThreadIoContext) {
     }
 }
 
+fn dispatch_notify(_: &epoll_event, _: &mut ThreadIoContext) {}
+
 #[derive(Default)]
 struct Ops {
     queue: VecDeque<Box<Perform>>,
@@ -54,10 +59,18 @@ struct Ops {
     canceled: bool,
 }
 
+impl Ops {
+    /// Takes the first operation whose deadline is expired.
+    fn take_expired(&mut self) -> Option<Box<Perform>> {
+        let i = self.queue.iter().position(|op| op.is_expired())?;
+        self.queue.remove(i)
+    }
+}
+
 pub struct Epoll {
     fd: RawFd,
-    input: Ops,
-    output: Ops,
+    input: UnsafeCell<Ops>,
+    output: UnsafeCell<Ops>,
     dispatch: fn(&epoll_event, &mut ThreadIoContext),
 }
 
@@ -79,6 +92,16 @@ impl Epoll {
             dispatch: dispatch_intr,
         }
     }
+
+    /// Only wakes up the reactor, the `fd` is read by the owner.
+    pub fn notify(fd: RawFd) -> Self {
+        Epoll {
+            fd: fd,
+            input: Default::default(),
+            output: Default::default(),
+            dispatch: dispatch_notify,
+        }
+    }
 }
 
 impl AsRawFd for Epoll {
@@ -114,12 +137,6 @@ impl Deref for EpollRef {
     }
 }
 
-impl DerefMut for EpollRef {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { &mut *(self.0 as *mut Epoll) }
-    }
-}
-
 pub struct EpollReactor {
     epfd: RawFd,
     mutex: Mutex<HashSet<EpollRef>>,
@@ -200,10 +217,13 @@ impl EpollReactor {
         op: Box<Perform>,
         err: SystemError,
     ) {
-        let ops = &mut EpollRef(eev).input;
+        let ops = unsafe { &mut *eev.input.get() };
         let _ep = self.mutex.lock().unwrap();
         if err == SystemError::default() {
-            if ops.queue.is_empty() && !ops.blocked {
+            if op.is_expired() && !ops.blocked {
+                ops.blocked = true;
+                this.push(op, OPERATION_CANCELED);
+            } else if ops.queue.is_empty() && !ops.blocked {
                 ops.blocked = true;
                 this.push(op, SystemError::default());
             } else {
@@ -215,8 +235,12 @@ impl EpollReactor {
                 this.push(op, OPERATION_CANCELED);
             }
         } else {
-            ops.blocked = false;
             ops.queue.push_front(op);
+            match ops.take_expired() {
+                // the next operations are continued by the expired one.
+                Some(op) => this.push(op, OPERATION_CANCELED),
+                None => ops.blocked = false,
+            }
         }
     }
 
@@ -227,10 +251,13 @@ impl EpollReactor {
         op: Box<Perform>,
         err: SystemError,
     ) {
-        let ops = &mut EpollRef(eev).output;
+        let ops = unsafe { &mut *eev.output.get() };
         let _epoll = self.mutex.lock().unwrap();
         if err == SystemError::default() {
-            if ops.queue.is_empty() && !ops.blocked {
+            if op.is_expired() && !ops.blocked {
+                ops.blocked = true;
+                this.push(op, OPERATION_CANCELED);
+            } else if ops.queue.is_empty() && !ops.blocked {
                 ops.blocked = true;
                 this.push(op, SystemError::default());
             } else {
@@ -243,19 +270,25 @@ impl EpollReactor {
             }
         } else {
             println!("add wirte_op {}", err);
-            ops.blocked = false;
             ops.queue.push_front(op);
+            match ops.take_expired() {
+                // the next operations are continued by the expired one.
+                Some(op) => this.push(op, OPERATION_CANCELED),
+                None => ops.blocked = false,
+            }
         }
     }
 
     pub fn next_read_op(&self, eev: &Epoll, this: &mut ThreadIoContext) {
-        let ops = &mut EpollRef(eev).input;
+        let ops = unsafe { &mut *eev.input.get() };
         let _epoll = self.mutex.lock().unwrap();
         if ops.canceled {
             ops.canceled = false;
             for op in ops.queue.drain(..) {
                 this.push(op, OPERATION_CANCELED);
             }
+        } else if let Some(op) = ops.take_expired() {
+            this.push(op, OPERATION_CANCELED);
         } else {
             if let Some(op) = ops.queue.pop_front() {
                 this.push(op, SystemError::default());
@@ -266,13 +299,15 @@ impl EpollReactor {
     }
 
     pub fn next_write_op(&self, eev: &Epoll, this: &mut ThreadIoContext) {
-        let ops = &mut EpollRef(eev).output;
+        let ops = unsafe { &mut *eev.output.get() };
         let _epoll = self.mutex.lock().unwrap();
         if ops.canceled {
             ops.canceled = false;
             for op in ops.queue.drain(..) {
                 this.push(op, OPERATION_CANCELED);
             }
+        } else if let Some(op) = ops.take_expired() {
+            this.push(op, OPERATION_CANCELED);
         } else {
             if let Some(op) = ops.queue.pop_front() {
                 this.push(op, SystemError::default());
@@ -287,8 +322,23 @@ impl EpollReactor {
         self.cancel_ops_nolock(eev, ctx, err)
     }
 
+    pub fn cancel_expired_ops(&self, eev: &Epoll, ctx: &IoContext) {
+        let _epoll = self.mutex.lock().unwrap();
+        let (input, output) = unsafe { (&mut *eev.input.get(), &mut *eev.output.get()) };
+        for ops in &mut [input, output] {
+            // the blocked operations are continued by the `next_read_op` or the `add_read_op`.
+            if !ops.canceled && !ops.blocked {
+                if let Some(op) = ops.take_expired() {
+                    ops.blocked = true;
+                    ctx.do_post((op, OPERATION_CANCELED))
+                }
+            }
+        }
+    }
+
     fn cancel_ops_nolock(&self, eev: &Epoll, ctx: &IoContext, err: SystemError) {
-        for ops in &mut [&mut EpollRef(eev).input, &mut EpollRef(eev).output] {
+        let (input, output) = unsafe { (&mut *eev.input.get(), &mut *eev.output.get()) };
+        for ops in &mut [input, output] {
             if !ops.canceled {
                 ops.canceled = true;
                 if !ops.blocked {
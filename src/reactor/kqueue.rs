@@ -73,6 +73,14 @@ struct Ops {
     canceled: bool,
 }
 
+impl Ops {
+    /// Takes the first operation whose deadline is expired.
+    fn take_expired(&mut self) -> Option<Box<Perform>> {
+        let i = self.queue.iter().position(|op| op.is_expired())?;
+        self.queue.remove(i)
+    }
+}
+
 pub struct Kevent {
     fd: RawFd,
     input: Ops,
@@ -300,7 +308,10 @@ impl KqueueReactor {
         let ops = &mut KeventRef(kev).input;
         let _kq = self.mutex.lock().unwrap();
         if err == SystemError::default() {
-            if ops.queue.is_empty() && !ops.blocked {
+            if op.is_expired() && !ops.blocked {
+                ops.blocked = true;
+                this.push(op, OPERATION_CANCELED);
+            } else if ops.queue.is_empty() && !ops.blocked {
                 ops.blocked = true;
                 this.push(op, SystemError::default());
             } else {
@@ -322,8 +333,12 @@ impl KqueueReactor {
                 ],
             );
         } else {
-            ops.blocked = false;
             ops.queue.push_front(op);
+            if let Some(op) = ops.take_expired() {
+                // the next operations are continued by the expired one.
+                return this.push(op, OPERATION_CANCELED);
+            }
+            ops.blocked = false;
             this.as_ctx().as_reactor().kevent(
                 &[
                     ev_set(
@@ -347,7 +362,10 @@ impl KqueueReactor {
         let ops = &mut KeventRef(kev).output;
         let _kq = self.mutex.lock().unwrap();
         if err == SystemError::default() {
-            if ops.queue.is_empty() && !ops.blocked {
+            if op.is_expired() && !ops.blocked {
+                ops.blocked = true;
+                this.push(op, OPERATION_CANCELED);
+            } else if ops.queue.is_empty() && !ops.blocked {
                 ops.blocked = true;
                 this.push(op, SystemError::default());
             } else {
@@ -369,8 +387,12 @@ impl KqueueReactor {
                 ],
             );
         } else {
-            ops.blocked = false;
             ops.queue.push_front(op);
+            if let Some(op) = ops.take_expired() {
+                // the next operations are continued by the expired one.
+                return this.push(op, OPERATION_CANCELED);
+            }
+            ops.blocked = false;
             this.as_ctx().as_reactor().kevent(
                 &[
                     ev_set(
@@ -392,6 +414,8 @@ impl KqueueReactor {
             for op in ops.queue.drain(..) {
                 this.push(op, OPERATION_CANCELED);
             }
+        } else if let Some(op) = ops.take_expired() {
+            this.push(op, OPERATION_CANCELED);
         } else {
             if let Some(op) = ops.queue.pop_front() {
                 this.push(op, SystemError::default());
@@ -419,6 +443,8 @@ impl KqueueReactor {
             for op in ops.queue.drain(..) {
                 this.push(op, OPERATION_CANCELED);
             }
+        } else if let Some(op) = ops.take_expired() {
+            this.push(op, OPERATION_CANCELED);
         } else {
             if let Some(op) = ops.queue.pop_front() {
                 this.push(op, SystemError::default());
@@ -443,6 +469,23 @@ impl KqueueReactor {
         self.cancel_ops_nolock(kev, ctx, err)
     }
 
+    pub fn cancel_expired_ops(&self, kev: &Kevent, ctx: &IoContext) {
+        let _kq = self.mutex.lock().unwrap();
+        for ops in &mut [
+            &mut KeventRef(kev).input,
+            &mut KeventRef(kev).output,
+        ]
+        {
+            // the blocked operations are continued by the `next_read_op` or the `add_read_op`.
+            if !ops.canceled && !ops.blocked {
+                if let Some(op) = ops.take_expired() {
+                    ops.blocked = true;
+                    ctx.do_post((op, OPERATION_CANCELED))
+                }
+            }
+        }
+    }
+
     pub fn cancel_ops_nolock(&self, kev: &Kevent, ctx: &IoContext, err: SystemError) {
         for ops in &mut [
             &mut KeventRef(kev).input,
@@ -44,6 +44,10 @@ impl<T> SocketImpl<T> {
             OPERATION_CANCELED,
         )
     }
+
+    pub fn cancel_expired(&self) {
+        self.ctx.clone().as_reactor().cancel_expired_ops(&self.fd, &self.ctx)
+    }
 }
 
 unsafe impl<T> AsIoContext for SocketImpl<T> {
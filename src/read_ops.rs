@@ -155,6 +155,10 @@ where
             self.failure(this, err.into())
         }
     }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
 }
 
 impl<F, R> Exec for AsyncRead<F, R>
@@ -110,6 +110,10 @@ impl Cancel for SerialPort {
     fn cancel(&self) {
         self.pimpl.cancel()
     }
+
+    fn cancel_expired(&self) {
+        self.pimpl.cancel_expired()
+    }
 }
 
 impl AsRawFd for SerialPort {
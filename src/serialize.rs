@@ -0,0 +1,510 @@
+use ip::{IpAddr, IpAddrV4, IpAddrV6, IpEndpoint, IpNetwork, IpNetworkV4, IpNetworkV6, IpProtocol,
+         LlAddr};
+#[cfg(unix)]
+use local::LocalEndpoint;
+
+use std::fmt;
+use std::str::FromStr;
+use std::marker::PhantomData;
+#[cfg(unix)]
+use std::path::Path;
+#[cfg(unix)]
+use std::ffi::OsStr;
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{self, EnumAccess, Unexpected, VariantAccess, Visitor};
+#[cfg(unix)]
+use serde::ser::Error;
+
+/// Parses the human-readable text forms by `FromStr`.
+struct FromStrVisitor<T> {
+    expecting: &'static str,
+    _marker: PhantomData<T>,
+}
+
+impl<T> FromStrVisitor<T> {
+    fn new(expecting: &'static str) -> Self {
+        FromStrVisitor {
+            expecting: expecting,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, T: FromStr> Visitor<'de> for FromStrVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<T, E> {
+        T::from_str(s).map_err(|_| E::invalid_value(Unexpected::Str(s), &self))
+    }
+}
+
+fn deserialize_str<'de, D, T>(deserializer: D, expecting: &'static str) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+{
+    deserializer.deserialize_str(FromStrVisitor::new(expecting))
+}
+
+/// The variant identifier of the version-independent types.
+enum Family {
+    V4,
+    V6,
+}
+
+const VARIANTS: &'static [&'static str] = &["V4", "V6"];
+
+impl<'de> Deserialize<'de> for Family {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FamilyVisitor;
+
+        impl<'de> Visitor<'de> for FamilyVisitor {
+            type Value = Family;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("`V4` or `V6`")
+            }
+
+            fn visit_u64<E: de::Error>(self, n: u64) -> Result<Family, E> {
+                match n {
+                    0 => Ok(Family::V4),
+                    1 => Ok(Family::V6),
+                    _ => Err(E::invalid_value(Unexpected::Unsigned(n), &self)),
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Family, E> {
+                match s {
+                    "V4" => Ok(Family::V4),
+                    "V6" => Ok(Family::V6),
+                    _ => Err(E::unknown_variant(s, VARIANTS)),
+                }
+            }
+        }
+
+        deserializer.deserialize_identifier(FamilyVisitor)
+    }
+}
+
+/// Deserializes the compact binary form of `IpAddr` or `IpNetwork`.
+struct EnumVisitor<A, B, T> {
+    v4: fn(A) -> T,
+    v6: fn(B) -> T,
+}
+
+impl<'de, A, B, T> Visitor<'de> for EnumVisitor<A, B, T>
+where
+    A: Deserialize<'de>,
+    B: Deserialize<'de>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("IP-v4 or IP-v6 variant")
+    }
+
+    fn visit_enum<E: EnumAccess<'de>>(self, data: E) -> Result<T, E::Error> {
+        match try!(data.variant()) {
+            (Family::V4, var) => var.newtype_variant().map(self.v4),
+            (Family::V6, var) => var.newtype_variant().map(self.v6),
+        }
+    }
+}
+
+impl Serialize for LlAddr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.as_bytes().serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for LlAddr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserialize_str(deserializer, "LL-address")
+        } else {
+            <[u8; 6]>::deserialize(deserializer).map(LlAddr::from)
+        }
+    }
+}
+
+impl Serialize for IpAddrV4 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.as_bytes().serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IpAddrV4 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserialize_str(deserializer, "IP-v4 address")
+        } else {
+            <[u8; 4]>::deserialize(deserializer).map(IpAddrV4::from)
+        }
+    }
+}
+
+/// The scope-id is serialized as the decimal number in the human-readable form.
+impl Serialize for IpAddrV6 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            match self.scope_id() {
+                0 => serializer.collect_str(self),
+                id => serializer.collect_str(&format_args!("{}%{}", self, id)),
+            }
+        } else {
+            (self.as_bytes(), self.scope_id()).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IpAddrV6 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserialize_str(deserializer, "IP-v6 address")
+        } else {
+            <([u8; 16], u32)>::deserialize(deserializer).map(|(bytes, id)| IpAddrV6::from(bytes, id))
+        }
+    }
+}
+
+impl Serialize for IpAddr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            match self {
+                &IpAddr::V4(ref addr) => addr.serialize(serializer),
+                &IpAddr::V6(ref addr) => addr.serialize(serializer),
+            }
+        } else {
+            match self {
+                &IpAddr::V4(ref addr) => serializer.serialize_newtype_variant("IpAddr", 0, "V4", addr),
+                &IpAddr::V6(ref addr) => serializer.serialize_newtype_variant("IpAddr", 1, "V6", addr),
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IpAddr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserialize_str(deserializer, "IP address")
+        } else {
+            deserializer.deserialize_enum(
+                "IpAddr",
+                VARIANTS,
+                EnumVisitor {
+                    v4: IpAddr::V4,
+                    v6: IpAddr::V6,
+                },
+            )
+        }
+    }
+}
+
+impl Serialize for IpNetworkV4 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            (self.address(), self.prefix_len() as u8).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IpNetworkV4 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserialize_str(deserializer, "IP-v4 network")
+        } else {
+            let (addr, len) = try!(<(IpAddrV4, u8)>::deserialize(deserializer));
+            IpNetworkV4::from(addr, len as u16).ok_or_else(|| {
+                de::Error::invalid_value(Unexpected::Unsigned(len as u64), &"prefix length 0..32")
+            })
+        }
+    }
+}
+
+impl Serialize for IpNetworkV6 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            (self.address().as_bytes(), self.prefix_len() as u8).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IpNetworkV6 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserialize_str(deserializer, "IP-v6 network")
+        } else {
+            let (bytes, len) = try!(<([u8; 16], u8)>::deserialize(deserializer));
+            IpNetworkV6::from(bytes.into(), len as u16).ok_or_else(|| {
+                de::Error::invalid_value(Unexpected::Unsigned(len as u64), &"prefix length 0..128")
+            })
+        }
+    }
+}
+
+impl Serialize for IpNetwork {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            match self {
+                &IpNetwork::V4(ref net) => {
+                    serializer.serialize_newtype_variant("IpNetwork", 0, "V4", net)
+                }
+                &IpNetwork::V6(ref net) => {
+                    serializer.serialize_newtype_variant("IpNetwork", 1, "V6", net)
+                }
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IpNetwork {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserialize_str(deserializer, "IP network")
+        } else {
+            deserializer.deserialize_enum(
+                "IpNetwork",
+                VARIANTS,
+                EnumVisitor {
+                    v4: IpNetwork::V4,
+                    v6: IpNetwork::V6,
+                },
+            )
+        }
+    }
+}
+
+/// The scope-id is serialized as the decimal number in the human-readable form as `IpAddrV6`,
+/// since the interface name depends on the host.
+impl<P: IpProtocol> Serialize for IpEndpoint<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            match self.addr() {
+                IpAddr::V6(ref addr) if addr.scope_id() != 0 => {
+                    let id = addr.scope_id();
+                    serializer.collect_str(&format_args!("[{}%{}]:{}", addr, id, self.port()))
+                }
+                _ => serializer.collect_str(self),
+            }
+        } else {
+            (self.addr(), self.port()).serialize(serializer)
+        }
+    }
+}
+
+impl<'de, P: IpProtocol> Deserialize<'de> for IpEndpoint<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserialize_str(deserializer, "IP endpoint")
+        } else {
+            <(IpAddr, u16)>::deserialize(deserializer).map(|(addr, port)| IpEndpoint::new(addr, port))
+        }
+    }
+}
+
+/// The unnamed endpoint is serialized as the empty path name.
+#[cfg(unix)]
+impl<P> Serialize for LocalEndpoint<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let path = self.as_pathname().unwrap_or(Path::new(""));
+        if serializer.is_human_readable() {
+            match path.to_str() {
+                Some(path) => serializer.serialize_str(path),
+                None => Err(S::Error::custom("path name must be valid UTF-8")),
+            }
+        } else {
+            serializer.serialize_bytes(path.as_os_str().as_bytes())
+        }
+    }
+}
+
+#[cfg(unix)]
+struct LocalEndpointVisitor<P>(PhantomData<P>);
+
+#[cfg(unix)]
+impl<'de, P> Visitor<'de> for LocalEndpointVisitor<P> {
+    type Value = LocalEndpoint<P>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("UNIX domain path name")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        self.visit_bytes(s.as_bytes())
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        LocalEndpoint::new(OsStr::from_bytes(v)).map_err(|_| E::invalid_length(v.len(), &self))
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut vec = Vec::new();
+        while let Some(ch) = try!(seq.next_element()) {
+            vec.push(ch);
+        }
+        self.visit_bytes(&vec)
+    }
+}
+
+#[cfg(unix)]
+impl<'de, P> Deserialize<'de> for LocalEndpoint<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(LocalEndpointVisitor(PhantomData))
+        } else {
+            deserializer.deserialize_bytes(LocalEndpointVisitor(PhantomData))
+        }
+    }
+}
+
+#[test]
+fn test_serde_lladdr() {
+    use serde_test::{Configure, Token, assert_tokens};
+
+    let mac = LlAddr::new(0xaa, 0xbb, 0xcc, 0, 1, 2);
+    assert_tokens(&mac.readable(), &[Token::Str("AA:BB:CC:00:01:02")]);
+    assert_tokens(
+        &mac.compact(),
+        &[
+            Token::Tuple { len: 6 },
+            Token::U8(0xaa),
+            Token::U8(0xbb),
+            Token::U8(0xcc),
+            Token::U8(0),
+            Token::U8(1),
+            Token::U8(2),
+            Token::TupleEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_serde_ip_addr() {
+    use serde_test::{Configure, Readable, Token, assert_de_tokens_error, assert_tokens};
+
+    let v4 = IpAddrV4::new(192, 168, 0, 1);
+    assert_tokens(&v4.readable(), &[Token::Str("192.168.0.1")]);
+    assert_tokens(
+        &IpAddr::V4(v4).compact(),
+        &[
+            Token::NewtypeVariant { name: "IpAddr", variant: "V4" },
+            Token::Tuple { len: 4 },
+            Token::U8(192),
+            Token::U8(168),
+            Token::U8(0),
+            Token::U8(1),
+            Token::TupleEnd,
+        ],
+    );
+
+    let v6 = IpAddrV6::with_scope_id(0xfe80, 0, 0, 0, 0, 0, 0, 1, 1);
+    assert_tokens(&v6.readable(), &[Token::Str("fe80::1%1")]);
+    assert_tokens(&IpAddr::V6(IpAddrV6::loopback()).readable(), &[Token::Str("::1")]);
+    let mut tokens = vec![
+        Token::NewtypeVariant { name: "IpAddr", variant: "V6" },
+        Token::Tuple { len: 2 },
+        Token::Tuple { len: 16 },
+        Token::U8(0xfe),
+        Token::U8(0x80),
+    ];
+    tokens.extend((0..13).map(|_| Token::U8(0)));
+    tokens.extend_from_slice(&[Token::U8(1), Token::TupleEnd, Token::U32(1), Token::TupleEnd]);
+    assert_tokens(&IpAddr::V6(v6).compact(), &tokens);
+
+    assert_de_tokens_error::<Readable<IpAddr>>(
+        &[Token::Str("192.168.0.256")],
+        "invalid value: string \"192.168.0.256\", expected IP address",
+    );
+}
+
+#[test]
+fn test_serde_ip_network() {
+    use serde_test::{Configure, Readable, Token, assert_de_tokens_error, assert_tokens};
+
+    let net = IpNetwork::V4(IpNetworkV4::from(IpAddrV4::new(10, 0, 0, 1), 8).unwrap());
+    assert_tokens(&net.readable(), &[Token::Str("10.0.0.1/8")]);
+    assert_tokens(
+        &net.compact(),
+        &[
+            Token::NewtypeVariant { name: "IpNetwork", variant: "V4" },
+            Token::Tuple { len: 2 },
+            Token::Tuple { len: 4 },
+            Token::U8(10),
+            Token::U8(0),
+            Token::U8(0),
+            Token::U8(1),
+            Token::TupleEnd,
+            Token::U8(8),
+            Token::TupleEnd,
+        ],
+    );
+    assert_de_tokens_error::<Readable<IpNetworkV4>>(
+        &[Token::Str("10.0.0.1/33")],
+        "invalid value: string \"10.0.0.1/33\", expected IP-v4 network",
+    );
+
+    let net = IpNetworkV6::from(IpAddrV6::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0), 32).unwrap();
+    assert_tokens(&net.readable(), &[Token::Str("2001:db8::/32")]);
+    assert_tokens(&IpNetwork::V6(net).readable(), &[Token::Str("2001:db8::/32")]);
+}
+
+#[test]
+fn test_serde_ip_endpoint() {
+    use ip::{TcpEndpoint, UdpEndpoint};
+    use serde_test::{Configure, Token, assert_tokens};
+
+    let ep = TcpEndpoint::new(IpAddrV4::new(127, 0, 0, 1), 80);
+    assert_tokens(&ep.clone().readable(), &[Token::Str("127.0.0.1:80")]);
+    assert_tokens(
+        &ep.compact(),
+        &[
+            Token::Tuple { len: 2 },
+            Token::NewtypeVariant { name: "IpAddr", variant: "V4" },
+            Token::Tuple { len: 4 },
+            Token::U8(127),
+            Token::U8(0),
+            Token::U8(0),
+            Token::U8(1),
+            Token::TupleEnd,
+            Token::U16(80),
+            Token::TupleEnd,
+        ],
+    );
+
+    let ep = UdpEndpoint::new(IpAddrV6::with_scope_id(0xfe80, 0, 0, 0, 0, 0, 0, 1, 1), 546);
+    assert_tokens(&ep.readable(), &[Token::Str("[fe80::1%1]:546")]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_serde_local_endpoint() {
+    use local::LocalStreamEndpoint;
+    use serde_test::{Configure, Token, assert_tokens};
+
+    let ep = LocalStreamEndpoint::new("/tmp/asyncio.sock").unwrap();
+    assert_tokens(&ep.clone().readable(), &[Token::Str("/tmp/asyncio.sock")]);
+    assert_tokens(&ep.compact(), &[Token::Bytes(b"/tmp/asyncio.sock")]);
+
+    let ep = LocalStreamEndpoint::new("").unwrap();
+    assert_tokens(&ep.readable(), &[Token::Str("")]);
+}
@@ -124,6 +124,10 @@ where
             self.failure(this, err.into())
         }
     }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
 }
 
 pub fn async_wait<S, F>(sig: &S, handler: F) -> F::Output
@@ -131,7 +135,7 @@ where
     S: AsRawFd + AsyncReadOp,
     F: Handler<Signal, io::Error>,
 {
-    handler.wrap(sig.as_ctx(), |ctx, handler| {
+    handler.wrap_cancel(sig, |ctx, handler| {
         ctx.do_dispatch(SignalWait {
             sig: sig,
             handler: handler,
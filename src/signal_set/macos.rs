@@ -92,6 +92,10 @@ where
             Err(err) => self.failure(this, err.into()),
         }
     }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
 }
 
 pub fn async_wait<S, F>(sig: &S, handler: F) -> F::Output
@@ -99,7 +103,7 @@ where
     S: AsyncReadOp,
     F: Handler<Signal, io::Error>,
 {
-    handler.wrap(sig.as_ctx(), move |ctx, handler| {
+    handler.wrap_cancel(sig, move |ctx, handler| {
         ctx.do_dispatch(SignalWait {
             sig: sig,
             handler: handler,
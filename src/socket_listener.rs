@@ -102,6 +102,10 @@ impl<P: 'static> Cancel for SocketListener<P> {
     fn cancel(&self) {
         self.pimpl.cancel()
     }
+
+    fn cancel_expired(&self) {
+        self.pimpl.cancel_expired()
+    }
 }
 
 impl<P> AsyncReadOp for SocketListener<P>
@@ -1,12 +1,21 @@
-use ffi::Timeout;
-use core::{AsIoContext, IoContext, ThreadIoContext, Cancel};
-use handler::{Handler};
-use strand::{Strand, StrandImmutable, StrandHandler};
+use ffi::{SystemError, Timeout, ALREADY_STARTED, OPERATION_CANCELED};
+use core::{AsIoContext, Exec, IoContext, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+use strand::{Strand, StrandImpl, StrandImmutable};
 use SteadyTimer;
 
 use context::{Context, Transfer};
 use context::stack::{ProtectedFixedSizeStack, Stack, StackError};
 
+use std::io;
+use std::any::Any;
+use std::error;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::marker::PhantomData;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
 trait CoroutineExec: Send + 'static {
     fn call_box(self: Box<Self>, coro: Coroutine);
 }
@@ -23,6 +32,11 @@ where
 pub struct CoroutineData {
     context: Option<Context>,
     timer: SteadyTimer,
+    name: Option<String>,
+    yield_id: usize,
+    suspended: bool,
+    canceled: bool,
+    cancel: Option<CancelRef>,
 }
 
 unsafe impl AsIoContext for CoroutineData {
@@ -32,14 +46,22 @@ unsafe impl AsIoContext for CoroutineData {
 }
 
 #[derive(Clone)]
-struct CancelRef(*const Cancel, *const Timeout);
+struct CancelRef(*const Cancel, Option<*const Timeout>);
 
 impl CancelRef {
+    fn cancel(&self) {
+        unsafe { &*self.0 }.cancel()
+    }
+
     fn timeout(self, coro: &Strand<CoroutineData>) {
-        coro.timer.expires_from_now(unsafe { &*self.1 }.get());
+        let timeout = match self.1 {
+            Some(timeout) => unsafe { &*timeout }.get(),
+            None => return,
+        };
+        coro.timer.expires_from_now(timeout);
         coro.timer.async_wait(
             coro.wrap(move |_, res| if let Ok(_) = res {
-                unsafe { &*self.0 }.cancel();
+                self.cancel();
             }),
         )
     }
@@ -49,28 +71,58 @@ unsafe impl Send for CancelRef {}
 
 unsafe impl Sync for CancelRef {}
 
-type Caller<R, E> = fn(Strand<CoroutineData>, Result<R, E>);
-
-pub struct CoroutineHandler<R, E>(StrandHandler<CoroutineData, Caller<R, E>, R, E>);
+#[doc(hidden)]
+pub struct CoroutineComplete<R, E> {
+    data: Arc<StrandImpl<CoroutineData>>,
+    id: usize,
+    _marker: PhantomData<(R, E)>,
+}
 
-impl<R, E> Handler<R, E> for CoroutineHandler<R, E>
+impl<R, E> Complete<R, E> for CoroutineComplete<R, E>
 where
     R: Send + 'static,
     E: Send + 'static,
 {
-    type Output = Result<R, E>;
+    fn success(self, this: &mut ThreadIoContext, res: R) {
+        let CoroutineComplete { data, id, _marker } = self;
+        StrandImpl::run(this, &data, move |coro: Strand<CoroutineData>| caller(coro, id, Ok::<R, E>(res)))
+    }
 
-    #[doc(hidden)]
-    type WrappedHandler = StrandHandler<CoroutineData, fn(Strand<CoroutineData>, Result<R, E>), R, E>;
+    fn failure(self, this: &mut ThreadIoContext, err: E) {
+        let CoroutineComplete { data, id, _marker } = self;
+        StrandImpl::run(this, &data, move |coro: Strand<CoroutineData>| caller(coro, id, Err::<R, E>(err)))
+    }
+}
 
-    #[doc(hidden)]
-    fn wrap<W>(self, ctx: &IoContext, wrapper: W) -> Self::Output
+pub struct CoroutineHandler<R, E>(CoroutineComplete<R, E>);
+
+impl<R, E> CoroutineHandler<R, E>
+where
+    R: Send + 'static,
+    E: From<SystemError> + Send + 'static,
+{
+    fn suspend<W>(self, ctx: &IoContext, cancel: Option<CancelRef>, wrapper: W) -> Result<R, E>
     where
-        W: FnOnce(&IoContext, Self::WrappedHandler),
+        W: FnOnce(&IoContext, CoroutineComplete<R, E>),
     {
-        let mut data: Option<CancelRef> = None;
-        let coro: &mut CoroutineData = unsafe { &mut *self.0.data.clone().cell.get() };
-        wrapper(ctx, self.0);
+        let mut handler = self.0;
+        let coro: &mut CoroutineData = unsafe { &mut *handler.data.clone().cell.get() };
+        if coro.canceled && cancel.is_none() {
+            // the operation which has no object to cancel is not started.
+            coro.canceled = false;
+            return Err(OPERATION_CANCELED.into());
+        }
+        coro.yield_id = coro.yield_id.wrapping_add(1);
+        handler.id = coro.yield_id;
+        wrapper(ctx, handler);
+        if coro.canceled {
+            coro.canceled = false;
+            // the operation completes with the `OPERATION_CANCELED`.
+            if let Some(ref cancel) = cancel {
+                cancel.cancel();
+            }
+        }
+        let mut data = cancel;
         let Transfer { context, data } = unsafe {
             coro.context.take().unwrap().resume(
                 &mut data as *mut _ as usize,
@@ -78,33 +130,50 @@ where
         };
         coro.context = Some(context);
         coro.timer.cancel();
-        let res: &mut Option<Self::Output> = unsafe { &mut *(data as *mut Option<Self::Output>) };
+        let res: &mut Option<Result<R, E>> = unsafe { &mut *(data as *mut Option<Result<R, E>>) };
         res.take().unwrap()
     }
+}
+
+impl<R, E> Handler<R, E> for CoroutineHandler<R, E>
+where
+    R: Send + 'static,
+    E: From<SystemError> + Send + 'static,
+{
+    type Output = Result<R, E>;
+
+    #[doc(hidden)]
+    type WrappedHandler = CoroutineComplete<R, E>;
+
+    #[doc(hidden)]
+    fn wrap<W>(self, ctx: &IoContext, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        self.suspend(ctx, None, wrapper)
+    }
 
     #[doc(hidden)]
     fn wrap_timeout<W>(self, ctx: &Cancel, timeout: &Timeout, wrapper: W) -> Self::Output
     where
         W: FnOnce(&IoContext, Self::WrappedHandler),
     {
-        let mut data = Some(CancelRef(ctx, timeout));
-        let coro: &mut CoroutineData = unsafe { &mut *self.0.data.clone().cell.get() };
-        wrapper(ctx.as_ctx(), self.0);
-        let Transfer { context, data } = unsafe {
-            coro.context.take().unwrap().resume(
-                &mut data as *mut _ as usize,
-            )
-        };
-        coro.context = Some(context);
-        coro.timer.cancel();
-        let res: &mut Option<Self::Output> = unsafe { &mut *(data as *mut Option<Self::Output>) };
-        res.take().unwrap()
+        self.suspend(ctx.as_ctx(), Some(CancelRef(ctx, Some(timeout))), wrapper)
+    }
+
+    #[doc(hidden)]
+    fn wrap_cancel<W>(self, ctx: &Cancel, wrapper: W) -> Self::Output
+    where
+        W: FnOnce(&IoContext, Self::WrappedHandler),
+    {
+        self.suspend(ctx.as_ctx(), Some(CancelRef(ctx, None)), wrapper)
     }
 }
 
 struct InitData {
     stack: ProtectedFixedSizeStack,
     ctx: IoContext,
+    name: Option<String>,
     exec: Box<CoroutineExec>,
 }
 
@@ -113,7 +182,12 @@ pub struct Coroutine<'a>(Strand<'a, CoroutineData>);
 
 impl<'a> Coroutine<'a> {
     extern "C" fn entry(t: Transfer) -> ! {
-        let InitData { stack, ctx, exec } = unsafe { &mut *(t.data as *mut Option<InitData>) }
+        let InitData {
+            stack,
+            ctx,
+            name,
+            exec,
+        } = unsafe { &mut *(t.data as *mut Option<InitData>) }
             .take()
             .unwrap();
         let mut coro: StrandImmutable<CoroutineData> = Strand::new(
@@ -121,6 +195,11 @@ impl<'a> Coroutine<'a> {
             CoroutineData {
                 context: Some(t.context),
                 timer: SteadyTimer::new(&ctx),
+                name: name,
+                yield_id: 0,
+                suspended: false,
+                canceled: false,
+                cancel: None,
             },
         );
         let this = {
@@ -148,8 +227,15 @@ impl<'a> Coroutine<'a> {
         t
     }
 
+    /// Returns the name of the coroutine.
+    pub fn name(&self) -> Option<&str> {
+        self.0.name.as_ref().map(|name| name.as_str())
+    }
+
     /// Provides a `Coroutine` handler to asynchronous operation.
     ///
+    /// If the coroutine is canceled by the `JoinHandle::cancel` while waiting, it returns the `OPERATION_CANCELED`.
+    ///
     /// # Examples
     ///
     /// ```
@@ -167,29 +253,55 @@ impl<'a> Coroutine<'a> {
     pub fn wrap<R, E>(&self) -> CoroutineHandler<R, E>
     where
         R: Send + 'static,
-        E: Send + 'static,
+        E: From<SystemError> + Send + 'static,
     {
-        let handler: StrandHandler<CoroutineData, Caller<R, E>, R, E> = self.0.wrap(caller::<R, E>);
-        CoroutineHandler(handler)
+        CoroutineHandler(CoroutineComplete {
+            data: self.0.data.clone(),
+            id: 0,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// Resumes the suspended coroutine with the `data`.
+fn resume(mut coro: Strand<CoroutineData>, data: usize) {
+    coro.suspended = false;
+    let Transfer { context, data } = unsafe { coro.context.take().unwrap().resume(data) };
+    if data != 0 {
+        let cancel = unsafe { &mut *(data as *mut Option<CancelRef>) }.take();
+        if let Some(ref cancel) = cancel {
+            cancel.clone().timeout(&coro);
+        }
+        coro.cancel = cancel;
+        coro.context = Some(context);
+        coro.suspended = true;
     }
 }
 
-fn caller<R, E>(mut coro: Strand<CoroutineData>, res: Result<R, E>)
+fn caller<R, E>(coro: Strand<CoroutineData>, id: usize, res: Result<R, E>)
 where
     R: Send + 'static,
     E: Send + 'static,
 {
-    let mut data = Some(res);
-    let Transfer { context, data } = unsafe {
-        coro.context.take().unwrap().resume(
-            &mut data as *mut _ as usize,
-        )
-    };
-    if data != 0 {
-        if let Some(ctx) = unsafe { &mut *(data as *mut Option<CancelRef>) }.take() {
-            ctx.timeout(&coro);
+    // the result of the canceled wait is discarded.
+    if coro.suspended && coro.yield_id == id {
+        let mut data = Some(res);
+        resume(coro, &mut data as *mut _ as usize)
+    }
+}
+
+fn cancel(mut coro: Strand<CoroutineData>) {
+    if coro.suspended {
+        // the operation completes with the `OPERATION_CANCELED` by the cancel of its object.
+        // the one which has no object is not interrupted,
+        // since it may still use the buffers on the stack of the coroutine.
+        if let Some(ref cancel) = coro.cancel {
+            cancel.cancel();
+            return;
         }
-        coro.context = Some(context);
+        coro.canceled = true;
+    } else if coro.context.is_some() {
+        coro.canceled = true;
     }
 }
 
@@ -199,31 +311,362 @@ unsafe impl<'a> AsIoContext for Coroutine<'a> {
     }
 }
 
+/// Options of the coroutine which is spawned by the `spawn_with`.
+#[derive(Clone, Debug)]
+pub struct CoroutineOptions {
+    /// The stack size in bytes. The default is `Stack::default_size()`.
+    pub stack_size: usize,
+
+    /// The name for identification.
+    pub name: Option<String>,
+}
+
+impl Default for CoroutineOptions {
+    fn default() -> Self {
+        CoroutineOptions {
+            stack_size: Stack::default_size(),
+            name: None,
+        }
+    }
+}
+
+/// The error of joining on the coroutine which has panicked, which holds the payload of the panic.
+///
+/// It is wrapped in the `io::Error` of the kind `Other`.
+///
+/// # Examples
+///
+/// ```
+/// use std::panic;
+/// use asyncio::{IoContext, Panicked, spawn_with};
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let coro = spawn_with(ctx, Default::default(), |_| -> () { panic!("coroutine") }).unwrap();
+/// ctx.run();
+/// let err = coro.join().unwrap_err();
+/// let panicked = err.into_inner().unwrap().downcast::<Panicked>().unwrap();
+/// let res = panic::catch_unwind(|| panic::resume_unwind(panicked.into_panic()));
+/// assert_eq!(res.unwrap_err().downcast_ref::<&str>(), Some(&"coroutine"));
+/// ```
+pub struct Panicked(Mutex<Box<Any + Send>>);
+
+impl Panicked {
+    /// Returns the payload of the panic, which can be resumed by the `panic::resume_unwind`.
+    pub fn into_panic(self) -> Box<Any + Send> {
+        self.0.into_inner().unwrap()
+    }
+}
+
+impl fmt::Debug for Panicked {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Panicked { .. }")
+    }
+}
+
+impl fmt::Display for Panicked {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("coroutine panicked")
+    }
+}
+
+impl error::Error for Panicked {
+    fn description(&self) -> &str {
+        "coroutine panicked"
+    }
+}
+
+fn panicked(err: Box<Any + Send>) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, Panicked(Mutex::new(err)))
+}
+
+trait JoinComplete<T>: Send + 'static {
+    fn complete_box(self: Box<Self>, this: &mut ThreadIoContext, res: io::Result<T>);
+}
+
+impl<T, F> JoinComplete<T> for F
+where
+    F: Complete<T, io::Error>,
+{
+    fn complete_box(self: Box<Self>, this: &mut ThreadIoContext, res: io::Result<T>) {
+        complete(*self, this, res)
+    }
+}
+
+fn complete<T, F>(handler: F, this: &mut ThreadIoContext, res: io::Result<T>)
+where
+    F: Complete<T, io::Error>,
+{
+    match res {
+        Ok(res) => handler.success(this, res),
+        Err(err) => handler.failure(this, err),
+    }
+}
+
+struct JoinInner<T> {
+    result: Option<thread::Result<T>>,
+    waiter: Option<Box<JoinComplete<T>>>,
+    joined: bool,
+}
+
+struct JoinState<T> {
+    ctx: IoContext,
+    mutex: Mutex<JoinInner<T>>,
+    condvar: Condvar,
+}
+
+impl<T> JoinState<T>
+where
+    T: Send + 'static,
+{
+    fn finish(&self, res: thread::Result<T>) {
+        let mut inner = self.mutex.lock().unwrap();
+        if let Some(waiter) = inner.waiter.take() {
+            self.ctx.do_post(JoinDone {
+                res: res.map_err(panicked),
+                waiter: waiter,
+            });
+        } else {
+            inner.result = Some(res);
+            self.condvar.notify_all();
+        }
+    }
+}
+
+unsafe impl<T> AsIoContext for JoinState<T> {
+    fn as_ctx(&self) -> &IoContext {
+        &self.ctx
+    }
+}
+
+/// The waiting `async_join` completes with the `OPERATION_CANCELED`, and the coroutine can be joined again.
+impl<T> Cancel for JoinState<T>
+where
+    T: Send + 'static,
+{
+    fn cancel(&self) {
+        let mut inner = self.mutex.lock().unwrap();
+        if let Some(waiter) = inner.waiter.take() {
+            inner.joined = false;
+            self.ctx.do_post(JoinDone {
+                res: Err(OPERATION_CANCELED.into()),
+                waiter: waiter,
+            });
+        }
+    }
+}
+
+struct JoinWait<T, F> {
+    state: Arc<JoinState<T>>,
+    handler: F,
+}
+
+impl<T, F> Exec for JoinWait<T, F>
+where
+    T: Send + 'static,
+    F: Complete<T, io::Error>,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        let JoinWait { state, handler } = self;
+        let mut inner = state.mutex.lock().unwrap();
+        if inner.joined {
+            drop(inner);
+            handler.failure(this, ALREADY_STARTED.into())
+        } else {
+            inner.joined = true;
+            if let Some(res) = inner.result.take() {
+                drop(inner);
+                complete(handler, this, res.map_err(panicked))
+            } else {
+                inner.waiter = Some(Box::new(handler));
+            }
+        }
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.call(this)
+    }
+}
+
+struct JoinDone<T> {
+    res: io::Result<T>,
+    waiter: Box<JoinComplete<T>>,
+}
+
+unsafe impl<T: Send> Send for JoinDone<T> {}
+
+impl<T> Exec for JoinDone<T>
+where
+    T: Send + 'static,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        let JoinDone { res, waiter } = self;
+        waiter.complete_box(this, res)
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.call(this)
+    }
+
+    fn outstanding_work(&self, _: &IoContext) {}
+}
+
+/// An owned permission to join on the coroutine.
+///
+/// The coroutine is detached when the `JoinHandle` is dropped.
+pub struct JoinHandle<T> {
+    ctx: IoContext,
+    data: Arc<StrandImpl<CoroutineData>>,
+    state: Arc<JoinState<T>>,
+    name: Option<String>,
+}
+
+impl<T> JoinHandle<T>
+where
+    T: Send + 'static,
+{
+    /// Asynchronously waits for the coroutine to finish, and completes with the returned value.
+    ///
+    /// If the coroutine has panicked, it fails with the `Panicked` error, whose payload the `handler` may resume.
+    /// It fails with `ALREADY_STARTED` if the coroutine has been already joined.
+    pub fn async_join<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<T, io::Error>,
+    {
+        handler.wrap_cancel(&*self.state, |ctx, handler| {
+            ctx.do_dispatch(JoinWait {
+                state: self.state.clone(),
+                handler: handler,
+            })
+        })
+    }
+
+    /// Waits for the coroutine to finish, and returns the returned value.
+    ///
+    /// If the coroutine has panicked, it fails with the `Panicked` error.
+    /// Must not be called from the thread running the `IoContext` unless another thread runs it.
+    pub fn join(self) -> io::Result<T> {
+        let mut inner = self.state.mutex.lock().unwrap();
+        if inner.joined {
+            return Err(ALREADY_STARTED.into());
+        }
+        inner.joined = true;
+        loop {
+            if let Some(res) = inner.result.take() {
+                return match res {
+                    Ok(res) => Ok(res),
+                    Err(err) => Err(panicked(err)),
+                };
+            }
+            inner = self.state.condvar.wait(inner).unwrap();
+        }
+    }
+
+    /// Cancels the coroutine.
+    ///
+    /// The asynchronous operation which the coroutine currently waits for, including `async_wait` of the timer
+    /// and `async_join`, returns `OPERATION_CANCELED`. If the coroutine is not waiting, the next one returns it.
+    /// The other operations of the object are also canceled, same as its `cancel`.
+    pub fn cancel(&self) {
+        self.ctx.do_dispatch((self.data.clone(), cancel));
+        if ThreadIoContext::callstack(&self.ctx).is_none() {
+            self.ctx.as_reactor().interrupt();
+        }
+    }
+
+    /// Returns the name of the coroutine.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|name| name.as_str())
+    }
+}
+
+unsafe impl<T> AsIoContext for JoinHandle<T> {
+    fn as_ctx(&self) -> &IoContext {
+        if let Some(this) = ThreadIoContext::callstack(&self.ctx) {
+            this.as_ctx()
+        } else {
+            &self.ctx
+        }
+    }
+}
+
+impl<T> Cancel for JoinHandle<T>
+where
+    T: Send + 'static,
+{
+    fn cancel(&self) {
+        JoinHandle::cancel(self)
+    }
+}
+
+/// Spawns a coroutine with the default options.
 pub fn spawn<F>(ctx: &IoContext, func: F) -> Result<(), StackError>
 where
     F: FnOnce(Coroutine) + Send + 'static,
 {
+    spawn_with(ctx, CoroutineOptions::default(), func).map(|_| ())
+}
+
+/// Spawns a coroutine with the `options`, and returns a `JoinHandle` for it.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::{IoContext, AsIoContext, CoroutineOptions, spawn_with};
+/// use asyncio::ip::{IpProtocol, Tcp, TcpEndpoint, TcpListener};
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let options = CoroutineOptions { stack_size: 64 * 1024, name: Some("acceptor".to_string()) };
+/// let coro = spawn_with(ctx, options, |coro| {
+///     let soc = TcpListener::new(coro.as_ctx(), Tcp::v4()).unwrap();
+///     soc.bind(&TcpEndpoint::new(Tcp::v4(), 0)).unwrap();
+///     soc.listen().unwrap();
+///     soc.async_accept(coro.wrap()).is_err()
+/// }).unwrap();
+/// assert_eq!(coro.name(), Some("acceptor"));
+/// coro.cancel();
+/// ctx.run();
+/// assert!(coro.join().unwrap());
+/// ```
+pub fn spawn_with<F, T>(ctx: &IoContext, options: CoroutineOptions, func: F) -> Result<JoinHandle<T>, StackError>
+where
+    F: FnOnce(Coroutine) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let state = Arc::new(JoinState {
+        ctx: ctx.clone(),
+        mutex: Mutex::new(JoinInner {
+            result: None,
+            waiter: None,
+            joined: false,
+        }),
+        condvar: Condvar::new(),
+    });
+    let join = state.clone();
     let data = InitData {
-        stack: ProtectedFixedSizeStack::new(Stack::default_size())?,
+        stack: ProtectedFixedSizeStack::new(options.stack_size)?,
         ctx: ctx.clone(),
-        exec: Box::new(func),
+        name: options.name.clone(),
+        exec: Box::new(move |coro: Coroutine| {
+            let res = panic::catch_unwind(AssertUnwindSafe(move || func(coro)));
+            join.finish(res)
+        }),
     };
     let context = unsafe { Context::new(&data.stack, Coroutine::entry) };
     let data = Some(data);
     let Transfer { context, data } = unsafe { context.resume(&data as *const _ as usize) };
     let coro = unsafe { &mut *(data as *mut StrandImmutable<CoroutineData>) };
     unsafe { coro.get() }.context = Some(context);
-    coro.post(move |mut coro| {
-        let data = coro.this as *mut _ as usize;
-        let Transfer { context, data } = unsafe { coro.context.take().unwrap().resume(data) };
-        if data != 0 {
-            if let Some(ctx) = unsafe { &mut *(data as *mut Option<CancelRef>) }.take() {
-                ctx.timeout(&coro);
-            }
-            coro.context = Some(context);
-        }
+    let strand = coro.data.clone();
+    coro.post(move |coro| {
+        let data = coro.this as usize;
+        resume(coro, data)
     });
-    Ok(())
+    Ok(JoinHandle {
+        ctx: ctx.clone(),
+        data: strand,
+        state: state,
+        name: options.name,
+    })
 }
 
 #[test]
@@ -232,3 +675,165 @@ fn test_spawn() {
     spawn(ctx, |coro| {});
     ctx.run();
 }
+
+#[test]
+fn test_spawn_with() {
+    let ctx = &IoContext::new().unwrap();
+    let options = CoroutineOptions {
+        stack_size: 32 * 1024,
+        name: Some("test".to_string()),
+    };
+    let coro = spawn_with(ctx, options, |coro| coro.name().unwrap().len()).unwrap();
+    assert_eq!(coro.name(), Some("test"));
+    ctx.run();
+    assert_eq!(coro.join().unwrap(), 4);
+}
+
+#[test]
+fn test_async_join() {
+    use std::time::Duration;
+
+    let ctx = &IoContext::new().unwrap();
+    let first = spawn_with(ctx, Default::default(), |coro| {
+        let timer = SteadyTimer::new(coro.as_ctx());
+        timer.expires_from_now(Duration::new(0, 1000000));
+        timer.async_wait(coro.wrap()).unwrap();
+        1
+    }).unwrap();
+    let second = spawn_with(ctx, Default::default(), move |coro| {
+        let res = first.async_join(coro.wrap()).unwrap();
+        let err = first.async_join(coro.wrap()).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(::libc::EALREADY));
+        res + 1
+    }).unwrap();
+    ctx.run();
+    assert_eq!(second.join().unwrap(), 2);
+}
+
+#[test]
+fn test_coroutine_cancel() {
+    use ip::{IpAddrV4, IpProtocol, Tcp, TcpEndpoint, TcpListener};
+    use std::time::{Duration, Instant};
+
+    let ctx = &IoContext::new().unwrap();
+    let coro = Arc::new(spawn_with(ctx, Default::default(), |coro| {
+        let soc = TcpListener::new(coro.as_ctx(), Tcp::v4()).unwrap();
+        soc.bind(&TcpEndpoint::new(IpAddrV4::loopback(), 0)).unwrap();
+        soc.listen().unwrap();
+        soc.async_accept(coro.wrap()).unwrap_err().raw_os_error()
+    }).unwrap());
+    let handle = coro.clone();
+    ctx.post(move |_| handle.cancel());
+    let now = Instant::now();
+    ctx.run();
+    assert!(now.elapsed() < Duration::new(60, 0));
+    assert_eq!(
+        Arc::try_unwrap(coro).ok().unwrap().join().unwrap(),
+        Some(::libc::ECANCELED)
+    );
+}
+
+#[test]
+fn test_coroutine_cancel_timer() {
+    use std::time::{Duration, Instant};
+
+    let ctx = &IoContext::new().unwrap();
+    let coro = Arc::new(spawn_with(ctx, Default::default(), |coro| {
+        let timer = SteadyTimer::new(coro.as_ctx());
+        timer.expires_from_now(Duration::new(60, 0));
+        let first = timer.async_wait(coro.wrap()).unwrap_err().raw_os_error();
+        // the next wait is not canceled.
+        timer.expires_from_now(Duration::new(0, 1000000));
+        let second = timer.async_wait(coro.wrap()).is_ok();
+        (first, second)
+    }).unwrap());
+    let handle = coro.clone();
+    ctx.post(move |_| handle.cancel());
+    let now = Instant::now();
+    ctx.run();
+    assert!(now.elapsed() < Duration::new(60, 0));
+    assert_eq!(
+        Arc::try_unwrap(coro).ok().unwrap().join().unwrap(),
+        (Some(::libc::ECANCELED), true)
+    );
+}
+
+#[test]
+fn test_coroutine_cancel_join() {
+    use std::time::{Duration, Instant};
+
+    let ctx = &IoContext::new().unwrap();
+    let coro = Arc::new(spawn_with(ctx, Default::default(), |coro| {
+        let sleeper = spawn_with(coro.as_ctx(), Default::default(), |coro| {
+            let timer = SteadyTimer::new(coro.as_ctx());
+            timer.expires_from_now(Duration::new(60, 0));
+            timer.async_wait(coro.wrap()).unwrap_err().raw_os_error()
+        }).unwrap();
+        let first = sleeper.async_join(coro.wrap()).unwrap_err().raw_os_error();
+        // the canceled coroutine can be joined again.
+        sleeper.cancel();
+        let second = sleeper.async_join(coro.wrap()).unwrap();
+        (first, second)
+    }).unwrap());
+    let handle = coro.clone();
+    ctx.post(move |_| handle.cancel());
+    let now = Instant::now();
+    ctx.run();
+    assert!(now.elapsed() < Duration::new(60, 0));
+    assert_eq!(
+        Arc::try_unwrap(coro).ok().unwrap().join().unwrap(),
+        (Some(::libc::ECANCELED), Some(::libc::ECANCELED))
+    );
+}
+
+#[test]
+fn test_coroutine_cancel_before_start() {
+    use ip::{IpAddrV4, IpProtocol, Tcp, TcpEndpoint, TcpListener};
+
+    let ctx = &IoContext::new().unwrap();
+    let coro = spawn_with(ctx, Default::default(), |coro| {
+        let soc = TcpListener::new(coro.as_ctx(), Tcp::v4()).unwrap();
+        soc.bind(&TcpEndpoint::new(IpAddrV4::loopback(), 0)).unwrap();
+        soc.listen().unwrap();
+        soc.async_accept(coro.wrap()).unwrap_err().raw_os_error()
+    }).unwrap();
+    coro.cancel();
+    ctx.run();
+    assert_eq!(coro.join().unwrap(), Some(::libc::ECANCELED));
+}
+
+#[test]
+fn test_coroutine_panic() {
+    let ctx = &IoContext::new().unwrap();
+    let coro = spawn_with(ctx, Default::default(), |_| -> () { panic!("coroutine") }).unwrap();
+    ctx.run();
+    let err = coro.join().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+    let err = err.into_inner().unwrap().downcast::<Panicked>().unwrap().into_panic();
+    assert_eq!(err.downcast_ref::<&str>(), Some(&"coroutine"));
+}
+
+#[test]
+fn test_async_join_panic() {
+    use std::time::Duration;
+
+    let ctx = &IoContext::new().unwrap();
+    let first = spawn_with(ctx, Default::default(), |_| -> () { panic!("first") }).unwrap();
+    let second = spawn_with(ctx, Default::default(), |coro| -> () {
+        let timer = SteadyTimer::new(coro.as_ctx());
+        timer.expires_from_now(Duration::new(0, 1000000));
+        timer.async_wait(coro.wrap()).unwrap();
+        panic!("second")
+    }).unwrap();
+    // joins on the coroutine which has already finished, and the one which has not.
+    let third = spawn_with(ctx, Default::default(), move |coro| {
+        let first = first.async_join(coro.wrap()).unwrap_err();
+        let second = second.async_join(coro.wrap()).unwrap_err();
+        (first.into_inner().unwrap().downcast::<Panicked>().unwrap().into_panic(),
+         second.into_inner().unwrap().downcast::<Panicked>().unwrap().into_panic())
+    }).unwrap();
+    ctx.run();
+    let (first, second) = third.join().unwrap();
+    assert_eq!(first.downcast_ref::<&str>(), Some(&"first"));
+    assert_eq!(second.downcast_ref::<&str>(), Some(&"second"));
+}
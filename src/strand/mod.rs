@@ -22,6 +22,7 @@ where
         self(Strand {
             this: this,
             data: data,
+            _marker: PhantomData,
         });
         this.decrease_outstanding_work();
     }
@@ -30,6 +31,7 @@ where
         self(Strand {
             this: this,
             data: data,
+            _marker: PhantomData,
         });
         this.decrease_outstanding_work();
     }
@@ -154,8 +156,9 @@ where
 
 /// Provides serialized data and handler execution.
 pub struct Strand<'a, T: 'a> {
-    this: &'a mut ThreadIoContext,
+    this: *mut ThreadIoContext,
     data: &'a Arc<StrandImpl<T>>,
+    _marker: PhantomData<&'a mut ThreadIoContext>,
 }
 
 impl<'a, T> Strand<'a, T>
@@ -176,6 +179,8 @@ where
     }
 
     /// Returns a `&mut T` to the memory safely.
+    // the data is accessed by only one handler at a time, which is serialized by the strand.
+    #[allow(clippy::mut_from_ref)]
     pub fn get(&self) -> &mut T {
         unsafe { &mut *self.data.cell.get() }
     }
@@ -185,8 +190,8 @@ where
     where
         F: FnOnce(Strand<T>) + Send + 'static,
     {
-        self.this.increase_outstanding_work();
-        let this = &mut unsafe { &mut *(self as *const _ as *mut Self) }.this;
+        let this = unsafe { &mut *self.this };
+        this.increase_outstanding_work();
         StrandImpl::run(this, self.data, func)
     }
 
@@ -195,7 +200,7 @@ where
     where
         F: FnOnce(Strand<T>) + Send + 'static,
     {
-        self.this.increase_outstanding_work();
+        unsafe { &*self.this }.increase_outstanding_work();
         let mut owner = self.data.mutex.lock().unwrap();
         debug_assert_eq!(owner.locked, true);
         owner.queue.push_back(Box::new(func))
@@ -220,7 +225,7 @@ where
 
 unsafe impl<'a, T> AsIoContext for Strand<'a, T> {
     fn as_ctx(&self) -> &IoContext {
-        self.this.as_ctx()
+        unsafe { &*self.this }.as_ctx()
     }
 }
 
@@ -290,6 +295,7 @@ where
         Strand {
             this: this,
             data: &self.data,
+            _marker: PhantomData,
         }
     }
 }
@@ -308,11 +314,135 @@ impl<'a, T> Deref for StrandImmutable<'a, T> {
     }
 }
 
+/// Provides an owned strand, which serializes the handler execution and the access to the data.
+///
+/// It is cheap to clone, and may be stored in the structs or moved into the handlers,
+/// and used from many threads which run the `IoContext`.
+/// The data is only accessible by the `Strand` which is given to the handler.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::{IoContext, StrandOwned};
+/// use std::thread;
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let st = StrandOwned::new(ctx, 0);
+/// for _ in 0..100 {
+///     st.post(|mut st| *st += 1);
+/// }
+/// let threads: Vec<_> = (0..4).map(|_| { let ctx = ctx.clone(); thread::spawn(move || ctx.run()) }).collect();
+/// for t in threads {
+///     t.join().unwrap();
+/// }
+/// ctx.restart();
+/// st.dispatch(|st| assert_eq!(*st, 100));
+/// ctx.run();
+/// ```
+pub struct StrandOwned<T> {
+    ctx: IoContext,
+    data: Arc<StrandImpl<T>>,
+}
+
+impl<T> StrandOwned<T>
+where
+    T: Send + 'static,
+{
+    pub fn new(ctx: &IoContext, data: T) -> Self {
+        StrandOwned {
+            ctx: ctx.clone(),
+            data: Arc::new(StrandImpl {
+                mutex: Mutex::new(StrandQueue {
+                    locked: false,
+                    queue: VecDeque::new(),
+                }),
+                cell: UnsafeCell::new(data),
+            }),
+        }
+    }
+
+    /// Request the strand to invoke the given handler.
+    ///
+    /// The handler may be invoked in this function if it is called from the thread running the `IoContext`.
+    pub fn dispatch<F>(&self, func: F)
+    where
+        F: FnOnce(Strand<T>) + Send + 'static,
+    {
+        self.ctx.do_dispatch((self.data.clone(), func))
+    }
+
+    /// Request the strand to invoke the given handler and return immediately.
+    pub fn post<F>(&self, func: F)
+    where
+        F: FnOnce(Strand<T>) + Send + 'static,
+    {
+        self.ctx.do_post((self.data.clone(), func))
+    }
+
+    /// Request the strand to invoke the given handler after the currently executing handler of the strand.
+    ///
+    /// If this thread is running the `IoContext` and the strand is executing a handler on any thread,
+    /// the handler is queued to the strand without passing through the `IoContext`, otherwise it is same as the `post`.
+    pub fn defer<F>(&self, func: F)
+    where
+        F: FnOnce(Strand<T>) + Send + 'static,
+    {
+        if let Some(this) = ThreadIoContext::callstack(&self.ctx) {
+            let mut owner = self.data.mutex.lock().unwrap();
+            if owner.locked {
+                this.increase_outstanding_work();
+                owner.queue.push_back(Box::new(func));
+                return;
+            }
+        }
+        self.post(func)
+    }
+
+    /// Provides a `Strand` handler to asynchronous operation.
+    ///
+    /// The StrandHandler has trait the `Handler`, that type of `Handler::Output` is `()`.
+    pub fn wrap<F, R, E>(&self, handler: F) -> StrandHandler<T, F, R, E>
+    where
+        F: FnOnce(Strand<T>, Result<R, E>) + Send + 'static,
+        R: Send + 'static,
+        E: Send + 'static,
+    {
+        StrandHandler {
+            data: self.data.clone(),
+            handler: handler,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for StrandOwned<T> {
+    fn clone(&self) -> Self {
+        StrandOwned {
+            ctx: self.ctx.clone(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+unsafe impl<T: Send> Send for StrandOwned<T> {}
+
+unsafe impl<T: Send> Sync for StrandOwned<T> {}
+
+unsafe impl<T> AsIoContext for StrandOwned<T> {
+    fn as_ctx(&self) -> &IoContext {
+        if let Some(this) = ThreadIoContext::callstack(&self.ctx) {
+            this.as_ctx()
+        } else {
+            &self.ctx
+        }
+    }
+}
+
 
 #[cfg(feature = "context")]
 mod coroutine;
 #[cfg(feature = "context")]
-pub use self::coroutine::{spawn, Coroutine, CoroutineHandler};
+pub use self::coroutine::{spawn, spawn_with, Coroutine, CoroutineHandler, CoroutineOptions, JoinHandle, Panicked};
 
 #[test]
 fn test_strand() {
@@ -338,3 +468,56 @@ fn test_strand_post() {
     ctx.run();
     assert_eq!(*st, 1);
 }
+
+#[test]
+fn test_strand_owned() {
+    use std::thread;
+
+    let ctx = &IoContext::new().unwrap();
+    let st = StrandOwned::new(ctx, 0);
+    for _ in 0..1000 {
+        let st2 = st.clone();
+        st.post(move |mut st| {
+            *st += 1;
+            st2.defer(|mut st| *st += 1);
+        });
+    }
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let ctx = ctx.clone();
+            thread::spawn(move || ctx.run())
+        })
+        .collect();
+    for t in threads {
+        t.join().unwrap();
+    }
+    ctx.restart();
+    st.dispatch(|st| assert_eq!(*st, 2000));
+    ctx.run();
+}
+
+#[test]
+fn test_strand_owned_wrap() {
+    use std::io;
+    use std::time::Duration;
+    use SteadyTimer;
+
+    struct Conn {
+        st: StrandOwned<(SteadyTimer, bool)>,
+    }
+
+    let ctx = &IoContext::new().unwrap();
+    let conn = Conn { st: StrandOwned::new(ctx, (SteadyTimer::new(ctx), false)) };
+    let st = conn.st.clone();
+    conn.st.dispatch(move |st2| {
+        st2.0.expires_from_now(Duration::new(0, 1000000));
+        st2.0.async_wait(st.wrap(|mut st, res: io::Result<()>| {
+            res.unwrap();
+            st.1 = true;
+        }));
+    });
+    ctx.run();
+    ctx.restart();
+    conn.st.dispatch(|st| assert!(st.1));
+    ctx.run();
+}
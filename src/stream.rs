@@ -60,6 +60,10 @@ where
             self.handler.failure(this, err)
         }
     }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
 }
 
 struct AsyncReadUntil<F, S, M> {
@@ -125,6 +129,10 @@ where
     fn failure(self, this: &mut ThreadIoContext, err: S::Error) {
         self.handler.failure(this, err)
     }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
 }
 
 struct AsyncWriteAt<F, S> {
@@ -182,6 +190,10 @@ where
     fn failure(self, this: &mut ThreadIoContext, err: S::Error) {
         self.handler.failure(this, err)
     }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
 }
 
 pub trait Stream: AsIoContext + Cancel + Sized + Send + 'static {
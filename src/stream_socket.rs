@@ -152,6 +152,10 @@ impl<P: 'static> Cancel for StreamSocket<P> {
     fn cancel(&self) {
         self.pimpl.cancel()
     }
+
+    fn cancel_expired(&self) {
+        self.pimpl.cancel_expired()
+    }
 }
 
 impl<P> AsyncReadOp for StreamSocket<P>
@@ -191,8 +191,7 @@ impl StreamBuf {
             return Err(NO_BUFFER_SPACE.into());
         }
 
-        self.buf.reserve(len.0);
-        unsafe { self.buf.set_len(len.0) };
+        self.buf.resize(len.0, 0);
         Ok(&mut self.buf[self.wpos.0..])
     }
 
@@ -220,8 +219,7 @@ impl StreamBuf {
         }
 
         len += self.wpos;
-        self.buf.reserve(len.0);
-        unsafe { self.buf.set_len(len.0) };
+        self.buf.resize(len.0, 0);
         Ok(&mut self.buf[self.wpos.0..])
     }
 
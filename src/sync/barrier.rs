@@ -0,0 +1,156 @@
+use core::{AsIoContext, IoContext, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+use sync::{self, Acquire, Waiter, async_acquire, cancel_waiters, wake};
+
+use std::io;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+struct BarrierState {
+    count: usize,
+    waiters: Vec<Box<Waiter<bool>>>,
+}
+
+struct BarrierImpl {
+    ctx: IoContext,
+    num: usize,
+    state: Mutex<BarrierState>,
+}
+
+impl Acquire<bool> for BarrierImpl {
+    fn acquire<F>(this: &Arc<Self>, thrd: &mut ThreadIoContext, handler: F)
+    where
+        F: Complete<bool, io::Error>,
+    {
+        let mut state = this.state.lock().unwrap();
+        state.count += 1;
+        if state.count < this.num {
+            state.waiters.push(Box::new(handler));
+        } else {
+            state.count = 0;
+            let waiters = mem::replace(&mut state.waiters, Vec::new());
+            drop(state);
+            for waiter in waiters {
+                wake(&this.ctx, waiter, Ok(false));
+            }
+            handler.success(thrd, true)
+        }
+    }
+}
+
+/// A barrier which parks the waiting handlers until all of them have arrived.
+///
+/// If the barrier is dropped, the pending `async_wait` fails with `OPERATION_CANCELED`.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use asyncio::{IoContext, AsyncBarrier, spawn};
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let barrier = Arc::new(AsyncBarrier::new(ctx, 3));
+/// for _ in 0..3 {
+///     let barrier = barrier.clone();
+///     spawn(ctx, move |coro| {
+///         barrier.async_wait(coro.wrap()).unwrap();
+///     }).unwrap();
+/// }
+/// ctx.run();
+/// ```
+pub struct AsyncBarrier {
+    pimpl: Arc<BarrierImpl>,
+}
+
+impl AsyncBarrier {
+    /// Returns a barrier for the `num` handlers.
+    ///
+    /// # Panics
+    /// Panics if `num` is zero.
+    pub fn new(ctx: &IoContext, num: usize) -> Self {
+        assert!(num > 0, "the number of the barrier must be non-zero");
+        AsyncBarrier {
+            pimpl: Arc::new(BarrierImpl {
+                ctx: ctx.clone(),
+                num: num,
+                state: Mutex::new(BarrierState {
+                    count: 0,
+                    waiters: Vec::new(),
+                }),
+            }),
+        }
+    }
+
+    /// Asynchronously waits until all handlers have arrived at the barrier.
+    ///
+    /// The last arrived one completes with `true` as the leader, and the others complete with `false`.
+    /// The barrier is reusable after all handlers are released.
+    pub fn async_wait<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<bool, io::Error>,
+    {
+        async_acquire(self, &self.pimpl, handler)
+    }
+}
+
+unsafe impl AsIoContext for AsyncBarrier {
+    fn as_ctx(&self) -> &IoContext {
+        sync::as_ctx(&self.pimpl.ctx)
+    }
+}
+
+impl Cancel for AsyncBarrier {
+    /// Fails the pending `async_wait` with `OPERATION_CANCELED`, and resets the count of the arrived handlers.
+    fn cancel(&self) {
+        let waiters = {
+            let mut state = self.pimpl.state.lock().unwrap();
+            state.count = 0;
+            mem::replace(&mut state.waiters, Vec::new())
+        };
+        cancel_waiters(&self.pimpl.ctx, waiters)
+    }
+}
+
+impl Drop for AsyncBarrier {
+    fn drop(&mut self) {
+        self.cancel()
+    }
+}
+
+#[test]
+fn test_async_barrier() {
+    use wrap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static LEADERS: AtomicUsize = AtomicUsize::new(0);
+
+    let ctx = &IoContext::new().unwrap();
+    let barrier = Arc::new(AsyncBarrier::new(ctx, 2));
+    for _ in 0..4 {
+        barrier.async_wait(wrap(&barrier, |_: Arc<AsyncBarrier>, res: io::Result<bool>| if res.unwrap() {
+            LEADERS.fetch_add(1, Ordering::SeqCst);
+        }));
+    }
+    ctx.run();
+    assert_eq!(LEADERS.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_async_barrier_drop() {
+    use wrap;
+    use SteadyTimer;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static CANCELED: AtomicBool = AtomicBool::new(false);
+
+    let ctx = &IoContext::new().unwrap();
+    let owner = Arc::new(SteadyTimer::new(ctx));
+    let barrier = AsyncBarrier::new(ctx, 2);
+    barrier.async_wait(wrap(&owner, |_: Arc<SteadyTimer>, res: io::Result<bool>| {
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(::libc::ECANCELED));
+        CANCELED.store(true, Ordering::SeqCst);
+    }));
+    ctx.post(move |_| drop(barrier));
+    ctx.run();
+    assert!(CANCELED.load(Ordering::SeqCst));
+}
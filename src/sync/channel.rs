@@ -0,0 +1,571 @@
+use ffi::{SystemError, BROKEN_PIPE, CONNECTION_ABORTED, OPERATION_CANCELED, WOULD_BLOCK};
+use core::{AsIoContext, Exec, IoContext, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+use sync::{self, Acquire, Waiter, async_acquire, wake};
+
+use std::io;
+use std::mem;
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+
+/// The result of the sender which is blocked in the `SyncSender::send`.
+struct SyncSlot {
+    res: Mutex<Option<io::Result<()>>>,
+    condvar: Condvar,
+}
+
+enum Sender {
+    Async(Box<Waiter<()>>),
+    Sync(Arc<SyncSlot>),
+}
+
+impl Sender {
+    fn complete(self, ctx: &IoContext, res: io::Result<()>) {
+        match self {
+            Sender::Async(waiter) => wake(ctx, waiter, res),
+            Sender::Sync(slot) => {
+                *slot.res.lock().unwrap() = Some(res);
+                slot.condvar.notify_one();
+            }
+        }
+    }
+}
+
+struct ChannelState<T> {
+    buffer: VecDeque<T>,
+    capacity: Option<usize>,
+    senders: VecDeque<(T, Sender)>,
+    receivers: VecDeque<Box<Waiter<T>>>,
+    handles: usize,
+    closed: bool,
+}
+
+impl<T> ChannelState<T>
+where
+    T: Send + 'static,
+{
+    fn is_full(&self) -> bool {
+        match self.capacity {
+            Some(capacity) => self.buffer.len() >= capacity,
+            None => false,
+        }
+    }
+
+    /// Hands over the `value` to the oldest receiver, or stores it into the buffer.
+    fn try_send(&mut self, ctx: &IoContext, value: T) -> Result<(), TrySendError<T>> {
+        if self.closed {
+            Err(TrySendError::Closed(value))
+        } else if let Some(waiter) = self.receivers.pop_front() {
+            wake(ctx, waiter, Ok(value));
+            Ok(())
+        } else if self.is_full() {
+            Err(TrySendError::Full(value))
+        } else {
+            self.buffer.push_back(value);
+            Ok(())
+        }
+    }
+
+    /// Takes the oldest value, and refills the buffer by the oldest sender.
+    fn try_receive(&mut self, ctx: &IoContext) -> Result<T, SystemError> {
+        if let Some(value) = self.buffer.pop_front() {
+            if let Some((next, sender)) = self.senders.pop_front() {
+                self.buffer.push_back(next);
+                sender.complete(ctx, Ok(()));
+            }
+            Ok(value)
+        } else if let Some((value, sender)) = self.senders.pop_front() {
+            sender.complete(ctx, Ok(()));
+            Ok(value)
+        } else if self.closed {
+            Err(CONNECTION_ABORTED)
+        } else {
+            Err(WOULD_BLOCK)
+        }
+    }
+
+    /// Closes the channel, and fails the pending senders and receivers.
+    fn close(&mut self, ctx: &IoContext) {
+        self.closed = true;
+        self.cancel(ctx, BROKEN_PIPE, CONNECTION_ABORTED)
+    }
+
+    /// Fails the pending senders and receivers with the `err`.
+    fn cancel(&mut self, ctx: &IoContext, send_err: SystemError, recv_err: SystemError) {
+        for (_, sender) in mem::replace(&mut self.senders, VecDeque::new()) {
+            sender.complete(ctx, Err(send_err.into()))
+        }
+        for waiter in mem::replace(&mut self.receivers, VecDeque::new()) {
+            wake(ctx, waiter, Err(recv_err.into()))
+        }
+    }
+}
+
+struct ChannelImpl<T> {
+    ctx: IoContext,
+    state: Mutex<ChannelState<T>>,
+}
+
+impl<T> Acquire<T> for ChannelImpl<T>
+where
+    T: Send + 'static,
+{
+    fn acquire<F>(this: &Arc<Self>, thrd: &mut ThreadIoContext, handler: F)
+    where
+        F: Complete<T, io::Error>,
+    {
+        let mut state = this.state.lock().unwrap();
+        match state.try_receive(&this.ctx) {
+            Ok(value) => {
+                drop(state);
+                handler.success(thrd, value)
+            }
+            Err(WOULD_BLOCK) => state.receivers.push_back(Box::new(handler)),
+            Err(err) => {
+                drop(state);
+                handler.failure(thrd, err.into())
+            }
+        }
+    }
+}
+
+struct AsyncSend<T, F> {
+    pimpl: Arc<ChannelImpl<T>>,
+    value: T,
+    handler: F,
+}
+
+impl<T, F> Exec for AsyncSend<T, F>
+where
+    T: Send + 'static,
+    F: Complete<(), io::Error>,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        let AsyncSend {
+            pimpl,
+            value,
+            handler,
+        } = self;
+        let mut state = pimpl.state.lock().unwrap();
+        match state.try_send(&pimpl.ctx, value) {
+            Ok(_) => {
+                drop(state);
+                handler.success(this, ())
+            }
+            Err(TrySendError::Full(value)) => state.senders.push_back((value, Sender::Async(Box::new(handler)))),
+            Err(TrySendError::Closed(_)) => {
+                drop(state);
+                handler.failure(this, BROKEN_PIPE.into())
+            }
+        }
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.call(this)
+    }
+}
+
+/// An error which is returned from the `try_send`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// The buffer of the channel is full, or no receiver is waiting for the rendezvous channel.
+    Full(T),
+
+    /// The channel has been closed.
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Returns the value which could not be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(value) => value,
+            TrySendError::Closed(value) => value,
+        }
+    }
+}
+
+/// A channel which passes the values between the handlers, the coroutines and the threads.
+///
+/// The channel is bounded, unbounded, or rendezvous which has no buffer.
+/// The `async_send` fails with `BROKEN_PIPE` if the channel has been closed,
+/// and the `async_receive` fails with `CONNECTION_ABORTED` after all values sent before the close are received.
+/// The channel is closed when the last handle of it is dropped, since no one is able to receive anymore.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use asyncio::{IoContext, Channel, spawn};
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let ch = Arc::new(Channel::new(ctx, 1));
+/// let tx = ch.clone();
+/// spawn(ctx, move |coro| {
+///     for i in 0..3 {
+///         tx.async_send(i, coro.wrap()).unwrap();
+///     }
+///     tx.close();
+/// }).unwrap();
+/// spawn(ctx, move |coro| {
+///     let mut values = Vec::new();
+///     while let Ok(value) = ch.async_receive(coro.wrap()) {
+///         values.push(value);
+///     }
+///     assert_eq!(values, [0, 1, 2]);
+/// }).unwrap();
+/// ctx.run();
+/// ```
+pub struct Channel<T>
+where
+    T: Send + 'static,
+{
+    pimpl: Arc<ChannelImpl<T>>,
+}
+
+impl<T> Channel<T>
+where
+    T: Send + 'static,
+{
+    fn with_capacity(ctx: &IoContext, capacity: Option<usize>) -> Self {
+        Channel {
+            pimpl: Arc::new(ChannelImpl {
+                ctx: ctx.clone(),
+                state: Mutex::new(ChannelState {
+                    buffer: VecDeque::new(),
+                    capacity: capacity,
+                    senders: VecDeque::new(),
+                    receivers: VecDeque::new(),
+                    handles: 1,
+                    closed: false,
+                }),
+            }),
+        }
+    }
+
+    /// Returns a channel which buffers up to the `capacity` values.
+    ///
+    /// If the `capacity` is zero, it is a rendezvous channel which the sender waits for the receiver.
+    pub fn new(ctx: &IoContext, capacity: usize) -> Self {
+        Self::with_capacity(ctx, Some(capacity))
+    }
+
+    /// Returns a channel which buffers any number of values.
+    pub fn unbounded(ctx: &IoContext) -> Self {
+        Self::with_capacity(ctx, None)
+    }
+
+    /// Asynchronously sends the `value`, and completes when it is buffered or received.
+    pub fn async_send<F>(&self, value: T, handler: F) -> F::Output
+    where
+        F: Handler<(), io::Error>,
+    {
+        handler.wrap_cancel(self, |ctx, handler| {
+            ctx.do_dispatch(AsyncSend {
+                pimpl: self.pimpl.clone(),
+                value: value,
+                handler: handler,
+            })
+        })
+    }
+
+    /// Asynchronously receives the oldest value.
+    pub fn async_receive<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<T, io::Error>,
+    {
+        async_acquire(self, &self.pimpl, handler)
+    }
+
+    /// Attempts to send the `value` without waiting.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        self.pimpl.state.lock().unwrap().try_send(
+            &self.pimpl.ctx,
+            value,
+        )
+    }
+
+    /// Attempts to receive the oldest value without waiting.
+    ///
+    /// It fails with `WOULD_BLOCK` if no value is available.
+    pub fn try_receive(&self) -> io::Result<T> {
+        Ok(self.pimpl.state.lock().unwrap().try_receive(
+            &self.pimpl.ctx,
+        )?)
+    }
+
+    /// Closes the channel.
+    ///
+    /// The pending senders fail with `BROKEN_PIPE`, and the pending receivers fail with `CONNECTION_ABORTED`.
+    /// The values in the buffer remain to be received.
+    pub fn close(&self) {
+        self.pimpl.state.lock().unwrap().close(&self.pimpl.ctx)
+    }
+
+    /// Returns `true` if the channel has not been closed.
+    pub fn is_open(&self) -> bool {
+        !self.pimpl.state.lock().unwrap().closed
+    }
+
+    /// Returns a sender which is able to send from the foreign threads.
+    pub fn sync_sender(&self) -> SyncSender<T> {
+        SyncSender { pimpl: self.pimpl.clone() }
+    }
+}
+
+impl<T> Clone for Channel<T>
+where
+    T: Send + 'static,
+{
+    fn clone(&self) -> Self {
+        self.pimpl.state.lock().unwrap().handles += 1;
+        Channel { pimpl: self.pimpl.clone() }
+    }
+}
+
+impl<T> Drop for Channel<T>
+where
+    T: Send + 'static,
+{
+    fn drop(&mut self) {
+        let mut state = self.pimpl.state.lock().unwrap();
+        state.handles -= 1;
+        if state.handles == 0 {
+            // the pending senders would wait forever, since no one is able to receive.
+            state.close(&self.pimpl.ctx)
+        }
+    }
+}
+
+unsafe impl<T> AsIoContext for Channel<T>
+where
+    T: Send + 'static,
+{
+    fn as_ctx(&self) -> &IoContext {
+        sync::as_ctx(&self.pimpl.ctx)
+    }
+}
+
+impl<T> Cancel for Channel<T>
+where
+    T: Send + 'static,
+{
+    /// Fails the pending `async_send` and `async_receive` with `OPERATION_CANCELED`.
+    fn cancel(&self) {
+        self.pimpl.state.lock().unwrap().cancel(
+            &self.pimpl.ctx,
+            OPERATION_CANCELED,
+            OPERATION_CANCELED,
+        )
+    }
+}
+
+/// A sender of the `Channel`, which blocks the current thread instead of parking a handler.
+///
+/// Must not be blocked on the thread running the `IoContext` unless another thread runs it.
+pub struct SyncSender<T> {
+    pimpl: Arc<ChannelImpl<T>>,
+}
+
+impl<T> SyncSender<T>
+where
+    T: Send + 'static,
+{
+    /// Sends the `value`, and blocks until it is buffered or received.
+    ///
+    /// It fails with `BROKEN_PIPE` if the channel has been closed.
+    pub fn send(&self, value: T) -> io::Result<()> {
+        let slot = {
+            let mut state = self.pimpl.state.lock().unwrap();
+            match state.try_send(&self.pimpl.ctx, value) {
+                Ok(_) => return Ok(()),
+                Err(TrySendError::Closed(_)) => return Err(BROKEN_PIPE.into()),
+                Err(TrySendError::Full(value)) => {
+                    let slot = Arc::new(SyncSlot {
+                        res: Mutex::new(None),
+                        condvar: Condvar::new(),
+                    });
+                    state.senders.push_back((value, Sender::Sync(slot.clone())));
+                    slot
+                }
+            }
+        };
+        let mut res = slot.res.lock().unwrap();
+        loop {
+            if let Some(res) = res.take() {
+                return res;
+            }
+            res = slot.condvar.wait(res).unwrap();
+        }
+    }
+
+    /// Attempts to send the `value` without blocking.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        self.pimpl.state.lock().unwrap().try_send(
+            &self.pimpl.ctx,
+            value,
+        )
+    }
+}
+
+impl<T> Clone for SyncSender<T> {
+    fn clone(&self) -> Self {
+        SyncSender { pimpl: self.pimpl.clone() }
+    }
+}
+
+#[test]
+fn test_channel_bounded() {
+    let ctx = &IoContext::new().unwrap();
+    let ch = Channel::new(ctx, 2);
+    ch.try_send(1).unwrap();
+    ch.try_send(2).unwrap();
+    assert_eq!(ch.try_send(3), Err(TrySendError::Full(3)));
+    assert_eq!(ch.try_receive().unwrap(), 1);
+    ch.close();
+    assert_eq!(ch.try_send(3), Err(TrySendError::Closed(3)));
+    assert_eq!(ch.try_receive().unwrap(), 2);
+    assert_eq!(ch.try_receive().unwrap_err().kind(), io::ErrorKind::ConnectionAborted);
+}
+
+#[test]
+fn test_channel_unbounded() {
+    let ctx = &IoContext::new().unwrap();
+    let ch = Channel::unbounded(ctx);
+    for i in 0..1000 {
+        ch.try_send(i).unwrap();
+    }
+    assert_eq!(ch.try_receive().unwrap(), 0);
+    assert_eq!(ch.try_receive().unwrap(), 1);
+}
+
+#[test]
+fn test_channel_rendezvous() {
+    use wrap;
+
+    let ctx = &IoContext::new().unwrap();
+    let ch = Arc::new(Channel::new(ctx, 0));
+    assert_eq!(ch.try_send(1), Err(TrySendError::Full(1)));
+    ch.async_send(1, wrap(&ch, |ch: Arc<Channel<i32>>, res: io::Result<()>| {
+        res.unwrap();
+        assert_eq!(ch.try_receive().unwrap_err().kind(), io::ErrorKind::WouldBlock);
+    }));
+    ch.async_receive(wrap(&ch, |_: Arc<Channel<i32>>, res: io::Result<i32>| {
+        assert_eq!(res.unwrap(), 1);
+    }));
+    ctx.run();
+}
+
+#[test]
+fn test_channel_close() {
+    use wrap;
+
+    let ctx = &IoContext::new().unwrap();
+    let ch = Arc::new(Channel::new(ctx, 0));
+    ch.async_receive(wrap(&ch, |_: Arc<Channel<i32>>, res: io::Result<i32>| {
+        assert_eq!(res.unwrap_err().kind(), io::ErrorKind::ConnectionAborted);
+    }));
+    let ch2 = ch.clone();
+    ctx.post(move |_| ch2.close());
+    ctx.run();
+    assert!(!ch.is_open());
+}
+
+#[test]
+fn test_channel_cancel() {
+    use wrap;
+
+    let ctx = &IoContext::new().unwrap();
+    let ch = Arc::new(Channel::new(ctx, 0));
+    ch.async_send(1, wrap(&ch, |_: Arc<Channel<i32>>, res: io::Result<()>| {
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(::libc::ECANCELED));
+    }));
+    let ch2 = ch.clone();
+    ctx.post(move |_| ch2.cancel());
+    ctx.run();
+    assert!(ch.is_open());
+}
+
+#[test]
+fn test_channel_sync_sender() {
+    use wrap;
+    use std::thread;
+
+    fn on_receive(ch: Arc<Channel<i32>>, res: io::Result<i32>) {
+        if let Ok(value) = res {
+            assert!(value < 100);
+            ch.async_receive(wrap(&ch, on_receive));
+        }
+    }
+
+    let ctx = &IoContext::new().unwrap();
+    let ch = Arc::new(Channel::new(ctx, 0));
+    let tx = ch.sync_sender();
+    let thrd = thread::spawn(move || {
+        for i in 0..100 {
+            tx.send(i).unwrap();
+        }
+        tx
+    });
+    ch.async_receive(wrap(&ch, on_receive));
+    let ctx2 = ctx.clone();
+    let run = thread::spawn(move || ctx2.run());
+    let tx = thrd.join().unwrap();
+    ch.close();
+    run.join().unwrap();
+    assert_eq!(tx.send(100).unwrap_err().kind(), io::ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn test_channel_drop() {
+    use std::thread;
+
+    let ctx = &IoContext::new().unwrap();
+    let ch = Channel::new(ctx, 0);
+    let ch2 = ch.clone();
+    let tx = ch.sync_sender();
+    let thrd = thread::spawn(move || tx.send(1));
+    drop(ch);
+    assert!(ch2.is_open());
+    drop(ch2);
+    assert_eq!(thrd.join().unwrap().unwrap_err().kind(), io::ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn test_channel_drop_async_send() {
+    use wrap;
+
+    let ctx = &IoContext::new().unwrap();
+    let ch = Channel::new(ctx, 0);
+    let errors = Arc::new(Channel::unbounded(ctx));
+    ch.async_send(1, wrap(&errors, |errors: Arc<Channel<io::ErrorKind>>, res: io::Result<()>| {
+        errors.try_send(res.unwrap_err().kind()).unwrap();
+    }));
+    ctx.post(move |_| drop(ch));
+    ctx.run();
+    assert_eq!(errors.try_receive().unwrap(), io::ErrorKind::BrokenPipe);
+}
+
+#[cfg(feature = "context")]
+#[test]
+fn test_channel_coroutine() {
+    use spawn;
+
+    let ctx = &IoContext::new().unwrap();
+    let ch = Arc::new(Channel::unbounded(ctx));
+    let rx = ch.clone();
+    spawn(ctx, move |coro| {
+        let mut sum = 0;
+        while let Ok(value) = rx.async_receive(coro.wrap()) {
+            sum += value;
+        }
+        assert_eq!(sum, 45);
+    }).unwrap();
+    spawn(ctx, move |coro| {
+        for i in 0..10 {
+            ch.async_send(i, coro.wrap()).unwrap();
+        }
+        ch.close();
+    }).unwrap();
+    ctx.run();
+}
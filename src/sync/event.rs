@@ -0,0 +1,173 @@
+use core::{AsIoContext, IoContext, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+use sync::{self, Acquire, Waiter, async_acquire, cancel_waiters, wake};
+
+use std::io;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+struct EventState {
+    signaled: bool,
+    waiters: Vec<Box<Waiter<()>>>,
+}
+
+struct EventImpl {
+    ctx: IoContext,
+    state: Mutex<EventState>,
+}
+
+impl Acquire<()> for EventImpl {
+    fn acquire<F>(this: &Arc<Self>, thrd: &mut ThreadIoContext, handler: F)
+    where
+        F: Complete<(), io::Error>,
+    {
+        let mut state = this.state.lock().unwrap();
+        if state.signaled {
+            drop(state);
+            handler.success(thrd, ())
+        } else {
+            state.waiters.push(Box::new(handler));
+        }
+    }
+}
+
+/// A manual-reset event which parks the waiting handlers until it is set.
+///
+/// If the event is dropped, the pending `async_wait` fails with `OPERATION_CANCELED`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::sync::Arc;
+/// use asyncio::{IoContext, AsyncEvent, wrap};
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let ev = Arc::new(AsyncEvent::new(ctx));
+/// ev.async_wait(wrap(&ev, |ev: Arc<AsyncEvent>, res: io::Result<()>| {
+///     res.unwrap();
+///     assert!(ev.is_set());
+/// }));
+/// ev.set();
+/// ctx.run();
+/// ```
+pub struct AsyncEvent {
+    pimpl: Arc<EventImpl>,
+}
+
+impl AsyncEvent {
+    /// Returns an event which is not set.
+    pub fn new(ctx: &IoContext) -> Self {
+        AsyncEvent {
+            pimpl: Arc::new(EventImpl {
+                ctx: ctx.clone(),
+                state: Mutex::new(EventState {
+                    signaled: false,
+                    waiters: Vec::new(),
+                }),
+            }),
+        }
+    }
+
+    /// Asynchronously waits until the event is set.
+    pub fn async_wait<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<(), io::Error>,
+    {
+        async_acquire(self, &self.pimpl, handler)
+    }
+
+    /// Sets the event, and wakes up all waiters.
+    ///
+    /// The event remains set until the `reset` is called.
+    pub fn set(&self) {
+        let waiters = {
+            let mut state = self.pimpl.state.lock().unwrap();
+            state.signaled = true;
+            mem::replace(&mut state.waiters, Vec::new())
+        };
+        for waiter in waiters {
+            wake(&self.pimpl.ctx, waiter, Ok(()))
+        }
+    }
+
+    /// Resets the event.
+    pub fn reset(&self) {
+        self.pimpl.state.lock().unwrap().signaled = false
+    }
+
+    /// Returns `true` if the event is set.
+    pub fn is_set(&self) -> bool {
+        self.pimpl.state.lock().unwrap().signaled
+    }
+}
+
+unsafe impl AsIoContext for AsyncEvent {
+    fn as_ctx(&self) -> &IoContext {
+        sync::as_ctx(&self.pimpl.ctx)
+    }
+}
+
+impl Cancel for AsyncEvent {
+    /// Fails the pending `async_wait` with `OPERATION_CANCELED`.
+    fn cancel(&self) {
+        let waiters = mem::replace(&mut self.pimpl.state.lock().unwrap().waiters, Vec::new());
+        cancel_waiters(&self.pimpl.ctx, waiters)
+    }
+}
+
+impl Drop for AsyncEvent {
+    fn drop(&mut self) {
+        self.cancel()
+    }
+}
+
+#[test]
+fn test_async_event() {
+    use wrap;
+    use std::thread;
+
+    let ctx = &IoContext::new().unwrap();
+    let ev = Arc::new(AsyncEvent::new(ctx));
+    for _ in 0..3 {
+        ev.async_wait(wrap(&ev, |_: Arc<AsyncEvent>, res: io::Result<()>| res.unwrap()));
+    }
+    let ev2 = ev.clone();
+    let ctx2 = ctx.clone();
+    let thrd = thread::spawn(move || ctx2.run());
+    ev2.set();
+    thrd.join().unwrap();
+    ev.reset();
+    assert!(!ev.is_set());
+}
+
+#[test]
+fn test_async_event_cancel() {
+    let ctx = &IoContext::new().unwrap();
+    let ev = Arc::new(AsyncEvent::new(ctx));
+    ev.async_wait(::wrap(&ev, |_: Arc<AsyncEvent>, res: io::Result<()>| {
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(::libc::ECANCELED));
+    }));
+    let ev2 = ev.clone();
+    ctx.post(move |_| ev2.cancel());
+    ctx.run();
+}
+
+#[test]
+fn test_async_event_drop() {
+    use SteadyTimer;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static CANCELED: AtomicBool = AtomicBool::new(false);
+
+    let ctx = &IoContext::new().unwrap();
+    let owner = Arc::new(SteadyTimer::new(ctx));
+    let ev = AsyncEvent::new(ctx);
+    ev.async_wait(::wrap(&owner, |_: Arc<SteadyTimer>, res: io::Result<()>| {
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(::libc::ECANCELED));
+        CANCELED.store(true, Ordering::SeqCst);
+    }));
+    ctx.post(move |_| drop(ev));
+    ctx.run();
+    assert!(CANCELED.load(Ordering::SeqCst));
+}
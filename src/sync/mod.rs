@@ -0,0 +1,147 @@
+use ffi::OPERATION_CANCELED;
+use core::{AsIoContext, Exec, IoContext, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+
+use std::io;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+mod mutex;
+pub use self::mutex::{AsyncMutex, AsyncMutexGuard, AsyncCondvar};
+
+mod rwlock;
+pub use self::rwlock::{AsyncRwLock, AsyncRwLockReadGuard, AsyncRwLockWriteGuard};
+
+mod semaphore;
+pub use self::semaphore::{AsyncSemaphore, AsyncSemaphorePermit};
+
+mod barrier;
+pub use self::barrier::AsyncBarrier;
+
+mod event;
+pub use self::event::AsyncEvent;
+
+mod promise;
+pub use self::promise::Promise;
+
+mod channel;
+pub use self::channel::{Channel, SyncSender, TrySendError};
+
+/// Returns the `IoContext` of the running handler if this thread runs the `ctx`, otherwise the `ctx`.
+fn as_ctx(ctx: &IoContext) -> &IoContext {
+    if let Some(this) = ThreadIoContext::callstack(ctx) {
+        this.as_ctx()
+    } else {
+        ctx
+    }
+}
+
+/// A parked handler which waits for the primitive.
+trait Waiter<R>: Send + 'static {
+    fn complete_box(self: Box<Self>, this: &mut ThreadIoContext, res: io::Result<R>);
+}
+
+impl<R, F> Waiter<R> for F
+where
+    F: Complete<R, io::Error>,
+{
+    fn complete_box(self: Box<Self>, this: &mut ThreadIoContext, res: io::Result<R>) {
+        match res {
+            Ok(res) => self.success(this, res),
+            Err(err) => self.failure(this, err),
+        }
+    }
+}
+
+struct Wake<R> {
+    waiter: Box<Waiter<R>>,
+    res: io::Result<R>,
+}
+
+impl<R> Exec for Wake<R>
+where
+    R: Send + 'static,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        let Wake { waiter, res } = self;
+        waiter.complete_box(this, res)
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.call(this)
+    }
+
+    fn outstanding_work(&self, _: &IoContext) {}
+}
+
+/// Completes the parked handler on the `IoContext`.
+///
+/// It may be called from any thread, e.g. in the `Drop` of a guard.
+fn wake<R>(ctx: &IoContext, waiter: Box<Waiter<R>>, res: io::Result<R>)
+where
+    R: Send + 'static,
+{
+    ctx.do_post(Wake {
+        waiter: waiter,
+        res: res,
+    });
+    if ThreadIoContext::callstack(ctx).is_none() {
+        ctx.as_reactor().interrupt();
+    }
+}
+
+/// Fails the parked handlers with `OPERATION_CANCELED`.
+fn cancel_waiters<R, I>(ctx: &IoContext, waiters: I)
+where
+    R: Send + 'static,
+    I: IntoIterator<Item = Box<Waiter<R>>>,
+{
+    for waiter in waiters {
+        wake(ctx, waiter, Err(OPERATION_CANCELED.into()))
+    }
+}
+
+/// The shared state of the primitive, which completes the handler immediately or parks it.
+trait Acquire<R>: Send + Sync + 'static {
+    fn acquire<F>(this: &Arc<Self>, thrd: &mut ThreadIoContext, handler: F)
+    where
+        F: Complete<R, io::Error>;
+}
+
+struct AcquireOp<S, F, R> {
+    state: Arc<S>,
+    handler: F,
+    _marker: PhantomData<R>,
+}
+
+unsafe impl<S, F: Send, R> Send for AcquireOp<S, F, R> {}
+
+impl<S, F, R> Exec for AcquireOp<S, F, R>
+where
+    S: Acquire<R>,
+    F: Complete<R, io::Error>,
+    R: Send + 'static,
+{
+    fn call(self, this: &mut ThreadIoContext) {
+        S::acquire(&self.state, this, self.handler)
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.call(this)
+    }
+}
+
+fn async_acquire<S, F, R>(soc: &Cancel, state: &Arc<S>, handler: F) -> F::Output
+where
+    S: Acquire<R>,
+    F: Handler<R, io::Error>,
+    R: Send + 'static,
+{
+    handler.wrap_cancel(soc, |ctx, handler| {
+        ctx.do_dispatch(AcquireOp {
+            state: state.clone(),
+            handler: handler,
+            _marker: PhantomData,
+        })
+    })
+}
@@ -0,0 +1,439 @@
+use ffi::OPERATION_CANCELED;
+use core::{AsIoContext, Exec, IoContext, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+use sync::{self, Acquire, Waiter, async_acquire, cancel_waiters, wake};
+
+use std::io;
+use std::mem;
+use std::cell::{Cell, UnsafeCell};
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+struct MutexState<T>
+where
+    T: Send + 'static,
+{
+    locked: bool,
+    waiters: VecDeque<Box<Waiter<AsyncMutexGuard<T>>>>,
+}
+
+struct MutexImpl<T>
+where
+    T: Send + 'static,
+{
+    ctx: IoContext,
+    state: Mutex<MutexState<T>>,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send + 'static> Send for MutexImpl<T> {}
+
+unsafe impl<T: Send + 'static> Sync for MutexImpl<T> {}
+
+impl<T> MutexImpl<T>
+where
+    T: Send + 'static,
+{
+    /// Locks the mutex for the `waiter`, or parks it until unlocked.
+    fn lock_or_park(this: &Arc<Self>, waiter: Box<Waiter<AsyncMutexGuard<T>>>) {
+        let mut state = this.state.lock().unwrap();
+        if state.locked {
+            state.waiters.push_back(waiter);
+        } else {
+            state.locked = true;
+            wake(&this.ctx, waiter, Ok(AsyncMutexGuard::new(this.clone())));
+        }
+    }
+
+    /// Hands over the lock to the oldest waiter, or unlocks the mutex.
+    fn unlock(this: &Arc<Self>) {
+        let mut state = this.state.lock().unwrap();
+        if let Some(waiter) = state.waiters.pop_front() {
+            wake(&this.ctx, waiter, Ok(AsyncMutexGuard::new(this.clone())));
+        } else {
+            state.locked = false;
+        }
+    }
+}
+
+impl<T> Acquire<AsyncMutexGuard<T>> for MutexImpl<T>
+where
+    T: Send + 'static,
+{
+    fn acquire<F>(this: &Arc<Self>, thrd: &mut ThreadIoContext, handler: F)
+    where
+        F: Complete<AsyncMutexGuard<T>, io::Error>,
+    {
+        let mut state = this.state.lock().unwrap();
+        if state.locked {
+            state.waiters.push_back(Box::new(handler));
+        } else {
+            state.locked = true;
+            drop(state);
+            handler.success(thrd, AsyncMutexGuard::new(this.clone()))
+        }
+    }
+}
+
+/// A mutual exclusion primitive which parks the waiting handlers instead of blocking the thread.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use asyncio::{IoContext, AsyncMutex, spawn};
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let mutex = Arc::new(AsyncMutex::new(ctx, 0));
+/// for _ in 0..10 {
+///     let mutex = mutex.clone();
+///     spawn(ctx, move |coro| {
+///         let mut data = mutex.async_lock(coro.wrap()).unwrap();
+///         *data += 1;
+///     }).unwrap();
+/// }
+/// ctx.run();
+/// assert_eq!(*mutex.try_lock().unwrap(), 10);
+/// ```
+pub struct AsyncMutex<T>
+where
+    T: Send + 'static,
+{
+    pimpl: Arc<MutexImpl<T>>,
+}
+
+impl<T> AsyncMutex<T>
+where
+    T: Send + 'static,
+{
+    pub fn new(ctx: &IoContext, data: T) -> Self {
+        AsyncMutex {
+            pimpl: Arc::new(MutexImpl {
+                ctx: ctx.clone(),
+                state: Mutex::new(MutexState {
+                    locked: false,
+                    waiters: VecDeque::new(),
+                }),
+                data: UnsafeCell::new(data),
+            }),
+        }
+    }
+
+    /// Asynchronously acquires the mutex, and completes with a guard which releases it when dropped.
+    ///
+    /// The waiters acquire the mutex in the order of the requests.
+    pub fn async_lock<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<AsyncMutexGuard<T>, io::Error>,
+    {
+        async_acquire(self, &self.pimpl, handler)
+    }
+
+    /// Attempts to acquire the mutex without waiting.
+    pub fn try_lock(&self) -> Option<AsyncMutexGuard<T>> {
+        let mut state = self.pimpl.state.lock().unwrap();
+        if state.locked {
+            None
+        } else {
+            state.locked = true;
+            Some(AsyncMutexGuard::new(self.pimpl.clone()))
+        }
+    }
+}
+
+unsafe impl<T> AsIoContext for AsyncMutex<T>
+where
+    T: Send + 'static,
+{
+    fn as_ctx(&self) -> &IoContext {
+        sync::as_ctx(&self.pimpl.ctx)
+    }
+}
+
+impl<T> Cancel for AsyncMutex<T>
+where
+    T: Send + 'static,
+{
+    /// Fails the pending `async_lock` with `OPERATION_CANCELED`.
+    fn cancel(&self) {
+        let waiters = mem::replace(&mut self.pimpl.state.lock().unwrap().waiters, VecDeque::new());
+        cancel_waiters(&self.pimpl.ctx, waiters)
+    }
+}
+
+/// An RAII guard of the `AsyncMutex`, which hands over the lock to the next waiter when dropped.
+///
+/// Like the `std::sync::MutexGuard`, it is `Sync` only if `T` is `Sync`.
+pub struct AsyncMutexGuard<T>
+where
+    T: Send + 'static,
+{
+    pimpl: Arc<MutexImpl<T>>,
+    _marker: PhantomData<Cell<()>>,
+}
+
+unsafe impl<T: Send + Sync + 'static> Sync for AsyncMutexGuard<T> {}
+
+impl<T> AsyncMutexGuard<T>
+where
+    T: Send + 'static,
+{
+    fn new(pimpl: Arc<MutexImpl<T>>) -> Self {
+        AsyncMutexGuard {
+            pimpl: pimpl,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Deref for AsyncMutexGuard<T>
+where
+    T: Send + 'static,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.pimpl.data.get() }
+    }
+}
+
+impl<T> DerefMut for AsyncMutexGuard<T>
+where
+    T: Send + 'static,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.pimpl.data.get() }
+    }
+}
+
+impl<T> Drop for AsyncMutexGuard<T>
+where
+    T: Send + 'static,
+{
+    fn drop(&mut self) {
+        MutexImpl::unlock(&self.pimpl)
+    }
+}
+
+trait CondvarWaiter: Send + 'static {
+    fn notify(self: Box<Self>);
+
+    fn cancel(self: Box<Self>);
+}
+
+struct Relock<T>
+where
+    T: Send + 'static,
+{
+    pimpl: Arc<MutexImpl<T>>,
+    waiter: Box<Waiter<AsyncMutexGuard<T>>>,
+}
+
+impl<T> CondvarWaiter for Relock<T>
+where
+    T: Send + 'static,
+{
+    fn notify(self: Box<Self>) {
+        let Relock { pimpl, waiter } = *self;
+        MutexImpl::lock_or_park(&pimpl, waiter)
+    }
+
+    fn cancel(self: Box<Self>) {
+        let Relock { pimpl, waiter } = *self;
+        wake(&pimpl.ctx, waiter, Err(OPERATION_CANCELED.into()))
+    }
+}
+
+struct CondvarWait<T, F>
+where
+    T: Send + 'static,
+{
+    waiters: Arc<Mutex<VecDeque<Box<CondvarWaiter>>>>,
+    guard: AsyncMutexGuard<T>,
+    handler: F,
+}
+
+impl<T, F> Exec for CondvarWait<T, F>
+where
+    T: Send + 'static,
+    F: Complete<AsyncMutexGuard<T>, io::Error>,
+{
+    fn call(self, _: &mut ThreadIoContext) {
+        let CondvarWait {
+            waiters,
+            guard,
+            handler,
+        } = self;
+        waiters.lock().unwrap().push_back(Box::new(Relock {
+            pimpl: guard.pimpl.clone(),
+            waiter: Box::new(handler),
+        }));
+        // unlocks after parking, so that the notification is not lost.
+        drop(guard)
+    }
+
+    fn call_box(self: Box<Self>, this: &mut ThreadIoContext) {
+        self.call(this)
+    }
+}
+
+/// A condition variable which is used with the `AsyncMutex`.
+///
+/// If the condition variable is dropped, the pending `async_wait` fails with `OPERATION_CANCELED`.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use asyncio::{IoContext, AsyncMutex, AsyncCondvar, spawn};
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let pair = Arc::new((AsyncMutex::new(ctx, false), AsyncCondvar::new(ctx)));
+/// let pair2 = pair.clone();
+/// spawn(ctx, move |coro| {
+///     let mut ready = pair2.0.async_lock(coro.wrap()).unwrap();
+///     while !*ready {
+///         ready = pair2.1.async_wait(ready, coro.wrap()).unwrap();
+///     }
+/// }).unwrap();
+/// spawn(ctx, move |coro| {
+///     *pair.0.async_lock(coro.wrap()).unwrap() = true;
+///     pair.1.notify_one();
+/// }).unwrap();
+/// ctx.run();
+/// ```
+pub struct AsyncCondvar {
+    ctx: IoContext,
+    waiters: Arc<Mutex<VecDeque<Box<CondvarWaiter>>>>,
+}
+
+impl AsyncCondvar {
+    pub fn new(ctx: &IoContext) -> Self {
+        AsyncCondvar {
+            ctx: ctx.clone(),
+            waiters: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Releases the `guard` and waits for a notification, then completes with the reacquired guard.
+    ///
+    /// If it is canceled, it fails with `OPERATION_CANCELED` without reacquiring the mutex.
+    pub fn async_wait<T, F>(&self, guard: AsyncMutexGuard<T>, handler: F) -> F::Output
+    where
+        T: Send + 'static,
+        F: Handler<AsyncMutexGuard<T>, io::Error>,
+    {
+        handler.wrap_cancel(self, |ctx, handler| {
+            ctx.do_dispatch(CondvarWait {
+                waiters: self.waiters.clone(),
+                guard: guard,
+                handler: handler,
+            })
+        })
+    }
+
+    /// Wakes up the oldest waiter.
+    pub fn notify_one(&self) {
+        let waiter = self.waiters.lock().unwrap().pop_front();
+        if let Some(waiter) = waiter {
+            waiter.notify()
+        }
+    }
+
+    /// Wakes up all waiters.
+    pub fn notify_all(&self) {
+        let waiters = mem::replace(&mut *self.waiters.lock().unwrap(), VecDeque::new());
+        for waiter in waiters {
+            waiter.notify()
+        }
+    }
+}
+
+unsafe impl AsIoContext for AsyncCondvar {
+    fn as_ctx(&self) -> &IoContext {
+        sync::as_ctx(&self.ctx)
+    }
+}
+
+impl Cancel for AsyncCondvar {
+    /// Fails the pending `async_wait` with `OPERATION_CANCELED`.
+    fn cancel(&self) {
+        let waiters = mem::replace(&mut *self.waiters.lock().unwrap(), VecDeque::new());
+        for waiter in waiters {
+            waiter.cancel()
+        }
+    }
+}
+
+impl Drop for AsyncCondvar {
+    fn drop(&mut self) {
+        self.cancel()
+    }
+}
+
+#[test]
+fn test_async_mutex() {
+    use std::thread;
+
+    let ctx = &IoContext::new().unwrap();
+    let mutex = Arc::new(AsyncMutex::new(ctx, Vec::new()));
+    let guard = mutex.try_lock().unwrap();
+    assert!(mutex.try_lock().is_none());
+    for i in 0..3 {
+        mutex.async_lock(::wrap(
+            &mutex,
+            move |_: Arc<AsyncMutex<Vec<i32>>>, res: io::Result<AsyncMutexGuard<Vec<i32>>>| res.unwrap().push(i),
+        ));
+    }
+    let ctx2 = ctx.clone();
+    let thrd = thread::spawn(move || ctx2.run());
+    drop(guard);
+    thrd.join().unwrap();
+    assert_eq!(*mutex.try_lock().unwrap(), vec![0, 1, 2]);
+}
+
+#[test]
+fn test_async_mutex_cancel() {
+    let ctx = &IoContext::new().unwrap();
+    let mutex = Arc::new(AsyncMutex::new(ctx, 0));
+    let guard = mutex.try_lock().unwrap();
+    mutex.async_lock(::wrap(
+        &mutex,
+        |_: Arc<AsyncMutex<i32>>, res: io::Result<AsyncMutexGuard<i32>>| {
+            assert_eq!(res.err().unwrap().raw_os_error(), Some(::libc::ECANCELED));
+        },
+    ));
+    let mutex2 = mutex.clone();
+    ctx.post(move |_| mutex2.cancel());
+    ctx.run();
+    drop(guard);
+    assert!(mutex.try_lock().is_some());
+}
+
+#[cfg(feature = "context")]
+#[test]
+fn test_async_condvar() {
+    use spawn;
+
+    let ctx = &IoContext::new().unwrap();
+    let pair = Arc::new((AsyncMutex::new(ctx, 0), AsyncCondvar::new(ctx)));
+    for _ in 0..3 {
+        let pair = pair.clone();
+        spawn(ctx, move |coro| {
+            let mut count = pair.0.async_lock(coro.wrap()).unwrap();
+            while *count == 0 {
+                count = pair.1.async_wait(count, coro.wrap()).unwrap();
+            }
+            *count += 1;
+        }).unwrap();
+    }
+    let pair2 = pair.clone();
+    spawn(ctx, move |coro| {
+        *pair2.0.async_lock(coro.wrap()).unwrap() = 1;
+        pair2.1.notify_all();
+    }).unwrap();
+    ctx.run();
+    assert_eq!(*pair.0.try_lock().unwrap(), 4);
+}
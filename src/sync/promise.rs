@@ -0,0 +1,223 @@
+use ffi::{ALREADY_STARTED, BROKEN_PIPE};
+use core::{AsIoContext, IoContext, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+use sync::{self, Acquire, Waiter, async_acquire, cancel_waiters, wake};
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+struct PromiseState<T> {
+    value: Option<io::Result<T>>,
+    waiter: Option<Box<Waiter<T>>>,
+    satisfied: bool,
+    retrieved: bool,
+}
+
+struct PromiseImpl<T> {
+    ctx: IoContext,
+    state: Mutex<PromiseState<T>>,
+}
+
+impl<T> PromiseImpl<T>
+where
+    T: Send + 'static,
+{
+    fn set(&self, res: io::Result<T>) -> Result<(), io::Result<T>> {
+        let mut state = self.state.lock().unwrap();
+        if state.satisfied {
+            return Err(res);
+        }
+        state.satisfied = true;
+        match state.waiter.take() {
+            Some(waiter) => wake(&self.ctx, waiter, res),
+            None => state.value = Some(res),
+        }
+        Ok(())
+    }
+}
+
+impl<T> Acquire<T> for PromiseImpl<T>
+where
+    T: Send + 'static,
+{
+    fn acquire<F>(this: &Arc<Self>, thrd: &mut ThreadIoContext, handler: F)
+    where
+        F: Complete<T, io::Error>,
+    {
+        let mut state = this.state.lock().unwrap();
+        if state.retrieved {
+            drop(state);
+            return handler.failure(thrd, ALREADY_STARTED.into());
+        }
+        state.retrieved = true;
+        match state.value.take() {
+            Some(Ok(res)) => {
+                drop(state);
+                handler.success(thrd, res)
+            }
+            Some(Err(err)) => {
+                drop(state);
+                handler.failure(thrd, err)
+            }
+            None => state.waiter = Some(Box::new(handler)),
+        }
+    }
+}
+
+/// A one-shot value which is set once and retrieved asynchronously once.
+///
+/// If the promise is dropped without being satisfied, the pending `async_get` fails with `BROKEN_PIPE`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::sync::Arc;
+/// use std::thread;
+/// use asyncio::{IoContext, Promise, wrap};
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let promise = Arc::new(Promise::new(ctx));
+/// promise.async_get(wrap(&promise, |_: Arc<Promise<i32>>, res: io::Result<i32>| {
+///     assert_eq!(res.unwrap(), 42);
+/// }));
+/// let promise2 = promise.clone();
+/// thread::spawn(move || promise2.set_value(42).unwrap());
+/// ctx.run();
+/// ```
+pub struct Promise<T>
+where
+    T: Send + 'static,
+{
+    pimpl: Arc<PromiseImpl<T>>,
+}
+
+impl<T> Promise<T>
+where
+    T: Send + 'static,
+{
+    pub fn new(ctx: &IoContext) -> Self {
+        Promise {
+            pimpl: Arc::new(PromiseImpl {
+                ctx: ctx.clone(),
+                state: Mutex::new(PromiseState {
+                    value: None,
+                    waiter: None,
+                    satisfied: false,
+                    retrieved: false,
+                }),
+            }),
+        }
+    }
+
+    /// Asynchronously waits for the value or the error to be set.
+    ///
+    /// It fails with `ALREADY_STARTED` if the value has been already retrieved.
+    pub fn async_get<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<T, io::Error>,
+    {
+        async_acquire(self, &self.pimpl, handler)
+    }
+
+    /// Sets the value, or returns it back if the promise has been already satisfied.
+    pub fn set_value(&self, value: T) -> Result<(), T> {
+        match self.pimpl.set(Ok(value)) {
+            Ok(_) => Ok(()),
+            Err(res) => Err(res.ok().unwrap()),
+        }
+    }
+
+    /// Sets the error, or returns it back if the promise has been already satisfied.
+    pub fn set_error(&self, err: io::Error) -> Result<(), io::Error> {
+        match self.pimpl.set(Err(err)) {
+            Ok(_) => Ok(()),
+            Err(res) => Err(res.err().unwrap()),
+        }
+    }
+}
+
+unsafe impl<T> AsIoContext for Promise<T>
+where
+    T: Send + 'static,
+{
+    fn as_ctx(&self) -> &IoContext {
+        sync::as_ctx(&self.pimpl.ctx)
+    }
+}
+
+impl<T> Cancel for Promise<T>
+where
+    T: Send + 'static,
+{
+    /// Fails the pending `async_get` with `OPERATION_CANCELED`.
+    fn cancel(&self) {
+        let waiter = self.pimpl.state.lock().unwrap().waiter.take();
+        cancel_waiters(&self.pimpl.ctx, waiter)
+    }
+}
+
+impl<T> Drop for Promise<T>
+where
+    T: Send + 'static,
+{
+    fn drop(&mut self) {
+        let waiter = self.pimpl.state.lock().unwrap().waiter.take();
+        if let Some(waiter) = waiter {
+            wake(&self.pimpl.ctx, waiter, Err(BROKEN_PIPE.into()))
+        }
+    }
+}
+
+#[test]
+fn test_promise() {
+    use wrap;
+
+    let ctx = &IoContext::new().unwrap();
+    let promise = Arc::new(Promise::new(ctx));
+    promise.set_value(1).unwrap();
+    assert_eq!(promise.set_value(2), Err(2));
+    promise.async_get(wrap(&promise, |_: Arc<Promise<i32>>, res: io::Result<i32>| {
+        assert_eq!(res.unwrap(), 1)
+    }));
+    promise.async_get(wrap(&promise, |_: Arc<Promise<i32>>, res: io::Result<i32>| {
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(::libc::EALREADY))
+    }));
+    ctx.run();
+}
+
+#[test]
+fn test_promise_error() {
+    use wrap;
+
+    let ctx = &IoContext::new().unwrap();
+    let promise = Arc::new(Promise::new(ctx));
+    promise.async_get(wrap(&promise, |_: Arc<Promise<()>>, res: io::Result<()>| {
+        assert_eq!(res.unwrap_err().kind(), io::ErrorKind::BrokenPipe)
+    }));
+    let promise2 = promise.clone();
+    ctx.post(move |_| {
+        promise2.set_error(io::ErrorKind::BrokenPipe.into()).unwrap()
+    });
+    ctx.run();
+}
+
+#[test]
+fn test_promise_broken() {
+    use wrap;
+    use SteadyTimer;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static BROKEN: AtomicBool = AtomicBool::new(false);
+
+    let ctx = &IoContext::new().unwrap();
+    let owner = Arc::new(SteadyTimer::new(ctx));
+    let promise = Promise::new(ctx);
+    promise.async_get(wrap(&owner, |_: Arc<SteadyTimer>, res: io::Result<i32>| {
+        assert_eq!(res.unwrap_err().kind(), io::ErrorKind::BrokenPipe);
+        BROKEN.store(true, Ordering::SeqCst);
+    }));
+    ctx.post(move |_| drop(promise));
+    ctx.run();
+    assert!(BROKEN.load(Ordering::SeqCst));
+}
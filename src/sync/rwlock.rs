@@ -0,0 +1,332 @@
+use core::{AsIoContext, IoContext, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+use sync::{self, Acquire, Waiter, async_acquire, cancel_waiters, wake};
+
+use std::io;
+use std::mem;
+use std::cell::UnsafeCell;
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+enum RwWaiter<T>
+where
+    T: Send + Sync + 'static,
+{
+    Read(Box<Waiter<AsyncRwLockReadGuard<T>>>),
+    Write(Box<Waiter<AsyncRwLockWriteGuard<T>>>),
+}
+
+struct RwState<T>
+where
+    T: Send + Sync + 'static,
+{
+    readers: usize,
+    writer: bool,
+    waiters: VecDeque<RwWaiter<T>>,
+}
+
+struct RwLockImpl<T>
+where
+    T: Send + Sync + 'static,
+{
+    ctx: IoContext,
+    state: Mutex<RwState<T>>,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send + Sync> Send for RwLockImpl<T> {}
+
+unsafe impl<T: Send + Sync> Sync for RwLockImpl<T> {}
+
+impl<T> RwLockImpl<T>
+where
+    T: Send + Sync + 'static,
+{
+    /// Hands over the lock to the oldest writer, or to the consecutive readers.
+    fn wake_next(this: &Arc<Self>, state: &mut RwState<T>) {
+        while !state.writer {
+            match state.waiters.front() {
+                Some(&RwWaiter::Write(_)) if state.readers == 0 => (),
+                Some(&RwWaiter::Read(_)) => (),
+                _ => return,
+            }
+            match state.waiters.pop_front() {
+                Some(RwWaiter::Read(waiter)) => {
+                    state.readers += 1;
+                    wake(&this.ctx, waiter, Ok(AsyncRwLockReadGuard { pimpl: this.clone() }));
+                }
+                Some(RwWaiter::Write(waiter)) => {
+                    state.writer = true;
+                    wake(&this.ctx, waiter, Ok(AsyncRwLockWriteGuard { pimpl: this.clone() }));
+                }
+                None => unreachable!(),
+            }
+        }
+    }
+
+    fn read_unlock(this: &Arc<Self>) {
+        let mut state = this.state.lock().unwrap();
+        state.readers -= 1;
+        if state.readers == 0 {
+            Self::wake_next(this, &mut state)
+        }
+    }
+
+    fn write_unlock(this: &Arc<Self>) {
+        let mut state = this.state.lock().unwrap();
+        state.writer = false;
+        Self::wake_next(this, &mut state)
+    }
+
+    fn try_read(this: &Arc<Self>) -> Option<AsyncRwLockReadGuard<T>> {
+        let mut state = this.state.lock().unwrap();
+        // the readers do not overtake the waiting writer.
+        if state.writer || !state.waiters.is_empty() {
+            None
+        } else {
+            state.readers += 1;
+            Some(AsyncRwLockReadGuard { pimpl: this.clone() })
+        }
+    }
+
+    fn try_write(this: &Arc<Self>) -> Option<AsyncRwLockWriteGuard<T>> {
+        let mut state = this.state.lock().unwrap();
+        if state.writer || state.readers > 0 {
+            None
+        } else {
+            state.writer = true;
+            Some(AsyncRwLockWriteGuard { pimpl: this.clone() })
+        }
+    }
+}
+
+impl<T> Acquire<AsyncRwLockReadGuard<T>> for RwLockImpl<T>
+where
+    T: Send + Sync + 'static,
+{
+    fn acquire<F>(this: &Arc<Self>, thrd: &mut ThreadIoContext, handler: F)
+    where
+        F: Complete<AsyncRwLockReadGuard<T>, io::Error>,
+    {
+        let mut state = this.state.lock().unwrap();
+        if state.writer || !state.waiters.is_empty() {
+            state.waiters.push_back(RwWaiter::Read(Box::new(handler)));
+        } else {
+            state.readers += 1;
+            drop(state);
+            handler.success(thrd, AsyncRwLockReadGuard { pimpl: this.clone() })
+        }
+    }
+}
+
+impl<T> Acquire<AsyncRwLockWriteGuard<T>> for RwLockImpl<T>
+where
+    T: Send + Sync + 'static,
+{
+    fn acquire<F>(this: &Arc<Self>, thrd: &mut ThreadIoContext, handler: F)
+    where
+        F: Complete<AsyncRwLockWriteGuard<T>, io::Error>,
+    {
+        let mut state = this.state.lock().unwrap();
+        if state.writer || state.readers > 0 {
+            state.waiters.push_back(RwWaiter::Write(Box::new(handler)));
+        } else {
+            state.writer = true;
+            drop(state);
+            handler.success(thrd, AsyncRwLockWriteGuard { pimpl: this.clone() })
+        }
+    }
+}
+
+/// A reader-writer lock which parks the waiting handlers instead of blocking the thread.
+///
+/// The waiters acquire the lock in the order of the requests, so that the writer does not starve.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use asyncio::{IoContext, AsyncRwLock, spawn};
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let lock = Arc::new(AsyncRwLock::new(ctx, 0));
+/// let lock2 = lock.clone();
+/// spawn(ctx, move |coro| {
+///     *lock2.async_write(coro.wrap()).unwrap() += 1;
+///     assert_eq!(*lock2.async_read(coro.wrap()).unwrap(), 1);
+/// }).unwrap();
+/// ctx.run();
+/// ```
+pub struct AsyncRwLock<T>
+where
+    T: Send + Sync + 'static,
+{
+    pimpl: Arc<RwLockImpl<T>>,
+}
+
+impl<T> AsyncRwLock<T>
+where
+    T: Send + Sync + 'static,
+{
+    pub fn new(ctx: &IoContext, data: T) -> Self {
+        AsyncRwLock {
+            pimpl: Arc::new(RwLockImpl {
+                ctx: ctx.clone(),
+                state: Mutex::new(RwState {
+                    readers: 0,
+                    writer: false,
+                    waiters: VecDeque::new(),
+                }),
+                data: UnsafeCell::new(data),
+            }),
+        }
+    }
+
+    /// Asynchronously acquires the shared read access.
+    pub fn async_read<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<AsyncRwLockReadGuard<T>, io::Error>,
+    {
+        async_acquire(self, &self.pimpl, handler)
+    }
+
+    /// Asynchronously acquires the exclusive write access.
+    pub fn async_write<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<AsyncRwLockWriteGuard<T>, io::Error>,
+    {
+        async_acquire(self, &self.pimpl, handler)
+    }
+
+    /// Attempts to acquire the shared read access without waiting.
+    pub fn try_read(&self) -> Option<AsyncRwLockReadGuard<T>> {
+        RwLockImpl::try_read(&self.pimpl)
+    }
+
+    /// Attempts to acquire the exclusive write access without waiting.
+    pub fn try_write(&self) -> Option<AsyncRwLockWriteGuard<T>> {
+        RwLockImpl::try_write(&self.pimpl)
+    }
+}
+
+unsafe impl<T> AsIoContext for AsyncRwLock<T>
+where
+    T: Send + Sync + 'static,
+{
+    fn as_ctx(&self) -> &IoContext {
+        sync::as_ctx(&self.pimpl.ctx)
+    }
+}
+
+impl<T> Cancel for AsyncRwLock<T>
+where
+    T: Send + Sync + 'static,
+{
+    /// Fails the pending `async_read` and `async_write` with `OPERATION_CANCELED`.
+    fn cancel(&self) {
+        let waiters = mem::replace(&mut self.pimpl.state.lock().unwrap().waiters, VecDeque::new());
+        let (mut readers, mut writers) = (Vec::new(), Vec::new());
+        for waiter in waiters {
+            match waiter {
+                RwWaiter::Read(waiter) => readers.push(waiter),
+                RwWaiter::Write(waiter) => writers.push(waiter),
+            }
+        }
+        cancel_waiters(&self.pimpl.ctx, readers);
+        cancel_waiters(&self.pimpl.ctx, writers);
+    }
+}
+
+/// An RAII guard of the shared read access of the `AsyncRwLock`.
+pub struct AsyncRwLockReadGuard<T>
+where
+    T: Send + Sync + 'static,
+{
+    pimpl: Arc<RwLockImpl<T>>,
+}
+
+impl<T> Deref for AsyncRwLockReadGuard<T>
+where
+    T: Send + Sync + 'static,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.pimpl.data.get() }
+    }
+}
+
+impl<T> Drop for AsyncRwLockReadGuard<T>
+where
+    T: Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        RwLockImpl::read_unlock(&self.pimpl)
+    }
+}
+
+/// An RAII guard of the exclusive write access of the `AsyncRwLock`.
+pub struct AsyncRwLockWriteGuard<T>
+where
+    T: Send + Sync + 'static,
+{
+    pimpl: Arc<RwLockImpl<T>>,
+}
+
+impl<T> Deref for AsyncRwLockWriteGuard<T>
+where
+    T: Send + Sync + 'static,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.pimpl.data.get() }
+    }
+}
+
+impl<T> DerefMut for AsyncRwLockWriteGuard<T>
+where
+    T: Send + Sync + 'static,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.pimpl.data.get() }
+    }
+}
+
+impl<T> Drop for AsyncRwLockWriteGuard<T>
+where
+    T: Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        RwLockImpl::write_unlock(&self.pimpl)
+    }
+}
+
+#[test]
+fn test_async_rwlock() {
+    use wrap;
+
+    type Lock = AsyncRwLock<Vec<&'static str>>;
+
+    let ctx = &IoContext::new().unwrap();
+    let lock = Arc::new(AsyncRwLock::new(ctx, Vec::new()));
+    let r1 = lock.try_read().unwrap();
+    let r2 = lock.try_read().unwrap();
+    assert!(lock.try_write().is_none());
+    lock.async_write(wrap(&lock, |_: Arc<Lock>, res: io::Result<AsyncRwLockWriteGuard<Vec<&'static str>>>| {
+        res.unwrap().push("write")
+    }));
+    lock.async_read(wrap(&lock, |_: Arc<Lock>, res: io::Result<AsyncRwLockReadGuard<Vec<&'static str>>>| {
+        assert_eq!(*res.unwrap(), vec!["write"])
+    }));
+    let lock2 = lock.clone();
+    ctx.post(move |_| {
+        // the reader does not overtake the waiting writer.
+        assert!(lock2.try_read().is_none());
+        drop(r1);
+        drop(r2);
+    });
+    ctx.run();
+    assert!(lock.try_write().is_some());
+}
@@ -0,0 +1,205 @@
+use core::{AsIoContext, IoContext, ThreadIoContext, Cancel};
+use handler::{Handler, Complete};
+use sync::{self, Acquire, Waiter, async_acquire, cancel_waiters, wake};
+
+use std::io;
+use std::mem;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+struct SemaphoreState {
+    permits: usize,
+    waiters: VecDeque<Box<Waiter<AsyncSemaphorePermit>>>,
+}
+
+struct SemaphoreImpl {
+    ctx: IoContext,
+    state: Mutex<SemaphoreState>,
+}
+
+impl SemaphoreImpl {
+    /// Hands over the permits to the oldest waiters.
+    fn release(this: &Arc<Self>, permits: usize) {
+        let mut state = this.state.lock().unwrap();
+        state.permits += permits;
+        while state.permits > 0 {
+            match state.waiters.pop_front() {
+                Some(waiter) => {
+                    state.permits -= 1;
+                    wake(&this.ctx, waiter, Ok(AsyncSemaphorePermit { pimpl: this.clone() }));
+                }
+                None => return,
+            }
+        }
+    }
+}
+
+impl Acquire<AsyncSemaphorePermit> for SemaphoreImpl {
+    fn acquire<F>(this: &Arc<Self>, thrd: &mut ThreadIoContext, handler: F)
+    where
+        F: Complete<AsyncSemaphorePermit, io::Error>,
+    {
+        let mut state = this.state.lock().unwrap();
+        if state.permits == 0 {
+            state.waiters.push_back(Box::new(handler));
+        } else {
+            state.permits -= 1;
+            drop(state);
+            handler.success(thrd, AsyncSemaphorePermit { pimpl: this.clone() })
+        }
+    }
+}
+
+/// A counting semaphore which parks the waiting handlers instead of blocking the thread.
+///
+/// If the semaphore is dropped, the pending `async_acquire` fails with `OPERATION_CANCELED`,
+/// and the outstanding permits remain valid.
+///
+/// # Examples
+///
+/// ```
+/// use asyncio::{IoContext, AsyncSemaphore};
+///
+/// let ctx = &IoContext::new().unwrap();
+/// let sem = AsyncSemaphore::new(ctx, 1);
+/// let permit = sem.try_acquire().unwrap();
+/// assert!(sem.try_acquire().is_none());
+/// drop(permit);
+/// assert_eq!(sem.available_permits(), 1);
+/// ```
+pub struct AsyncSemaphore {
+    pimpl: Arc<SemaphoreImpl>,
+}
+
+impl AsyncSemaphore {
+    pub fn new(ctx: &IoContext, permits: usize) -> Self {
+        AsyncSemaphore {
+            pimpl: Arc::new(SemaphoreImpl {
+                ctx: ctx.clone(),
+                state: Mutex::new(SemaphoreState {
+                    permits: permits,
+                    waiters: VecDeque::new(),
+                }),
+            }),
+        }
+    }
+
+    /// Asynchronously acquires a permit, which is released when dropped.
+    ///
+    /// The waiters acquire the permits in the order of the requests.
+    pub fn async_acquire<F>(&self, handler: F) -> F::Output
+    where
+        F: Handler<AsyncSemaphorePermit, io::Error>,
+    {
+        async_acquire(self, &self.pimpl, handler)
+    }
+
+    /// Attempts to acquire a permit without waiting.
+    pub fn try_acquire(&self) -> Option<AsyncSemaphorePermit> {
+        let mut state = self.pimpl.state.lock().unwrap();
+        if state.permits == 0 {
+            None
+        } else {
+            state.permits -= 1;
+            Some(AsyncSemaphorePermit { pimpl: self.pimpl.clone() })
+        }
+    }
+
+    /// Adds the `permits` to the semaphore.
+    pub fn add_permits(&self, permits: usize) {
+        SemaphoreImpl::release(&self.pimpl, permits)
+    }
+
+    /// Returns a number of the available permits.
+    pub fn available_permits(&self) -> usize {
+        self.pimpl.state.lock().unwrap().permits
+    }
+}
+
+unsafe impl AsIoContext for AsyncSemaphore {
+    fn as_ctx(&self) -> &IoContext {
+        sync::as_ctx(&self.pimpl.ctx)
+    }
+}
+
+impl Cancel for AsyncSemaphore {
+    /// Fails the pending `async_acquire` with `OPERATION_CANCELED`.
+    fn cancel(&self) {
+        let waiters = mem::replace(&mut self.pimpl.state.lock().unwrap().waiters, VecDeque::new());
+        cancel_waiters(&self.pimpl.ctx, waiters)
+    }
+}
+
+impl Drop for AsyncSemaphore {
+    fn drop(&mut self) {
+        self.cancel()
+    }
+}
+
+/// An RAII permit of the `AsyncSemaphore`, which is released when dropped.
+pub struct AsyncSemaphorePermit {
+    pimpl: Arc<SemaphoreImpl>,
+}
+
+impl AsyncSemaphorePermit {
+    /// Consumes the permit without releasing it.
+    pub fn forget(self) {
+        let pimpl = unsafe { ::std::ptr::read(&self.pimpl) };
+        mem::forget(self);
+        drop(pimpl)
+    }
+}
+
+impl Drop for AsyncSemaphorePermit {
+    fn drop(&mut self) {
+        SemaphoreImpl::release(&self.pimpl, 1)
+    }
+}
+
+#[test]
+fn test_async_semaphore() {
+    use wrap;
+
+    let ctx = &IoContext::new().unwrap();
+    let sem = Arc::new(AsyncSemaphore::new(ctx, 2));
+    let permit = sem.try_acquire().unwrap();
+    sem.try_acquire().unwrap().forget();
+    for _ in 0..2 {
+        sem.async_acquire(wrap(
+            &sem,
+            |_: Arc<AsyncSemaphore>, res: io::Result<AsyncSemaphorePermit>| {
+                res.unwrap();
+            },
+        ));
+    }
+    let sem2 = sem.clone();
+    ctx.post(move |_| {
+        drop(permit);
+        sem2.add_permits(1);
+    });
+    ctx.run();
+    assert_eq!(sem.available_permits(), 2);
+}
+
+#[test]
+fn test_async_semaphore_drop() {
+    use wrap;
+    use SteadyTimer;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static CANCELED: AtomicBool = AtomicBool::new(false);
+
+    let ctx = &IoContext::new().unwrap();
+    let owner = Arc::new(SteadyTimer::new(ctx));
+    let sem = AsyncSemaphore::new(ctx, 0);
+    sem.async_acquire(wrap(
+        &owner,
+        |_: Arc<SteadyTimer>, res: io::Result<AsyncSemaphorePermit>| {
+            assert_eq!(res.err().unwrap().raw_os_error(), Some(::libc::ECANCELED));
+            CANCELED.store(true, Ordering::SeqCst);
+        },
+    ));
+    ctx.post(move |_| drop(sem));
+    ctx.run();
+    assert!(CANCELED.load(Ordering::SeqCst));
+}
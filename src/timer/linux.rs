@@ -1,28 +1,65 @@
 use super::TimerImpl;
-use ffi::{AsRawFd, SystemError};
+use ffi::{AsRawFd, RawFd, SystemError, OPERATION_CANCELED};
 use reactor::{Handle, Reactor};
 
-use libc::{timerfd_create, timerfd_settime, timespec, itimerspec, TFD_TIMER_ABSTIME,
-           CLOCK_MONOTONIC, TFD_NONBLOCK, TFD_CLOEXEC};
+use libc::{self, timerfd_create, timerfd_settime, timespec, itimerspec, TFD_TIMER_ABSTIME,
+           TFD_TIMER_CANCEL_ON_SET, CLOCK_MONOTONIC, CLOCK_REALTIME, TFD_NONBLOCK, TFD_CLOEXEC};
+
+/// A far future of the system clock, which is never expired but watches the clock changes.
+const FAR_FUTURE: i64 = 1 << 32;
+
+fn timerfd(clock: libc::clockid_t) -> Result<RawFd, SystemError> {
+    match unsafe { timerfd_create(clock, TFD_NONBLOCK | TFD_CLOEXEC) } {
+        -1 => Err(SystemError::last_error()),
+        fd => Ok(fd),
+    }
+}
+
+fn settime(tfd: &Handle, flags: i32, it_value: timespec) {
+    use std::ptr;
+
+    let iti = itimerspec {
+        it_interval: timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        },
+        it_value: it_value,
+    };
+    unsafe {
+        timerfd_settime(tfd.as_raw_fd(), flags, &iti, ptr::null_mut());
+    }
+}
 
 pub struct TimerFd {
     tfd: Handle,
+    rtfd: Handle,
 }
 
 impl TimerFd {
     pub fn new() -> Result<Self, SystemError> {
-        match unsafe { timerfd_create(CLOCK_MONOTONIC, TFD_NONBLOCK | TFD_CLOEXEC) } {
-            -1 => Err(SystemError::last_error()),
-            fd => Ok(TimerFd { tfd: Handle::intr(fd) }),
-        }
+        Ok(TimerFd {
+            tfd: Handle::intr(timerfd(CLOCK_MONOTONIC)?),
+            // the changes of the system clock are read only by `clock_changed`.
+            rtfd: Handle::notify(timerfd(CLOCK_REALTIME)?),
+        })
     }
 
     pub fn startup(&self, reactor: &Reactor) {
-        reactor.register_intr(&self.tfd)
+        reactor.register_intr(&self.tfd);
+        reactor.register_intr(&self.rtfd);
+        settime(
+            &self.rtfd,
+            TFD_TIMER_ABSTIME | TFD_TIMER_CANCEL_ON_SET,
+            timespec {
+                tv_sec: FAR_FUTURE,
+                tv_nsec: 0,
+            },
+        );
     }
 
     pub fn cleanup(&self, reactor: &Reactor) {
-        reactor.deregister_intr(&self.tfd)
+        reactor.deregister_intr(&self.tfd);
+        reactor.deregister_intr(&self.rtfd);
     }
 
     pub fn wait_duration(&self, max: usize) -> usize {
@@ -30,22 +67,15 @@ impl TimerFd {
     }
 
     pub fn reset_timeout(&self, timer: &TimerImpl) {
-        use std::ptr;
+        settime(&self.tfd, TFD_TIMER_ABSTIME, timer.expiry.get().abs_time())
+    }
 
-        let iti = itimerspec {
-            it_interval: timespec {
-                tv_sec: 0,
-                tv_nsec: 0,
-            },
-            it_value: timer.expiry.abs_time(),
-        };
-        unsafe {
-            timerfd_settime(
-                self.tfd.as_raw_fd(),
-                TFD_TIMER_ABSTIME,
-                &iti,
-                ptr::null_mut(),
-            );
+    /// Returns true if the system clock has been changed discontinuously since the last call.
+    pub fn clock_changed(&self) -> bool {
+        let mut buf = [0u8; 8];
+        match unsafe { libc::read(self.rtfd.as_raw_fd(), buf.as_mut_ptr() as *mut _, buf.len()) } {
+            -1 => SystemError::last_error() == OPERATION_CANCELED,
+            _ => false,
         }
     }
 }
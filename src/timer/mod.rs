@@ -1,10 +1,14 @@
-use ffi::{SystemError, OPERATION_CANCELED};
+use ffi::{SystemError, CLOCK_CHANGED, OPERATION_CANCELED};
 use reactor::Reactor;
 use core::{AsIoContext, IoContext, Perform, ThreadIoContext};
 
+use std::cmp;
+use std::mem;
+use std::cell::{Cell, UnsafeCell};
 use std::cmp::Ordering;
-use std::ops::{Deref, DerefMut};
-use std::sync::Mutex;
+use std::ops::{Add, Deref};
+use std::sync::{Condvar, Mutex};
+use std::sync::atomic::{self, AtomicUsize};
 use std::time::{Duration, Instant, SystemTime};
 
 use libc::timespec;
@@ -57,6 +61,15 @@ impl Expiry {
         self.diff(Expiry::now())
     }
 
+    /// Returns a duration from the `earlier`, or zero if it is later than `self`.
+    pub fn duration_since(&self, earlier: Self) -> Duration {
+        if self.0 > earlier.0 {
+            self.0 - earlier.0
+        } else {
+            Duration::new(0, 0)
+        }
+    }
+
     pub fn abs_time(&self) -> timespec {
         timespec {
             tv_sec: self.0.as_secs() as i64,
@@ -65,6 +78,14 @@ impl Expiry {
     }
 }
 
+impl Add<Duration> for Expiry {
+    type Output = Self;
+
+    fn add(self, rhs: Duration) -> Self {
+        Expiry(self.0 + rhs)
+    }
+}
+
 impl From<Instant> for Expiry {
     fn from(t: Instant) -> Self {
         use std::mem;
@@ -81,37 +102,210 @@ impl From<SystemTime> for Expiry {
     }
 }
 
+impl From<Expiry> for Instant {
+    fn from(t: Expiry) -> Self {
+        let zero: Instant = unsafe { mem::zeroed() };
+        zero + t.0
+    }
+}
+
+impl From<Expiry> for SystemTime {
+    fn from(t: Expiry) -> Self {
+        let now = Expiry::now();
+        if t > now {
+            SystemTime::now() + (t.0 - now.0)
+        } else {
+            SystemTime::now() - (now.0 - t.0)
+        }
+    }
+}
+
+/// The index of the timer which is not in the `TimerQueue`.
+const NOT_QUEUED: usize = !0;
+
+/// The nearby expiries within it are coalesced into one arm of the timer. (same as the default timer slack of Linux)
+const TIMER_SLACK: usize = 50_000;
+
+#[derive(Default)]
+struct SyncWait {
+    waiters: usize,
+    generation: usize,
+}
+
 pub struct TimerImpl {
     ctx: IoContext,
-    expiry: Expiry,
-    op: Option<Box<Perform>>,
+    expiry: Cell<Expiry>,
+    wall: Cell<Option<SystemTime>>,
+    cancel_on_clock_change: Cell<bool>,
+    index: Cell<usize>,
+    ops: UnsafeCell<Vec<Box<Perform>>>,
+    sync: Mutex<SyncWait>,
+    condvar: Condvar,
 }
 
 impl TimerImpl {
     pub fn new(ctx: &IoContext) -> Box<Self> {
         Box::new(TimerImpl {
             ctx: ctx.clone(),
-            expiry: Expiry::zero(),
-            op: None,
+            expiry: Cell::new(Expiry::zero()),
+            wall: Cell::new(None),
+            cancel_on_clock_change: Cell::new(false),
+            index: Cell::new(NOT_QUEUED),
+            ops: UnsafeCell::new(Vec::new()),
+            sync: Mutex::default(),
+            condvar: Condvar::new(),
         })
     }
 
+    pub fn expiry(&self) -> Expiry {
+        let _tq = self.ctx.as_reactor().tq.mutex.lock().unwrap();
+        self.expiry.get()
+    }
+
+    /// Returns the current time on the clock of the `IoContext`.
+    pub fn now(&self) -> Expiry {
+        self.ctx.as_reactor().tq.now()
+    }
+
+    /// Returns the current time on the manual clock, or `None` if the `IoContext` does not use it.
+    pub fn manual_now(&self) -> Option<Expiry> {
+        self.ctx.as_reactor().tq.manual_now()
+    }
+
     pub fn set_wait_op(&self, this: &mut ThreadIoContext, op: Box<Perform>) {
-        if let Some(op) = self.ctx.as_reactor().tq.insert(self, op) {
+        if op.is_expired() {
             this.push(op, OPERATION_CANCELED)
+        } else {
+            self.ctx.as_reactor().tq.insert(self, op)
         }
     }
 
-    pub fn reset_expiry(&self, expiry: Expiry) {
-        if let Some(op) = self.ctx.as_reactor().tq.erase(self, expiry) {
-            self.ctx.do_dispatch((op, OPERATION_CANCELED))
+    /// Sets the expiry, and returns a number of the canceled waits.
+    ///
+    /// If the `wall` is given, the expiry follows the changes of the system clock.
+    pub fn reset_expiry(&self, expiry: Expiry, wall: Option<SystemTime>) -> usize {
+        let ops = self.ctx.as_reactor().tq.erase(self, Some((expiry, wall)));
+        self.cancel_ops(ops) + self.cancel_sync()
+    }
+
+    /// Cancels all of the waits, and returns a number of them.
+    pub fn cancel(&self) -> usize {
+        let ops = self.ctx.as_reactor().tq.erase(self, None);
+        self.cancel_ops(ops) + self.cancel_sync()
+    }
+
+    /// Cancels the oldest asynchronous wait, and returns a number of it.
+    pub fn cancel_one(&self) -> usize {
+        match self.ctx.as_reactor().tq.erase_one(self) {
+            Some(op) => self.cancel_ops(vec![op]),
+            None => 0,
         }
     }
 
-    pub fn cancel(&self) {
-        if let Some(op) = self.ctx.as_reactor().tq.erase(self, Expiry::zero()) {
+    /// Cancels the asynchronous waits whose deadline is expired.
+    pub fn cancel_expired(&self) {
+        let ops = self.ctx.as_reactor().tq.erase_expired(self);
+        self.cancel_ops(ops);
+    }
+
+    /// Sets whether the asynchronous waits fail with `CLOCK_CHANGED` when the system clock is changed.
+    pub fn set_cancel_on_clock_change(&self, on: bool) {
+        let _tq = self.ctx.as_reactor().tq.mutex.lock().unwrap();
+        self.cancel_on_clock_change.set(on)
+    }
+
+    /// Blocks until the expiry, or the wait is canceled by the other thread.
+    pub fn wait(&self) -> Result<(), SystemError> {
+        if self.manual_now().is_some() {
+            return self.wait_manual();
+        }
+        let mut sync = self.sync.lock().unwrap();
+        let generation = sync.generation;
+        sync.waiters += 1;
+        loop {
+            if sync.generation != generation {
+                return Err(OPERATION_CANCELED);
+            }
+            let left = {
+                let _tq = self.ctx.as_reactor().tq.mutex.lock().unwrap();
+                match self.wall.get() {
+                    // re-checks the system clock periodically, since it may be changed during the wait.
+                    Some(wall) => cmp::min(Expiry::from(wall).left(), 1_000_000_000),
+                    None => self.expiry.get().left(),
+                }
+            };
+            if left == 0 {
+                sync.waiters -= 1;
+                return Ok(());
+            }
+            sync = self.condvar
+                .wait_timeout(sync, Duration::from_nanos(left as u64))
+                .unwrap()
+                .0;
+        }
+    }
+
+    /// Blocks on the manual clock, which is notified by the `advance`.
+    fn wait_manual(&self) -> Result<(), SystemError> {
+        let tq = &self.ctx.as_reactor().tq;
+        let mut heap = tq.mutex.lock().unwrap();
+        let generation = {
+            let mut sync = self.sync.lock().unwrap();
+            sync.waiters += 1;
+            sync.generation
+        };
+        loop {
+            {
+                let mut sync = self.sync.lock().unwrap();
+                if sync.generation != generation {
+                    return Err(OPERATION_CANCELED);
+                }
+                if heap.manual.map_or(true, |now| self.expiry.get() <= now) {
+                    sync.waiters -= 1;
+                    return Ok(());
+                }
+            }
+            heap = tq.advanced.wait(heap).unwrap();
+        }
+    }
+
+    fn cancel_ops(&self, ops: Vec<Box<Perform>>) -> usize {
+        let len = ops.len();
+        for op in ops {
             self.ctx.do_dispatch((op, OPERATION_CANCELED))
         }
+        len
+    }
+
+    fn cancel_sync(&self) -> usize {
+        let len = {
+            let mut sync = self.sync.lock().unwrap();
+            let len = mem::replace(&mut sync.waiters, 0);
+            if len > 0 {
+                sync.generation = sync.generation.wrapping_add(1);
+                self.condvar.notify_all();
+            }
+            len
+        };
+        if len > 0 {
+            // the waits on the manual clock are blocked on the condvar of the `TimerQueue`.
+            let tq = &self.ctx.as_reactor().tq;
+            let _tq = tq.mutex.lock().unwrap();
+            tq.advanced.notify_all();
+        }
+        len
+    }
+
+    /// Must be dereferenced in the lock of the `TimerQueue`.
+    fn ops(&self) -> *mut Vec<Box<Perform>> {
+        self.ops.get()
+    }
+}
+
+impl Drop for TimerImpl {
+    fn drop(&mut self) {
+        // the pending waits must not be left in the queue after the timer is dropped.
+        self.cancel();
     }
 }
 
@@ -136,12 +330,6 @@ impl Deref for TimerImplRef {
     }
 }
 
-impl DerefMut for TimerImplRef {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { &mut *(self.0 as *mut TimerImpl) }
-    }
-}
-
 impl PartialEq for TimerImplRef {
     fn eq(&self, other: &Self) -> bool {
         self.0.eq(&other.0)
@@ -152,7 +340,9 @@ impl Eq for TimerImplRef {}
 
 impl PartialOrd for TimerImplRef {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match unsafe { &*self.0 }.expiry.partial_cmp(&unsafe { &*other.0 }.expiry) {
+        match unsafe { &*self.0 }.expiry.get().partial_cmp(
+            &unsafe { &*other.0 }.expiry.get(),
+        ) {
             Some(Ordering::Equal) => self.0.partial_cmp(&other.0),
             cmp => cmp,
         }
@@ -161,15 +351,100 @@ impl PartialOrd for TimerImplRef {
 
 impl Ord for TimerImplRef {
     fn cmp(&self, other: &Self) -> Ordering {
-        match unsafe { &*self.0 }.expiry.cmp(&unsafe { &*other.0 }.expiry) {
+        match unsafe { &*self.0 }.expiry.get().cmp(
+            &unsafe { &*other.0 }.expiry.get(),
+        ) {
             Ordering::Equal => self.0.cmp(&other.0),
             cmp => cmp,
         }
     }
 }
 
+/// A binary min-heap of the timers, which tracks the index of each timer to erase it in O(log n).
+#[derive(Default)]
+struct TimerHeap {
+    timers: Vec<TimerImplRef>,
+    armed: Option<Expiry>,
+    manual: Option<Expiry>,
+}
+
+impl TimerHeap {
+    fn top(&self) -> Option<&TimerImplRef> {
+        self.timers.first()
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.timers.swap(i, j);
+        self.timers[i].index.set(i);
+        self.timers[j].index.set(j);
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if self.timers[i] >= self.timers[parent] {
+                break;
+            }
+            self.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let left = i * 2 + 1;
+            if left >= self.timers.len() {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < self.timers.len() && self.timers[right] < self.timers[left] {
+                right
+            } else {
+                left
+            };
+            if self.timers[child] >= self.timers[i] {
+                break;
+            }
+            self.swap(i, child);
+            i = child;
+        }
+    }
+
+    fn push(&mut self, timer: TimerImplRef) {
+        let i = self.timers.len();
+        timer.index.set(i);
+        self.timers.push(timer);
+        self.sift_up(i);
+    }
+
+    fn remove(&mut self, i: usize) -> TimerImplRef {
+        let last = self.timers.len() - 1;
+        if i != last {
+            self.swap(i, last);
+        }
+        let timer = self.timers.pop().unwrap();
+        timer.index.set(NOT_QUEUED);
+        if i < self.timers.len() {
+            self.sift_down(i);
+            self.sift_up(i);
+        }
+        timer
+    }
+
+    /// Rebuilds the heap after the expiries are changed.
+    fn rebuild(&mut self) {
+        // a sorted array is also a valid heap.
+        self.timers.sort();
+        for (i, timer) in self.timers.iter().enumerate() {
+            timer.index.set(i);
+        }
+    }
+}
+
 pub struct TimerQueue {
-    mutex: Mutex<Vec<TimerImplRef>>,
+    mutex: Mutex<TimerHeap>,
+    advanced: Condvar,
+    realtime: AtomicUsize,
     ctl: TimerCtl,
 }
 
@@ -177,6 +452,8 @@ impl TimerQueue {
     pub fn new() -> Result<Self, SystemError> {
         Ok(TimerQueue {
             mutex: Mutex::default(),
+            advanced: Condvar::new(),
+            realtime: AtomicUsize::new(0),
             ctl: try!(TimerCtl::new()),
         })
     }
@@ -193,42 +470,157 @@ impl TimerQueue {
         self.ctl.wait_duration(max)
     }
 
+    /// Switches to the manual clock, which starts at now and advances only by `advance`.
+    pub fn set_manual_clock(&self) {
+        self.mutex.lock().unwrap().manual = Some(Expiry::now())
+    }
+
+    /// Advances the manual clock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the manual clock is not used.
+    pub fn advance(&self, dur: Duration) {
+        let mut tq = self.mutex.lock().unwrap();
+        let now = tq.manual.expect("the manual clock is not used");
+        tq.manual = Some(now + dur);
+        self.advanced.notify_all();
+    }
+
+    pub fn manual_now(&self) -> Option<Expiry> {
+        self.mutex.lock().unwrap().manual
+    }
+
+    pub fn now(&self) -> Expiry {
+        self.manual_now().unwrap_or_else(Expiry::now)
+    }
+
     pub fn get_ready_timers(&self, this: &mut ThreadIoContext) {
         let mut tq = self.mutex.lock().unwrap();
-        let i = match tq.binary_search_by(|e| e.expiry.cmp(&Expiry::now())) {
-            Ok(i) => i + 1,
-            Err(i) => i,
+        if self.realtime.load(atomic::Ordering::SeqCst) > 0 && self.ctl.clock_changed() {
+            self.reset_wall_clock(&mut tq, this);
+        }
+        let now = tq.manual.unwrap_or_else(Expiry::now);
+        if tq.armed.map_or(false, |armed| armed <= now) {
+            tq.armed = None;
+        }
+        while tq.top().map_or(false, |top| top.expiry.get() <= now) {
+            let e = self.remove(&mut tq, 0);
+            for op in unsafe { &mut *e.ops() }.drain(..) {
+                this.push(op, SystemError::default());
+            }
+        }
+        self.rearm(&mut tq);
+    }
+
+    /// Re-evaluates the expiries of the timers which follow the system clock.
+    fn reset_wall_clock(&self, tq: &mut TimerHeap, this: &mut ThreadIoContext) {
+        let mut i = 0;
+        while i < tq.timers.len() {
+            if let Some(wall) = tq.timers[i].wall.get() {
+                if tq.timers[i].cancel_on_clock_change.get() {
+                    let e = self.remove(tq, i);
+                    for op in unsafe { &mut *e.ops() }.drain(..) {
+                        this.push(op, CLOCK_CHANGED);
+                    }
+                    continue;
+                }
+                tq.timers[i].expiry.set(wall.into());
+            }
+            i += 1;
+        }
+        tq.rebuild();
+        tq.armed = None;
+    }
+
+    /// Arms the timer to the earliest expiry, unless the current arm wakes up the reactor in time.
+    fn rearm(&self, tq: &mut TimerHeap) {
+        if tq.manual.is_some() {
+            return;
+        }
+        let timer = match tq.top() {
+            Some(timer) => timer.clone(),
+            None => return,
         };
-        for mut e in tq.drain(..i) {
-            this.push(e.op.take().unwrap(), SystemError::default());
+        let expiry = timer.expiry.get();
+        if let Some(armed) = tq.armed {
+            // the earlier arm wakes up the reactor, and then re-arms to the earliest expiry.
+            if armed <= expiry || armed.diff(expiry) <= TIMER_SLACK {
+                return;
+            }
         }
+        tq.armed = Some(expiry);
+        self.ctl.reset_timeout(&timer);
     }
 
-    pub fn insert(&self, timer: &TimerImpl, op: Box<Perform>) -> Option<Box<Perform>> {
-        let mut tq = self.mutex.lock().unwrap();
-        let mut timer = TimerImplRef(timer);
-        let old_op = timer.op.take();
-        timer.op = Some(op);
-        let i = tq.binary_search(&timer).unwrap_err();
-        tq.insert(i, timer.clone());
-        if i == 0 {
-            self.ctl.reset_timeout(&timer);
+    fn remove(&self, tq: &mut TimerHeap, i: usize) -> TimerImplRef {
+        let timer = tq.remove(i);
+        if timer.wall.get().is_some() {
+            self.realtime.fetch_sub(1, atomic::Ordering::SeqCst);
         }
-        old_op
+        timer
     }
 
-    pub fn erase(&self, timer: &TimerImpl, expiry: Expiry) -> Option<Box<Perform>> {
+    pub fn insert(&self, timer: &TimerImpl, op: Box<Perform>) {
         let mut tq = self.mutex.lock().unwrap();
-        let mut timer = TimerImplRef(timer);
-        let old_op = timer.op.take();
-        if let Ok(i) = tq.binary_search(&timer) {
-            tq.remove(i);
-            for timer in tq.first().iter() {
-                self.ctl.reset_timeout(&timer);
+        let timer = TimerImplRef(timer);
+        let ops = unsafe { &mut *timer.ops() };
+        ops.push(op);
+        if ops.len() == 1 {
+            if let Some(wall) = timer.wall.get() {
+                if self.realtime.fetch_add(1, atomic::Ordering::SeqCst) == 0 {
+                    // discards the changes of the system clock while no one watches it.
+                    self.ctl.clock_changed();
+                }
+                timer.expiry.set(wall.into());
             }
+            tq.push(timer);
+            self.rearm(&mut tq);
+        }
+    }
+
+    pub fn erase(
+        &self,
+        timer: &TimerImpl,
+        expiry: Option<(Expiry, Option<SystemTime>)>,
+    ) -> Vec<Box<Perform>> {
+        let mut tq = self.mutex.lock().unwrap();
+        let ops = mem::replace(unsafe { &mut *timer.ops() }, Vec::new());
+        if timer.index.get() != NOT_QUEUED {
+            self.remove(&mut tq, timer.index.get());
+        }
+        if let Some((expiry, wall)) = expiry {
+            timer.expiry.set(expiry);
+            // the manual clock does not follow the system clock.
+            timer.wall.set(if tq.manual.is_some() { None } else { wall });
+        }
+        ops
+    }
+
+    pub fn erase_one(&self, timer: &TimerImpl) -> Option<Box<Perform>> {
+        let mut tq = self.mutex.lock().unwrap();
+        let ops = unsafe { &mut *timer.ops() };
+        if ops.is_empty() {
+            return None;
+        }
+        let op = ops.remove(0);
+        if ops.is_empty() && timer.index.get() != NOT_QUEUED {
+            self.remove(&mut tq, timer.index.get());
         }
-        timer.expiry = expiry;
-        old_op
+        Some(op)
+    }
+
+    pub fn erase_expired(&self, timer: &TimerImpl) -> Vec<Box<Perform>> {
+        let mut tq = self.mutex.lock().unwrap();
+        let ops = unsafe { &mut *timer.ops() };
+        let (expired, rest): (Vec<_>, Vec<_>) = mem::replace(ops, Vec::new()).into_iter().partition(
+            |op| op.is_expired(),
+        );
+        *ops = rest;
+        if ops.is_empty() && timer.index.get() != NOT_QUEUED {
+            self.remove(&mut tq, timer.index.get());
+        }
+        expired
     }
 }
 
@@ -282,14 +674,24 @@ fn test_eq() {
     let ctx = &IoContext::new().unwrap();
     let t1 = TimerImpl {
         ctx: ctx.clone(),
-        expiry: now.into(),
-        op: None,
+        expiry: Cell::new(now.into()),
+        wall: Cell::new(None),
+        cancel_on_clock_change: Cell::new(false),
+        index: Cell::new(NOT_QUEUED),
+        ops: UnsafeCell::new(Vec::new()),
+        sync: Mutex::default(),
+        condvar: Condvar::new(),
     };
 
     let t2 = TimerImpl {
         ctx: ctx.clone(),
-        expiry: now.into(),
-        op: None,
+        expiry: Cell::new(now.into()),
+        wall: Cell::new(None),
+        cancel_on_clock_change: Cell::new(false),
+        index: Cell::new(NOT_QUEUED),
+        ops: UnsafeCell::new(Vec::new()),
+        sync: Mutex::default(),
+        condvar: Condvar::new(),
     };
 
     assert!(TimerImplRef(&t1) == TimerImplRef(&t1));
@@ -304,20 +706,35 @@ fn test_cmp() {
     let ctx = &IoContext::new().unwrap();
     let t1 = TimerImpl {
         ctx: ctx.clone(),
-        expiry: (now + Duration::new(1, 0)).into(),
-        op: None,
+        expiry: Cell::new((now + Duration::new(1, 0)).into()),
+        wall: Cell::new(None),
+        cancel_on_clock_change: Cell::new(false),
+        index: Cell::new(NOT_QUEUED),
+        ops: UnsafeCell::new(Vec::new()),
+        sync: Mutex::default(),
+        condvar: Condvar::new(),
     };
 
     let t2 = TimerImpl {
         ctx: ctx.clone(),
-        expiry: (now + Duration::new(2, 0)).into(),
-        op: None,
+        expiry: Cell::new((now + Duration::new(2, 0)).into()),
+        wall: Cell::new(None),
+        cancel_on_clock_change: Cell::new(false),
+        index: Cell::new(NOT_QUEUED),
+        ops: UnsafeCell::new(Vec::new()),
+        sync: Mutex::default(),
+        condvar: Condvar::new(),
     };
 
     let t3 = TimerImpl {
         ctx: ctx.clone(),
-        expiry: (now + Duration::new(2, 0)).into(),
-        op: None,
+        expiry: Cell::new((now + Duration::new(2, 0)).into()),
+        wall: Cell::new(None),
+        cancel_on_clock_change: Cell::new(false),
+        index: Cell::new(NOT_QUEUED),
+        ops: UnsafeCell::new(Vec::new()),
+        sync: Mutex::default(),
+        condvar: Condvar::new(),
     };
 
     assert!(TimerImplRef(&t1) < TimerImplRef(&t2));
@@ -328,3 +745,41 @@ fn test_cmp() {
         assert!(TimerImplRef(&t3) < TimerImplRef(&t2));
     }
 }
+
+#[test]
+fn test_timer_heap() {
+    let ctx = &IoContext::new().unwrap();
+    let now = Instant::now();
+    let timers: Vec<_> = (0..100)
+        .map(|i| {
+            let timer = TimerImpl::new(ctx);
+            timer.expiry.set(
+                (now + Duration::new(0, (i * 7919 % 100) as u32)).into(),
+            );
+            timer
+        })
+        .collect();
+
+    let mut heap = TimerHeap::default();
+    for timer in &timers {
+        heap.push(TimerImplRef(&**timer));
+    }
+    for timer in timers.iter().step_by(3) {
+        let i = timer.index.get();
+        assert!(heap.remove(i) == TimerImplRef(&**timer));
+        assert_eq!(timer.index.get(), NOT_QUEUED);
+    }
+    for (i, timer) in heap.timers.iter().enumerate() {
+        assert_eq!(timer.index.get(), i);
+    }
+
+    let mut last = Expiry::zero();
+    let mut len = 0;
+    while let Some(timer) = heap.top().cloned() {
+        assert!(last <= timer.expiry.get());
+        last = timer.expiry.get();
+        heap.remove(0);
+        len += 1;
+    }
+    assert_eq!(len, 66);
+}
@@ -24,9 +24,14 @@ impl TimerCtl {
 
     pub fn reset_timeout(&self, timer: &TimerImpl) {
         self.timeout_nsec.store(
-            timer.expiry.left(),
+            timer.expiry.get().left(),
             Ordering::SeqCst,
         );
         timer.ctx.as_reactor().interrupt();
     }
+
+    /// The changes of the system clock are not detected.
+    pub fn clock_changed(&self) -> bool {
+        false
+    }
 }
@@ -163,6 +163,10 @@ where
             self.failure(this, err.into())
         }
     }
+
+    fn is_expired(&self) -> bool {
+        self.handler.is_expired()
+    }
 }
 
 impl<F, W> Exec for AsyncWrite<F, W>